@@ -0,0 +1,104 @@
+//! Benchmarks of block and header deserialization with simultaneous hashing.
+//!
+//! Run with `cargo bench -p chain`.
+
+#[macro_use]
+extern crate criterion;
+extern crate chain;
+extern crate crypto;
+extern crate rug;
+extern crate serialization as ser;
+
+use chain::{Block, BlockHeader, IndexedBlock, ReadAndHash};
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use crypto::dhash256;
+use crypto::sr25519::PK;
+use rug::Integer;
+use ser::{deserialize, serialize, Reader};
+
+/// Size of the single proof element, in bits.
+const PROOF_ELEMENT_BITS: u32 = 2048;
+
+fn header() -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        previous_header_hash: [2; 32].into(),
+        bits: 5.into(),
+        pubkey: PK::from_bytes(&[6; 32]).expect("valid public key; qed"),
+        iterations: 7,
+        solution: Integer::from(Integer::u_pow_u(3, 1290)),
+    }
+}
+
+/// Block with a proof of `proof_len` elements of `PROOF_ELEMENT_BITS` bits each.
+fn block(proof_len: usize) -> Block {
+    let element = (Integer::from(1) << PROOF_ELEMENT_BITS) - 1u32;
+    Block::new(header(), vec![element; proof_len])
+}
+
+fn bench_header(c: &mut Criterion) {
+    let raw = serialize(&header());
+
+    let mut group = c.benchmark_group("header");
+    group.throughput(Throughput::Bytes(raw.len() as u64));
+    group.bench_function("read_and_hash", |b| {
+        b.iter(|| {
+            let mut reader = Reader::new(&raw);
+            black_box(reader.read_and_hash::<BlockHeader>().unwrap())
+        })
+    });
+    group.bench_function("read_then_dhash256", |b| {
+        b.iter(|| {
+            let mut reader = Reader::new(&raw);
+            let header: BlockHeader = reader.read().unwrap();
+            black_box((dhash256(&raw), header))
+        })
+    });
+    group.finish();
+}
+
+fn bench_block(c: &mut Criterion) {
+    // genesis-like block with an empty proof, normal block with a proof of
+    // ~8 KB and a stress block with a proof of ~1 MB
+    let blocks = vec![
+        ("genesis", block(0)),
+        ("normal", block(32)),
+        ("stress", block(4096)),
+    ];
+
+    for &(throughput_name, by_bytes) in &[("bytes", true), ("blocks", false)] {
+        let mut group = c.benchmark_group(format!("block/{}", throughput_name));
+        for &(name, ref block) in &blocks {
+            let raw = serialize(block);
+            group.throughput(if by_bytes {
+                Throughput::Bytes(raw.len() as u64)
+            } else {
+                Throughput::Elements(1)
+            });
+
+            group.bench_with_input(BenchmarkId::new("from_raw", name), block, |b, block| {
+                b.iter(|| black_box(IndexedBlock::from_raw(block.clone())))
+            });
+            group.bench_with_input(
+                BenchmarkId::new("deserialize_indexed", name),
+                &raw,
+                |b, raw| b.iter(|| black_box(deserialize::<_, IndexedBlock>(&raw[..]).unwrap())),
+            );
+            group.bench_with_input(
+                BenchmarkId::new("deserialize_then_hash", name),
+                &raw,
+                |b, raw| {
+                    b.iter(|| {
+                        let block: Block = deserialize(&raw[..]).unwrap();
+                        black_box(IndexedBlock::from_raw(block))
+                    })
+                },
+            );
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_header, bench_block);
+criterion_main!(benches);
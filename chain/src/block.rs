@@ -1,6 +1,7 @@
+use bytes::Bytes;
 use crypto::vdf;
 use hex::FromHex;
-use ser::deserialize;
+use ser::{deserialize, serialized_list_size};
 use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
 use std::io;
 use BlockHeader;
@@ -52,8 +53,62 @@ impl Block {
         &self.block_header
     }
 
+    /// Returns true if this is the first block of the chain
+    pub fn is_genesis(&self) -> bool {
+        self.block_header.previous_header_hash.is_zero()
+    }
+
+    /// Size of the serialized block header
+    pub fn header_serialized_size(&self) -> usize {
+        self.block_header.serialized_size()
+    }
+
+    /// Size of the serialized VDF proof, including its length prefix
+    pub fn proof_serialized_size(&self) -> usize {
+        serialized_list_size(&self.proof)
+    }
+
+    /// Serialized block: header, followed by the VDF proof
+    pub fn to_bytes(&self) -> Bytes {
+        self.serialize_to_vec().into()
+    }
+
     #[cfg(any(test, feature = "test-helpers"))]
     pub fn hash(&self) -> H256 {
         self.block_header.hash()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Block;
+    use rug::Integer;
+    use ser::deserialize;
+    use {BlockHeader, PK};
+
+    #[test]
+    fn test_block_round_trip() {
+        let block = Block::new(
+            BlockHeader {
+                version: 1,
+                previous_header_hash: [2; 32].into(),
+                bits: 5.into(),
+                pubkey: PK::from_bytes(&[6; 32]).unwrap(),
+                iterations: 7,
+                solution: Integer::from(8),
+            },
+            vec![Integer::from(9), Integer::from(0x1234_5678)],
+        );
+
+        let bytes = block.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            block.header_serialized_size() + block.proof_serialized_size()
+        );
+
+        let decoded: Block = deserialize(bytes.as_ref()).unwrap();
+        assert_eq!(decoded.block_header, block.block_header);
+        assert_eq!(decoded.proof, block.proof);
+        assert_eq!(decoded, block);
+    }
+}
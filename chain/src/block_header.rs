@@ -3,12 +3,14 @@ use compact::Compact;
 use crypto::dhash256;
 use crypto::sr25519::PK;
 use hash::H256;
+use heapsize::HeapSizeOf;
 use hex::FromHex;
 use rug::Integer;
 use ser::{deserialize, serialize};
 use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
 use std::fmt;
 use std::io;
+use ShortInteger;
 
 #[derive(PartialEq, Clone)]
 pub struct BlockHeader {
@@ -80,7 +82,7 @@ impl fmt::Debug for BlockHeader {
             .field("bits", &self.bits)
             .field("pubkey", &self.pubkey)
             .field("iterations", &self.iterations)
-            .field("solution", &self.solution)
+            .field("solution", &ShortInteger(self.solution.clone()))
             .finish()
     }
 }
@@ -91,6 +93,17 @@ impl From<&'static str> for BlockHeader {
     }
 }
 
+impl HeapSizeOf for BlockHeader {
+    fn heap_size_of_children(&self) -> usize {
+        integer_heap_size(&self.solution)
+    }
+}
+
+/// Number of bytes, allocated by the big integer.
+pub(crate) fn integer_heap_size(integer: &Integer) -> usize {
+    integer.capacity() / 8
+}
+
 /// Compute hash of the block header.
 pub(crate) fn block_header_hash(block_header: &BlockHeader) -> H256 {
     dhash256(&serialize(block_header))
@@ -1,13 +1,14 @@
 use block::Block;
+use block_header::integer_heap_size;
 use crypto::vdf;
 use hash::H256;
+use heapsize::HeapSizeOf;
 use hex::FromHex;
 use indexed_header::IndexedBlockHeader;
 use rug::Integer;
 use ser::{deserialize, serialized_list_size};
 use ser::{Deserializable, Error as ReaderError, Reader, Serializable};
-use std::cmp;
-use std::io;
+use std::{cmp, io, mem};
 
 #[derive(Debug, Clone)]
 pub struct IndexedBlock {
@@ -36,6 +37,14 @@ impl From<Block> for IndexedBlock {
     }
 }
 
+impl HeapSizeOf for IndexedBlock {
+    fn heap_size_of_children(&self) -> usize {
+        self.header.heap_size_of_children()
+            + self.proof.capacity() * mem::size_of::<Integer>()
+            + self.proof.iter().map(integer_heap_size).sum::<usize>()
+    }
+}
+
 impl cmp::PartialEq for IndexedBlock {
     fn eq(&self, other: &Self) -> bool {
         self.header.hash == other.header.hash
@@ -69,10 +78,21 @@ impl IndexedBlock {
         Block::new(self.header.raw, self.proof)
     }
 
+    /// Returns true if this is the first block of the chain
+    pub fn is_genesis(&self) -> bool {
+        self.header.raw.previous_header_hash.is_zero()
+    }
+
     pub fn size(&self) -> usize {
-        let header_size = self.header.raw.serialized_size();
-        let proof_size = serialized_list_size(&self.proof);
-        header_size + proof_size
+        self.header_size() + self.proof_size()
+    }
+
+    pub fn header_size(&self) -> usize {
+        self.header.raw.serialized_size()
+    }
+
+    pub fn proof_size(&self) -> usize {
+        serialized_list_size(&self.proof)
     }
 
     pub fn randomness(&self) -> &Integer {
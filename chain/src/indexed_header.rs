@@ -1,5 +1,6 @@
 use block_header::{block_header_hash, BlockHeader};
 use hash::H256;
+use heapsize::HeapSizeOf;
 use read_and_hash::ReadAndHash;
 use ser::{Deserializable, Error as ReaderError, Reader};
 use std::{cmp, fmt, io};
@@ -19,6 +20,12 @@ impl fmt::Debug for IndexedBlockHeader {
     }
 }
 
+impl HeapSizeOf for IndexedBlockHeader {
+    fn heap_size_of_children(&self) -> usize {
+        self.raw.heap_size_of_children()
+    }
+}
+
 #[cfg(feature = "test-helpers")]
 impl From<BlockHeader> for IndexedBlockHeader {
     fn from(header: BlockHeader) -> Self {
@@ -47,6 +54,25 @@ impl cmp::PartialEq for IndexedBlockHeader {
     }
 }
 
+impl cmp::Eq for IndexedBlockHeader {}
+
+impl cmp::PartialOrd for IndexedBlockHeader {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Headers are ordered by number of VDF iterations, which is used as a proxy for block work.
+/// Headers with the same number of iterations are ordered by hash => ordering is consistent with equality.
+impl cmp::Ord for IndexedBlockHeader {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.raw
+            .iterations
+            .cmp(&other.raw.iterations)
+            .then_with(|| self.hash[..].cmp(&other.hash[..]))
+    }
+}
+
 impl Deserializable for IndexedBlockHeader {
     fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
     where
@@ -62,3 +88,46 @@ impl Deserializable for IndexedBlockHeader {
         Ok(header)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedBlockHeader;
+    use block_header::BlockHeader;
+    use rug::Integer;
+    use std::collections::BinaryHeap;
+    use PK;
+
+    fn header(iterations: u32, solution: u32) -> IndexedBlockHeader {
+        IndexedBlockHeader::from_raw(BlockHeader {
+            version: 1,
+            previous_header_hash: [2; 32].into(),
+            bits: 5.into(),
+            pubkey: PK::from_bytes(&[6; 32]).unwrap(),
+            iterations: iterations,
+            solution: Integer::from(solution),
+        })
+    }
+
+    #[test]
+    fn test_indexed_header_ordering() {
+        let h1 = header(1, 8);
+        let h2 = header(2, 8);
+        let h3 = header(3, 8);
+        assert!(h1 < h2);
+        assert!(h3 > h2);
+        assert_eq!(h2.cmp(&h2.clone()), ::std::cmp::Ordering::Equal);
+
+        // same iterations => ordered by hash, but never equal
+        let h2_other = header(2, 9);
+        assert!(h2_other != h2);
+        assert!(h2_other.cmp(&h2) != ::std::cmp::Ordering::Equal);
+        assert!(h2_other > h1 && h2_other < h3);
+
+        let mut heap: BinaryHeap<_> = vec![h2.clone(), h3.clone(), h1.clone()]
+            .into_iter()
+            .collect();
+        assert_eq!(heap.pop(), Some(h3));
+        assert_eq!(heap.pop(), Some(h2));
+        assert_eq!(heap.pop(), Some(h1));
+    }
+}
@@ -15,6 +15,7 @@ mod indexed_block;
 mod indexed_header;
 /// `IndexedBlock` extension
 mod read_and_hash;
+mod short_integer;
 
 pub use primitives::{bigint, bytes, compact, hash};
 
@@ -24,3 +25,4 @@ pub use block_header::BlockHeader;
 pub use indexed_block::IndexedBlock;
 pub use indexed_header::IndexedBlockHeader;
 pub use read_and_hash::{HashedData, ReadAndHash};
+pub use short_integer::ShortInteger;
@@ -0,0 +1,58 @@
+use rug::Integer;
+use std::fmt;
+
+/// Number of leading hex digits, printed by `ShortInteger`
+const SHORT_INTEGER_DIGITS: usize = 16;
+
+/// Integer wrapper, which only prints leading hex digits of the value.
+/// Useful for logging VDF solutions, which are hundreds of digits long.
+#[derive(PartialEq, Clone)]
+pub struct ShortInteger(pub Integer);
+
+impl ShortInteger {
+    /// Underlying value
+    pub fn full(&self) -> &Integer {
+        &self.0
+    }
+}
+
+impl From<Integer> for ShortInteger {
+    fn from(i: Integer) -> Self {
+        ShortInteger(i)
+    }
+}
+
+impl fmt::Display for ShortInteger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hex = format!("{:x}", self.0);
+        if hex.len() > SHORT_INTEGER_DIGITS {
+            write!(f, "0x{}...", &hex[..SHORT_INTEGER_DIGITS])
+        } else {
+            write!(f, "0x{}", hex)
+        }
+    }
+}
+
+impl fmt::Debug for ShortInteger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShortInteger;
+    use rug::Integer;
+
+    #[test]
+    fn test_short_integer_format() {
+        assert!(format!("{:?}", ShortInteger(Integer::from(42))).len() < 50);
+        assert_eq!(format!("{}", ShortInteger(Integer::from(0x1234))), "0x1234");
+
+        let long = ShortInteger(Integer::from(Integer::u_pow_u(3, 1290)));
+        let formatted = format!("{:?}", long);
+        assert_eq!(formatted.len(), 2 + 16 + 3);
+        assert!(formatted.ends_with("..."));
+        assert_eq!(long.full(), &Integer::from(Integer::u_pow_u(3, 1290)));
+    }
+}
@@ -8,6 +8,7 @@ extern crate sha3;
 extern crate siphasher;
 #[macro_use]
 extern crate lazy_static;
+extern crate rayon;
 extern crate rug;
 extern crate sha2;
 
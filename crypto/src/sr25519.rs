@@ -1,3 +1,4 @@
+use rand::{thread_rng, RngCore};
 use schnorrkel::context::signing_context;
 use schnorrkel::vrf::{VRFPreOut, VRFProof};
 use schnorrkel::{ExpansionMode, Keypair, MiniSecretKey, PublicKey, SecretKey, Signature};
@@ -31,6 +32,18 @@ pub fn create_keypair(seed: &[u8]) -> (SK, PK) {
     }
 }
 
+/// Generate new random keypair
+pub fn random_keypair() -> (SK, PK) {
+    let mut seed = [0u8; 32];
+    thread_rng().fill_bytes(&mut seed);
+    keypair_from_seed(&seed)
+}
+
+/// Deterministically derive keypair from the seed
+pub fn keypair_from_seed(seed: &[u8; 32]) -> (SK, PK) {
+    create_keypair(seed)
+}
+
 pub fn sign(sk: &SK, message: &[u8]) -> Vec<u8> {
     let context = b"";
     let pk = sk.to_public();
@@ -130,4 +143,25 @@ pub mod tests {
 
         assert!(vrf_verify(&pk, message, &vrf_out, &vrf_proof));
     }
+
+    #[test]
+    fn can_sign_with_random_keypair() {
+        let (sk, pk) = random_keypair();
+        let message = b"this is a message";
+        let signature = sign(&sk, message);
+
+        assert!(verify(&pk, message, &signature[..]));
+        assert!(random_keypair().1 != pk);
+    }
+
+    #[test]
+    fn keypair_from_seed_is_deterministic() {
+        let seed = [7u8; 32];
+        let (sk1, pk1) = keypair_from_seed(&seed);
+        let (sk2, pk2) = keypair_from_seed(&seed);
+
+        assert_eq!(sk1.to_bytes()[..], sk2.to_bytes()[..]);
+        assert_eq!(pk1, pk2);
+        assert_eq!(pk1, create_keypair(&seed).1);
+    }
 }
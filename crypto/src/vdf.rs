@@ -1,5 +1,8 @@
+use rayon::prelude::*;
 use rug::{integer::Order, Integer};
 use sha2::{Digest, Sha256};
+use sha3::Sha3_256;
+use std::cmp::min;
 use std::str::FromStr;
 use std::vec::Vec;
 
@@ -53,6 +56,39 @@ pub fn eval(g: &Integer, t: u64) -> Integer {
     y
 }
 
+/// Group element, derived from the seed
+fn g_from_seed(seed: &[u8; 32]) -> Integer {
+    let hash = Sha3_256::digest(seed);
+    let result = Integer::from_digits(&hash, Order::Msf);
+    result.div_rem_floor(MODULUS.clone()).1
+}
+
+/// Deterministic VDF output for the given seed. Useful in tests, where no block is available.
+pub fn eval_with_seed(seed: &[u8; 32], iterations: u64) -> Integer {
+    eval(&g_from_seed(seed), iterations)
+}
+
+/// Deterministic VDF proof for the given seed. Useful in tests, where no block is available.
+pub fn prove_with_seed(seed: &[u8; 32], iterations: u64) -> Proof {
+    let g = g_from_seed(seed);
+    let y = eval(&g, iterations);
+    prove(&g, &y, iterations)
+}
+
+/// Number of proof elements, produced by `prove` for the given number of iterations.
+pub fn proof_len(iterations: u64) -> usize {
+    let mut t = iterations;
+    let mut len = 0;
+    while t >= 2 {
+        t = t / 2;
+        if (t % 2 != 0) && (t != 1) {
+            t += 1;
+        }
+        len += 1;
+    }
+    len
+}
+
 pub fn prove(g: &Integer, y: &Integer, iterations: u64) -> Proof {
     let (mut x_i, mut y_i) = (g.clone(), y.clone());
     let mut proof = Proof::new();
@@ -83,11 +119,102 @@ pub fn prove(g: &Integer, y: &Integer, iterations: u64) -> Proof {
     proof
 }
 
-pub fn verify(g: &Integer, y: &Integer, iterations: u64, proof: &Proof) -> bool {
+/// Evaluate VDF, remembering every `interval`-th intermediate output:
+/// `checkpoints[k] = g^(2^(k * interval))`, starting with `g` itself.
+pub fn eval_with_checkpoints(g: &Integer, t: u64, interval: u64) -> (Integer, Vec<Integer>) {
+    assert!(interval > 0, "checkpoints interval must be positive");
+
+    let mut y = g.clone();
+    let mut checkpoints = vec![y.clone()];
+    let mut evaluated = 0;
+    while evaluated < t {
+        let n = min(interval, t - evaluated);
+        y = eval(&y, n);
+        evaluated += n;
+        if n == interval {
+            checkpoints.push(y.clone());
+        }
+    }
+
+    (y, checkpoints)
+}
+
+/// `g^(2^offset)`, evaluated starting from the closest preceding checkpoint.
+fn checkpoint_power(checkpoints: &[Integer], interval: u64, offset: u64) -> Integer {
+    let index = min(offset / interval, checkpoints.len() as u64 - 1);
+    eval(&checkpoints[index as usize], offset - index * interval)
+}
+
+/// Same proof as `prove`, computed on the current rayon thread pool using intermediate
+/// outputs of the VDF evaluation (see `eval_with_checkpoints`).
+///
+/// Every `x_i` of the first rounds is kept as a product of powers of `g^(2^offset)`, so
+/// `mu_i = x_i^(2^(t/2))` is computed from the checkpoints, one thread per factor, instead
+/// of `t/2` sequential squarings. Once this gets more expensive than sequential squaring
+/// (the number of factors doubles every round), the remaining rounds are computed as in `prove`.
+pub fn prove_with_checkpoints(
+    g: &Integer,
+    y: &Integer,
+    iterations: u64,
+    checkpoints: &[Integer],
+    interval: u64,
+) -> Proof {
+    let threads = rayon::current_num_threads() as u64;
+    prove_with_checkpoints_impl(
+        g,
+        y,
+        iterations,
+        checkpoints,
+        interval,
+        |factors, exponent_bits, half| {
+            // every factor costs up to `interval` squarings + exponentiation
+            let rounds = (factors as u64 + threads - 1) / threads;
+            rounds * (interval + exponent_bits as u64) < half
+        },
+    )
+}
+
+fn prove_with_checkpoints_impl<F>(
+    g: &Integer,
+    y: &Integer,
+    iterations: u64,
+    checkpoints: &[Integer],
+    interval: u64,
+    use_checkpoints: F,
+) -> Proof
+where
+    F: Fn(usize, u32, u64) -> bool,
+{
+    if checkpoints.is_empty() || interval == 0 {
+        return prove(g, y, iterations);
+    }
+
     let (mut x_i, mut y_i) = (g.clone(), y.clone());
+    let mut proof = Proof::new();
+    // x_i = prod(g^(2^offset * exponent)); empty once rounds are computed sequentially
+    let mut factors: Vec<(u64, Integer)> = vec![(0, Integer::from(1))];
+
     let mut t = iterations;
     let two = Integer::from(2);
-    for mu_i in proof {
+    while t >= 2 {
+        let half = t / 2;
+        let mu_i = if factors.is_empty() {
+            let two_exp = Integer::from(1) << half as u32; // 2^(t/2)
+            x_i.clone().pow_mod(&two_exp, &MODULUS).unwrap()
+        } else {
+            factors
+                .par_iter()
+                .map(|&(offset, ref exponent)| {
+                    checkpoint_power(checkpoints, interval, offset + half)
+                        .pow_mod(exponent, &MODULUS)
+                        .unwrap()
+                })
+                .reduce(
+                    || Integer::from(1),
+                    |a, b| (a * b).div_rem_floor(MODULUS.clone()).1,
+                )
+        };
+
         let r_i = hash_fs(&[&x_i, &y_i, &mu_i]);
 
         let xi_ri = x_i.clone().pow_mod(&r_i, &MODULUS).unwrap();
@@ -101,7 +228,164 @@ pub fn verify(g: &Integer, y: &Integer, iterations: u64, proof: &Proof) -> bool
             t += 1;
             y_i = y_i.clone().pow_mod(&two, &MODULUS).unwrap();
         }
+
+        // x_(i+1) = x_i^r_i * x_i^(2^(t/2))
+        if !factors.is_empty() {
+            let exponent_bits = factors
+                .iter()
+                .map(|&(_, ref exponent)| exponent.significant_bits())
+                .max()
+                .unwrap_or(0)
+                + r_i.significant_bits();
+            if use_checkpoints(factors.len() * 2, exponent_bits, t / 2) {
+                let shifted: Vec<_> = factors
+                    .iter()
+                    .map(|&(offset, ref exponent)| (offset + half, exponent.clone()))
+                    .collect();
+                for factor in factors.iter_mut() {
+                    factor.1 *= &r_i;
+                }
+                factors.extend(shifted);
+            } else {
+                factors.clear();
+            }
+        }
+
+        proof.push(mu_i);
+    }
+
+    proof
+}
+
+/// Rounds are verified one after another (`r_i` depends on the previous round), but both
+/// exponentiations of each round are computed on the current rayon thread pool.
+pub fn verify(g: &Integer, y: &Integer, iterations: u64, proof: &Proof) -> bool {
+    let (mut x_i, mut y_i) = (g.clone(), y.clone());
+    let mut t = iterations;
+    let two = Integer::from(2);
+    for mu_i in proof {
+        let r_i = hash_fs(&[&x_i, &y_i, &mu_i]);
+
+        let (xi_ri, mui_ri) = rayon::join(
+            || x_i.clone().pow_mod(&r_i, &MODULUS).unwrap(),
+            || mu_i.clone().pow_mod(&r_i, &MODULUS).unwrap(),
+        );
+        x_i = (xi_ri * mu_i.clone()).div_rem_floor(MODULUS.clone()).1;
+        y_i = (mui_ri * y_i.clone()).div_rem_floor(MODULUS.clone()).1;
+
+        t = t / 2;
+        if (t % 2 != 0) && (t != 1) {
+            t += 1;
+            y_i = y_i.clone().pow_mod(&two, &MODULUS).unwrap();
+        }
     }
 
     y_i == x_i.pow_mod(&two, &MODULUS).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        eval, eval_with_checkpoints, eval_with_seed, g_from_seed, proof_len, prove,
+        prove_with_checkpoints, prove_with_checkpoints_impl, prove_with_seed, verify, MODULUS,
+    };
+    use rug::Integer;
+
+    #[test]
+    fn modulus_is_rsa_2048() {
+        assert!(MODULUS.is_odd());
+        assert_eq!(MODULUS.significant_bits(), 2048);
+        // product of two distinct primes can't be a perfect square
+        assert!(!MODULUS.is_perfect_square());
+    }
+
+    #[cfg(feature = "slow-tests")]
+    #[test]
+    fn modulus_is_not_prime() {
+        use rug::integer::IsPrime;
+
+        assert_eq!(MODULUS.is_probably_prime(30), IsPrime::No);
+    }
+
+    #[test]
+    fn eval_with_seed_known_answer() {
+        let g = Integer::from_str_radix(
+            "9e6291970cb44dd94008c79bcaf9d86f18b4b49ba5b2a04781db7199ed3b9e4e",
+            16,
+        )
+        .unwrap();
+        assert_eq!(g_from_seed(&[0u8; 32]), g);
+
+        let expected = Integer::from_str_radix(
+            "756edef312310bd006f749adfe1018f087ce41faebd8d5a93f9d396e8349c68c\
+             0b2bd3c4ab59a8e9a683371c4fbe4996ef1899888b2b7c36e5c5821be2ea9d6e\
+             8d2fe62ccbbd8912ef63cdf9e25852f36f937c07a89bddc2cc66bfe2eba202a1\
+             d70c73838c41cfcfc7e6befc6ab1d0a09e05d2a201f5e8534aa34f264c348c69\
+             9ec31e47dbb27df39150dd2f761f65513d7ab85135f9d5b35ccc5569d4626c5b\
+             35a68da3e176d8916e46193390e334eeddf235b43ffc4b67a973b9e006a51e36\
+             3e66d0555270dfde4b5612736d7db601cf3fc3748d8cfa08a001e0c773d8d5d1\
+             5ca8053d3b128b3502100a86b7fb3bdffca8769c58d851051300c7187449de56",
+            16,
+        )
+        .unwrap();
+        assert_eq!(eval_with_seed(&[0u8; 32], 8), expected);
+    }
+
+    #[test]
+    fn prove_with_seed_verifies() {
+        let seed = [7u8; 32];
+        let iterations = 100;
+        let y = eval_with_seed(&seed, iterations);
+        let proof = prove_with_seed(&seed, iterations);
+        assert!(verify(&g_from_seed(&seed), &y, iterations, &proof));
+        assert!(!verify(&g_from_seed(&[8u8; 32]), &y, iterations, &proof));
+    }
+
+    #[test]
+    fn proof_len_matches_prove() {
+        let seed = [5u8; 32];
+        for &iterations in [0, 1, 2, 3, 4, 5, 7, 100, 1024].iter() {
+            assert_eq!(
+                prove_with_seed(&seed, iterations).len(),
+                proof_len(iterations),
+                "iterations: {}",
+                iterations
+            );
+        }
+    }
+
+    #[test]
+    fn eval_with_checkpoints_collects_intermediate_outputs() {
+        let g = g_from_seed(&[1u8; 32]);
+        let (y, checkpoints) = eval_with_checkpoints(&g, 23, 5);
+        assert_eq!(y, eval(&g, 23));
+        assert_eq!(checkpoints.len(), 5);
+        for (k, checkpoint) in checkpoints.iter().enumerate() {
+            assert_eq!(*checkpoint, eval(&g, k as u64 * 5));
+        }
+    }
+
+    #[test]
+    fn prove_with_checkpoints_matches_prove() {
+        let g = g_from_seed(&[3u8; 32]);
+        for &(iterations, interval) in [(100, 8), (37, 5), (64, 64), (3, 1)].iter() {
+            let (y, checkpoints) = eval_with_checkpoints(&g, iterations, interval);
+            let expected = prove(&g, &y, iterations);
+            // checkpoints are used in all rounds
+            let proof = prove_with_checkpoints_impl(
+                &g,
+                &y,
+                iterations,
+                &checkpoints,
+                interval,
+                |_, _, _| true,
+            );
+            assert_eq!(proof, expected);
+            assert_eq!(
+                prove_with_checkpoints(&g, &y, iterations, &checkpoints, interval),
+                expected
+            );
+            assert!(verify(&g, &y, iterations, &proof));
+        }
+    }
+}
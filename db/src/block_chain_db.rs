@@ -1,19 +1,29 @@
 use bytes::Bytes;
 use chain::{Block, IndexedBlock, IndexedBlockHeader};
 use hash::H256;
+use header_cache::{HeaderCache, DEFAULT_HEADER_CACHE_CAPACITY};
 use kv::{
     AutoFlushingOverlayDatabase, CacheDatabase, DatabaseConfig, DiskDatabase, Key, KeyState,
-    KeyValue, KeyValueDatabase, MemoryDatabase, OverlayDatabase, Transaction as DBTransaction,
-    Value,
+    KeyValue, KeyValueDatabase, MemoryDatabase, OverlayDatabase, RawTransaction,
+    Transaction as DBTransaction, Value,
 };
-use kv::{COL_BLOCKS, COL_BLOCK_HASHES, COL_BLOCK_NUMBERS, COL_COUNT};
-use parking_lot::RwLock;
+use kv::{
+    COL_BLOCKS, COL_BLOCK_HASHES, COL_BLOCK_NUMBERS, COL_CHAIN_WORK, COL_COUNT, COL_RANDOMNESS,
+};
+use migrations;
+use parking_lot::{Mutex, RwLock};
+use primitives::bigint::U256;
+use rug::Integer;
 use ser::{deserialize, serialize};
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::path::Path;
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::Duration;
 use storage::{
-    BestBlock, BlockChain, BlockHeaderProvider, BlockOrigin, BlockProvider, BlockRef, CanonStore,
-    ConfigStore, Error, ForkChain, Forkable, SideChainOrigin, Store,
+    BestBlock, BlockChain, BlockHeaderProvider, BlockOrigin, BlockProvider, BlockRef, CacheStats,
+    CanonStore, ConfigStore, DatabaseStats, Error, ForkChain, Forkable, SideChainOrigin, Store,
 };
 
 const KEY_BEST_BLOCK_NUMBER: &'static str = "best_block_number";
@@ -21,11 +31,19 @@ const KEY_BEST_BLOCK_HASH: &'static str = "best_block_hash";
 
 const MAX_FORK_ROUTE_PRESET: usize = 2048;
 
+/// Database is compacted after this period of inactivity.
+const COMPACTION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// How often the compaction thread checks whether database is idle.
+const COMPACTION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Approximate memory footprint of the single header cache entry, in bytes.
+const HEADER_CACHE_ENTRY_SIZE: usize = 512;
+
 pub struct BlockChainDatabase<T>
 where
     T: KeyValueDatabase,
 {
     best_block: RwLock<BestBlock>,
+    header_cache: Mutex<HeaderCache>,
     db: T,
 }
 
@@ -49,7 +67,7 @@ where
     }
 }
 
-impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>> {
+impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<Arc<DiskDatabase>>>> {
     pub fn open_at_path<P>(path: P, total_cache: usize) -> Result<Self, Error>
     where
         P: AsRef<Path>,
@@ -68,10 +86,127 @@ impl BlockChainDatabase<CacheDatabase<AutoFlushingOverlayDatabase<DiskDatabase>>
         cfg.set_cache(Some(COL_BLOCK_NUMBERS), total_cache / 12);
 
         match DiskDatabase::open(cfg, path) {
-            Ok(db) => Ok(Self::open_with_cache(db)),
+            Ok(db) => {
+                migrations::migrate(&db).map_err(Error::DatabaseError)?;
+                let db = Arc::new(db);
+                Self::spawn_compaction_thread(Arc::downgrade(&db));
+                let store = Self::open_with_cache(db);
+                // headers get the same share of the cache as canon chain indexes
+                store.set_header_cache_capacity(
+                    total_cache * 1024 * 1024 / 12 / HEADER_CACHE_ENTRY_SIZE,
+                );
+                Ok(store)
+            }
             Err(err) => Err(Error::DatabaseError(err)),
         }
     }
+
+    /// Repairs corrupted database at given path. Database must not be opened.
+    ///
+    /// If best block metadata is missing or inconsistent after the repair, it is
+    /// restored by walking the canon chain from genesis. Note that the last few
+    /// blocks may be lost if write-ahead log files were corrupted.
+    pub fn repair<P>(path: P) -> Result<(), String>
+    where
+        P: AsRef<Path>,
+    {
+        DiskDatabase::repair(path.as_ref())?;
+
+        let db = DiskDatabase::open(DatabaseConfig::with_columns(Some(COL_COUNT)), path)?;
+        let store = BlockChainDatabase {
+            best_block: RwLock::new(BestBlock::default()),
+            header_cache: Mutex::new(HeaderCache::new(DEFAULT_HEADER_CACHE_CAPACITY)),
+            db: db,
+        };
+        if !store.is_best_block_consistent() {
+            store.restore_best_block().map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Wipes canon chain indexes of the database at given path. Database must not be opened.
+    ///
+    /// Returns hashes of all stored blocks, which are connected to the genesis block, ordered
+    /// so that every block follows its parent. Canon chain is then rebuilt by the caller, who
+    /// re-verifies and re-inserts these blocks.
+    pub fn reindex<P>(path: P) -> Result<Vec<H256>, String>
+    where
+        P: AsRef<Path>,
+    {
+        let db = DiskDatabase::open(DatabaseConfig::with_columns(Some(COL_COUNT)), path)?;
+
+        let mut children: HashMap<H256, Vec<H256>> = HashMap::new();
+        for (key, value) in db.iter(COL_BLOCKS.into()) {
+            let hash: H256 =
+                deserialize(&*key).map_err(|err| format!("Invalid block key: {:?}", err))?;
+            let block: Block = deserialize(&*value)
+                .map_err(|err| format!("Invalid block {}: {:?}", hash.reversed(), err))?;
+            children
+                .entry(block.block_header.previous_header_hash)
+                .or_insert_with(Vec::new)
+                .push(hash);
+        }
+
+        let mut hashes = Vec::new();
+        let mut queue: VecDeque<H256> =
+            children.remove(&H256::default()).unwrap_or_default().into();
+        while let Some(hash) = queue.pop_front() {
+            if let Some(block_children) = children.remove(&hash) {
+                queue.extend(block_children);
+            }
+            hashes.push(hash);
+        }
+        if !children.is_empty() {
+            warn!(target: "db", "{} blocks are not connected to the genesis block", children.values().map(Vec::len).sum::<usize>());
+        }
+
+        let mut meta_update = DBTransaction::new();
+        meta_update.delete(Key::Meta(KEY_BEST_BLOCK_HASH));
+        meta_update.delete(Key::Meta(KEY_BEST_BLOCK_NUMBER));
+        let mut update = RawTransaction::from(&meta_update);
+        for column in &[
+            COL_BLOCK_HASHES,
+            COL_BLOCK_NUMBERS,
+            COL_RANDOMNESS,
+            COL_CHAIN_WORK,
+        ] {
+            for (key, _) in db.iter((*column).into()) {
+                update.delete_raw((*column).into(), &key);
+            }
+        }
+        db.write(update)?;
+
+        Ok(hashes)
+    }
+
+    /// Spawns thread, which compacts database after `COMPACTION_IDLE_TIMEOUT` of inactivity.
+    /// Thread stops when database is closed.
+    fn spawn_compaction_thread(db: Weak<DiskDatabase>) {
+        thread::Builder::new()
+            .name("Database compaction thread".to_owned())
+            .spawn(move || {
+                let mut compacted = false;
+                loop {
+                    thread::sleep(COMPACTION_CHECK_INTERVAL);
+                    let db = match db.upgrade() {
+                        Some(db) => db,
+                        None => break,
+                    };
+
+                    if db.idle_time() < COMPACTION_IDLE_TIMEOUT {
+                        compacted = false;
+                    } else if !compacted {
+                        info!(target: "db", "Database is idle, compacting");
+                        if let Err(err) = db.compact() {
+                            error!(target: "db", "Database compaction failed: {}", err);
+                        }
+                        compacted = true;
+                    }
+                }
+                trace!(target: "db", "Database compaction thread stopped");
+            })
+            .expect("Error creating database compaction thread");
+    }
 }
 
 impl BlockChainDatabase<MemoryDatabase> {
@@ -93,11 +228,7 @@ where
 {
     pub fn open_with_cache(db: T) -> Self {
         let db = CacheDatabase::new(AutoFlushingOverlayDatabase::new(db, 50));
-        let best_block = Self::read_best_block(&db).unwrap_or_default();
-        BlockChainDatabase {
-            best_block: RwLock::new(best_block),
-            db: db,
-        }
+        BlockChainDatabase::open(db)
     }
 }
 
@@ -126,18 +257,87 @@ where
         }
     }
 
+    /// Checks that best block metadata is present and points to the canon block.
+    fn is_best_block_consistent(&self) -> bool {
+        let best_number = self
+            .get(Key::Meta(KEY_BEST_BLOCK_NUMBER))
+            .and_then(Value::as_meta)
+            .and_then(|number| deserialize::<_, u32>(&**number).ok());
+        let best_hash = self
+            .get(Key::Meta(KEY_BEST_BLOCK_HASH))
+            .and_then(Value::as_meta)
+            .and_then(|hash| deserialize::<_, H256>(&**hash).ok());
+
+        match (best_number, best_hash) {
+            (Some(number), Some(hash)) => {
+                self.block_hash(number).as_ref() == Some(&hash) && self.contains_block(hash.into())
+            }
+            _ => false,
+        }
+    }
+
+    /// Rewrites best block metadata with the last block of canon chain,
+    /// which is stored in the database without gaps.
+    fn restore_best_block(&self) -> Result<(), Error> {
+        let mut best_block = None;
+        let mut number = 0;
+        while let Some(hash) = self.block_hash(number) {
+            if !self.contains_block(hash.clone().into()) {
+                break;
+            }
+            best_block = Some(BestBlock {
+                number: number,
+                hash: hash,
+            });
+            number += 1;
+        }
+
+        let mut update = DBTransaction::new();
+        match best_block {
+            Some(best_block) => {
+                info!(target: "db", "Restoring best block: {:?}", best_block);
+                update.insert(KeyValue::Meta(
+                    KEY_BEST_BLOCK_HASH,
+                    serialize(&best_block.hash),
+                ));
+                update.insert(KeyValue::Meta(
+                    KEY_BEST_BLOCK_NUMBER,
+                    serialize(&best_block.number),
+                ));
+            }
+            None => {
+                info!(target: "db", "No canon blocks found, resetting best block");
+                update.delete(Key::Meta(KEY_BEST_BLOCK_HASH));
+                update.delete(Key::Meta(KEY_BEST_BLOCK_NUMBER));
+            }
+        }
+
+        self.db.write(update).map_err(Error::DatabaseError)
+    }
+
     pub fn open(db: T) -> Self {
         let best_block = Self::read_best_block(&db).unwrap_or_default();
         BlockChainDatabase {
             best_block: RwLock::new(best_block),
+            header_cache: Mutex::new(HeaderCache::new(DEFAULT_HEADER_CACHE_CAPACITY)),
             db: db,
         }
     }
 
+    /// Sets max number of headers, kept in the in-memory header cache.
+    pub fn set_header_cache_capacity(&self, capacity: usize) {
+        self.header_cache.lock().set_capacity(capacity);
+    }
+
     pub fn best_block(&self) -> BestBlock {
         self.best_block.read().clone()
     }
 
+    /// Compacts underlying database.
+    pub fn compact(&self) -> Result<(), Error> {
+        self.db.compact().map_err(Error::DatabaseError)
+    }
+
     pub fn fork(&self, side_chain: SideChainOrigin) -> Result<ForkChainDatabase<T>, Error> {
         let overlay = BlockChainDatabase::open(OverlayDatabase::new(&self.db));
 
@@ -160,7 +360,15 @@ where
     pub fn switch_to_fork(&self, fork: ForkChainDatabase<T>) -> Result<(), Error> {
         let mut best_block = self.best_block.write();
         *best_block = fork.blockchain.best_block.read().clone();
-        fork.blockchain.db.flush().map_err(Error::DatabaseError)
+        fork.blockchain.db.flush().map_err(Error::DatabaseError)?;
+        self.header_cache.lock().clear_hashes();
+        Ok(())
+    }
+
+    /// Cumulative work of the canon chain up to the block at given height.
+    fn known_chain_work(&self, number: u32) -> Result<U256, Error> {
+        self.chain_work(number)
+            .ok_or(Error::UnknownChainWork(number))
     }
 
     pub fn block_origin(&self, header: &IndexedBlockHeader) -> Result<BlockOrigin, Error> {
@@ -185,22 +393,30 @@ where
         }
 
         let mut sidechain_route = Vec::new();
+        // work (number of VDF iterations) of the side chain after the common ancestor
+        let mut sidechain_work = header.raw.iterations as u64;
         let mut next_hash = header.raw.previous_header_hash.clone();
 
         for fork_len in 0..MAX_FORK_ROUTE_PRESET {
             match self.block_number(&next_hash) {
                 Some(number) => {
                     let block_number = number + fork_len as u32 + 1;
+                    let decanonized_route: Vec<H256> = (number + 1..best_block.number + 1)
+                        .into_iter()
+                        .filter_map(|decanonized_bn| self.block_hash(decanonized_bn))
+                        .collect();
+                    let canon_work: u64 = decanonized_route
+                        .iter()
+                        .filter_map(|hash| self.block_header(hash.clone().into()))
+                        .map(|header| header.raw.iterations as u64)
+                        .sum();
                     let origin = SideChainOrigin {
                         ancestor: number,
                         canonized_route: sidechain_route.into_iter().rev().collect(),
-                        decanonized_route: (number + 1..best_block.number + 1)
-                            .into_iter()
-                            .filter_map(|decanonized_bn| self.block_hash(decanonized_bn))
-                            .collect(),
+                        decanonized_route: decanonized_route,
                         block_number: block_number,
                     };
-                    if block_number > best_block.number {
+                    if sidechain_work > canon_work {
                         return Ok(BlockOrigin::SideChainBecomingCanonChain(origin));
                     } else {
                         return Ok(BlockOrigin::SideChain(origin));
@@ -208,11 +424,12 @@ where
                 }
                 None => {
                     sidechain_route.push(next_hash.clone());
-                    next_hash = self
+                    let sidechain_header = self
                         .block_header(next_hash.into())
                         .expect("not to find orphaned side chain in database; qed")
-                        .raw
-                        .previous_header_hash;
+                        .raw;
+                    sidechain_work += sidechain_header.iterations as u64;
+                    next_hash = sidechain_header.previous_header_hash;
                 }
             }
         }
@@ -222,11 +439,11 @@ where
 
     pub fn insert(&self, block: IndexedBlock) -> Result<(), Error> {
         if self.contains_block(block.hash().clone().into()) {
-            return Ok(());
+            return Err(Error::Duplicate(block.hash().clone()));
         }
 
         let parent_hash = block.header.raw.previous_header_hash.clone();
-        if !self.contains_block(parent_hash.clone().into()) && !parent_hash.is_zero() {
+        if !block.is_genesis() && !self.contains_block(parent_hash.into()) {
             return Err(Error::UnknownParent);
         }
 
@@ -242,31 +459,41 @@ where
         self.db.write(update).map_err(Error::DatabaseError)
     }
 
-    /// Rollbacks single best block
-    // TODO:
-    // 1. implement this
-    // 2. consider update randomness data or metadata
-    fn rollback_best(&self) -> Result<H256, Error> {
-        unimplemented!()
+    /// Removes block, which is not in the canon chain, from the database and returns it.
+    pub fn take_block(&self, hash: &H256) -> Result<Option<IndexedBlock>, Error> {
+        if self.block_number(hash).is_some() {
+            return Err(Error::CannotDecanonize);
+        }
 
-        // let best_block_hash = self.best_block.read().hash.clone();
-        // let tx_to_decanonize = self.block_transaction_hashes(best_block_hash.into());
-        // let decanonized_hash = self.decanonize()?;
-        // debug_assert_eq!(best_block_hash, decanonized_hash);
+        let block = match self.block(hash.clone().into()) {
+            Some(block) => block,
+            None => return Ok(None),
+        };
 
-        // // and now remove decanonized block from database
-        // // all code currently works in assumption that origin of all blocks is one of:
-        // // {CanonChain, SideChain, SideChainBecomingCanonChain}
-        // let mut update = DBTransaction::new();
-        // update.delete(Key::BlockHeader(decanonized_hash.clone()));
-        // update.delete(Key::BlockTransactions(decanonized_hash.clone()));
-        // for tx_hash in tx_to_decanonize {
-        //     update.delete(Key::Transaction(tx_hash));
-        // }
+        let mut update = DBTransaction::new();
+        update.delete(Key::Block(hash.clone()));
+        self.db.write(update).map_err(Error::DatabaseError)?;
+        self.header_cache.lock().remove_header(hash);
 
-        // self.db.write(update).map_err(Error::DatabaseError)?;
+        Ok(Some(block))
+    }
+
+    /// Rollbacks single best block
+    pub fn rollback_best(&self) -> Result<H256, Error> {
+        let best_block_hash = self.best_block.read().hash.clone();
+        let decanonized_hash = self.decanonize()?;
+        debug_assert_eq!(best_block_hash, decanonized_hash);
+
+        // and now remove decanonized block from database
+        // all code currently works in assumption that origin of all blocks is one of:
+        // {CanonChain, SideChain, SideChainBecomingCanonChain}
+        let mut update = DBTransaction::new();
+        update.delete(Key::Block(decanonized_hash.clone()));
 
-        // Ok(self.best_block().hash)
+        self.db.write(update).map_err(Error::DatabaseError)?;
+        self.header_cache.lock().remove_header(&decanonized_hash);
+
+        Ok(self.best_block().hash)
     }
 
     /// Marks block as a new best block.
@@ -294,7 +521,7 @@ where
 
         let new_best_block = BestBlock {
             hash: hash.clone(),
-            number: if block.header.raw.previous_header_hash.is_zero() {
+            number: if block.is_genesis() {
                 assert_eq!(best_block.number, 0);
                 0
             } else {
@@ -302,6 +529,12 @@ where
             },
         };
 
+        let parent_chain_work = match new_best_block.number {
+            0 => U256::default(),
+            number => self.known_chain_work(number - 1)?,
+        };
+        let chain_work = parent_chain_work + block.header.raw.bits.to_work();
+
         trace!(target: "db", "canonize {:?}", new_best_block);
 
         let mut update = DBTransaction::new();
@@ -313,6 +546,11 @@ where
             new_best_block.hash.clone(),
             new_best_block.number,
         ));
+        update.insert(KeyValue::Randomness(
+            new_best_block.number,
+            block.randomness().clone(),
+        ));
+        update.insert(KeyValue::ChainWork(new_best_block.number, chain_work));
         update.insert(KeyValue::Meta(
             KEY_BEST_BLOCK_HASH,
             serialize(&new_best_block.hash),
@@ -323,6 +561,11 @@ where
         ));
 
         self.db.write(update).map_err(Error::DatabaseError)?;
+        {
+            let mut header_cache = self.header_cache.lock();
+            header_cache.insert_hash(new_best_block.number, new_best_block.hash.clone());
+            header_cache.insert_header(block.header);
+        }
         *best_block = new_best_block;
         Ok(())
     }
@@ -344,7 +587,7 @@ where
             number: if best_block.number > 0 {
                 best_block.number - 1
             } else {
-                assert!(block.header.raw.previous_header_hash.is_zero());
+                assert!(block.is_genesis());
                 0
             },
         };
@@ -354,6 +597,8 @@ where
         let mut update = DBTransaction::new();
         update.delete(Key::BlockHash(block_number));
         update.delete(Key::BlockNumber(block_hash.clone()));
+        update.delete(Key::Randomness(block_number));
+        update.delete(Key::ChainWork(block_number));
         update.insert(KeyValue::Meta(
             KEY_BEST_BLOCK_HASH,
             serialize(&new_best_block.hash),
@@ -364,6 +609,7 @@ where
         ));
 
         self.db.write(update).map_err(Error::DatabaseError)?;
+        self.header_cache.lock().remove_hash(block_number);
         *best_block = new_best_block;
         Ok(block_hash)
     }
@@ -394,9 +640,21 @@ where
 
     fn block_header(&self, block_ref: BlockRef) -> Option<IndexedBlockHeader> {
         self.resolve_hash(block_ref).and_then(|block_hash| {
-            self.get(Key::Block(block_hash.clone()))
+            // the lock is held while reading the database, so that concurrent
+            // removal of the block can't be overwritten with the stale header
+            let mut header_cache = self.header_cache.lock();
+            if let Some(header) = header_cache.header(&block_hash) {
+                return Some(header);
+            }
+
+            let header = self
+                .get(Key::Block(block_hash.clone()))
                 .and_then(Value::as_block)
-                .map(|block| IndexedBlockHeader::new(block_hash, block.block_header))
+                .map(|block| IndexedBlockHeader::new(block_hash, block.block_header));
+            if let Some(ref header) = header {
+                header_cache.insert_header(header.clone());
+            }
+            header
         })
     }
 }
@@ -411,8 +669,18 @@ where
     }
 
     fn block_hash(&self, number: u32) -> Option<H256> {
-        self.get(Key::BlockHash(number))
-            .and_then(Value::as_block_hash)
+        let mut header_cache = self.header_cache.lock();
+        if let Some(hash) = header_cache.hash(number) {
+            return Some(hash);
+        }
+
+        let hash = self
+            .get(Key::BlockHash(number))
+            .and_then(Value::as_block_hash);
+        if let Some(ref hash) = hash {
+            header_cache.insert_hash(number, hash.clone());
+        }
+        hash
     }
 
     fn block(&self, block_ref: BlockRef) -> Option<IndexedBlock> {
@@ -474,7 +742,9 @@ where
     fn switch_to_fork<'a>(&self, fork: Box<dyn ForkChain + 'a>) -> Result<(), Error> {
         let mut best_block = self.best_block.write();
         *best_block = fork.store().best_block();
-        fork.flush()
+        fork.flush()?;
+        self.header_cache.lock().clear_hashes();
+        Ok(())
     }
 }
 
@@ -485,6 +755,33 @@ where
     fn as_store(&self) -> &dyn Store {
         &*self
     }
+
+    fn compact(&self) -> Result<(), Error> {
+        BlockChainDatabase::compact(self)
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        self.db.cache_stats()
+    }
+
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        self.db.database_stats()
+    }
+
+    fn begin_batch(&self) -> Result<(), Error> {
+        self.db.begin_batch().map_err(Error::DatabaseError)
+    }
+
+    fn commit_batch(&self) -> Result<(), Error> {
+        self.db.commit_batch().map_err(Error::DatabaseError)
+    }
+
+    fn discard_batch(&self) {
+        let mut best_block = self.best_block.write();
+        self.db.discard_batch();
+        *best_block = Self::read_best_block(&self.db).unwrap_or_default();
+        self.header_cache.lock().clear();
+    }
 }
 
 impl<T> Store for BlockChainDatabase<T>
@@ -505,6 +802,18 @@ where
     fn difficulty(&self) -> f64 {
         self.best_header().raw.bits.to_f64()
     }
+
+    /// get randomness of the canon block at given height
+    fn randomness(&self, number: u32) -> Option<Integer> {
+        self.get(Key::Randomness(number))
+            .and_then(Value::as_randomness)
+    }
+
+    /// get cumulative work of the canon chain up to the block at given height
+    fn chain_work(&self, number: u32) -> Option<U256> {
+        self.get(Key::ChainWork(number))
+            .and_then(Value::as_chain_work)
+    }
 }
 
 impl<T> ConfigStore for BlockChainDatabase<T>
@@ -0,0 +1,100 @@
+//! In-memory cache of block headers.
+
+use chain::IndexedBlockHeader;
+use hash::H256;
+use lru_cache::LruCache;
+
+/// Default number of headers, kept in the cache.
+pub const DEFAULT_HEADER_CACHE_CAPACITY: usize = 8192;
+
+/// LRU cache of block headers (by hash) and of canon chain hashes (by number).
+///
+/// Headers are immutable, so entries by hash are only removed when the block is removed from
+/// the database. Entries by number must be updated whenever canon chain changes.
+pub struct HeaderCache {
+    by_hash: LruCache<H256, IndexedBlockHeader>,
+    by_number: LruCache<u32, H256>,
+}
+
+impl HeaderCache {
+    pub fn new(capacity: usize) -> Self {
+        HeaderCache {
+            by_hash: LruCache::new(capacity),
+            by_number: LruCache::new(capacity),
+        }
+    }
+
+    /// Change capacity of the cache, dropping least recently used entries if required.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.by_hash.set_capacity(capacity);
+        self.by_number.set_capacity(capacity);
+    }
+
+    pub fn header(&mut self, hash: &H256) -> Option<IndexedBlockHeader> {
+        self.by_hash.get_mut(hash).cloned()
+    }
+
+    pub fn hash(&mut self, number: u32) -> Option<H256> {
+        self.by_number.get_mut(&number).cloned()
+    }
+
+    pub fn insert_header(&mut self, header: IndexedBlockHeader) {
+        self.by_hash.insert(header.hash.clone(), header);
+    }
+
+    pub fn insert_hash(&mut self, number: u32, hash: H256) {
+        self.by_number.insert(number, hash);
+    }
+
+    /// Forget header of the block, removed from the database.
+    pub fn remove_header(&mut self, hash: &H256) {
+        self.by_hash.remove(hash);
+    }
+
+    /// Forget hash of the decanonized block.
+    pub fn remove_hash(&mut self, number: u32) {
+        self.by_number.remove(&number);
+    }
+
+    /// Forget all canon chain hashes. Called when canon chain is replaced at once.
+    pub fn clear_hashes(&mut self) {
+        self.by_number.clear();
+    }
+
+    /// Forget everything. Called when buffered database changes are discarded.
+    pub fn clear(&mut self) {
+        self.by_hash.clear();
+        self.by_number.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::HeaderCache;
+    use chain::IndexedBlockHeader;
+
+    #[test]
+    fn header_cache_evicts_least_recently_used() {
+        let h0 = test_data::block_h0();
+        let h1 = test_data::block_h1();
+        let mut cache = HeaderCache::new(1);
+
+        cache.insert_header(IndexedBlockHeader::new(h0.hash(), h0.block_header.clone()));
+        cache.insert_hash(0, h0.hash());
+        assert_eq!(cache.header(&h0.hash()).map(|h| h.hash), Some(h0.hash()));
+        assert_eq!(cache.hash(0), Some(h0.hash()));
+
+        cache.insert_header(IndexedBlockHeader::new(h1.hash(), h1.block_header.clone()));
+        cache.insert_hash(1, h1.hash());
+        assert!(cache.header(&h0.hash()).is_none());
+        assert_eq!(cache.hash(0), None);
+        assert_eq!(cache.header(&h1.hash()).map(|h| h.hash), Some(h1.hash()));
+
+        cache.remove_header(&h1.hash());
+        cache.clear_hashes();
+        assert!(cache.header(&h1.hash()).is_none());
+        assert_eq!(cache.hash(1), None);
+    }
+}
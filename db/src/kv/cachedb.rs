@@ -3,6 +3,8 @@ use hash::H256;
 use kv::{Key, KeyState, KeyValue, KeyValueDatabase, Operation, Transaction, Value};
 use lru_cache::LruCache;
 use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use storage::{CacheStats, DatabaseStats};
 
 pub struct CacheDatabase<T>
 where
@@ -10,6 +12,8 @@ where
 {
     db: T,
     block: Mutex<LruCache<H256, KeyState<Block>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
 }
 
 impl<T> CacheDatabase<T>
@@ -22,6 +26,44 @@ where
             // TODO: reconfig this
             // 144 (blocks per day) * 14 (days) + 100 (arbitrary number)
             block: Mutex::new(LruCache::new(2116)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of block reads, served from the cache.
+    pub fn cache_hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of block reads, served from the underlying database.
+    pub fn cache_misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Share of block reads, served from the cache. Zero if nothing has been read yet.
+    pub fn hit_rate(&self) -> f64 {
+        let hits = self.cache_hits();
+        let total = hits + self.cache_misses();
+        if total == 0 {
+            0f64
+        } else {
+            hits as f64 / total as f64
+        }
+    }
+
+    /// Cache statistics.
+    pub fn stats(&self) -> CacheStats {
+        let (entries, max_entries) = {
+            let block = self.block.lock();
+            (block.len(), block.capacity())
+        };
+        CacheStats {
+            hits: self.cache_hits(),
+            misses: self.cache_misses(),
+            hit_rate: self.hit_rate(),
+            entries: entries,
+            max_entries: max_entries,
         }
     }
 }
@@ -51,9 +93,44 @@ where
         if let Key::Block(ref hash) = *key {
             let mut block = self.block.lock();
             if let Some(state) = block.get_mut(hash) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Ok(state.clone().map(Value::Block));
             }
+
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            let state = self.db.get(key)?;
+            // remember found blocks => next read is served from the cache
+            if let KeyState::Insert(Value::Block(ref value)) = state {
+                block.insert(hash.clone(), KeyState::Insert(value.clone()));
+            }
+            return Ok(state);
         }
         self.db.get(key)
     }
+
+    fn compact(&self) -> Result<(), String> {
+        self.db.compact()
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        Some(self.stats())
+    }
+
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        self.db.database_stats()
+    }
+
+    fn begin_batch(&self) -> Result<(), String> {
+        self.db.begin_batch()
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        self.db.commit_batch()
+    }
+
+    fn discard_batch(&self) {
+        // cache may contain blocks of the discarded batch
+        self.block.lock().clear();
+        self.db.discard_batch()
+    }
 }
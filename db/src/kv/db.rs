@@ -1,7 +1,75 @@
 use kv::{Key, KeyState, Transaction, Value};
+use std::sync::Arc;
+use storage::{CacheStats, DatabaseStats};
 
 pub trait KeyValueDatabase: Send + Sync {
     fn write(&self, tx: Transaction) -> Result<(), String>;
 
     fn get(&self, key: &Key) -> Result<KeyState<Value>, String>;
+
+    /// Compacts underlying storage. Noop for databases without on-disk representation.
+    fn compact(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Statistics of the blocks cache. None for databases without cache.
+    fn cache_stats(&self) -> Option<CacheStats> {
+        None
+    }
+
+    /// Statistics of the on-disk database. None for databases without on-disk representation.
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        None
+    }
+
+    /// Keeps all following writes of the calling thread in memory until `commit_batch` is called.
+    /// Fails for databases, which do not buffer writes.
+    fn begin_batch(&self) -> Result<(), String> {
+        Err("Database does not support batches".into())
+    }
+
+    /// Writes all changes of the calling thread, buffered since `begin_batch`, in a single transaction.
+    fn commit_batch(&self) -> Result<(), String> {
+        Err("Database does not support batches".into())
+    }
+
+    /// Drops all changes of the calling thread, buffered since `begin_batch`.
+    fn discard_batch(&self) {}
+}
+
+impl<T> KeyValueDatabase for Arc<T>
+where
+    T: KeyValueDatabase,
+{
+    fn write(&self, tx: Transaction) -> Result<(), String> {
+        (**self).write(tx)
+    }
+
+    fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
+        (**self).get(key)
+    }
+
+    fn compact(&self) -> Result<(), String> {
+        (**self).compact()
+    }
+
+    fn cache_stats(&self) -> Option<CacheStats> {
+        (**self).cache_stats()
+    }
+
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        (**self).database_stats()
+    }
+
+    fn begin_batch(&self) -> Result<(), String> {
+        (**self).begin_batch()
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        (**self).commit_batch()
+    }
+
+    fn discard_batch(&self) {
+        (**self).discard_batch()
+    }
 }
@@ -5,15 +5,22 @@ use kv::{
     Key, KeyState, KeyValueDatabase, Location, RawKey, RawKeyValue, RawOperation, RawTransaction,
     Transaction, Value,
 };
+use metrics;
 use rocksdb::{
     BlockBasedOptions, Cache, Column, DBCompactionStyle, DBIterator, IteratorMode, Options,
     ReadOptions, Writable, WriteBatch, WriteOptions, DB,
 };
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use storage::DatabaseStats;
 
 const DB_BACKGROUND_FLUSHES: i32 = 2;
 const DB_BACKGROUND_COMPACTIONS: i32 = 2;
+/// Upper bound of the key range used for manual compaction.
+/// All keys we store are shorter than this, so `[0xff; N]` is greater than any of them.
+const COMPACTION_RANGE_END: [u8; 64] = [0xff; 64];
 
 /// Compaction profile for the database settings
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -118,6 +125,12 @@ pub struct Database {
     db: DBAndColumns,
     write_opts: WriteOptions,
     read_opts: ReadOptions,
+    /// Unix timestamp (in seconds) of the last committed write.
+    last_write: AtomicU64,
+    /// Is manual compaction in progress?
+    compacting: AtomicBool,
+    /// Unix timestamp (in seconds) of the last completed manual compaction, 0 if never.
+    last_compaction: AtomicU64,
 }
 
 impl KeyValueDatabase for Database {
@@ -131,6 +144,14 @@ impl KeyValueDatabase for Database {
             None => Ok(KeyState::Unknown),
         }
     }
+
+    fn compact(&self) -> Result<(), String> {
+        Database::compact(self)
+    }
+
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        Some(self.stats())
+    }
 }
 
 impl Database {
@@ -259,9 +280,23 @@ impl Database {
             db: DBAndColumns { db: db, cfs: cfs },
             write_opts: write_opts,
             read_opts: read_opts,
+            last_write: AtomicU64::new(unix_time()),
+            compacting: AtomicBool::new(false),
+            last_compaction: AtomicU64::new(0),
         })
     }
 
+    /// Try to repair corrupted database at given path.
+    /// Database must not be opened while it is being repaired.
+    pub fn repair<P>(path: P) -> Result<(), String>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_string_lossy();
+        info!("Attempting DB repair for {}", path);
+        DB::repair(&Options::new(), &path)
+    }
+
     /// Commit transaction to database.
     pub fn write(&self, tx: RawTransaction) -> Result<(), String> {
         let DBAndColumns { ref db, ref cfs } = self.db;
@@ -282,7 +317,83 @@ impl Database {
                 },
             }
         }
-        db.write_opt(batch, &self.write_opts)
+        metrics::DB_WRITE.time(|| db.write_opt(batch, &self.write_opts))?;
+        self.last_write.store(unix_time(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Manually compact all columns of the database.
+    /// Columns are compacted one by one. Compaction writes are throttled by the RocksDB rate
+    /// limiter, configured from `write_rate_limit` of the compaction profile.
+    pub fn compact(&self) -> Result<(), String> {
+        if self.compacting.swap(true, Ordering::SeqCst) {
+            return Err("Database compaction is already in progress".into());
+        }
+
+        let DBAndColumns { ref db, ref cfs } = self.db;
+        trace!(target: "db", "Compacting database");
+        if cfs.is_empty() {
+            db.compact_range(&[], &COMPACTION_RANGE_END);
+        }
+        for cf in cfs {
+            db.compact_range_cf(*cf, &[], &COMPACTION_RANGE_END);
+        }
+
+        self.last_compaction.store(unix_time(), Ordering::Relaxed);
+        self.compacting.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Statistics of the database.
+    /// All values are read from RocksDB properties, so this call is cheap.
+    pub fn stats(&self) -> DatabaseStats {
+        let column_sizes = match self.db.cfs.len() {
+            0 => vec![self.column_size(Location::DB)],
+            columns => (0..columns as u32)
+                .map(|col| self.column_size(Location::Column(col)))
+                .collect(),
+        };
+        let last_compaction = match self.last_compaction.load(Ordering::Relaxed) {
+            0 => None,
+            timestamp => Some(timestamp),
+        };
+
+        DatabaseStats {
+            column_sizes: column_sizes,
+            is_compacting: self.compacting.load(Ordering::Relaxed) || self.compaction_pending(),
+            last_compaction: last_compaction,
+        }
+    }
+
+    /// Estimated size (in bytes) of live data, flushed to disk at given location.
+    fn column_size(&self, location: Location) -> u64 {
+        self.int_property(location, "rocksdb.estimate-live-data-size")
+    }
+
+    /// Is background compaction of any column pending?
+    fn compaction_pending(&self) -> bool {
+        match self.db.cfs.len() {
+            0 => self.int_property(Location::DB, "rocksdb.compaction-pending") != 0,
+            columns => (0..columns as u32).any(|col| {
+                self.int_property(Location::Column(col), "rocksdb.compaction-pending") != 0
+            }),
+        }
+    }
+
+    /// Read integer RocksDB property at given location. Unknown properties are read as 0.
+    fn int_property(&self, location: Location, name: &str) -> u64 {
+        let DBAndColumns { ref db, ref cfs } = self.db;
+        let value = match location {
+            Location::DB => db.get_property_int(name),
+            Location::Column(col) => db.get_property_int_cf(cfs[col as usize], name),
+        };
+        value.unwrap_or_default()
+    }
+
+    /// Time elapsed since the last write to the database.
+    pub fn idle_time(&self) -> Duration {
+        let last_write = self.last_write.load(Ordering::Relaxed);
+        Duration::from_secs(unix_time().saturating_sub(last_write))
     }
 
     /// Get value by key.
@@ -318,6 +429,13 @@ impl Database {
     }
 }
 
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     extern crate tempdir;
@@ -378,6 +496,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn stats_and_compaction() {
+        let tempdir = TempDir::new("").unwrap();
+        let db = Database::open(DatabaseConfig::with_columns(Some(2)), tempdir.path()).unwrap();
+
+        let mut batch = RawTransaction::default();
+        batch.insert_raw(Location::Column(1), b"key1", b"cat");
+        batch.insert_raw(Location::Column(1), b"key2", b"elephant");
+        db.write(batch).unwrap();
+
+        // data is still in memtable => it is not counted yet
+        let stats = db.stats();
+        assert_eq!(stats.column_sizes, vec![0, 0]);
+        assert_eq!(stats.last_compaction, None);
+
+        db.compact().unwrap();
+        let stats = db.stats();
+        assert_eq!(stats.column_sizes[0], 0);
+        assert!(stats.column_sizes[1] > 0);
+        assert!(stats.last_compaction.is_some());
+    }
+
     #[test]
     fn kvdb() {
         let tempdir = TempDir::new("").unwrap();
@@ -3,6 +3,8 @@ use chain::Block;
 use hash::H256;
 use kv::{Key, KeyState, KeyValue, KeyValueDatabase, Operation, Transaction, Value};
 use parking_lot::RwLock;
+use primitives::bigint::U256;
+use rug::Integer;
 use std::collections::HashMap;
 use std::mem::replace;
 use std::sync::Arc;
@@ -14,6 +16,8 @@ struct InnerDatabase {
     block: HashMap<H256, KeyState<Block>>,
     block_number: HashMap<H256, KeyState<u32>>,
     configuration: HashMap<&'static str, KeyState<Bytes>>,
+    randomness: HashMap<u32, KeyState<Integer>>,
+    chain_work: HashMap<u32, KeyState<U256>>,
 }
 
 #[derive(Default, Debug)]
@@ -50,12 +54,26 @@ impl MemoryDatabase {
                 state.into_operation(key, KeyValue::Configuration, Key::Configuration)
             });
 
+        let randomness = replace(&mut db.randomness, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::Randomness, Key::Randomness)
+            });
+
+        let chain_work = replace(&mut db.chain_work, HashMap::default())
+            .into_iter()
+            .flat_map(|(key, state)| {
+                state.into_operation(key, KeyValue::ChainWork, Key::ChainWork)
+            });
+
         Transaction {
             operations: meta
                 .chain(block_hash)
                 .chain(block)
                 .chain(block_number)
                 .chain(configuration)
+                .chain(randomness)
+                .chain(chain_work)
                 .collect(),
         }
     }
@@ -82,6 +100,12 @@ impl KeyValueDatabase for MemoryDatabase {
                     KeyValue::Configuration(key, value) => {
                         db.configuration.insert(key, KeyState::Insert(value));
                     }
+                    KeyValue::Randomness(key, value) => {
+                        db.randomness.insert(key, KeyState::Insert(value));
+                    }
+                    KeyValue::ChainWork(key, value) => {
+                        db.chain_work.insert(key, KeyState::Insert(value));
+                    }
                 },
                 Operation::Delete(delete) => match delete {
                     Key::Meta(key) => {
@@ -99,6 +123,12 @@ impl KeyValueDatabase for MemoryDatabase {
                     Key::Configuration(key) => {
                         db.configuration.insert(key, KeyState::Delete);
                     }
+                    Key::Randomness(key) => {
+                        db.randomness.insert(key, KeyState::Delete);
+                    }
+                    Key::ChainWork(key) => {
+                        db.chain_work.insert(key, KeyState::Delete);
+                    }
                 },
             }
         }
@@ -138,6 +168,18 @@ impl KeyValueDatabase for MemoryDatabase {
                 .cloned()
                 .unwrap_or_default()
                 .map(Value::Configuration),
+            Key::Randomness(ref key) => db
+                .randomness
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::Randomness),
+            Key::ChainWork(ref key) => db
+                .chain_work
+                .get(key)
+                .cloned()
+                .unwrap_or_default()
+                .map(Value::ChainWork),
         };
 
         Ok(result)
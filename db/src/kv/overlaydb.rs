@@ -1,5 +1,7 @@
 use kv::{Key, KeyState, KeyValueDatabase, MemoryDatabase, Transaction, Value};
 use parking_lot::Mutex;
+use std::thread::{self, ThreadId};
+use storage::DatabaseStats;
 
 pub struct OverlayDatabase<'a, T>
 where
@@ -49,6 +51,11 @@ where
     overlay: MemoryDatabase,
     operations: Mutex<usize>,
     max_operations: usize,
+    /// Thread, which has opened the batch. Its operations are kept in `batch_overlay`
+    /// until the batch is committed. Operations of other threads are not batched.
+    batch_owner: Mutex<Option<ThreadId>>,
+    /// Operations of the open batch.
+    batch_overlay: MemoryDatabase,
 }
 
 impl<T> AutoFlushingOverlayDatabase<T>
@@ -61,12 +68,18 @@ where
             overlay: MemoryDatabase::default(),
             operations: Mutex::default(),
             max_operations: max_operations,
+            batch_owner: Mutex::default(),
+            batch_overlay: MemoryDatabase::default(),
         }
     }
 
     fn flush(&self) -> Result<(), String> {
         self.db.write(self.overlay.drain_transaction())
     }
+
+    fn is_batch_owner(&self) -> bool {
+        *self.batch_owner.lock() == Some(thread::current().id())
+    }
 }
 
 impl<T> KeyValueDatabase for AutoFlushingOverlayDatabase<T>
@@ -75,9 +88,13 @@ where
 {
     fn write(&self, tx: Transaction) -> Result<(), String> {
         let mut operations = self.operations.lock();
+        if self.is_batch_owner() {
+            return self.batch_overlay.write(tx);
+        }
+
         *operations += 1;
         self.overlay.write(tx)?;
-        if *operations == self.max_operations {
+        if *operations >= self.max_operations {
             self.flush()?;
             *operations = 0;
         }
@@ -85,11 +102,58 @@ where
     }
 
     fn get(&self, key: &Key) -> Result<KeyState<Value>, String> {
+        if self.is_batch_owner() {
+            match self.batch_overlay.get(key)? {
+                KeyState::Unknown => (),
+                exists => return Ok(exists),
+            }
+        }
         match self.overlay.get(key)? {
             KeyState::Unknown => self.db.get(key),
             exists => Ok(exists),
         }
     }
+
+    fn compact(&self) -> Result<(), String> {
+        // flush pending operations first, so that they are compacted too
+        let mut operations = self.operations.lock();
+        self.flush()?;
+        *operations = 0;
+        self.db.compact()
+    }
+
+    fn database_stats(&self) -> Option<DatabaseStats> {
+        self.db.database_stats()
+    }
+
+    fn begin_batch(&self) -> Result<(), String> {
+        let mut batch_owner = self.batch_owner.lock();
+        if batch_owner.is_some() {
+            return Err("Batch is already open".into());
+        }
+        *batch_owner = Some(thread::current().id());
+        Ok(())
+    }
+
+    fn commit_batch(&self) -> Result<(), String> {
+        let mut operations = self.operations.lock();
+        if !self.is_batch_owner() {
+            return Err("Batch is not open".into());
+        }
+        self.overlay.write(self.batch_overlay.drain_transaction())?;
+        *self.batch_owner.lock() = None;
+        self.flush()?;
+        *operations = 0;
+        Ok(())
+    }
+
+    fn discard_batch(&self) {
+        let _operations = self.operations.lock();
+        if self.is_batch_owner() {
+            self.batch_overlay.drain_transaction();
+            *self.batch_owner.lock() = None;
+        }
+    }
 }
 
 impl<T> Drop for AutoFlushingOverlayDatabase<T>
@@ -100,3 +164,51 @@ where
         self.flush().expect("Failed to save database");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AutoFlushingOverlayDatabase;
+    use hash::H256;
+    use kv::{Key, KeyValue, KeyValueDatabase, MemoryDatabase, Transaction};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn insert_block_hash(db: &dyn KeyValueDatabase, number: u32) {
+        let mut tx = Transaction::new();
+        tx.insert(KeyValue::BlockHash(number, H256::from(number as u8)));
+        db.write(tx).unwrap();
+    }
+
+    fn has_block_hash(db: &dyn KeyValueDatabase, number: u32) -> bool {
+        db.get(&Key::BlockHash(number))
+            .unwrap()
+            .into_option()
+            .is_some()
+    }
+
+    #[test]
+    fn discarded_batch_keeps_writes_of_other_threads() {
+        let db = Arc::new(AutoFlushingOverlayDatabase::new(
+            MemoryDatabase::default(),
+            50,
+        ));
+        db.begin_batch().unwrap();
+        assert!(db.begin_batch().is_err());
+        insert_block_hash(&*db, 1);
+        assert!(has_block_hash(&*db, 1));
+
+        let other_db = db.clone();
+        thread::spawn(move || {
+            // other threads neither see, nor write to the open batch
+            assert!(!has_block_hash(&*other_db, 1));
+            insert_block_hash(&*other_db, 2);
+            assert!(other_db.commit_batch().is_err());
+        })
+        .join()
+        .unwrap();
+
+        db.discard_batch();
+        assert!(!has_block_hash(&*db, 1));
+        assert!(has_block_hash(&*db, 2));
+    }
+}
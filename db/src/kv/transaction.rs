@@ -1,6 +1,8 @@
 use bytes::Bytes;
 use chain::Block;
 use hash::H256;
+use primitives::bigint::U256;
+use rug::Integer;
 use ser::{deserialize, serialize};
 
 pub const COL_COUNT: u32 = 10;
@@ -9,6 +11,8 @@ pub const COL_BLOCK_HASHES: u32 = 1;
 pub const COL_BLOCKS: u32 = 2;
 pub const COL_BLOCK_NUMBERS: u32 = 3;
 pub const COL_CONFIGURATION: u32 = 4;
+pub const COL_RANDOMNESS: u32 = 5;
+pub const COL_CHAIN_WORK: u32 = 6;
 
 #[derive(Debug)]
 pub enum Operation {
@@ -24,6 +28,8 @@ pub enum KeyValue {
     Block(H256, Block),
     BlockNumber(H256, u32),
     Configuration(&'static str, Bytes),
+    Randomness(u32, Integer),
+    ChainWork(u32, U256),
 }
 
 #[derive(Debug)]
@@ -33,6 +39,8 @@ pub enum Key {
     Block(H256),
     BlockNumber(H256),
     Configuration(&'static str),
+    Randomness(u32),
+    ChainWork(u32),
 }
 
 #[derive(Debug, Clone)]
@@ -42,6 +50,8 @@ pub enum Value {
     Block(Block),
     BlockNumber(u32),
     Configuration(Bytes),
+    Randomness(Integer),
+    ChainWork(U256),
 }
 
 impl Value {
@@ -52,6 +62,8 @@ impl Value {
             Key::Block(_) => deserialize(bytes).map(Value::Block),
             Key::BlockNumber(_) => deserialize(bytes).map(Value::BlockNumber),
             Key::Configuration(_) => deserialize(bytes).map(Value::Configuration),
+            Key::Randomness(_) => deserialize(bytes).map(Value::Randomness),
+            Key::ChainWork(_) => deserialize(bytes).map(Value::ChainWork),
         }
         .map_err(|e| format!("{:?}", e))
     }
@@ -90,6 +102,20 @@ impl Value {
             _ => None,
         }
     }
+
+    pub fn as_randomness(self) -> Option<Integer> {
+        match self {
+            Value::Randomness(randomness) => Some(randomness),
+            _ => None,
+        }
+    }
+
+    pub fn as_chain_work(self) -> Option<U256> {
+        match self {
+            Value::ChainWork(chain_work) => Some(chain_work),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -202,6 +228,12 @@ impl<'a> From<&'a KeyValue> for RawKeyValue {
             KeyValue::Configuration(ref key, ref value) => {
                 (COL_CONFIGURATION, serialize(key), serialize(value))
             }
+            KeyValue::Randomness(ref key, ref value) => {
+                (COL_RANDOMNESS, serialize(key), serialize(value))
+            }
+            KeyValue::ChainWork(ref key, ref value) => {
+                (COL_CHAIN_WORK, serialize(key), serialize(value))
+            }
         };
 
         RawKeyValue {
@@ -237,6 +269,8 @@ impl<'a> From<&'a Key> for RawKey {
             Key::Block(ref key) => (COL_BLOCKS, serialize(key)),
             Key::BlockNumber(ref key) => (COL_BLOCK_NUMBERS, serialize(key)),
             Key::Configuration(ref key) => (COL_CONFIGURATION, serialize(key)),
+            Key::Randomness(ref key) => (COL_RANDOMNESS, serialize(key)),
+            Key::ChainWork(ref key) => (COL_CHAIN_WORK, serialize(key)),
         };
 
         RawKey {
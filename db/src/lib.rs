@@ -5,14 +5,19 @@ extern crate parking_lot;
 extern crate log;
 extern crate bit_vec;
 extern crate lru_cache;
+extern crate rug;
 
 extern crate chain;
+extern crate metrics;
 extern crate primitives;
 extern crate serialization as ser;
 extern crate storage;
 
 mod block_chain_db;
+mod header_cache;
 pub mod kv;
+mod migrations;
 
 pub use block_chain_db::{BlockChainDatabase, ForkChainDatabase};
+pub use migrations::SCHEMA_VERSION;
 pub use primitives::{bytes, hash};
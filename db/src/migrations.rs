@@ -0,0 +1,219 @@
+//! Database schema migrations.
+//!
+//! Schema version is stored in the `COL_META` column. Databases, created before the
+//! versioning has been introduced, have version 0. When database is opened, all migrations
+//! between its version and `SCHEMA_VERSION` are applied in order.
+
+use chain::Block;
+use hash::H256;
+use kv::{Key, KeyValue, KeyValueDatabase, Transaction, Value};
+use primitives::bigint::{Uint, U256};
+use ser::{deserialize, serialize};
+
+const KEY_SCHEMA_VERSION: &'static str = "schema_version";
+
+/// Current version of the database schema.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Number of blocks, updated by a single migration transaction.
+const MIGRATION_BATCH_SIZE: u32 = 1000;
+
+/// Single migration step, which upgrades schema from `version - 1` to `version`.
+struct Migration<T> {
+    version: u32,
+    description: &'static str,
+    apply: fn(&T) -> Result<(), String>,
+}
+
+fn migrations<T>() -> Vec<Migration<T>>
+where
+    T: KeyValueDatabase,
+{
+    vec![
+        Migration {
+            version: 1,
+            description: "index randomness by height",
+            apply: index_randomness,
+        },
+        Migration {
+            version: 2,
+            description: "index chain work by height",
+            apply: index_chain_work,
+        },
+    ]
+}
+
+/// Read schema version of the database. Returns None for empty databases.
+pub fn schema_version<T>(db: &T) -> Result<Option<u32>, String>
+where
+    T: KeyValueDatabase,
+{
+    let version = db
+        .get(&Key::Meta(KEY_SCHEMA_VERSION))?
+        .into_option()
+        .and_then(Value::as_meta);
+    match version {
+        Some(version) => deserialize(&**version)
+            .map(Some)
+            .map_err(|err| format!("Invalid schema version: {:?}", err)),
+        None if canon_block_hash(db, 0)?.is_none() => Ok(None),
+        None => Ok(Some(0)),
+    }
+}
+
+/// Upgrade schema of the database to `SCHEMA_VERSION`.
+pub fn migrate<T>(db: &T) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+{
+    let version = match schema_version(db)? {
+        Some(version) if version > SCHEMA_VERSION => {
+            return Err(format!(
+                "Database schema version {} is newer than supported version {}",
+                version, SCHEMA_VERSION
+            ))
+        }
+        Some(version) => version,
+        // empty database is created with the current schema
+        None => return write_schema_version(db, SCHEMA_VERSION),
+    };
+
+    for migration in migrations::<T>()
+        .into_iter()
+        .filter(|migration| migration.version > version)
+    {
+        info!(target: "db", "Migrating database to schema version {}: {}", migration.version, migration.description);
+        (migration.apply)(db)?;
+        write_schema_version(db, migration.version)?;
+    }
+
+    Ok(())
+}
+
+fn write_schema_version<T>(db: &T, version: u32) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+{
+    let mut update = Transaction::new();
+    update.insert(KeyValue::Meta(KEY_SCHEMA_VERSION, serialize(&version)));
+    db.write(update)
+}
+
+fn canon_block_hash<T>(db: &T, number: u32) -> Result<Option<H256>, String>
+where
+    T: KeyValueDatabase,
+{
+    Ok(db
+        .get(&Key::BlockHash(number))?
+        .into_option()
+        .and_then(Value::as_block_hash))
+}
+
+/// Calls `f` for every block of the canon chain, writing updates in batches.
+fn update_canon_blocks<T, F>(db: &T, mut f: F) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+    F: FnMut(&mut Transaction, u32, Block),
+{
+    let mut update = Transaction::new();
+    let mut number = 0;
+    while let Some(hash) = canon_block_hash(db, number)? {
+        let block = db
+            .get(&Key::Block(hash.clone()))?
+            .into_option()
+            .and_then(Value::as_block)
+            .ok_or_else(|| format!("Canon block {} is missing", hash.reversed()))?;
+        f(&mut update, number, block);
+
+        number += 1;
+        if number % MIGRATION_BATCH_SIZE == 0 {
+            db.write(update)?;
+            update = Transaction::new();
+        }
+    }
+    db.write(update)
+}
+
+fn index_randomness<T>(db: &T) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+{
+    update_canon_blocks(db, |update, number, block| {
+        update.insert(KeyValue::Randomness(number, block.block_header.solution));
+    })
+}
+
+fn index_chain_work<T>(db: &T) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+{
+    let mut chain_work = U256::zero();
+    update_canon_blocks(db, |update, number, block| {
+        chain_work = chain_work + block.block_header.bits.to_work();
+        update.insert(KeyValue::ChainWork(number, chain_work));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{migrate, schema_version, write_schema_version, SCHEMA_VERSION};
+    use chain::Block;
+    use kv::{Key, KeyValue, KeyValueDatabase, MemoryDatabase, Transaction, Value};
+
+    fn legacy_database(blocks: &[Block]) -> MemoryDatabase {
+        let db = MemoryDatabase::default();
+        let mut update = Transaction::new();
+        for (number, block) in blocks.iter().enumerate() {
+            update.insert(KeyValue::Block(block.hash(), block.clone()));
+            update.insert(KeyValue::BlockHash(number as u32, block.hash()));
+            update.insert(KeyValue::BlockNumber(block.hash(), number as u32));
+        }
+        db.write(update).unwrap();
+        db
+    }
+
+    #[test]
+    fn empty_database_is_created_with_current_schema() {
+        let db = MemoryDatabase::default();
+        assert_eq!(schema_version(&db), Ok(None));
+        migrate(&db).unwrap();
+        assert_eq!(schema_version(&db), Ok(Some(SCHEMA_VERSION)));
+    }
+
+    #[test]
+    fn legacy_database_is_migrated() {
+        let b0 = test_data::block_h0();
+        let b1 = test_data::block_h1();
+        let db = legacy_database(&[b0.clone(), b1.clone()]);
+        assert_eq!(schema_version(&db), Ok(Some(0)));
+
+        migrate(&db).unwrap();
+        assert_eq!(schema_version(&db), Ok(Some(SCHEMA_VERSION)));
+
+        let randomness = db
+            .get(&Key::Randomness(1))
+            .unwrap()
+            .into_option()
+            .and_then(Value::as_randomness);
+        assert_eq!(randomness, Some(b1.block_header.solution.clone()));
+
+        let chain_work = db
+            .get(&Key::ChainWork(1))
+            .unwrap()
+            .into_option()
+            .and_then(Value::as_chain_work);
+        assert_eq!(
+            chain_work,
+            Some(b0.block_header.bits.to_work() + b1.block_header.bits.to_work())
+        );
+    }
+
+    #[test]
+    fn newer_schema_is_rejected() {
+        let db = legacy_database(&[test_data::block_h0()]);
+        write_schema_version(&db, SCHEMA_VERSION + 1).unwrap();
+        assert!(migrate(&db).is_err());
+    }
+}
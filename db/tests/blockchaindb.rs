@@ -1,12 +1,19 @@
 extern crate chain;
 extern crate db;
 extern crate storage;
+extern crate tempdir;
 extern crate test_data;
 
 use chain::IndexedBlock;
-use db::kv::{MemoryDatabase, SharedMemoryDatabase};
+use db::kv::{
+    CacheDatabase, Key, KeyValue, KeyValueDatabase, MemoryDatabase, SharedMemoryDatabase,
+    Transaction,
+};
 use db::BlockChainDatabase;
-use storage::{BlockProvider, ForkChain, SideChainOrigin};
+use std::fs::{self, OpenOptions};
+use std::sync::Arc;
+use storage::{BlockHeaderProvider, BlockProvider, Error, ForkChain, SideChainOrigin, Store};
+use tempdir::TempDir;
 
 #[test]
 fn insert_block() {
@@ -48,6 +55,131 @@ fn insert_block() {
     assert!(store.block_number(b2.hash()).is_none());
 }
 
+#[test]
+fn randomness_by_height() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    assert!(store.randomness(1).is_none());
+
+    store.canonize(b1.hash()).unwrap();
+    assert_eq!(Some(b0.randomness().clone()), store.randomness(0));
+    assert_eq!(Some(b1.randomness().clone()), store.randomness(1));
+
+    store.decanonize().unwrap();
+    assert!(store.randomness(1).is_none());
+}
+
+#[test]
+fn chain_work_by_height() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+
+    let b0_work = b0.header.raw.bits.to_work();
+    let b1_work = b1.header.raw.bits.to_work();
+    assert_eq!(Some(b0_work), store.chain_work(0));
+    assert_eq!(Some(b0_work + b1_work), store.chain_work(1));
+
+    store.decanonize().unwrap();
+    assert!(store.chain_work(1).is_none());
+}
+
+#[test]
+fn canonize_requires_parent_chain_work() {
+    let shared_database = SharedMemoryDatabase::default();
+    let store = BlockChainDatabase::open(shared_database.clone());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.insert(b1.clone()).unwrap();
+
+    // chain work of the parent block is lost
+    let mut tx = Transaction::new();
+    tx.delete(Key::ChainWork(0));
+    shared_database.write(tx).unwrap();
+
+    assert_eq!(store.canonize(b1.hash()), Err(Error::UnknownChainWork(0)));
+    assert_eq!(0, store.best_block().number);
+}
+
+#[test]
+fn rollback_best_block() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+
+    assert_eq!(b0.hash(), &store.rollback_best().unwrap());
+    assert_eq!(0, store.best_block().number);
+    assert!(store.block_number(b1.hash()).is_none());
+    assert!(!store.contains_block(b1.hash().clone().into()));
+
+    // rolled back block can be inserted again
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    assert_eq!(1, store.best_block().number);
+}
+
+#[test]
+fn header_cache_follows_canon_chain() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+
+    let header = store.block_header(1.into()).unwrap();
+    assert_eq!(b1.hash(), &header.hash);
+    assert_eq!(b1.header.raw, header.raw);
+
+    store.rollback_best().unwrap();
+    assert!(store.block_hash(1).is_none());
+    assert!(store.block_header(1.into()).is_none());
+    assert!(store.block_header(b1.hash().clone().into()).is_none());
+    assert_eq!(b0.hash(), &store.block_header(0.into()).unwrap().hash);
+}
+
+#[test]
+fn insert_duplicate_block() {
+    let store = BlockChainDatabase::open(MemoryDatabase::default());
+    let b0: IndexedBlock = test_data::block_h0().into();
+    let b1: IndexedBlock = test_data::block_h1().into();
+
+    store.insert(b0.clone()).unwrap();
+    store.insert(b1.clone()).unwrap();
+    assert_eq!(
+        store.insert(b1.clone()),
+        Err(Error::Duplicate(b1.hash().clone()))
+    );
+
+    store.canonize(b0.hash()).unwrap();
+    store.canonize(b1.hash()).unwrap();
+    assert_eq!(
+        store.insert(b0.clone()),
+        Err(Error::Duplicate(b0.hash().clone()))
+    );
+    assert_eq!(1, store.best_block().number);
+}
+
 #[test]
 fn reopen_db() {
     let shared_database = SharedMemoryDatabase::default();
@@ -127,3 +259,163 @@ fn switch_to_simple_fork() {
     assert_eq!(b2.hash(), &store.best_block().hash);
     assert_eq!(store.best_block().hash, store.block_hash(2).unwrap());
 }
+
+#[test]
+fn compact_disk_database() {
+    let tempdir = TempDir::new("").unwrap();
+    let store = BlockChainDatabase::open_at_path(tempdir.path(), 16).unwrap();
+    let b0: IndexedBlock = test_data::genesis().into();
+    let blocks = test_data::build_n_empty_blocks_from_genesis(1000, 0);
+
+    store.insert(b0.clone()).unwrap();
+    store.canonize(b0.hash()).unwrap();
+    for block in blocks.iter().cloned() {
+        let block: IndexedBlock = block.into();
+        store.insert(block.clone()).unwrap();
+        store.canonize(block.hash()).unwrap();
+    }
+
+    store.compact().unwrap();
+
+    assert_eq!(1000, store.best_block().number);
+    assert_eq!(b0.hash(), &store.block_hash(0).unwrap());
+    for (index, block) in blocks.iter().enumerate() {
+        let number = index as u32 + 1;
+        assert_eq!(block.hash(), store.block_hash(number).unwrap());
+        assert_eq!(number, store.block_number(&block.hash()).unwrap());
+        assert_eq!(
+            block.block_header,
+            store.block(number.into()).unwrap().header.raw
+        );
+    }
+}
+
+#[test]
+fn repair_truncated_disk_database() {
+    let tempdir = TempDir::new("").unwrap();
+    let b0: IndexedBlock = test_data::genesis().into();
+    let blocks = test_data::build_n_empty_blocks_from_genesis(100, 0);
+
+    {
+        let store = BlockChainDatabase::open_at_path(tempdir.path(), 16).unwrap();
+        store.insert(b0.clone()).unwrap();
+        store.canonize(b0.hash()).unwrap();
+        for block in blocks.iter().cloned() {
+            let block: IndexedBlock = block.into();
+            store.insert(block.clone()).unwrap();
+            store.canonize(block.hash()).unwrap();
+        }
+    }
+
+    // simulate power loss by truncating write-ahead log files
+    for entry in fs::read_dir(tempdir.path()).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(false, |ext| ext == "log") {
+            let file = OpenOptions::new().write(true).open(&path).unwrap();
+            let len = file.metadata().unwrap().len();
+            file.set_len(len / 2).unwrap();
+        }
+    }
+
+    BlockChainDatabase::repair(tempdir.path()).unwrap();
+
+    let store = BlockChainDatabase::open_at_path(tempdir.path(), 16).unwrap();
+    let best_block = store.best_block();
+    assert!(best_block.number <= 100);
+    assert_eq!(
+        Some(best_block.hash.clone()),
+        store.block_hash(best_block.number)
+    );
+    assert!(store.block(best_block.hash.into()).is_some());
+}
+
+#[test]
+fn reindex_disk_database() {
+    let tempdir = TempDir::new("").unwrap();
+    let b0: IndexedBlock = test_data::genesis().into();
+    let blocks: Vec<IndexedBlock> = test_data::build_n_empty_blocks_from_genesis(3, 0)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+
+    {
+        let store = BlockChainDatabase::open_at_path(tempdir.path(), 16).unwrap();
+        store.insert(b0.clone()).unwrap();
+        store.canonize(b0.hash()).unwrap();
+        for block in blocks.iter().cloned() {
+            store.insert(block.clone()).unwrap();
+            store.canonize(block.hash()).unwrap();
+        }
+    }
+
+    let hashes = BlockChainDatabase::reindex(tempdir.path()).unwrap();
+    let mut expected_hashes = vec![b0.hash().clone()];
+    expected_hashes.extend(blocks.iter().map(|block| block.hash().clone()));
+    assert_eq!(expected_hashes, hashes);
+
+    let store = BlockChainDatabase::open_at_path(tempdir.path(), 16).unwrap();
+    assert!(store.best_block().hash.is_zero());
+    assert!(store.block_hash(0).is_none());
+    assert!(store.randomness(1).is_none());
+    assert!(store.contains_block(blocks[2].hash().clone().into()));
+
+    store.canonize(b0.hash()).unwrap();
+    assert_eq!(
+        Err(Error::CannotDecanonize),
+        store.take_block(b0.hash()).map(|_| ())
+    );
+    for block in &blocks {
+        let block = store.take_block(block.hash()).unwrap().unwrap();
+        store.insert(block.clone()).unwrap();
+        store.canonize(block.hash()).unwrap();
+    }
+    assert_eq!(3, store.best_block().number);
+    assert_eq!(blocks[2].hash(), &store.block_hash(3).unwrap());
+}
+
+#[test]
+fn cache_database_hit_rate() {
+    let b0 = test_data::block_h0();
+    let b1 = test_data::block_h1();
+
+    // b0 is only known to the underlying database
+    let memory = Arc::new(MemoryDatabase::default());
+    let mut tx = Transaction::new();
+    tx.insert(KeyValue::Block(b0.hash(), b0.clone()));
+    memory.write(tx).unwrap();
+
+    // b1 is written through the cache
+    let cache = CacheDatabase::new(memory.clone());
+    let mut tx = Transaction::new();
+    tx.insert(KeyValue::Block(b1.hash(), b1.clone()));
+    cache.write(tx).unwrap();
+    assert_eq!(cache.hit_rate(), 0f64);
+
+    let keys = vec![Key::Block(b0.hash()), Key::Block(b1.hash())];
+
+    // first access: b0 is read from the underlying database
+    for key in &keys {
+        assert!(cache.get(key).unwrap().into_option().is_some());
+    }
+    assert_eq!(cache.cache_hits(), 1);
+    assert_eq!(cache.cache_misses(), 1);
+    assert_eq!(cache.hit_rate(), 0.5);
+
+    // second access: both blocks are read from the cache
+    for key in &keys {
+        assert!(cache.get(key).unwrap().into_option().is_some());
+    }
+    assert_eq!(cache.cache_hits(), 3);
+    assert_eq!(cache.cache_misses(), 1);
+    let second_access_hits = cache.cache_hits() - 1;
+    let second_access_reads = cache.cache_hits() + cache.cache_misses() - 2;
+    assert_eq!(second_access_hits as f64 / second_access_reads as f64, 1.0);
+
+    let stats = cache.stats();
+    assert_eq!(stats.hits, 3);
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.hit_rate, 0.75);
+    assert_eq!(stats.entries, 2);
+    assert_eq!(stats.max_entries, 2116);
+    assert_eq!(cache.cache_stats(), Some(stats));
+}
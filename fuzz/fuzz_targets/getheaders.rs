@@ -0,0 +1,22 @@
+//! Feeds arbitrary bytes into `getheaders` payload deserialization.
+//!
+//! Run with `cargo fuzz run getheaders fuzz/corpus/getheaders -- -dict=fuzz/dictionaries/getheaders.dict`.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate message;
+extern crate serialization as ser;
+
+use message::types::GetHeaders;
+
+fuzz_target!(|data: &[u8]| {
+    // any input must either be rejected or be deserialized to a value, which survives roundtrip
+    if let Ok(get_headers) = ser::deserialize::<_, GetHeaders>(data) {
+        let serialized = ser::serialize(&get_headers);
+        assert_eq!(
+            ser::deserialize::<_, GetHeaders>(&serialized[..]),
+            Ok(get_headers)
+        );
+    }
+});
@@ -0,0 +1,22 @@
+//! AFL++ version of the `getheaders` fuzz target.
+//!
+//! Run with `cargo afl fuzz -i fuzz/corpus/getheaders -o fuzz/afl-out -x fuzz/dictionaries/getheaders.dict target/debug/getheaders_afl`.
+
+#[macro_use]
+extern crate afl;
+extern crate message;
+extern crate serialization as ser;
+
+use message::types::GetHeaders;
+
+fn main() {
+    fuzz!(|data: &[u8]| {
+        if let Ok(get_headers) = ser::deserialize::<_, GetHeaders>(data) {
+            let serialized = ser::serialize(&get_headers);
+            assert_eq!(
+                ser::deserialize::<_, GetHeaders>(&serialized[..]),
+                Ok(get_headers)
+            );
+        }
+    });
+}
@@ -23,6 +23,27 @@ impl Services {
         self
     }
 
+    /// Node is able to answer queries on randomness, indexed by block height.
+    /// Reserved: there is no p2p randomness query yet, so randchaind does not advertise it.
+    pub fn randomness_index(&self) -> bool {
+        self.bit_at(24)
+    }
+
+    pub fn with_randomness_index(mut self, v: bool) -> Self {
+        self.set_bit(24, v);
+        self
+    }
+
+    /// Node is able to relay compact blocks.
+    pub fn compact_blocks(&self) -> bool {
+        self.bit_at(25)
+    }
+
+    pub fn with_compact_blocks(mut self, v: bool) -> Self {
+        self.set_bit(25, v);
+        self
+    }
+
     pub fn includes(&self, other: &Self) -> bool {
         self.0 & other.0 == other.0
     }
@@ -52,4 +73,21 @@ mod test {
         assert!(s1.includes(&s2));
         assert!(s2.includes(&s1));
     }
+
+    #[test]
+    fn test_randchain_services() {
+        let s = Services::default()
+            .with_network(true)
+            .with_randomness_index(true);
+        assert!(s.network());
+        assert!(s.randomness_index());
+        assert!(!s.compact_blocks());
+        assert_eq!(u64::from(s), 1 | (1 << 24));
+
+        let s = s.with_compact_blocks(true).with_randomness_index(false);
+        assert!(!s.randomness_index());
+        assert!(s.compact_blocks());
+        assert!(s.includes(&Services::default().with_compact_blocks(true)));
+        assert!(!s.includes(&Services::default().with_randomness_index(true)));
+    }
 }
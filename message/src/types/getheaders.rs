@@ -1,9 +1,11 @@
 use hash::H256;
-use ser::{Reader, Stream};
+use ser::{Deserializable, Error as ReaderError, Reader, Serializable, Stream};
 use std::io;
 use {MessageResult, Payload};
 
 pub const GETHEADERS_MAX_RESPONSE_HEADERS: usize = 2_000;
+/// Max number of block locator hashes in single request (same as in Bitcoin Core)
+pub const GETHEADERS_MAX_LOCATOR_HASHES: usize = 101;
 
 #[derive(Debug, PartialEq)]
 pub struct GetHeaders {
@@ -35,20 +37,78 @@ impl Payload for GetHeaders {
     where
         T: io::Read,
     {
-        let get_blocks = GetHeaders {
-            version: reader.read()?,
-            block_locator_hashes: reader.read_list_max(2000)?,
-            hash_stop: reader.read()?,
-        };
-
-        Ok(get_blocks)
+        Ok(reader.read()?)
     }
 
     fn serialize_payload(&self, stream: &mut Stream, _version: u32) -> MessageResult<()> {
+        stream.append(self);
+        Ok(())
+    }
+}
+
+impl Serializable for GetHeaders {
+    fn serialize(&self, stream: &mut Stream) {
         stream
             .append(&self.version)
             .append_list(&self.block_locator_hashes)
             .append(&self.hash_stop);
-        Ok(())
+    }
+}
+
+impl Deserializable for GetHeaders {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        // hash_stop is read as fixed-size H256 => it is always exactly 32 bytes
+        let get_headers = GetHeaders {
+            version: reader.read()?,
+            block_locator_hashes: reader.read_list_max(GETHEADERS_MAX_LOCATOR_HASHES)?,
+            hash_stop: reader.read()?,
+        };
+
+        Ok(get_headers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GetHeaders, GETHEADERS_MAX_LOCATOR_HASHES};
+    use hash::H256;
+    use ser::{deserialize, serialize, Error as ReaderError};
+
+    #[test]
+    fn test_getheaders_roundtrip() {
+        let get_headers = GetHeaders::with_block_locator_hashes(vec![H256::from(1), H256::from(2)]);
+        let serialized = serialize(&get_headers);
+        assert_eq!(serialized.len(), 4 + 1 + 2 * 32 + 32);
+        assert_eq!(
+            deserialize::<_, GetHeaders>(&serialized[..]).unwrap(),
+            get_headers
+        );
+    }
+
+    #[test]
+    fn test_getheaders_too_many_locator_hashes() {
+        let mut hashes = vec![H256::default(); GETHEADERS_MAX_LOCATOR_HASHES];
+        let serialized = serialize(&GetHeaders::with_block_locator_hashes(hashes.clone()));
+        assert!(deserialize::<_, GetHeaders>(&serialized[..]).is_ok());
+
+        hashes.push(H256::default());
+        let serialized = serialize(&GetHeaders::with_block_locator_hashes(hashes));
+        assert_eq!(
+            deserialize::<_, GetHeaders>(&serialized[..]).unwrap_err(),
+            ReaderError::MalformedData
+        );
+    }
+
+    #[test]
+    fn test_getheaders_short_hash_stop() {
+        let get_headers = GetHeaders::with_block_locator_hashes(vec![H256::from(1)]);
+        let serialized = serialize(&get_headers);
+        assert_eq!(
+            deserialize::<_, GetHeaders>(&serialized[..serialized.len() - 1]).unwrap_err(),
+            ReaderError::UnexpectedEnd
+        );
     }
 }
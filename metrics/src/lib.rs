@@ -0,0 +1,72 @@
+//! Node metrics.
+//!
+//! Metrics are process-wide and updated by instrumentation hooks in sync, verification and db.
+//! `render` encodes current values of all metrics in Prometheus text exposition format.
+
+mod metric;
+
+pub use metric::{Counter, Gauge, Metric, Timer};
+
+/// Height of the best storage block.
+pub static BEST_BLOCK_HEIGHT: Gauge = Gauge::new(
+    "randchain_best_block_height",
+    "Height of the best block in the storage.",
+);
+/// Number of synchronization peers.
+pub static PEERS: Gauge = Gauge::new("randchain_peers", "Number of synchronization peers.");
+/// 1 if synchronization is in progress, 0 if node is saturated.
+pub static SYNCHRONIZING: Gauge = Gauge::new(
+    "randchain_synchronizing",
+    "1 if blocks synchronization is in progress, 0 otherwise.",
+);
+/// Number of blocks, scheduled for requesting.
+pub static SCHEDULED_BLOCKS: Gauge = Gauge::new(
+    "randchain_sync_scheduled_blocks",
+    "Number of blocks, scheduled for requesting from peers.",
+);
+/// Number of blocks, requested from peers.
+pub static REQUESTED_BLOCKS: Gauge = Gauge::new(
+    "randchain_sync_requested_blocks",
+    "Number of blocks, requested from peers.",
+);
+/// Number of blocks in verification queue.
+pub static VERIFYING_BLOCKS: Gauge = Gauge::new(
+    "randchain_verification_queue_blocks",
+    "Number of blocks in verification queue.",
+);
+/// Number of blocks in orphan pool.
+pub static ORPHAN_BLOCKS: Gauge = Gauge::new(
+    "randchain_orphan_blocks",
+    "Number of blocks in orphan pool.",
+);
+/// Duration of block VDF verification.
+pub static VDF_VERIFY: Timer = Timer::new(
+    "randchain_vdf_verify_seconds",
+    "Time spent verifying block VDF proofs.",
+);
+/// Duration of database writes.
+pub static DB_WRITE: Timer = Timer::new(
+    "randchain_db_write_seconds",
+    "Time spent committing database transactions.",
+);
+
+/// Encode all metrics in Prometheus text exposition format.
+pub fn render() -> String {
+    let metrics: [&dyn Metric; 9] = [
+        &BEST_BLOCK_HEIGHT,
+        &PEERS,
+        &SYNCHRONIZING,
+        &SCHEDULED_BLOCKS,
+        &REQUESTED_BLOCKS,
+        &VERIFYING_BLOCKS,
+        &ORPHAN_BLOCKS,
+        &VDF_VERIFY,
+        &DB_WRITE,
+    ];
+
+    let mut out = String::new();
+    for metric in &metrics {
+        metric.encode(&mut out);
+    }
+    out
+}
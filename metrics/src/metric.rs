@@ -0,0 +1,186 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Metric, which could be encoded in Prometheus text format.
+pub trait Metric {
+    /// Append metric description and value(s) to the output.
+    fn encode(&self, out: &mut String);
+}
+
+fn encode_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    writeln!(out, "# HELP {} {}", name, help).expect("writing to string never fails; qed");
+    writeln!(out, "# TYPE {} {}", name, kind).expect("writing to string never fails; qed");
+}
+
+/// Monotonically increasing value.
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Counter {
+            name: name,
+            help: help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    fn encode(&self, out: &mut String) {
+        encode_header(out, self.name, self.help, "counter");
+        writeln!(out, "{} {}", self.name, self.get()).expect("writing to string never fails; qed");
+    }
+}
+
+/// Value, which could go up and down.
+pub struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicI64,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Gauge {
+            name: name,
+            help: help,
+            value: AtomicI64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Gauge {
+    fn encode(&self, out: &mut String) {
+        encode_header(out, self.name, self.help, "gauge");
+        writeln!(out, "{} {}", self.name, self.get()).expect("writing to string never fails; qed");
+    }
+}
+
+/// Number and total duration of observed operations. Encoded as Prometheus summary.
+pub struct Timer {
+    name: &'static str,
+    help: &'static str,
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Timer {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Timer {
+            name: name,
+            help: help,
+            count: AtomicU64::new(0),
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// Record duration of single operation.
+    pub fn observe(&self, duration: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Execute operation and record its duration.
+    pub fn time<F, T>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let start = Instant::now();
+        let result = f();
+        self.observe(start.elapsed());
+        result
+    }
+
+    /// Number of observed operations.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Total duration of observed operations (in seconds).
+    pub fn sum_secs(&self) -> f64 {
+        self.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000f64
+    }
+}
+
+impl Metric for Timer {
+    fn encode(&self, out: &mut String) {
+        encode_header(out, self.name, self.help, "summary");
+        writeln!(out, "{}_sum {}", self.name, self.sum_secs())
+            .expect("writing to string never fails; qed");
+        writeln!(out, "{}_count {}", self.name, self.count())
+            .expect("writing to string never fails; qed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Counter, Gauge, Metric, Timer};
+    use std::time::Duration;
+
+    fn encode(metric: &dyn Metric) -> String {
+        let mut out = String::new();
+        metric.encode(&mut out);
+        out
+    }
+
+    #[test]
+    fn counter_encode() {
+        let counter = Counter::new("test_total", "Test counter");
+        counter.inc();
+        counter.inc_by(2);
+        assert_eq!(
+            encode(&counter),
+            "# HELP test_total Test counter\n# TYPE test_total counter\ntest_total 3\n"
+        );
+    }
+
+    #[test]
+    fn gauge_encode() {
+        let gauge = Gauge::new("test_value", "Test gauge");
+        gauge.set(10);
+        gauge.set(-5);
+        assert_eq!(
+            encode(&gauge),
+            "# HELP test_value Test gauge\n# TYPE test_value gauge\ntest_value -5\n"
+        );
+    }
+
+    #[test]
+    fn timer_encode() {
+        let timer = Timer::new("test_seconds", "Test timer");
+        timer.observe(Duration::from_millis(1500));
+        timer.observe(Duration::from_millis(250));
+        assert_eq!(timer.time(|| 42), 42);
+        assert_eq!(timer.count(), 3);
+        assert!(encode(&timer).starts_with(
+            "# HELP test_seconds Test timer\n# TYPE test_seconds summary\ntest_seconds_sum 1.75"
+        ));
+        assert!(encode(&timer).ends_with("\ntest_seconds_count 3\n"));
+    }
+}
@@ -1,7 +1,9 @@
+use candidate_pool::CandidatePool;
+use chain::IndexedBlock;
 use network::Network;
 use primitives::compact::Compact;
 use primitives::hash::H256;
-use storage::SharedStore;
+use storage::{Error as DBError, SharedStore};
 use verification::work_required;
 
 const BLOCK_VERSION: u32 = 0x00000001;
@@ -24,7 +26,11 @@ pub struct BlockTemplate {
 pub struct BlockAssembler {}
 
 impl BlockAssembler {
-    pub fn create_new_block(&self, store: &SharedStore, network: &Network) -> BlockTemplate {
+    pub fn create_new_block(
+        &self,
+        store: &SharedStore,
+        network: &Network,
+    ) -> Result<BlockTemplate, DBError> {
         // get best block
         // take it's hash && height
         let best_block = store.best_block();
@@ -35,14 +41,28 @@ impl BlockAssembler {
             height,
             store.as_block_header_provider(),
             network,
-        );
+        )?;
         let version = BLOCK_VERSION;
 
-        BlockTemplate {
+        Ok(BlockTemplate {
             version: version,
             previous_header_hash: previous_header_hash,
             bits: bits,
             height: height,
-        }
+        })
+    }
+
+    /// Select the best pooled candidate, which solves given template.
+    pub fn candidate_for(
+        &self,
+        pool: &CandidatePool,
+        template: &BlockTemplate,
+    ) -> Option<IndexedBlock> {
+        pool.best_candidate(&template.previous_header_hash)
+            .filter(|candidate| {
+                let header = &candidate.header.raw;
+                header.version == template.version && header.bits == template.bits
+            })
+            .cloned()
     }
 }
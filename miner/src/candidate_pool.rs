@@ -0,0 +1,175 @@
+use chain::IndexedBlock;
+use primitives::hash::H256;
+
+/// Default maximal number of candidates in the pool
+pub const DEFAULT_MAX_CANDIDATES: usize = 64;
+
+/// Reason, why candidate has not been inserted into the pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CandidateError {
+    /// The same candidate is already in the pool
+    Duplicate,
+    /// There's no room for new candidates
+    PoolFull,
+}
+
+/// Pool of solved candidate blocks, waiting to be appended to the chain.
+///
+/// Sequential PoW allows several miners to solve the same template, so the pool keeps
+/// at most one candidate for every (parent, public key, iterations) triple.
+#[derive(Debug)]
+pub struct CandidatePool {
+    /// Candidates in insertion order
+    candidates: Vec<IndexedBlock>,
+    /// Maximal number of candidates in the pool
+    max_candidates: usize,
+}
+
+impl Default for CandidatePool {
+    fn default() -> Self {
+        CandidatePool::new(DEFAULT_MAX_CANDIDATES)
+    }
+}
+
+impl CandidatePool {
+    pub fn new(max_candidates: usize) -> Self {
+        CandidatePool {
+            candidates: Vec::new(),
+            max_candidates: max_candidates,
+        }
+    }
+
+    /// Number of candidates in the pool
+    pub fn len(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// Is the pool empty?
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// All candidates in insertion order
+    pub fn candidates(&self) -> &[IndexedBlock] {
+        &self.candidates
+    }
+
+    /// Insert new candidate. Candidate must be verified before inserting.
+    pub fn insert(&mut self, block: IndexedBlock) -> Result<(), CandidateError> {
+        if self.contains(&block) {
+            return Err(CandidateError::Duplicate);
+        }
+        if self.candidates.len() >= self.max_candidates {
+            return Err(CandidateError::PoolFull);
+        }
+
+        self.candidates.push(block);
+        Ok(())
+    }
+
+    /// Is there a candidate with the same parent, public key and iterations?
+    pub fn contains(&self, block: &IndexedBlock) -> bool {
+        let header = &block.header.raw;
+        self.candidates.iter().any(|candidate| {
+            let candidate = &candidate.header.raw;
+            candidate.previous_header_hash == header.previous_header_hash
+                && candidate.pubkey == header.pubkey
+                && candidate.iterations == header.iterations
+        })
+    }
+
+    /// Best candidate, built on top of given block: the one with the least number of iterations.
+    pub fn best_candidate(&self, parent_hash: &H256) -> Option<&IndexedBlock> {
+        self.candidates
+            .iter()
+            .filter(|candidate| &candidate.header.raw.previous_header_hash == parent_hash)
+            .min_by_key(|candidate| candidate.header.raw.iterations)
+    }
+
+    /// Remove all candidates, which are not built on top of the best block.
+    pub fn remove_stale(&mut self, best_block_hash: &H256) {
+        self.candidates
+            .retain(|candidate| &candidate.header.raw.previous_header_hash == best_block_hash);
+    }
+
+    /// Remove candidate with given hash.
+    pub fn remove(&mut self, hash: &H256) -> Option<IndexedBlock> {
+        let position = self
+            .candidates
+            .iter()
+            .position(|candidate| candidate.hash() == hash)?;
+        Some(self.candidates.remove(position))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use self::test_data::block_builder;
+    use super::{CandidateError, CandidatePool};
+    use chain::IndexedBlock;
+    use crypto::sr25519::create_keypair;
+    use primitives::hash::H256;
+
+    fn candidate(parent: H256, seed: u8, iterations: u32) -> IndexedBlock {
+        block_builder()
+            .header()
+            .parent(parent)
+            .pubkey(create_keypair(&[seed; 32]).1)
+            .iterations(iterations)
+            .build()
+            .build()
+            .into()
+    }
+
+    #[test]
+    fn candidate_pool_deduplicates() {
+        let mut pool = CandidatePool::default();
+        assert_eq!(pool.insert(candidate(1.into(), 1, 10)), Ok(()));
+        assert_eq!(
+            pool.insert(candidate(1.into(), 1, 10)),
+            Err(CandidateError::Duplicate)
+        );
+        assert_eq!(pool.insert(candidate(1.into(), 1, 11)), Ok(()));
+        assert_eq!(pool.insert(candidate(1.into(), 2, 10)), Ok(()));
+        assert_eq!(pool.insert(candidate(2.into(), 1, 10)), Ok(()));
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    fn candidate_pool_is_limited() {
+        let mut pool = CandidatePool::new(2);
+        assert_eq!(pool.insert(candidate(1.into(), 1, 10)), Ok(()));
+        assert_eq!(pool.insert(candidate(1.into(), 2, 10)), Ok(()));
+        assert_eq!(
+            pool.insert(candidate(1.into(), 3, 10)),
+            Err(CandidateError::PoolFull)
+        );
+        assert_eq!(
+            pool.insert(candidate(1.into(), 1, 10)),
+            Err(CandidateError::Duplicate)
+        );
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn candidate_pool_best_candidate() {
+        let mut pool = CandidatePool::default();
+        pool.insert(candidate(1.into(), 1, 30)).unwrap();
+        pool.insert(candidate(1.into(), 2, 20)).unwrap();
+        pool.insert(candidate(2.into(), 3, 10)).unwrap();
+
+        let best = pool.best_candidate(&1.into()).unwrap();
+        assert_eq!(best.header.raw.iterations, 20);
+        assert!(pool.best_candidate(&3.into()).is_none());
+
+        pool.remove_stale(&2.into());
+        assert_eq!(pool.len(), 1);
+        assert!(pool.best_candidate(&1.into()).is_none());
+
+        let hash = pool.candidates()[0].hash().clone();
+        assert!(pool.remove(&hash).is_some());
+        assert!(pool.is_empty());
+    }
+}
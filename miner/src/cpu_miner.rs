@@ -1,16 +1,22 @@
+use std::cmp::max;
 use std::time::{Duration, Instant};
 
 use block_assembler::BlockTemplate;
-use chain::BlockHeader;
+use chain::{Block, BlockHeader, IndexedBlock};
 use crypto::sr25519::PK;
 use crypto::{dhash256, vdf};
 use network::Network;
+use primitives::bigint::{Uint, U256};
 use primitives::bytes::Bytes;
+use rayon::ThreadPoolBuilder;
 use rug::{integer::Order, Integer};
 use ser::{serialize, Stream};
 use sha2::{Digest, Sha256};
 use verification::is_valid_proof_of_work_hash;
 
+/// Default number of threads, used to compute VDF proof (0 means one thread per CPU).
+pub const DEFAULT_PROVER_THREADS: usize = 0;
+
 // consistent with verification/src/verify_block.rs
 fn h_g(block: &BlockTemplate, pubkey: &PK) -> Integer {
     let mut stream = Stream::default();
@@ -113,13 +119,42 @@ pub fn verify(block: &BlockTemplate, pubkey: &PK, solution: &Solution) -> bool {
     return true;
 }
 
+/// Compute VDF proof on the dedicated pool of `threads` threads.
+fn prove_parallel(
+    g: &Integer,
+    y: &Integer,
+    iterations: u64,
+    checkpoints: &[Integer],
+    interval: u64,
+    threads: usize,
+) -> vdf::Proof {
+    match ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => {
+            pool.install(|| vdf::prove_with_checkpoints(g, y, iterations, checkpoints, interval))
+        }
+        Err(_) => vdf::prove(g, y, iterations),
+    }
+}
+
 /// Simple randchain cpu miner.
 pub fn find_solution(block: &BlockTemplate, pubkey: &PK, timeout: Duration) -> Option<Solution> {
+    find_solution_with_threads(block, pubkey, timeout, DEFAULT_PROVER_THREADS)
+}
+
+/// Simple randchain cpu miner, which computes VDF proof on `threads` threads.
+pub fn find_solution_with_threads(
+    block: &BlockTemplate,
+    pubkey: &PK,
+    timeout: Duration,
+    threads: usize,
+) -> Option<Solution> {
     let start_time = Instant::now();
     let step = Network::Mainnet.step_parameter();
     let g = h_g(block, pubkey);
     let mut cur_y = g.clone();
     let mut iterations = 0u64;
+    // g^(2^(k * step)), used to parallelize proof generation
+    let mut checkpoints = vec![g.clone()];
     loop {
         if timeout != Duration::new(0, 0) && start_time.elapsed() > timeout {
             return None;
@@ -131,6 +166,7 @@ pub fn find_solution(block: &BlockTemplate, pubkey: &PK, timeout: Duration) -> O
         }
 
         let new_y = vdf::eval(&cur_y, step);
+        checkpoints.push(new_y.clone());
         // consistent with chain/src/block_header.rs
         let block_header_hash = dhash256(&serialize(&BlockHeader {
             version: block.version,
@@ -144,7 +180,7 @@ pub fn find_solution(block: &BlockTemplate, pubkey: &PK, timeout: Duration) -> O
             let solution = Solution {
                 iterations: iterations,
                 element: new_y.clone(),
-                proof: vdf::prove(&g, &new_y, iterations),
+                proof: prove_parallel(&g, &new_y, iterations, &checkpoints, step, threads),
             };
 
             return Some(solution);
@@ -154,6 +190,24 @@ pub fn find_solution(block: &BlockTemplate, pubkey: &PK, timeout: Duration) -> O
     }
 }
 
+/// Mine a complete block for the owner of the given public key.
+///
+/// The VDF input is derived from the header fields via `h_g`, so the
+/// resulting block passes the regular block verification.
+pub fn find_solution_with_pubkey(template: &BlockTemplate, pk: &PK) -> Option<IndexedBlock> {
+    let solution = find_solution(template, pk, Duration::new(0, 0))?;
+    let header = BlockHeader {
+        version: template.version,
+        previous_header_hash: template.previous_header_hash,
+        bits: template.bits,
+        pubkey: pk.clone(),
+        iterations: solution.iterations as u32,
+        solution: solution.element,
+    };
+
+    Some(IndexedBlock::from_raw(Block::new(header, solution.proof)))
+}
+
 /// Dry run miner
 pub fn find_solution_dry(block: &BlockTemplate, pubkey: &PK) -> Option<Solution> {
     let g = h_g(block, pubkey);
@@ -169,12 +223,56 @@ pub fn find_solution_dry(block: &BlockTemplate, pubkey: &PK) -> Option<Solution>
     return Some(solution);
 }
 
+/// Expected number of SeqPoW steps (`step_parameter` VDF iterations each), needed to find
+/// a solution for the given template. Saturates at `u64::max_value()`.
+pub fn estimate_iterations_to_solution(block: &BlockTemplate) -> u64 {
+    let target = match block.bits.to_u256() {
+        Ok(target) => target,
+        Err(_) => return u64::max_value(),
+    };
+    if target == U256::max_value() {
+        return 1;
+    }
+
+    // every step hits the target with probability (target + 1) / 2^256
+    let steps = U256::max_value() / (target + U256::one()) + U256::one();
+    if steps > U256::from(u64::max_value()) {
+        u64::max_value()
+    } else {
+        steps.low_u64()
+    }
+}
+
+/// Expected number of VDF iterations, needed to find a solution for the given template.
+/// Saturates at `u64::max_value()`.
+pub fn target_iterations(block: &BlockTemplate) -> u64 {
+    estimate_iterations_to_solution(block).saturating_mul(Network::Mainnet.step_parameter())
+}
+
+/// Average time of a single VDF iteration, in seconds, measured over `samples` iterations.
+pub fn vdf_iteration_seconds(samples: u32) -> f64 {
+    let samples = max(samples, 1);
+    // full-size group element, so that squaring cost is representative
+    let g = vdf::MODULUS.clone() - 2u32;
+    let start_time = Instant::now();
+    vdf::eval(&g, samples as u64);
+    start_time.elapsed().as_secs_f64() / samples as f64
+}
+
+/// Estimated time to find a solution for the given template on the given network, in seconds.
+pub fn estimate_solution_time(block: &BlockTemplate, network: &Network, samples: u32) -> f64 {
+    estimate_iterations_to_solution(block) as f64
+        * network.step_parameter() as f64
+        * vdf_iteration_seconds(samples)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use block_assembler::BlockTemplate;
-    use crypto::sr25519::PK;
+    use crypto::sr25519::{create_keypair, PK};
     use primitives::bigint::{Uint, U256};
+    use primitives::compact::Compact;
     use std::time::Duration;
 
     #[test]
@@ -192,6 +290,22 @@ mod tests {
         assert!(solution.is_some());
     }
 
+    #[test]
+    fn test_cpu_miner_parallel_proof() {
+        let block_template = BlockTemplate {
+            version: 0,
+            previous_header_hash: 0.into(),
+            bits: U256::max_value().into(),
+            height: 0,
+        };
+
+        let pubkey: PK = PK::from_bytes(&[0; 32]).unwrap();
+        let solution =
+            find_solution_with_threads(&block_template, &pubkey, Duration::from_secs(0), 4)
+                .unwrap();
+        assert!(verify(&block_template, &pubkey, &solution));
+    }
+
     #[test]
     fn test_seqpow_low_difficulty() {
         let block_template = BlockTemplate {
@@ -213,4 +327,59 @@ mod tests {
         }
         assert_eq!(verify(&block_template, &pubkey, &solution), true);
     }
+
+    #[test]
+    fn test_find_solution_with_pubkey() {
+        let block_template = BlockTemplate {
+            version: 0,
+            previous_header_hash: 0.into(),
+            bits: U256::max_value().into(),
+            height: 0,
+        };
+
+        let (_, pk) = create_keypair(&[1; 32]);
+        assert!(pk.to_bytes() != [0; 32]);
+
+        let block = find_solution_with_pubkey(&block_template, &pk).unwrap();
+        let header = &block.header.raw;
+        assert_eq!(header.pubkey, pk);
+        assert!(vdf::verify(
+            &h_g(&block_template, &pk),
+            &header.solution,
+            header.iterations as u64,
+            &block.proof
+        ));
+    }
+
+    #[test]
+    fn test_estimate_iterations_to_solution() {
+        let mut block_template = BlockTemplate {
+            version: 0,
+            previous_header_hash: 0.into(),
+            bits: Compact::max_value(),
+            height: 0,
+        };
+        assert!(estimate_iterations_to_solution(&block_template) <= 10);
+
+        block_template.bits = U256::max_value().into();
+        assert!(estimate_iterations_to_solution(&block_template) <= 10);
+
+        block_template.bits = U256::from(1).into();
+        assert_eq!(
+            estimate_iterations_to_solution(&block_template),
+            u64::max_value()
+        );
+    }
+
+    #[test]
+    fn test_estimate_solution_time() {
+        let block_template = BlockTemplate {
+            version: 0,
+            previous_header_hash: 0.into(),
+            bits: Compact::max_value(),
+            height: 0,
+        };
+        assert!(vdf_iteration_seconds(10) > 0f64);
+        assert!(estimate_solution_time(&block_template, &Network::Testnet, 10) > 0f64);
+    }
 }
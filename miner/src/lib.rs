@@ -1,5 +1,7 @@
 extern crate bigint;
 extern crate heapsize;
+extern crate parking_lot;
+extern crate rayon;
 extern crate rug;
 extern crate sha2;
 
@@ -13,8 +15,16 @@ extern crate storage;
 extern crate verification;
 
 mod block_assembler;
+mod candidate_pool;
 mod cpu_miner;
+mod miner_status;
 
 pub use block_assembler::{BlockAssembler, BlockTemplate};
-pub use cpu_miner::Solution;
-pub use cpu_miner::{find_solution, find_solution_dry, init, prove, solve, verify};
+pub use candidate_pool::{CandidateError, CandidatePool, DEFAULT_MAX_CANDIDATES};
+pub use cpu_miner::{
+    estimate_iterations_to_solution, estimate_solution_time, find_solution, find_solution_dry,
+    find_solution_with_pubkey, find_solution_with_threads, init, prove, solve, target_iterations,
+    vdf_iteration_seconds, verify,
+};
+pub use cpu_miner::{Solution, DEFAULT_PROVER_THREADS};
+pub use miner_status::{MinerProgress, MinerStatus};
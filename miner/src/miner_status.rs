@@ -0,0 +1,140 @@
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Snapshot of the miner progress.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MinerStatus {
+    /// Is the miner searching for a solution right now?
+    pub enabled: bool,
+    /// Number of VDF iterations, evaluated for the current template.
+    pub iterations: u64,
+    /// Expected number of VDF iterations, required to find a solution for the current template.
+    pub target_iterations: u64,
+    /// Time, spent on the current template.
+    pub elapsed: Duration,
+}
+
+impl MinerStatus {
+    /// Average VDF speed on the current template.
+    pub fn iterations_per_second(&self) -> f64 {
+        let elapsed = self.elapsed.as_secs_f64();
+        if elapsed == 0f64 {
+            return 0f64;
+        }
+
+        self.iterations as f64 / elapsed
+    }
+
+    /// Expected time (in seconds) to find a solution at the current speed.
+    ///
+    /// Every SeqPoW step is an independent trial, so the expectation does not decrease
+    /// with the number of already evaluated iterations.
+    pub fn expected_time_to_block(&self) -> Option<f64> {
+        let speed = self.iterations_per_second();
+        if speed == 0f64 {
+            return None;
+        }
+
+        Some(self.target_iterations as f64 / speed)
+    }
+}
+
+#[derive(Debug, Default)]
+struct MinerProgressState {
+    /// When the miner has started working on the current template. None if the miner is stopped.
+    started: Option<Instant>,
+    /// Last reported status.
+    status: MinerStatus,
+}
+
+/// Progress of the miner, shared between the miner and its observers.
+#[derive(Debug, Default)]
+pub struct MinerProgress {
+    state: Mutex<MinerProgressState>,
+}
+
+impl MinerProgress {
+    /// Miner has started working on new template.
+    pub fn start(&self, target_iterations: u64) {
+        let mut state = self.state.lock();
+        state.started = Some(Instant::now());
+        state.status = MinerStatus {
+            enabled: true,
+            iterations: 0,
+            target_iterations: target_iterations,
+            elapsed: Duration::new(0, 0),
+        };
+    }
+
+    /// Miner has evaluated given number of iterations of the current template.
+    pub fn update(&self, iterations: u64) {
+        let mut state = self.state.lock();
+        if let Some(started) = state.started {
+            state.status.iterations = iterations;
+            state.status.elapsed = started.elapsed();
+        }
+    }
+
+    /// Miner has found a solution or has given up. Last status is preserved.
+    pub fn stop(&self) {
+        let mut state = self.state.lock();
+        if let Some(started) = state.started.take() {
+            state.status.elapsed = started.elapsed();
+        }
+        state.status.enabled = false;
+    }
+
+    /// Current miner status.
+    pub fn status(&self) -> MinerStatus {
+        let state = self.state.lock();
+        let mut status = state.status.clone();
+        if let Some(started) = state.started {
+            status.elapsed = started.elapsed();
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MinerProgress, MinerStatus};
+    use std::time::Duration;
+
+    #[test]
+    fn miner_status_speed_and_eta() {
+        let mut status = MinerStatus {
+            enabled: true,
+            iterations: 1_000,
+            target_iterations: 10_000,
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(status.iterations_per_second(), 100f64);
+        assert_eq!(status.expected_time_to_block(), Some(100f64));
+
+        status.elapsed = Duration::new(0, 0);
+        assert_eq!(status.iterations_per_second(), 0f64);
+        assert_eq!(status.expected_time_to_block(), None);
+    }
+
+    #[test]
+    fn miner_progress_lifecycle() {
+        let progress = MinerProgress::default();
+        assert_eq!(progress.status(), MinerStatus::default());
+
+        progress.start(500);
+        progress.update(100);
+        let status = progress.status();
+        assert!(status.enabled);
+        assert_eq!(status.iterations, 100);
+        assert_eq!(status.target_iterations, 500);
+
+        progress.stop();
+        let status = progress.status();
+        assert!(!status.enabled);
+        assert_eq!(status.iterations, 100);
+
+        // updates of the stopped miner are ignored
+        progress.update(200);
+        assert_eq!(progress.status().iterations, 100);
+    }
+}
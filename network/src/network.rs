@@ -13,7 +13,7 @@ use primitives::hash::H256;
 const MAGIC_MAINNET: u32 = 0xD9B4BEF9;
 const MAGIC_TESTNET: u32 = 0x0709110B;
 const MAGIC_REGTEST: u32 = 0xDAB5BFFA;
-const MAGIC_UNITEST: u32 = 0x00000000;
+const MAGIC_UNITEST: u32 = 0xDABADABA;
 
 lazy_static! {
     static ref MAX_BITS_MAINNET: U256 =
@@ -28,6 +28,10 @@ lazy_static! {
         "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
             .parse()
             .expect("hardcoded value should parse without errors");
+    static ref GENESIS_HASH_MAINNET: H256 = *Network::Mainnet.genesis_block().hash();
+    static ref GENESIS_HASH_TESTNET: H256 = *Network::Testnet.genesis_block().hash();
+    static ref GENESIS_HASH_REGTEST: H256 = *Network::Regtest.genesis_block().hash();
+    static ref GENESIS_HASH_UNITEST: H256 = *Network::Unitest.genesis_block().hash();
 }
 
 /// Network magic type.
@@ -69,6 +73,42 @@ impl Network {
         }
     }
 
+    pub fn from_magic(magic: Magic) -> Network {
+        match magic {
+            MAGIC_MAINNET => Network::Mainnet,
+            MAGIC_TESTNET => Network::Testnet,
+            MAGIC_REGTEST => Network::Regtest,
+            MAGIC_UNITEST => Network::Unitest,
+            value => Network::Other(value),
+        }
+    }
+
+    /// Network magic, as it appears in the P2P message frame header.
+    pub fn magic_bytes(&self) -> [u8; 4] {
+        self.magic().to_le_bytes()
+    }
+
+    /// Known network for the magic bytes of the P2P message frame header.
+    pub fn from_magic_bytes(bytes: [u8; 4]) -> Option<Network> {
+        match Network::from_magic(Magic::from_le_bytes(bytes)) {
+            Network::Other(_) => None,
+            network => Some(network),
+        }
+    }
+
+    /// Is this the production network, where dangerous operations are refused?
+    pub fn is_production(&self) -> bool {
+        *self == Network::Mainnet
+    }
+
+    /// Are consecutive blocks required to be mined with different public keys?
+    pub fn enforces_pubkey_uniqueness(&self) -> bool {
+        match *self {
+            Network::Mainnet | Network::Other(_) => true,
+            Network::Testnet | Network::Regtest | Network::Unitest => false,
+        }
+    }
+
     pub fn max_bits(&self) -> U256 {
         match *self {
             Network::Mainnet | Network::Other(_) => MAX_BITS_MAINNET.clone(),
@@ -86,6 +126,11 @@ impl Network {
         }
     }
 
+    /// Port, seed nodes are listening on
+    pub fn seednode_port(&self) -> u16 {
+        self.port()
+    }
+
     pub fn dns_port(&self) -> u16 {
         53u16
     }
@@ -106,6 +151,15 @@ impl Network {
         }
     }
 
+    /// Minimal protocol version accepted from remote peers during handshake.
+    pub fn min_protocol_version(&self) -> u32 {
+        match *self {
+            Network::Mainnet | Network::Testnet | Network::Other(_) => 70_001,
+            // local test networks accept any peer
+            Network::Regtest | Network::Unitest => 1,
+        }
+    }
+
     pub fn genesis_block(&self) -> IndexedBlock {
         match *self {
             Network::Mainnet | Network::Other(_) => {
@@ -142,7 +196,7 @@ impl Network {
                 };
                 IndexedBlock::from_raw(blk)
             }
-            Network::Regtest | Network::Unitest => {
+            Network::Regtest => {
                 let blk = Block {
                     block_header: BlockHeader {
                         version: 1,
@@ -159,6 +213,20 @@ impl Network {
                 };
                 IndexedBlock::from_raw(blk)
             }
+            Network::Unitest => {
+                let blk = Block {
+                    block_header: BlockHeader {
+                        version: 1,
+                        previous_header_hash: [0; 32].into(), // genesis_block has all-0 previous_header_hash
+                        bits: Compact::max_value(),
+                        pubkey: PK::from_bytes(&[6; 32]).unwrap(),
+                        iterations: 0,
+                        solution: rug::Integer::from(8),
+                    },
+                    proof: vec![],
+                };
+                IndexedBlock::from_raw(blk)
+            }
         }
     }
 
@@ -173,6 +241,23 @@ impl Network {
             _ => *self.genesis_block().hash(),
         }
     }
+
+    /// Hashes of blocks at fixed heights, which must be included in the chain.
+    /// Sorted by height.
+    pub fn checkpoints(&self) -> Vec<(u32, H256)> {
+        // TODO: add checkpoints for main and test networks, once they are launched
+        vec![(0, self.genesis_hash())]
+    }
+
+    /// Hash of the genesis block, computed once per network.
+    fn genesis_hash(&self) -> H256 {
+        match *self {
+            Network::Mainnet | Network::Other(_) => *GENESIS_HASH_MAINNET,
+            Network::Testnet => *GENESIS_HASH_TESTNET,
+            Network::Regtest => *GENESIS_HASH_REGTEST,
+            Network::Unitest => *GENESIS_HASH_UNITEST,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -191,6 +276,77 @@ mod tests {
         assert_eq!(MAGIC_UNITEST, Network::Unitest.magic());
     }
 
+    #[test]
+    fn test_network_from_magic() {
+        assert_eq!(Network::from_magic(MAGIC_MAINNET), Network::Mainnet);
+        assert_eq!(Network::from_magic(MAGIC_TESTNET), Network::Testnet);
+        assert_eq!(Network::from_magic(MAGIC_REGTEST), Network::Regtest);
+        assert_eq!(Network::from_magic(MAGIC_UNITEST), Network::Unitest);
+        assert_eq!(Network::from_magic(0x12345678), Network::Other(0x12345678));
+    }
+
+    #[test]
+    fn test_network_magic_bytes() {
+        assert_eq!(Network::Mainnet.magic_bytes(), [0xf9, 0xbe, 0xb4, 0xd9]);
+        assert_ne!(
+            Network::Mainnet.magic_bytes(),
+            Network::Testnet.magic_bytes()
+        );
+        assert_eq!(
+            Network::from_magic_bytes(Network::Testnet.magic_bytes()),
+            Some(Network::Testnet)
+        );
+        assert_eq!(
+            Network::from_magic_bytes(Network::Other(0x12345678).magic_bytes()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_network_is_production() {
+        assert!(Network::Mainnet.is_production());
+        assert!(!Network::Testnet.is_production());
+        assert!(!Network::Regtest.is_production());
+        assert!(!Network::Unitest.is_production());
+        assert!(!Network::Other(MAGIC_MAINNET + 1).is_production());
+    }
+
+    #[test]
+    fn test_network_enforces_pubkey_uniqueness() {
+        assert!(Network::Mainnet.enforces_pubkey_uniqueness());
+        assert!(!Network::Testnet.enforces_pubkey_uniqueness());
+        assert!(!Network::Regtest.enforces_pubkey_uniqueness());
+        assert!(!Network::Unitest.enforces_pubkey_uniqueness());
+        assert!(Network::Other(MAGIC_MAINNET + 1).enforces_pubkey_uniqueness());
+    }
+
+    #[test]
+    fn test_network_distinct() {
+        let networks = [
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Regtest,
+            Network::Unitest,
+        ];
+        for (i, a) in networks.iter().enumerate() {
+            for b in networks.iter().skip(i + 1) {
+                assert_ne!(a.magic(), b.magic());
+                // testnet still shares genesis block with mainnet
+                if (*a, *b) != (Network::Mainnet, Network::Testnet) {
+                    assert_ne!(a.genesis_block().hash(), b.genesis_block().hash());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_network_min_protocol_version() {
+        assert_eq!(Network::Mainnet.min_protocol_version(), 70_001);
+        assert_eq!(Network::Testnet.min_protocol_version(), 70_001);
+        assert_eq!(Network::Regtest.min_protocol_version(), 1);
+        assert_eq!(Network::Unitest.min_protocol_version(), 1);
+    }
+
     #[test]
     fn test_network_max_bits() {
         assert_eq!(Network::Mainnet.max_bits(), *MAX_BITS_MAINNET);
@@ -207,6 +363,12 @@ mod tests {
         assert_eq!(Network::Unitest.port(), 18444);
     }
 
+    #[test]
+    fn test_network_seednode_port() {
+        assert_eq!(Network::Mainnet.seednode_port(), Network::Mainnet.port());
+        assert_eq!(Network::Testnet.seednode_port(), 18333);
+    }
+
     #[test]
     fn test_network_rpc_port() {
         assert_eq!(Network::Mainnet.rpc_port(), 8332);
@@ -214,4 +376,19 @@ mod tests {
         assert_eq!(Network::Regtest.rpc_port(), 18443);
         assert_eq!(Network::Unitest.rpc_port(), 18443);
     }
+
+    #[test]
+    fn test_network_checkpoints() {
+        for network in &[
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Regtest,
+            Network::Unitest,
+            Network::Other(1),
+        ] {
+            let checkpoints = network.checkpoints();
+            assert_eq!(checkpoints[0], (0, *network.genesis_block().hash()));
+            assert!(checkpoints.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
 }
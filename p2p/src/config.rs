@@ -17,8 +17,14 @@ pub struct Config {
     pub peers: Vec<net::SocketAddr>,
     /// Connect to these nodes to retrieve peer addresses, and disconnect.
     pub seeds: Vec<String>,
+    /// Connect to these nodes, if none of seeds could be resolved.
+    pub fallback_seeds: Vec<net::SocketAddr>,
+    /// Resolve these hostnames at startup and periodically to fill the node table.
+    pub dns_seeds: Vec<String>,
     /// p2p/nodes.csv file path.
     pub node_table_path: path::PathBuf,
+    /// p2p/banlist.csv file path.
+    pub ban_list_path: path::PathBuf,
     /// Peers with this services will get a boost in node_table.
     pub preferable_services: Services,
     /// Internet protocol.
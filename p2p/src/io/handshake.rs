@@ -450,4 +450,44 @@ mod tests {
             .unwrap();
         assert_eq!(hs.1.unwrap_err(), expected);
     }
+
+    #[test]
+    fn test_accept_handshake_min_protocol_version() {
+        // peer speaking protocol version older than 70001 (no relay flag)
+        let remote_version = match remote_version() {
+            Version::V70001(mut v0, v106, _) => {
+                v0.version = 60_000;
+                Version::V106(v0, v106)
+            }
+            _ => unreachable!("remote_version is V70001; qed"),
+        };
+
+        let accept = |network: Network| {
+            let magic = network.magic();
+            let mut remote_stream = Stream::new();
+            remote_stream.append_slice(
+                Message::new(magic, 70012, &remote_version)
+                    .unwrap()
+                    .as_ref(),
+            );
+
+            let test_io = TestIo {
+                read: io::Cursor::new(remote_stream.out()),
+                write: Bytes::default(),
+            };
+
+            accept_handshake(
+                test_io,
+                magic,
+                local_version(),
+                network.min_protocol_version(),
+            )
+            .wait()
+            .unwrap()
+            .1
+        };
+
+        assert_eq!(accept(Network::Mainnet).unwrap_err(), Error::InvalidVersion);
+        assert_eq!(accept(Network::Regtest).unwrap().version.version(), 60_000);
+    }
 }
@@ -5,6 +5,7 @@ mod read_header;
 mod read_message;
 mod read_payload;
 mod sharedtcpstream;
+mod socks5;
 mod write_message;
 
 pub use self::deadline::{deadline, Deadline, DeadlineStatus};
@@ -16,4 +17,5 @@ pub use self::read_header::{read_header, ReadHeader};
 pub use self::read_message::{read_message, ReadMessage};
 pub use self::read_payload::{read_payload, ReadPayload};
 pub use self::sharedtcpstream::SharedTcpStream;
+pub use self::socks5::socks5_connect;
 pub use self::write_message::{write_message, WriteMessage};
@@ -97,6 +97,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_any_message_from_other_network() {
+        let raw: Bytes = "f9beb4d970696e6700000000000000000800000083c00c765845303b6da97786".into();
+        let mut raw = raw.take();
+        raw[..4].copy_from_slice(&Network::Testnet.magic_bytes());
+        let raw: Bytes = raw.into();
+
+        // mainnet node rejects testnet frame, so the channel is closed
+        assert_eq!(
+            read_any_message(raw.as_ref(), Network::Mainnet.magic())
+                .wait()
+                .unwrap(),
+            Err(Error::InvalidMagic)
+        );
+    }
+
     #[test]
     fn test_read_too_short_any_message() {
         let raw: Bytes = "f9beb4d970696e6700000000000000000800000083c00c765845303b6da977".into();
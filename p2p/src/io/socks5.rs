@@ -0,0 +1,127 @@
+use futures::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use tokio_core::net::TcpStream;
+use tokio_core::reactor::Handle;
+use tokio_io::io::{read_exact, write_all};
+use tokio_io::IoFuture;
+
+const SOCKS5_VERSION: u8 = 5;
+const NO_AUTHENTICATION: u8 = 0;
+const CONNECT_COMMAND: u8 = 1;
+const RESERVED: u8 = 0;
+const ADDRESS_TYPE_IPV4: u8 = 1;
+const ADDRESS_TYPE_IPV6: u8 = 4;
+const REPLY_SUCCEEDED: u8 = 0;
+
+/// Establishes TCP connection to the target address through SOCKS5 proxy (RFC 1928).
+/// Proxy authentication is not supported.
+pub fn socks5_connect(
+    proxy: &SocketAddr,
+    target: &SocketAddr,
+    handle: &Handle,
+) -> IoFuture<TcpStream> {
+    let request = connect_request(target);
+    let future = TcpStream::connect(proxy, handle)
+        .and_then(|stream| write_all(stream, [SOCKS5_VERSION, 1, NO_AUTHENTICATION]))
+        .and_then(|(stream, _)| read_exact(stream, [0u8; 2]))
+        .and_then(|(stream, response)| check_method_selection(&response).map(|_| stream))
+        .and_then(move |stream| write_all(stream, request))
+        .and_then(|(stream, _)| read_exact(stream, [0u8; 4]))
+        .and_then(|(stream, header)| bound_address_len(&header).map(|len| (stream, len)))
+        // bound address is not used => just read it
+        .and_then(|(stream, len)| read_exact(stream, vec![0u8; len]))
+        .map(|(stream, _)| stream);
+    Box::new(future)
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Builds CONNECT request for the target address.
+fn connect_request(target: &SocketAddr) -> Vec<u8> {
+    let mut request = vec![SOCKS5_VERSION, CONNECT_COMMAND, RESERVED];
+    match target.ip() {
+        IpAddr::V4(ip) => {
+            request.push(ADDRESS_TYPE_IPV4);
+            request.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            request.push(ADDRESS_TYPE_IPV6);
+            request.extend_from_slice(&ip.octets());
+        }
+    }
+    request.push((target.port() >> 8) as u8);
+    request.push(target.port() as u8);
+    request
+}
+
+/// Checks that proxy has accepted connection without authentication.
+fn check_method_selection(response: &[u8; 2]) -> io::Result<()> {
+    if response[0] != SOCKS5_VERSION {
+        return Err(invalid_data("Invalid SOCKS5 proxy version"));
+    }
+    if response[1] != NO_AUTHENTICATION {
+        return Err(invalid_data("SOCKS5 proxy requires authentication"));
+    }
+    Ok(())
+}
+
+/// Checks CONNECT reply header and returns length of the rest of reply (bound address and port).
+fn bound_address_len(header: &[u8; 4]) -> io::Result<usize> {
+    if header[0] != SOCKS5_VERSION {
+        return Err(invalid_data("Invalid SOCKS5 proxy version"));
+    }
+    if header[1] != REPLY_SUCCEEDED {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("SOCKS5 proxy has failed to connect with code {}", header[1]),
+        ));
+    }
+    match header[3] {
+        ADDRESS_TYPE_IPV4 => Ok(4 + 2),
+        ADDRESS_TYPE_IPV6 => Ok(16 + 2),
+        _ => Err(invalid_data("Unsupported SOCKS5 bound address type")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bound_address_len, check_method_selection, connect_request};
+    use std::io;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_connect_request() {
+        let ipv4: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        assert_eq!(
+            connect_request(&ipv4),
+            vec![5, 1, 0, 1, 10, 0, 0, 1, 0x20, 0x8d]
+        );
+
+        let ipv6: SocketAddr = "[::1]:18333".parse().unwrap();
+        let mut expected = vec![5, 1, 0, 4];
+        expected.extend_from_slice(&[0; 15]);
+        expected.extend_from_slice(&[1, 0x47, 0x9d]);
+        assert_eq!(connect_request(&ipv6), expected);
+    }
+
+    #[test]
+    fn test_check_method_selection() {
+        assert!(check_method_selection(&[5, 0]).is_ok());
+        assert!(check_method_selection(&[4, 0]).is_err());
+        assert!(check_method_selection(&[5, 0xff]).is_err());
+    }
+
+    #[test]
+    fn test_bound_address_len() {
+        assert_eq!(bound_address_len(&[5, 0, 0, 1]).unwrap(), 6);
+        assert_eq!(bound_address_len(&[5, 0, 0, 4]).unwrap(), 18);
+        assert_eq!(
+            bound_address_len(&[5, 5, 0, 1]).unwrap_err().kind(),
+            io::ErrorKind::ConnectionRefused
+        );
+        assert!(bound_address_len(&[5, 0, 0, 3]).is_err());
+    }
+}
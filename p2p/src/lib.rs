@@ -8,9 +8,7 @@ extern crate tokio_core;
 extern crate tokio_io;
 #[macro_use]
 extern crate log;
-extern crate abstract_ns;
 extern crate csv;
-extern crate ns_dns_tokio;
 
 extern crate crypto;
 extern crate message;
@@ -38,4 +36,7 @@ pub use protocol::{
     InboundSyncConnectionStateRef, LocalSyncNode, LocalSyncNodeRef, OutboundSyncConnection,
     OutboundSyncConnectionRef,
 };
-pub use util::{Direction, InternetProtocol, NodeTableError, PeerId, PeerInfo};
+pub use util::{
+    BannedNode, Direction, InternetProtocol, NodeTableError, PeerId, PeerInfo,
+    DEFAULT_BAN_TIME_SECS, MAX_BAN_TIME_SECS,
+};
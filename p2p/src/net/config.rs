@@ -16,6 +16,8 @@ pub struct Config {
     pub start_height: i32,
     pub relay: bool,
     pub network: network::Network,
+    /// SOCKS5 proxy, used for all outbound connections.
+    pub proxy: Option<SocketAddr>,
 }
 
 impl Config {
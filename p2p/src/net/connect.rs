@@ -1,5 +1,5 @@
 use futures::{Async, Future, Poll};
-use io::{deadline, handshake, Deadline, Handshake};
+use io::{deadline, handshake, socks5_connect, Deadline, Handshake};
 use message::types::Version;
 use message::Error;
 use net::{Config, Connection};
@@ -7,13 +7,30 @@ use network::Magic;
 use std::io;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio_core::net::{TcpStream, TcpStreamNew};
+use tokio_core::net::TcpStream;
 use tokio_core::reactor::Handle;
+use tokio_io::IoFuture;
+
+/// Time (in seconds) to establish direct connection and complete handshake.
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+/// Time (in seconds) to establish connection through proxy and complete handshake.
+const PROXY_CONNECT_TIMEOUT_SECS: u64 = 20;
 
 pub fn connect(address: &SocketAddr, handle: &Handle, config: &Config) -> Deadline<Connect> {
+    let (future, timeout): (IoFuture<TcpStream>, _) = match config.proxy {
+        Some(ref proxy) => (
+            socks5_connect(proxy, address, handle),
+            PROXY_CONNECT_TIMEOUT_SECS,
+        ),
+        None => (
+            Box::new(TcpStream::connect(address, handle)),
+            CONNECT_TIMEOUT_SECS,
+        ),
+    };
+
     let connect = Connect {
         state: ConnectState::TcpConnect {
-            future: TcpStream::connect(address, handle),
+            future: future,
             version: Some(config.version(address)),
         },
         magic: config.magic,
@@ -21,12 +38,12 @@ pub fn connect(address: &SocketAddr, handle: &Handle, config: &Config) -> Deadli
         protocol_minimum: config.protocol_minimum,
     };
 
-    deadline(Duration::new(5, 0), handle, connect).expect("Failed to create timeout")
+    deadline(Duration::from_secs(timeout), handle, connect).expect("Failed to create timeout")
 }
 
 enum ConnectState {
     TcpConnect {
-        future: TcpStreamNew,
+        future: IoFuture<TcpStream>,
         version: Option<Version>,
     },
     Handshake(Handshake<TcpStream>),
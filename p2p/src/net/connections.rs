@@ -46,6 +46,12 @@ impl Connections {
             .collect()
     }
 
+    /// Returns round-trip time of the last answered ping to the peer, in milliseconds.
+    pub fn last_rtt_ms(&self, id: PeerId) -> Option<u64> {
+        self.channel(id)
+            .and_then(|channel| channel.session().stats().lock().last_rtt_ms)
+    }
+
     /// Returns number of connections.
     pub fn count(&self) -> usize {
         self.channels.read().len()
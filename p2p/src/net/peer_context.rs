@@ -4,7 +4,7 @@ use net::PeerStats;
 use p2p::Context;
 use parking_lot::Mutex;
 use std::sync::Arc;
-use util::{ConfigurableSynchronizer, PeerInfo, ResponseQueue, Responses, Synchronizer};
+use util::{ConfigurableSynchronizer, Direction, PeerInfo, ResponseQueue, Responses, Synchronizer};
 
 pub struct PeerContext {
     context: Arc<Context>,
@@ -139,12 +139,17 @@ impl PeerContext {
         }
     }
 
-    /// Closes this context
+    /// Closes this context.
+    /// Closed outbound connection is immediately replaced with the new one.
     pub fn close(&self) {
         let context = self.context.clone();
         let peer_id = self.info.id;
+        let direction = self.info.direction;
         let close = lazy(move || {
             context.close_channel(peer_id);
+            if direction == Direction::Outbound {
+                Context::fill_outbound_connections(context);
+            }
             finished::<(), ()>(())
         });
         self.context.spawn(close);
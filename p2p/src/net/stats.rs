@@ -60,11 +60,14 @@ pub struct PeerStats<T: Interval = RealInterval> {
 
     pub avg_ping: f64,
     pub min_ping: Option<f64>,
+    /// Round-trip time of the last answered ping, in milliseconds.
+    pub last_rtt_ms: Option<u64>,
 
     send_avg: HashMap<Command, RunningAverage>,
     recv_avg: HashMap<Command, RunningAverage>,
 
     last_ping: Option<Instant>,
+    ping_nonce: Option<(u64, Instant)>,
     ping_count: u64,
 
     interval: T,
@@ -115,6 +118,24 @@ impl<I: Interval> PeerStats<I> {
         }
     }
 
+    /// Remembers nonce of the ping request we have just sent.
+    pub fn report_ping_nonce(&mut self, nonce: u64) {
+        self.ping_nonce = Some((nonce, self.interval.now()));
+    }
+
+    /// Updates round-trip time if pong answers the last ping request.
+    pub fn report_pong_nonce(&mut self, nonce: u64) {
+        match self.ping_nonce {
+            Some((ping_nonce, sent)) if ping_nonce == nonce => {
+                let dur = self.interval.elapsed(sent);
+                self.last_rtt_ms =
+                    Some(dur.as_secs() * 1000 + u64::from(dur.subsec_nanos()) / 1_000_000);
+                self.ping_nonce = None;
+            }
+            _ => (),
+        }
+    }
+
     pub fn report_recv(&mut self, command: Command, bytes: usize) {
         self.total_recv += bytes as u64;
         self.last_recv = ::time::get_time().sec as u32;
@@ -190,6 +211,29 @@ mod tests {
         assert!(stats.avg_ping < 0.1);
     }
 
+    #[test]
+    fn ping_pong_round_trip() {
+        let mut stats =
+            PeerStats::<FixedIntervalSpawner>::with_interval(FixedIntervalSpawner::new(50));
+        assert_eq!(stats.last_rtt_ms, None);
+
+        stats.report_send("ping".into(), 8);
+        stats.report_recv("pong".into(), 8);
+        assert_eq!(stats.last_rtt_ms, None);
+
+        stats.report_ping_nonce(7);
+        stats.report_pong_nonce(8);
+        assert_eq!(stats.last_rtt_ms, None);
+
+        stats.report_pong_nonce(7);
+        assert_eq!(stats.last_rtt_ms, Some(50));
+
+        // pong for the already answered ping is ignored
+        stats.last_rtt_ms = None;
+        stats.report_pong_nonce(7);
+        assert_eq!(stats.last_rtt_ms, None);
+    }
+
     #[test]
     fn avg_t() {
         let mut stats = PeerStats::<RealInterval>::default();
@@ -1,31 +1,39 @@
-use abstract_ns::Resolver;
 use futures::stream::Stream;
-use futures::{failed, finished, Future};
+use futures::{failed, finished, lazy, Future};
 use futures_cpupool::{Builder as CpuPoolBuilder, CpuPool};
 use io::DeadlineStatus;
 use message::common::Services;
 use message::types::addr::AddressEntry;
-use message::{Message, MessageResult, Payload};
+use message::{Error as MessageError, Message, MessageResult, Payload};
 use net::{
     accept_connection, connect, Channel, Config as NetConfig, ConnectionCounter, Connections,
 };
-use ns_dns_tokio::DnsResolver;
 use parking_lot::RwLock;
 use protocol::{InboundSyncConnectionRef, LocalSyncNodeRef, OutboundSyncConnectionRef};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use session::{NormalSessionFactory, SeednodeSessionFactory, SessionFactory};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::{error, io, net, time};
 use tokio_core::net::{TcpListener, TcpStream};
 use tokio_core::reactor::{Handle, Interval, Remote, Timeout};
 use tokio_io::IoFuture;
-use util::{Direction, Node, NodeTable, NodeTableError};
+use util::dns_seed::{
+    resolve_dns_seeds, resolve_seed_nodes, system_resolve, DNS_RETRY_DELAYS_MS,
+    DNS_SEED_INTERVAL_SECS,
+};
+use util::{
+    select_eviction_candidate, BanList, BannedNode, Direction, EvictionCandidate, Node, NodeTable,
+    NodeTableError,
+};
 use {Config, PeerId};
 
 pub type BoxedEmptyFuture = Box<dyn Future<Item = (), Error = ()> + Send>;
 
+/// Max number of addresses sent in response to `getaddr` message.
+const MAX_ADDR_ENTRIES: usize = 1000;
+
 /// Network context.
 pub struct Context {
     /// Connections.
@@ -34,6 +42,8 @@ pub struct Context {
     connection_counter: ConnectionCounter,
     /// Node Table.
     node_table: RwLock<NodeTable>,
+    /// Banned addresses.
+    ban_list: RwLock<BanList>,
     /// Thread pool handle.
     pool: CpuPool,
     /// Remote event loop handle.
@@ -62,6 +72,7 @@ impl Context {
                 config.preferable_services,
                 &config.node_table_path,
             )?),
+            ban_list: RwLock::new(BanList::from_file(&config.ban_list_path)?),
             pool: pool_handle,
             remote: remote,
             local_sync_node: local_sync_node,
@@ -101,11 +112,13 @@ impl Context {
         });
     }
 
-    /// Returns addresses of recently active nodes. Sorted and limited to 1000.
+    /// Returns random sample of known nodes, biased towards reliable ones. Limited to 1000.
     pub fn node_table_entries(&self) -> Vec<Node> {
-        self.node_table
-            .read()
-            .recently_active_nodes(self.config.internet_protocol)
+        self.node_table.read().sample_nodes(
+            self.config.internet_protocol,
+            MAX_ADDR_ENTRIES,
+            &mut thread_rng(),
+        )
     }
 
     /// Updates node table.
@@ -114,6 +127,21 @@ impl Context {
         self.node_table.write().insert_many(nodes);
     }
 
+    /// Adds nodes, discovered by DNS seeds, to table.
+    /// Already known and banned nodes are ignored.
+    fn add_seeded_nodes(&self, addresses: Vec<SocketAddr>) {
+        let services = Services::default();
+        let mut node_table = self.node_table.write();
+        let mut added = 0;
+        for address in addresses {
+            if !self.is_banned(&address.ip()) && !node_table.exists(address) {
+                node_table.insert(address, services);
+                added += 1;
+            }
+        }
+        trace!("Added {} nodes from DNS seeds to node table", added);
+    }
+
     /// Penalize node.
     pub fn penalize_node(&self, addr: &SocketAddr) {
         trace!("Penalizing node {}", addr);
@@ -134,6 +162,35 @@ impl Context {
         self.node_table.write().remove(&addr)
     }
 
+    /// Bans address for given number of seconds.
+    /// Existing connections to this address are not closed.
+    pub fn ban_node(&self, addr: IpAddr, duration_secs: i64) {
+        trace!("Banning node {} for {} seconds", addr, duration_secs);
+        self.ban_list.write().ban(addr, duration_secs);
+    }
+
+    /// Removes address from the ban list.
+    pub fn unban_node(&self, addr: &IpAddr) -> bool {
+        trace!("Unbanning node {}", addr);
+        self.ban_list.write().unban(addr)
+    }
+
+    /// Removes all addresses from the ban list.
+    pub fn clear_banned_nodes(&self) {
+        trace!("Clearing ban list");
+        self.ban_list.write().clear();
+    }
+
+    /// Returns all currently banned addresses.
+    pub fn banned_nodes(&self) -> Vec<BannedNode> {
+        self.ban_list.read().banned()
+    }
+
+    /// Is the address currently banned?
+    pub fn is_banned(&self, addr: &IpAddr) -> bool {
+        self.ban_list.read().is_banned(addr)
+    }
+
     /// Every 10 seconds check if we have reached maximum number of outbound connections.
     /// If not, connect to best peers.
     pub fn autoconnect(context: Arc<Context>, handle: &Handle) {
@@ -153,25 +210,7 @@ impl Context {
                         channel.session().maintain();
                     }
 
-                    let needed = context.connection_counter.outbound_connections_needed() as usize;
-                    if needed != 0 {
-                        let used_addresses = context.connections.addresses();
-                        let peers = context.node_table.read().nodes_with_services(
-                            &Services::default(),
-                            context.config.internet_protocol,
-                            &used_addresses,
-                            needed,
-                        );
-                        let addresses = peers
-                            .into_iter()
-                            .map(|peer| peer.address())
-                            .collect::<Vec<_>>();
-
-                        trace!("Creating {} more outbound connections", addresses.len());
-                        for address in addresses {
-                            Context::connect::<NormalSessionFactory>(context.clone(), address);
-                        }
-                    }
+                    Context::fill_outbound_connections(context.clone());
 
                     if let Err(_err) = context
                         .node_table
@@ -181,6 +220,15 @@ impl Context {
                         error!("Saving node table to disk failed");
                     }
 
+                    context.ban_list.write().sweep();
+                    if let Err(_err) = context
+                        .ban_list
+                        .read()
+                        .save_to_file(&context.config.ban_list_path)
+                    {
+                        error!("Saving ban list to disk failed");
+                    }
+
                     Ok(())
                 })
                 .for_each(|_| Ok(()))
@@ -189,6 +237,117 @@ impl Context {
         c.spawn(interval);
     }
 
+    /// Connects to the best known nodes, until configured number of outbound connections is reached.
+    pub fn fill_outbound_connections(context: Arc<Context>) {
+        let needed = context.connection_counter.outbound_connections_needed() as usize;
+        if needed == 0 {
+            return;
+        }
+
+        let used_addresses = context.connections.addresses();
+        let peers = context.node_table.read().nodes_with_services(
+            &Services::default(),
+            context.config.internet_protocol,
+            &used_addresses,
+            needed,
+        );
+        let addresses = peers
+            .into_iter()
+            .map(|peer| peer.address())
+            .filter(|address| !context.is_banned(&address.ip()))
+            .collect::<Vec<_>>();
+
+        trace!("Creating {} more outbound connections", addresses.len());
+        for address in addresses {
+            Context::connect::<NormalSessionFactory>(context.clone(), address);
+        }
+    }
+
+    /// Closes the least valuable inbound connection to make room for the new one.
+    /// Returns false if all inbound connections are protected from eviction.
+    fn evict_inbound_connection(&self) -> bool {
+        let candidates = self
+            .connections
+            .channels()
+            .into_iter()
+            .filter(|&(_, ref channel)| channel.peer_info().direction == Direction::Inbound)
+            .map(|(id, channel)| {
+                let stats = channel.session().stats().lock();
+                EvictionCandidate {
+                    id: id,
+                    address: channel.peer_info().address,
+                    min_ping: stats.min_ping,
+                    last_recv: stats.last_recv,
+                }
+            })
+            .collect();
+
+        match select_eviction_candidate(candidates) {
+            Some(id) => {
+                trace!("Evicting inbound connection with peer#{}", id);
+                self.close_channel(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Resolve DNS seeds now and then every `DNS_SEED_INTERVAL_SECS`.
+    /// Discovered addresses are added to the node table.
+    /// DNS seeding is disabled when proxy is used, so that lookups do not leak outside of proxy.
+    pub fn dns_seed(context: Arc<Context>, handle: &Handle) {
+        if context.config.dns_seeds.is_empty() || context.config.connection.proxy.is_some() {
+            return;
+        }
+
+        let c = context.clone();
+        let interval: BoxedEmptyFuture = Box::new(
+            Interval::new_at(
+                time::Instant::now(),
+                time::Duration::from_secs(DNS_SEED_INTERVAL_SECS),
+                handle,
+            )
+            .expect("Failed to create interval")
+            .and_then(move |_| {
+                // lookups are blocking => resolve seeds on the thread pool, not on the event loop
+                let context = context.clone();
+                context.clone().spawn(lazy(move || {
+                    let addresses = resolve_dns_seeds(&context.config.dns_seeds, system_resolve);
+                    context.add_seeded_nodes(addresses);
+                    finished::<(), ()>(())
+                }));
+                Ok(())
+            })
+            .for_each(|_| Ok(()))
+            .then(|_| finished(())),
+        );
+        c.spawn(interval);
+    }
+
+    /// Resolve seed nodes and connect to them. Failed lookups are repeated after
+    /// `DNS_RETRY_DELAYS_MS` and fallback seeds are used if none of seed nodes is resolved.
+    /// Seed nodes are resolved using system DNS resolver => skip them when proxy is used.
+    pub fn connect_seednodes(context: Arc<Context>) {
+        if context.config.seeds.is_empty() || context.config.connection.proxy.is_some() {
+            return;
+        }
+
+        // lookups are blocking => resolve seed nodes on the thread pool, not on the event loop
+        let c = context.clone();
+        c.spawn(lazy(move || {
+            let addresses = resolve_seed_nodes(
+                &context.config.seeds,
+                context.config.fallback_seeds.clone(),
+                system_resolve,
+                &DNS_RETRY_DELAYS_MS,
+            );
+            for address in addresses {
+                Context::connect_seednode(context.clone(), address);
+            }
+            finished::<(), ()>(())
+        }));
+    }
+
     /// Connect to socket using given context and handle.
     fn connect_future<T>(
         context: Arc<Context>,
@@ -208,10 +367,11 @@ impl Context {
                         Ok(DeadlineStatus::Meet(Ok(connection))) => {
                             // successfull hanshake
                             trace!("Connected to {}", connection.address);
-                            context
-                                .node_table
-                                .write()
-                                .insert(connection.address, connection.services);
+                            {
+                                let mut node_table = context.node_table.write();
+                                node_table.insert(connection.address, connection.services);
+                                node_table.note_connected(&connection.address);
+                            }
                             let channel = context.connections.store::<T>(
                                 context.clone(),
                                 connection,
@@ -256,6 +416,11 @@ impl Context {
     where
         T: SessionFactory,
     {
+        if !context.config.internet_protocol.is_allowed(&socket) {
+            trace!("Skipping connection to {}: network is not allowed", socket);
+            return;
+        }
+
         context.connection_counter.note_new_outbound_connection();
         context.remote.clone().spawn(move |handle| {
             let config = context.config.clone();
@@ -272,6 +437,10 @@ impl Context {
         Self::connect::<NormalSessionFactory>(context, socket)
     }
 
+    pub fn connect_seednode(context: Arc<Context>, socket: net::SocketAddr) {
+        Self::connect::<SeednodeSessionFactory>(context, socket)
+    }
+
     pub fn accept_connection_future(
         context: Arc<Context>,
         stream: TcpStream,
@@ -370,7 +539,12 @@ impl Context {
                     // because we acquire atomic value twice,
                     // it may happen that accept slightly more connections than we need
                     // we don't mind
-                    if context.connection_counter.inbound_connections_needed() > 0 {
+                    if context.is_banned(&socket.ip()) {
+                        trace!("Rejecting connection from banned node {}", socket);
+                        let _ = stream.shutdown(net::Shutdown::Both);
+                    } else if context.connection_counter.inbound_connections_needed() > 0
+                        || context.evict_inbound_connection()
+                    {
                         Context::accept_connection(context.clone(), stream, socket, config.clone());
                     } else {
                         // ignore result
@@ -415,6 +589,12 @@ impl Context {
                 }
                 Ok(Err(err)) => {
                     // protocol error
+                    if err == MessageError::InvalidMagic {
+                        warn!(
+                            "Wrong network magic from {}, disconnecting",
+                            channel.peer_info().address
+                        );
+                    }
                     context.close_channel_with_error(channel.peer_info().id, &err);
                     Box::new(finished(Err(err)))
                 }
@@ -552,8 +732,6 @@ impl Context {
 pub struct P2P {
     /// Global event loop handle.
     event_loop_handle: Handle,
-    /// Worker thread pool.
-    pool: CpuPool,
     /// P2P config.
     config: Config,
     /// Network context.
@@ -586,14 +764,13 @@ impl P2P {
 
         let context = Context::new(
             local_sync_node,
-            pool.clone(),
+            pool,
             handle.remote().clone(),
             config.clone(),
         )?;
 
         let p2p = P2P {
             event_loop_handle: handle.clone(),
-            pool: pool,
             context: Arc::new(context),
             config: config,
         };
@@ -613,11 +790,8 @@ impl P2P {
             self.connect::<NormalSessionFactory>(peer);
         }
 
-        let resolver = DnsResolver::system_config(&self.event_loop_handle)?;
-        for seed in &self.config.seeds {
-            self.connect_to_seednode(&resolver, seed);
-        }
-
+        Context::connect_seednodes(self.context.clone());
+        Context::dns_seed(self.context.clone(), &self.event_loop_handle);
         Context::autoconnect(self.context.clone(), &self.event_loop_handle);
         self.listen()?;
         Ok(())
@@ -631,37 +805,6 @@ impl P2P {
         Context::connect::<T>(self.context.clone(), addr);
     }
 
-    pub fn connect_to_seednode(&self, resolver: &dyn Resolver, seednode: &str) {
-        let owned_seednode = seednode.to_owned();
-        let context = self.context.clone();
-        let dns_lookup = resolver.resolve(seednode).then(move |result| {
-            match result {
-                Ok(address) => match address.pick_one() {
-                    Some(socket) => {
-                        trace!(
-                            "Dns lookup of seednode {} finished. Connecting to {}",
-                            owned_seednode,
-                            socket
-                        );
-                        Context::connect::<SeednodeSessionFactory>(context, socket);
-                    }
-                    None => {
-                        trace!(
-                            "Dns lookup of seednode {} resolved with no results",
-                            owned_seednode
-                        );
-                    }
-                },
-                Err(err) => {
-                    trace!("Dns lookup of seednode {} failed: {}", owned_seednode, err);
-                }
-            }
-            finished(())
-        });
-        let pool_work = self.pool.spawn(dns_lookup);
-        self.event_loop_handle.spawn(pool_work);
-    }
-
     fn listen(&self) -> Result<(), Box<dyn error::Error>> {
         let server = Context::listen(
             self.context.clone(),
@@ -9,16 +9,16 @@ use time;
 use util::nonce::{NonceGenerator, RandomNonce};
 
 /// Time that must pass since last message from this peer, before we send ping request
-const PING_INTERVAL_S: f64 = 60f64;
+const PING_INTERVAL_S: f64 = 120f64;
 /// If peer has not responded to our ping request with pong during this interval => close connection
-const MAX_PING_RESPONSE_TIME_S: f64 = 60f64;
+const PING_TIMEOUT_S: f64 = 20f64;
 
 /// Ping state
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum State {
     /// Peer is sending us messages && we wait for `PING_INTERVAL_S` to pass before sending ping request
     WaitingTimeout(f64),
-    /// Ping message is sent to the peer && we are waiting for pong response for `MAX_PING_RESPONSE_TIME_S`
+    /// Ping message is sent to the peer && we are waiting for pong response for `PING_TIMEOUT_S`
     WaitingPong(f64),
 }
 
@@ -59,18 +59,22 @@ impl Protocol for PingProtocol {
                     let nonce = self.nonce_generator.get();
                     self.state = State::WaitingPong(now);
                     self.last_ping_nonce = Some(nonce);
+                    self.context.stats().lock().report_ping_nonce(nonce);
                     let ping = Ping::new(nonce);
                     self.context.send_request(&ping);
                 }
             }
             State::WaitingPong(time) => {
-                // if no new messages from peer for last MAX_PING_RESPONSE_TIME_S => disconnect
-                if now - time > MAX_PING_RESPONSE_TIME_S {
+                // if no new messages from peer for last PING_TIMEOUT_S => disconnect
+                if now - time > PING_TIMEOUT_S {
                     trace!(
                         "closing connection to peer {}: no messages for last {} seconds",
                         self.context.info().id,
                         now - time
                     );
+                    self.context
+                        .global()
+                        .penalize_node(&self.context.info().address);
                     self.context.close();
                 }
             }
@@ -90,6 +94,7 @@ impl Protocol for PingProtocol {
             if Some(pong.nonce) != self.last_ping_nonce.take() {
                 return Err(Error::InvalidCommand);
             }
+            self.context.stats().lock().report_pong_nonce(pong.nonce);
         }
 
         Ok(())
@@ -3,6 +3,7 @@ use message::{deserialize_payload, types, Command, Error, Payload, Services};
 use net::PeerContext;
 use protocol::Protocol;
 use std::sync::Arc;
+use util::DEFAULT_BAN_TIME_SECS;
 
 pub type InboundSyncConnectionRef = Box<dyn InboundSyncConnection>;
 pub type OutboundSyncConnectionRef = Arc<dyn OutboundSyncConnection>;
@@ -48,6 +49,7 @@ pub trait OutboundSyncConnection: Send + Sync {
     fn send_notfound(&self, message: &types::NotFound);
     fn ignored(&self, id: u32);
     fn close(&self);
+    fn ban(&self);
 }
 
 struct OutboundSync {
@@ -107,6 +109,13 @@ impl OutboundSyncConnection for OutboundSync {
             .penalize_node(&self.context.info().address);
         self.context.close()
     }
+
+    fn ban(&self) {
+        self.context
+            .global()
+            .ban_node(self.context.info().address.ip(), DEFAULT_BAN_TIME_SECS);
+        self.context.close()
+    }
 }
 
 pub struct SyncProtocol {
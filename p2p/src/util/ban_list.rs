@@ -0,0 +1,232 @@
+use csv;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::{fs, io, path};
+use util::time::{RealTime, Time};
+
+/// Default duration of the ban (24 hours).
+pub const DEFAULT_BAN_TIME_SECS: i64 = 24 * 60 * 60;
+/// Maximal duration of the ban (10 years).
+pub const MAX_BAN_TIME_SECS: i64 = 10 * 365 * 24 * 60 * 60;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BannedNode {
+    /// Banned address.
+    addr: IpAddr,
+    /// Timestamp, until which the address is banned.
+    until: i64,
+}
+
+impl BannedNode {
+    pub fn address(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn banned_until(&self) -> i64 {
+        self.until
+    }
+}
+
+/// Addresses, which we are refusing to connect to or accept connections from.
+#[derive(Default, Debug)]
+pub struct BanList<T = RealTime>
+where
+    T: Time,
+{
+    /// Time source.
+    time: T,
+    /// Ban expiration time by address.
+    by_addr: HashMap<IpAddr, i64>,
+}
+
+impl BanList {
+    /// Opens a file and loads ban list from it.
+    pub fn from_file<P>(path: P) -> Result<Self, io::Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            // without opening for write, mac os returns os error 22
+            .write(true)
+            .open(path)
+            .and_then(Self::load)
+    }
+
+    /// Saves ban list to file
+    pub fn save_to_file<P>(&self, path: P) -> Result<(), io::Error>
+    where
+        P: AsRef<path::Path>,
+    {
+        fs::File::create(path).and_then(|file| self.save(file))
+    }
+}
+
+impl<T> BanList<T>
+where
+    T: Time,
+{
+    /// Bans address for given number of seconds, but no longer than `MAX_BAN_TIME_SECS`.
+    /// Existing ban is replaced.
+    pub fn ban(&mut self, addr: IpAddr, duration_secs: i64) {
+        let until = self
+            .time
+            .get()
+            .sec
+            .saturating_add(duration_secs.min(MAX_BAN_TIME_SECS));
+        self.by_addr.insert(addr, until);
+    }
+
+    /// Removes address from the ban list.
+    /// Returns `true` if address has been banned.
+    pub fn unban(&mut self, addr: &IpAddr) -> bool {
+        self.by_addr.remove(addr).is_some()
+    }
+
+    /// Removes all addresses from the ban list.
+    pub fn clear(&mut self) {
+        self.by_addr.clear();
+    }
+
+    /// Is the address currently banned?
+    pub fn is_banned(&self, addr: &IpAddr) -> bool {
+        let now = self.time.get().sec;
+        self.by_addr
+            .get(addr)
+            .map(|until| *until > now)
+            .unwrap_or(false)
+    }
+
+    /// Removes expired bans.
+    pub fn sweep(&mut self) {
+        let now = self.time.get().sec;
+        self.by_addr.retain(|_, until| *until > now);
+    }
+
+    /// Returns all active bans, sorted by address.
+    pub fn banned(&self) -> Vec<BannedNode> {
+        let now = self.time.get().sec;
+        let mut banned: Vec<_> = self
+            .by_addr
+            .iter()
+            .filter(|&(_, until)| *until > now)
+            .map(|(addr, until)| BannedNode {
+                addr: *addr,
+                until: *until,
+            })
+            .collect();
+        banned.sort_by_key(|node| node.addr);
+        banned
+    }
+
+    /// Save ban list in csv format.
+    pub fn save<W>(&self, write: W) -> Result<(), io::Error>
+    where
+        W: io::Write,
+    {
+        let mut writer = csv::WriterBuilder::new().delimiter(b' ').from_writer(write);
+
+        let err = || io::Error::new(io::ErrorKind::Other, "Write csv error");
+
+        for node in self.banned() {
+            let record = (node.addr.to_string(), node.until);
+            writer.serialize(record).map_err(|_| err())?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads ban list from a csv source.
+    pub fn load<R>(read: R) -> Result<Self, io::Error>
+    where
+        R: io::Read,
+        T: Default,
+    {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .delimiter(b' ')
+            .from_reader(read);
+
+        let mut ban_list = BanList::default();
+
+        let err = || io::Error::new(io::ErrorKind::Other, "Load csv error");
+
+        for row in rdr.deserialize() {
+            let (addr, until): (String, i64) = row.map_err(|_| err())?;
+            ban_list
+                .by_addr
+                .insert(addr.parse().map_err(|_| err())?, until);
+        }
+
+        ban_list.sweep();
+        Ok(ban_list)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BanList, MAX_BAN_TIME_SECS};
+    use std::net::IpAddr;
+    use util::time::{IncrementalTime, ZeroTime};
+
+    #[test]
+    fn test_ban_list_ban_unban() {
+        let a0: IpAddr = "127.0.0.1".parse().unwrap();
+        let a1: IpAddr = "127.0.0.2".parse().unwrap();
+        let mut list = BanList::<ZeroTime>::default();
+        list.ban(a0, 100);
+        assert!(list.is_banned(&a0));
+        assert!(!list.is_banned(&a1));
+        assert!(list.unban(&a0));
+        assert!(!list.unban(&a0));
+        assert!(!list.is_banned(&a0));
+
+        list.ban(a0, 100);
+        list.ban(a1, 100);
+        assert_eq!(list.banned().len(), 2);
+        list.clear();
+        assert!(list.banned().is_empty());
+    }
+
+    #[test]
+    fn test_ban_list_expiry() {
+        let a0: IpAddr = "127.0.0.1".parse().unwrap();
+        let a1: IpAddr = "127.0.0.2".parse().unwrap();
+        let mut list = BanList::<IncrementalTime>::default();
+        // banned until 2 and 10
+        list.ban(a0, 2);
+        list.ban(a1, 9);
+        // now is 2
+        assert!(!list.is_banned(&a0));
+        // now is 3
+        assert!(list.is_banned(&a1));
+        // now is 4
+        let banned = list.banned();
+        assert_eq!(banned.len(), 1);
+        assert_eq!(banned[0].address(), a1);
+        assert_eq!(banned[0].banned_until(), 10);
+    }
+
+    #[test]
+    fn test_ban_list_max_ban_time() {
+        let a0: IpAddr = "127.0.0.1".parse().unwrap();
+        let mut list = BanList::<ZeroTime>::default();
+        list.ban(a0, i64::max_value());
+        assert_eq!(list.banned()[0].banned_until(), MAX_BAN_TIME_SECS);
+    }
+
+    #[test]
+    fn test_ban_list_save_load() {
+        let a0: IpAddr = "127.0.0.1".parse().unwrap();
+        let a1: IpAddr = "::1".parse().unwrap();
+        let mut list = BanList::<ZeroTime>::default();
+        list.ban(a0, 100);
+        list.ban(a1, 200);
+
+        let mut db = Vec::new();
+        list.save(&mut db).unwrap();
+        let loaded = BanList::<ZeroTime>::load(&db as &[u8]).unwrap();
+        assert_eq!(loaded.banned(), list.banned());
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::thread;
+use std::time::Duration;
+
+/// Interval (in seconds) between repeated lookups of DNS seeds.
+pub const DNS_SEED_INTERVAL_SECS: u64 = 30 * 60;
+/// Delays (in ms) before repeating failed seed node DNS lookup.
+pub const DNS_RETRY_DELAYS_MS: [u64; 3] = [1000, 2000, 4000];
+
+/// Resolve DNS seed or seed node using system DNS resolver.
+/// Lookup is blocking => it must never be called from the event loop thread.
+pub fn system_resolve(seed: &str) -> io::Result<Vec<SocketAddr>> {
+    seed.to_socket_addrs().map(Iterator::collect)
+}
+
+/// Resolve all DNS seeds, returning discovered addresses without duplicates.
+/// Seeds, which could not be resolved, are skipped.
+pub fn resolve_dns_seeds<F>(seeds: &[String], resolve: F) -> Vec<SocketAddr>
+where
+    F: Fn(&str) -> io::Result<Vec<SocketAddr>>,
+{
+    let mut known = HashSet::new();
+    let mut resolved = Vec::new();
+    for seed in seeds {
+        match resolve(seed) {
+            Ok(addresses) => {
+                trace!(
+                    "DNS seed {} resolved to {} addresses",
+                    seed,
+                    addresses.len()
+                );
+                resolved.extend(addresses.into_iter().filter(|a| known.insert(*a)));
+            }
+            Err(err) => {
+                warn!("DNS lookup of seed {} has failed: {}", seed, err);
+            }
+        }
+    }
+    resolved
+}
+
+/// Resolve seed nodes addresses, repeating failed lookups after given delays.
+/// If none of seed nodes could be resolved, fallback nodes are returned.
+pub fn resolve_seed_nodes<F>(
+    seednodes: &[String],
+    fallback: Vec<SocketAddr>,
+    resolve: F,
+    retry_delays_ms: &[u64],
+) -> Vec<SocketAddr>
+where
+    F: Fn(&str) -> io::Result<Vec<SocketAddr>>,
+{
+    let mut resolved = Vec::new();
+    for seednode in seednodes {
+        let mut delays = retry_delays_ms.iter();
+        loop {
+            match resolve(seednode) {
+                Ok(addresses) => {
+                    resolved.extend(addresses);
+                    break;
+                }
+                Err(err) => match delays.next() {
+                    Some(delay) => {
+                        trace!(
+                            "DNS lookup of seednode {} has failed: {}. Retrying in {}ms",
+                            seednode,
+                            err,
+                            delay
+                        );
+                        thread::sleep(Duration::from_millis(*delay));
+                    }
+                    None => {
+                        warn!("DNS lookup of seednode {} has failed: {}", seednode, err);
+                        break;
+                    }
+                },
+            }
+        }
+    }
+
+    if resolved.is_empty() {
+        fallback
+    } else {
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resolve_dns_seeds, resolve_seed_nodes};
+    use std::cell::Cell;
+    use std::io;
+    use std::net::SocketAddr;
+
+    #[test]
+    fn resolve_dns_seeds_skips_failed_lookups() {
+        let address: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let resolved = resolve_dns_seeds(
+            &[
+                "bad.example:8333".to_owned(),
+                "good.example:8333".to_owned(),
+            ],
+            |seed| {
+                if seed.starts_with("bad") {
+                    Err(io::Error::new(io::ErrorKind::Other, "dns error"))
+                } else {
+                    Ok(vec![address])
+                }
+            },
+        );
+        assert_eq!(resolved, vec![address]);
+    }
+
+    #[test]
+    fn resolve_dns_seeds_removes_duplicates() {
+        let address1: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let address2: SocketAddr = "10.0.0.2:8333".parse().unwrap();
+        let resolved = resolve_dns_seeds(
+            &[
+                "seed1.example:8333".to_owned(),
+                "seed2.example:8333".to_owned(),
+            ],
+            |seed| {
+                if seed.starts_with("seed1") {
+                    Ok(vec![address1, address2])
+                } else {
+                    Ok(vec![address2, address1])
+                }
+            },
+        );
+        assert_eq!(resolved, vec![address1, address2]);
+    }
+
+    #[test]
+    fn resolve_seed_nodes_uses_fallback_on_dns_error() {
+        let attempts = Cell::new(0);
+        let fallback: Vec<SocketAddr> = vec!["127.0.0.1:8333".parse().unwrap()];
+        let resolved = resolve_seed_nodes(
+            &["seed.example:8333".to_owned()],
+            fallback.clone(),
+            |_| {
+                attempts.set(attempts.get() + 1);
+                Err(io::Error::new(io::ErrorKind::Other, "dns error"))
+            },
+            &[0, 0, 0],
+        );
+        assert_eq!(resolved, fallback);
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[test]
+    fn resolve_seed_nodes_retries_failed_lookups() {
+        let attempts = Cell::new(0);
+        let address: SocketAddr = "10.0.0.1:8333".parse().unwrap();
+        let resolved = resolve_seed_nodes(
+            &["seed.example:8333".to_owned()],
+            vec!["127.0.0.1:8333".parse().unwrap()],
+            |_| {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(io::Error::new(io::ErrorKind::Other, "dns error"))
+                } else {
+                    Ok(vec![address])
+                }
+            },
+            &[0, 0, 0],
+        );
+        assert_eq!(resolved, vec![address]);
+        assert_eq!(attempts.get(), 3);
+    }
+}
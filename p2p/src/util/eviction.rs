@@ -0,0 +1,108 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::f64;
+use std::net::{IpAddr, SocketAddr};
+use util::{network_group, PeerId};
+
+/// Number of inbound peers with the lowest ping, which are never evicted.
+const PROTECTED_BY_PING: usize = 8;
+/// Number of inbound peers, which have most recently sent us a message, which are never evicted.
+const PROTECTED_BY_ACTIVITY: usize = 4;
+
+/// Inbound connection, which could be closed to make room for the new one.
+#[derive(Debug, Clone)]
+pub struct EvictionCandidate {
+    /// Peer id. Ids are assigned incrementally, so older connections have lower ids.
+    pub id: PeerId,
+    /// Peer address.
+    pub address: SocketAddr,
+    /// Lowest ping time in seconds, if peer has ever responded to ping.
+    pub min_ping: Option<f64>,
+    /// Timestamp of the last message, received from peer.
+    pub last_recv: u32,
+}
+
+/// Selects inbound connection to evict.
+/// Peers with low ping, recently active peers and long-lived peers are protected.
+/// Among the rest, the youngest peer from the most populated network group is selected.
+/// Returns `None` if all connections are protected.
+pub fn select_eviction_candidate(mut candidates: Vec<EvictionCandidate>) -> Option<PeerId> {
+    candidates.sort_by(|a, b| {
+        let a_ping = a.min_ping.unwrap_or(f64::MAX);
+        let b_ping = b.min_ping.unwrap_or(f64::MAX);
+        a_ping.partial_cmp(&b_ping).expect("ping is never NaN; qed")
+    });
+    protect(&mut candidates, PROTECTED_BY_PING);
+
+    candidates.sort_by(|a, b| b.last_recv.cmp(&a.last_recv));
+    protect(&mut candidates, PROTECTED_BY_ACTIVITY);
+
+    candidates.sort_by_key(|c| c.id);
+    let protected_by_age = candidates.len() / 2;
+    protect(&mut candidates, protected_by_age);
+
+    let mut groups: HashMap<IpAddr, Vec<PeerId>> = HashMap::new();
+    for candidate in candidates {
+        groups
+            .entry(network_group(&candidate.address))
+            .or_insert_with(Vec::new)
+            .push(candidate.id);
+    }
+
+    // peers in every group are ordered by id => the last one is the youngest
+    groups
+        .values()
+        .max_by_key(|peers| (peers.len(), peers[peers.len() - 1]))
+        .map(|peers| peers[peers.len() - 1])
+}
+
+/// Removes first `count` candidates from the list.
+fn protect(candidates: &mut Vec<EvictionCandidate>, count: usize) {
+    let count = cmp::min(count, candidates.len());
+    candidates.drain(..count);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{select_eviction_candidate, EvictionCandidate};
+    use std::net::SocketAddr;
+
+    fn candidate(
+        id: usize,
+        address: &str,
+        min_ping: Option<f64>,
+        last_recv: u32,
+    ) -> EvictionCandidate {
+        EvictionCandidate {
+            id: id,
+            address: address.parse::<SocketAddr>().unwrap(),
+            min_ping: min_ping,
+            last_recv: last_recv,
+        }
+    }
+
+    #[test]
+    fn select_eviction_candidate_protects_all_peers() {
+        let candidates = (0..12)
+            .map(|id| candidate(id, "10.0.0.1:8333", Some(0.1), 0))
+            .collect();
+        assert_eq!(select_eviction_candidate(candidates), None);
+    }
+
+    #[test]
+    fn select_eviction_candidate_evicts_youngest_from_largest_group() {
+        // 8 fast peers and 4 recently active peers are protected
+        let mut candidates: Vec<_> = (0..8)
+            .map(|id| candidate(id, "10.0.0.1:8333", Some(0.1), 0))
+            .chain((8..12).map(|id| candidate(id, "10.0.0.1:8333", None, 100)))
+            .collect();
+        // 3 oldest of the remaining 6 peers are protected
+        candidates.push(candidate(12, "10.1.0.1:8333", None, 0));
+        candidates.push(candidate(13, "10.1.0.2:8333", None, 0));
+        candidates.push(candidate(14, "10.2.0.1:8333", None, 0));
+        candidates.push(candidate(15, "10.2.0.2:8333", None, 0));
+        candidates.push(candidate(16, "10.2.0.3:8333", None, 0));
+        candidates.push(candidate(17, "10.3.0.1:8333", None, 0));
+        assert_eq!(select_eviction_candidate(candidates), Some(16));
+    }
+}
@@ -1,3 +1,6 @@
+mod ban_list;
+pub mod dns_seed;
+mod eviction;
 mod internet_protocol;
 pub mod interval;
 mod node_table;
@@ -7,8 +10,10 @@ mod response_queue;
 mod synchronizer;
 pub mod time;
 
+pub use self::ban_list::{BanList, BannedNode, DEFAULT_BAN_TIME_SECS, MAX_BAN_TIME_SECS};
+pub use self::eviction::{select_eviction_candidate, EvictionCandidate};
 pub use self::internet_protocol::InternetProtocol;
-pub use self::node_table::{Node, NodeTable, NodeTableError};
+pub use self::node_table::{network_group, Node, NodeTable, NodeTableError};
 pub use self::peer::{Direction, PeerId, PeerInfo};
 pub use self::response_queue::{ResponseQueue, Responses};
 pub use self::synchronizer::{ConfigurableSynchronizer, Synchronizer};
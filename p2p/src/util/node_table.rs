@@ -1,20 +1,37 @@
 use csv;
 use message::common::{NetAddress, Services};
 use message::types::addr::AddressEntry;
+use rand::Rng;
 use std::cmp::{Ord, Ordering, PartialOrd};
 use std::collections::hash_map::Entry;
 use std::collections::{BTreeSet, HashMap, HashSet};
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::{fs, io, net, path};
 use util::time::{RealTime, Time};
 use util::InternetProtocol;
 
+/// Max number of never connected nodes from the same network group.
+/// Protects node table from being flooded with addresses from a single network.
+const MAX_NEW_NODES_PER_GROUP: usize = 64;
+/// Nodes not seen for this number of seconds are not advertised and not persisted.
+const MAX_NODE_AGE_SECS: i64 = 30 * 24 * 60 * 60;
+/// Never connected nodes are considered terrible after this number of failures.
+const MAX_NEW_NODE_FAILURES: u32 = 3;
+/// Previously connected nodes are considered terrible after this number of failures...
+const MAX_NODE_FAILURES: u32 = 10;
+/// ...if there were no successful connections during this number of seconds.
+const MIN_NODE_FAILURES_SECS: i64 = 7 * 24 * 60 * 60;
+/// Max number of nodes to store in node table file.
+const MAX_SAVED_NODES: usize = 1000;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Node {
     /// Node address.
     addr: SocketAddr,
     /// Timestamp of last interaction with a node.
     time: i64,
+    /// Timestamp of last successful outbound connection to a node. Zero if never connected.
+    last_success: i64,
     /// Services supported by the node.
     services: Services,
     /// Is preferable node?
@@ -27,6 +44,56 @@ impl Node {
     pub fn address(&self) -> SocketAddr {
         self.addr
     }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Have we ever successfully connected to this node?
+    fn is_tried(&self) -> bool {
+        self.last_success != 0
+    }
+
+    /// Is node too old or too unreliable to be advertised and persisted?
+    fn is_terrible(&self, now: i64) -> bool {
+        if now - self.time > MAX_NODE_AGE_SECS {
+            return true;
+        }
+
+        if self.is_tried() {
+            self.failures >= MAX_NODE_FAILURES && now - self.last_success > MIN_NODE_FAILURES_SECS
+        } else {
+            self.failures >= MAX_NEW_NODE_FAILURES
+        }
+    }
+
+    /// Weight of node, when randomly selecting nodes to advertise.
+    /// Recently seen and tried nodes are more likely to be selected.
+    fn sample_weight(&self, now: i64) -> f64 {
+        let age_days = (now - self.time).max(0) as f64 / (24 * 60 * 60) as f64;
+        let tried_factor = if self.is_tried() { 2f64 } else { 1f64 };
+        tried_factor / (1f64 + age_days) / (1f64 + self.failures as f64)
+    }
+}
+
+/// Network group of the address. Node table limits number of never connected
+/// nodes per group, so that single network can not occupy the whole table.
+pub fn network_group(addr: &SocketAddr) -> IpAddr {
+    let ipv4_group = |ip: Ipv4Addr| {
+        let octets = ip.octets();
+        IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], 0, 0))
+    };
+
+    match *addr {
+        SocketAddr::V4(ref v4) => ipv4_group(*v4.ip()),
+        SocketAddr::V6(ref v6) => match v6.ip().to_ipv4() {
+            Some(v4) => ipv4_group(v4),
+            None => {
+                let segments = v6.ip().segments();
+                IpAddr::V6(Ipv6Addr::new(segments[0], segments[1], 0, 0, 0, 0, 0, 0))
+            }
+        },
+    }
 }
 
 impl From<Node> for AddressEntry {
@@ -55,7 +122,9 @@ impl PartialOrd for NodeByScore {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         if self.0.failures == other.0.failures {
             if self.0.is_preferable == other.0.is_preferable {
-                if other.0.time == self.0.time {
+                if self.0.is_tried() != other.0.is_tried() {
+                    other.0.is_tried().partial_cmp(&self.0.is_tried())
+                } else if other.0.time == self.0.time {
                     other.0.partial_cmp(&self.0)
                 } else {
                     other.0.time.partial_cmp(&self.0.time)
@@ -75,7 +144,9 @@ impl Ord for NodeByScore {
     fn cmp(&self, other: &Self) -> Ordering {
         if self.0.failures == other.0.failures {
             if self.0.is_preferable == other.0.is_preferable {
-                if other.0.time == self.0.time {
+                if self.0.is_tried() != other.0.is_tried() {
+                    other.0.is_tried().cmp(&self.0.is_tried())
+                } else if other.0.time == self.0.time {
                     other.0.cmp(&self.0)
                 } else {
                     other.0.time.cmp(&self.0.time)
@@ -179,6 +250,8 @@ where
     by_score: BTreeSet<NodeByScore>,
     /// Nodes sorted by time.
     by_time: BTreeSet<NodeByTime>,
+    /// Number of never connected nodes by network group.
+    new_by_group: HashMap<IpAddr, usize>,
 }
 
 impl NodeTable {
@@ -235,12 +308,14 @@ where
                 let node = Node {
                     addr: addr,
                     time: now,
+                    last_success: 0,
                     services: services,
                     is_preferable: services.includes(&self.preferable_services),
                     failures: 0,
                 };
                 self.by_score.insert(node.clone().into());
                 self.by_time.insert(node.clone().into());
+                *self.new_by_group.entry(network_group(&addr)).or_insert(0) += 1;
                 entry.insert(node);
             }
         }
@@ -266,6 +341,9 @@ where
         let node = self.by_addr.remove(&addr);
         match node {
             Some(val) => {
+                if !val.is_tried() {
+                    self.forget_new_node(&val.addr);
+                }
                 self.by_time.remove(&val.clone().into());
                 self.by_score.remove(&val.into());
                 Ok(())
@@ -276,7 +354,8 @@ where
 
     /// Inserts many new addresses into node table.
     /// Used in `addr` request handler.
-    /// Discards all nodes with timestamp newer than current time.
+    /// Discards all nodes with timestamp newer than current time
+    /// and new nodes from network groups, which already have too many never connected nodes.
     pub fn insert_many(&mut self, addresses: Vec<AddressEntry>) {
         // discard all nodes with timestamp newer than current time.
         let now = self.time.get().sec;
@@ -289,6 +368,7 @@ where
             let node = Node {
                 addr: SocketAddr::new(addr.address.address.into(), addr.address.port.into()),
                 time: addr.timestamp as i64,
+                last_success: 0,
                 services: addr.address.services,
                 is_preferable: addr.address.services.includes(&self.preferable_services),
                 failures: 0,
@@ -310,6 +390,14 @@ where
                 }
                 Entry::Vacant(entry) => {
                     // it's first time we see this node
+                    let group = self
+                        .new_by_group
+                        .entry(network_group(&node.addr))
+                        .or_insert(0);
+                    if *group >= MAX_NEW_NODES_PER_GROUP {
+                        continue;
+                    }
+                    *group += 1;
                     self.by_score.insert(node.clone().into());
                     self.by_time.insert(node.clone().into());
                     entry.insert(node);
@@ -359,20 +447,34 @@ where
         self.by_addr.iter().map(|(_, n)| n).cloned().collect()
     }
 
-    /// Returns most recently active nodes.
-    ///
-    /// The documenation says:
-    /// "Non-advertised nodes should be forgotten after typically 3 hours"
-    /// but bitcoin client still advertises them even after a month.
-    /// Let's do the same.
-    ///
-    /// https://en.bitcoin.it/wiki/Protocol_documentation#addr
-    pub fn recently_active_nodes(&self, protocol: InternetProtocol) -> Vec<Node> {
-        self.by_time
+    /// Returns random sample of nodes to advertise in `addr` message.
+    /// Terrible nodes are never returned. Recently seen and previously
+    /// connected nodes are more likely to be selected.
+    pub fn sample_nodes<R>(
+        &self,
+        protocol: InternetProtocol,
+        limit: usize,
+        rng: &mut R,
+    ) -> Vec<Node>
+    where
+        R: Rng,
+    {
+        let now = self.time.get().sec;
+        // weighted random sampling: every node gets key `u^(1/weight)`, nodes with largest keys are selected
+        let mut candidates: Vec<(f64, &Node)> = self
+            .by_time
             .iter()
-            .filter(|node| protocol.is_allowed(&node.0.addr))
-            .map(|node| node.0.clone())
-            .take(1000)
+            .map(|node| &node.0)
+            .take_while(|node| now - node.time <= MAX_NODE_AGE_SECS)
+            .filter(|node| protocol.is_allowed(&node.addr))
+            .filter(|node| !node.is_terrible(now))
+            .map(|node| (rng.gen::<f64>().powf(1f64 / node.sample_weight(now)), node))
+            .collect();
+        candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(_, node)| node.clone())
             .collect()
     }
 
@@ -387,6 +489,29 @@ where
         }
     }
 
+    /// Notes successful outbound connection to the node.
+    pub fn note_connected(&mut self, addr: &SocketAddr) {
+        let now = self.time.get().sec;
+        let was_tried = match self.by_addr.get_mut(addr) {
+            Some(node) => {
+                assert!(self.by_score.remove(&node.clone().into()));
+                assert!(self.by_time.remove(&node.clone().into()));
+                let was_tried = node.is_tried();
+                node.last_success = now;
+                node.time = now;
+                node.failures = 0;
+                self.by_score.insert(node.clone().into());
+                self.by_time.insert(node.clone().into());
+                was_tried
+            }
+            None => return,
+        };
+
+        if !was_tried {
+            self.forget_new_node(addr);
+        }
+    }
+
     /// Notes failure.
     pub fn note_failure(&mut self, addr: &SocketAddr) {
         if let Some(ref mut node) = self.by_addr.get_mut(addr) {
@@ -398,13 +523,29 @@ where
         }
     }
 
+    /// Called when never connected node is either removed or connected.
+    fn forget_new_node(&mut self, addr: &SocketAddr) {
+        if let Entry::Occupied(mut group) = self.new_by_group.entry(network_group(addr)) {
+            *group.get_mut() -= 1;
+            if *group.get() == 0 {
+                group.remove();
+            }
+        }
+    }
+
     /// Save node table in csv format.
     pub fn save<W>(&self, write: W) -> Result<(), io::Error>
     where
         W: io::Write,
     {
         let mut writer = csv::WriterBuilder::new().delimiter(b' ').from_writer(write);
-        let iter = self.by_score.iter().map(|node| &node.0).take(1000);
+        let now = self.time.get().sec;
+        let iter = self
+            .by_score
+            .iter()
+            .map(|node| &node.0)
+            .filter(|node| !node.is_terrible(now))
+            .take(MAX_SAVED_NODES);
 
         let err = || io::Error::new(io::ErrorKind::Other, "Write csv error");
 
@@ -414,6 +555,7 @@ where
                 n.time,
                 u64::from(n.services),
                 n.failures,
+                n.last_success,
             );
             writer.serialize(record).map_err(|_| err())?;
         }
@@ -427,8 +569,10 @@ where
         R: io::Read,
         T: Default,
     {
+        // node tables, saved by previous versions, have no `last_success` column
         let mut rdr = csv::ReaderBuilder::new()
             .has_headers(false)
+            .flexible(true)
             .delimiter(b' ')
             .from_reader(read);
 
@@ -437,19 +581,32 @@ where
 
         let err = || io::Error::new(io::ErrorKind::Other, "Load csv error");
 
-        for row in rdr.deserialize() {
-            let (addr, time, services, failures): (String, i64, u64, u32) =
-                row.map_err(|_| err())?;
+        for row in rdr.records() {
+            let row = row.map_err(|_| err())?;
+            if row.len() != 4 && row.len() != 5 {
+                return Err(err());
+            }
 
+            let services: u64 = row[2].parse().map_err(|_| err())?;
             let services = services.into();
             let node = Node {
-                addr: addr.parse().map_err(|_| err())?,
-                time: time,
+                addr: row[0].parse().map_err(|_| err())?,
+                time: row[1].parse().map_err(|_| err())?,
+                last_success: match row.get(4) {
+                    Some(last_success) => last_success.parse().map_err(|_| err())?,
+                    None => 0,
+                },
                 services: services,
                 is_preferable: services.includes(&preferable_services),
-                failures: failures,
+                failures: row[3].parse().map_err(|_| err())?,
             };
 
+            if !node.is_tried() {
+                *node_table
+                    .new_by_group
+                    .entry(network_group(&node.addr))
+                    .or_insert(0) += 1;
+            }
             node_table.by_score.insert(node.clone().into());
             node_table.by_time.insert(node.clone().into());
             node_table.by_addr.insert(node.addr, node);
@@ -461,10 +618,13 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::NodeTable;
-    use message::common::Services;
+    use super::{NodeTable, MAX_NEW_NODES_PER_GROUP, MAX_NEW_NODE_FAILURES};
+    use message::common::{NetAddress, Services};
+    use message::types::addr::AddressEntry;
+    use rand::thread_rng;
     use std::collections::HashSet;
-    use std::net::SocketAddr;
+    use std::iter;
+    use std::net::{IpAddr, SocketAddr};
     use util::time::{IncrementalTime, ZeroTime};
     use util::InternetProtocol;
 
@@ -538,28 +698,9 @@ mod tests {
         assert_eq!(nodes[4].time, 3);
         assert_eq!(nodes[4].failures, 1);
 
-        let nodes = table.recently_active_nodes(InternetProtocol::default());
-        assert_eq!(nodes.len(), 5);
-
-        assert_eq!(nodes[0].addr, s1);
-        assert_eq!(nodes[0].time, 7);
-        assert_eq!(nodes[0].failures, 0);
-
-        assert_eq!(nodes[1].addr, s4);
-        assert_eq!(nodes[1].time, 6);
-        assert_eq!(nodes[1].failures, 0);
-
-        assert_eq!(nodes[2].addr, s2);
-        assert_eq!(nodes[2].time, 5);
-        assert_eq!(nodes[2].failures, 1);
-
-        assert_eq!(nodes[3].addr, s3);
-        assert_eq!(nodes[3].time, 3);
-        assert_eq!(nodes[3].failures, 1);
-
-        assert_eq!(nodes[4].addr, s0);
-        assert_eq!(nodes[4].time, 0);
-        assert_eq!(nodes[4].failures, 0);
+        let nodes = table.sample_nodes(InternetProtocol::default(), 10, &mut thread_rng());
+        let addresses: HashSet<_> = nodes.into_iter().map(|n| n.addr).collect();
+        assert_eq!(addresses, vec![s0, s1, s2, s3, s4].into_iter().collect());
     }
 
     #[test]
@@ -643,11 +784,11 @@ mod tests {
 
         let s = String::from_utf8(db).unwrap();
         assert_eq!(
-            "127.0.0.1:8001 7 0 0
-127.0.0.1:8004 6 0 0
-127.0.0.1:8000 0 0 0
-127.0.0.1:8002 5 0 1
-127.0.0.1:8003 3 0 1
+            "127.0.0.1:8001 7 0 0 0
+127.0.0.1:8004 6 0 0 0
+127.0.0.1:8000 0 0 0 0
+127.0.0.1:8002 5 0 1 0
+127.0.0.1:8003 3 0 1 0
 "
             .to_string(),
             s
@@ -697,4 +838,91 @@ mod tests {
             s1
         );
     }
+
+    #[test]
+    fn test_load_without_last_success() {
+        let db = "127.0.0.1:8001 7 0 0\n127.0.0.1:8002 5 0 1 4\n";
+        let table = NodeTable::<ZeroTime>::load(Services::default(), db.as_bytes()).unwrap();
+        let s1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let s2: SocketAddr = "127.0.0.1:8002".parse().unwrap();
+        assert_eq!(table.by_addr[&s1].last_success, 0);
+        assert_eq!(table.by_addr[&s2].last_success, 4);
+        assert_eq!(table.new_by_group.values().sum::<usize>(), 1);
+    }
+
+    #[test]
+    fn test_note_connected() {
+        let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+        let s1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let mut table = NodeTable::<IncrementalTime>::default();
+        table.insert(s0, Services::default());
+        table.insert(s1, Services::default());
+        table.note_failure(&s0);
+        table.note_connected(&s0);
+        table.note_used(&s1);
+        assert_eq!(table.new_by_group.values().sum::<usize>(), 1);
+
+        // connected node is preferred over more recently used one
+        let nodes = table.nodes_with_services(
+            &Services::default(),
+            InternetProtocol::default(),
+            &HashSet::new(),
+            2,
+        );
+        assert_eq!(nodes[0].addr, s0);
+        assert_eq!(nodes[0].failures, 0);
+        assert_eq!(nodes[0].last_success, 2);
+        assert_eq!(nodes[1].addr, s1);
+    }
+
+    #[test]
+    fn test_insert_many_limits_new_nodes_per_group() {
+        let mut table = NodeTable::<ZeroTime>::default();
+        let entries = (0..MAX_NEW_NODES_PER_GROUP + 10)
+            .map(|i| AddressEntry {
+                timestamp: 0,
+                address: NetAddress {
+                    services: Services::default(),
+                    address: format!("10.0.{}.{}", i / 256, i % 256)
+                        .parse::<IpAddr>()
+                        .unwrap()
+                        .into(),
+                    port: 8333.into(),
+                },
+            })
+            .chain(iter::once(AddressEntry {
+                timestamp: 0,
+                address: NetAddress {
+                    services: Services::default(),
+                    address: "10.1.0.1".parse::<IpAddr>().unwrap().into(),
+                    port: 8333.into(),
+                },
+            }))
+            .collect();
+        table.insert_many(entries);
+        assert_eq!(table.by_addr.len(), MAX_NEW_NODES_PER_GROUP + 1);
+        assert!(table.exists("10.1.0.1:8333".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_sample_nodes_skips_terrible_nodes() {
+        let s0: SocketAddr = "127.0.0.1:8000".parse().unwrap();
+        let s1: SocketAddr = "127.0.0.1:8001".parse().unwrap();
+        let mut table = NodeTable::<ZeroTime>::default();
+        table.insert(s0, Services::default());
+        table.insert(s1, Services::default());
+        for _ in 0..MAX_NEW_NODE_FAILURES {
+            table.note_failure(&s1);
+        }
+
+        let nodes = table.sample_nodes(InternetProtocol::default(), 10, &mut thread_rng());
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].addr, s0);
+        assert_eq!(
+            table
+                .sample_nodes(InternetProtocol::default(), 0, &mut thread_rng())
+                .len(),
+            0
+        );
+    }
 }
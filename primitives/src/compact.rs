@@ -1,6 +1,10 @@
 //! Compact representation of `U256`
 
 use bigint::{Uint, U256};
+use std::cmp;
+
+/// Maximal size (in bytes) of the number, encoded in compact, that fits into `U256`
+const MAX_COMPACT_SIZE: u32 = 32;
 
 /// Compact representation of `U256`
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -40,6 +44,17 @@ impl Compact {
         U256::max_value().into()
     }
 
+    /// Creates compact from arbitrary bits, clamping the exponent (size in bytes) to 32.
+    pub fn from_bits_truncated(bits: u32) -> Self {
+        let size = cmp::min(bits >> 24, MAX_COMPACT_SIZE);
+        Compact((size << 24) | (bits & 0x00ffffff))
+    }
+
+    /// Returns true if compact represents valid (non-negative and not overflown) target.
+    pub fn is_normalized(&self) -> bool {
+        self.to_u256().is_ok()
+    }
+
     /// Computes the target [0, T] that a blockhash must land in to be valid
     /// Returns value in error, if there is an overflow or its negative value
     pub fn to_u256(&self) -> Result<U256, U256> {
@@ -83,6 +98,24 @@ impl Compact {
         Compact(compact | (size << 24) as u32)
     }
 
+    /// Expected number of hashes, required to find a block with this target: 2^256 / (target + 1).
+    /// Invalid targets require no work.
+    pub fn to_work(&self) -> U256 {
+        let target = match self.to_u256() {
+            Ok(target) => target,
+            Err(_) => return U256::zero(),
+        };
+
+        if target.is_zero() {
+            return U256::max_value();
+        }
+        if target == U256::max_value() {
+            return U256::one();
+        }
+        // 2^256 doesn't fit into U256 => (2^256 - target - 1) / (target + 1) + 1
+        (!target / (target + U256::one())) + U256::one()
+    }
+
     pub fn to_f64(&self) -> f64 {
         let mut shift = (self.0 >> 24) & 0xff;
         let mut diff = f64::from(0x0000ffffu32) / f64::from(self.0 & 0x00ffffffu32);
@@ -135,6 +168,66 @@ mod tests {
         assert_eq!(compact, compact2);
     }
 
+    #[test]
+    fn test_from_bits_truncated() {
+        assert_eq!(
+            Compact::from_bits_truncated(0x1d00ffff),
+            Compact::new(0x1d00ffff)
+        );
+        assert_eq!(
+            Compact::from_bits_truncated(0xff00ffff),
+            Compact::new(0x2000ffff)
+        );
+        assert!(Compact::new(0x2000ffff).is_normalized());
+        assert!(!Compact::new(0x23123456).is_normalized());
+        // negative
+        assert!(!Compact::new(0x04923456).is_normalized());
+    }
+
+    #[test]
+    fn test_from_bits_truncated_fuzz() {
+        let check = |bits: u32| {
+            let compact = Compact::from_bits_truncated(bits);
+            let raw = u32::from(compact);
+            assert!(raw >> 24 <= 32);
+            assert_eq!(raw & 0x00ffffff, bits & 0x00ffffff);
+            if bits >> 24 <= 32 {
+                assert_eq!(compact, Compact::new(bits));
+            }
+            assert_eq!(compact.is_normalized(), compact.to_u256().is_ok());
+            // must not panic for any input
+            let _ = Compact::new(bits).to_u256();
+        };
+
+        // every exponent with boundary mantissas
+        for size in 0..256u32 {
+            for &word in &[
+                0, 1, 0xff, 0x100, 0xffff, 0x10000, 0x7fffff, 0x800000, 0xffffff,
+            ] {
+                check((size << 24) | word);
+            }
+        }
+
+        // pseudo-random sample of the whole u32 range
+        let mut bits = 0u32;
+        for _ in 0..1_000_000 {
+            check(bits);
+            bits = bits.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        }
+    }
+
+    #[test]
+    fn work() {
+        assert_eq!(
+            Compact::new(0x1d00ffff).to_work(),
+            U256::from(0x100010001u64)
+        );
+        assert_eq!(Compact::new(0x2100ffff).to_work(), U256::one());
+        assert_eq!(Compact::new(0x01003456).to_work(), U256::max_value());
+        // negative target
+        assert_eq!(Compact::new(0x04923456).to_work(), U256::zero());
+    }
+
     #[test]
     fn difficulty() {
         fn compare_f64(v1: f64, v2: f64) -> bool {
@@ -0,0 +1,84 @@
+use chain::Block;
+use clap::ArgMatches;
+use config::Config;
+use network::Magic;
+use ser::Stream;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use storage::BlockRef;
+
+pub fn export(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    let path = matches
+        .value_of("PATH")
+        .expect("PATH is required in cli.yml; qed");
+    let best_block_number = cfg.db.best_block().number;
+    let from = parse_height(matches.value_of("from"), 0)?;
+    let to = parse_height(matches.value_of("to"), best_block_number)?;
+    if to > best_block_number {
+        return Err(format!(
+            "Cannot export blocks up to {}: best block is {}",
+            to, best_block_number
+        ));
+    }
+    if from > to {
+        return Err(format!("Invalid blocks range: {}..{}", from, to));
+    }
+
+    let magic = cfg.network.magic();
+    let file = File::create(path).map_err(|err| format!("Cannot create {}: {}", path, err))?;
+    let mut writer = BufWriter::new(file);
+    for number in from..=to {
+        let block = cfg
+            .db
+            .block(BlockRef::Number(number))
+            .ok_or_else(|| format!("Block {} is missing from the database", number))?;
+        write_block(&mut writer, magic, &block.to_raw_block())
+            .map_err(|err| format!("Cannot write to {}: {}", path, err))?;
+    }
+    writer
+        .flush()
+        .map_err(|err| format!("Cannot write to {}: {}", path, err))?;
+
+    info!("Exported blocks {}..{} to {}", from, to, path);
+    Ok(())
+}
+
+fn parse_height(value: Option<&str>, default: u32) -> Result<u32, String> {
+    match value {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid block height: {}", value)),
+        None => Ok(default),
+    }
+}
+
+/// Write block in blk*.dat-style format, which is read by the `import` command
+pub fn write_block<W: Write>(writer: &mut W, magic: Magic, block: &Block) -> io::Result<()> {
+    let data = block.to_bytes();
+    let mut stream = Stream::default();
+    stream.append(&magic).append(&(data.len() as u32));
+    stream.append_slice(&data);
+    writer.write_all(&stream.out())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::write_block;
+    use commands::import::BlockFileReader;
+    use network::Network;
+
+    #[test]
+    fn exported_blocks_are_imported() {
+        let magic = Network::Unitest.magic();
+        let blocks = vec![test_data::genesis(), test_data::block_h1()];
+        let mut data = Vec::new();
+        for block in &blocks {
+            write_block(&mut data, magic, block).unwrap();
+        }
+
+        let read: Result<Vec<_>, _> = BlockFileReader::new(&data as &[u8], magic).collect();
+        assert_eq!(read, Ok(blocks));
+    }
+}
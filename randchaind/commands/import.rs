@@ -0,0 +1,181 @@
+use chain::{Block, IndexedBlock};
+use clap::ArgMatches;
+use config::Config;
+use network::Magic;
+use ser::{deserialize, Reader};
+use std::fs::{self, File};
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use sync::create_sync_blocks_writer;
+use util::{init_db, IMPORT_BATCH_SIZE};
+
+/// Number of imported blocks between two progress reports
+const PROGRESS_REPORT_INTERVAL: u64 = 1000;
+
+pub fn import(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    init_db(&cfg)?;
+
+    let blk_path = matches
+        .value_of("PATH")
+        .expect("PATH is required in cli.yml; qed");
+    let blk_files = block_files(Path::new(blk_path))?;
+    let magic = cfg.network.magic();
+
+    let mut writer = create_sync_blocks_writer(cfg.db, cfg.network, cfg.verification_params);
+    writer.set_batch_size(IMPORT_BATCH_SIZE);
+    let start = Instant::now();
+    let mut imported = 0u64;
+    for blk_file in blk_files {
+        info!("Importing blocks from {}", blk_file.display());
+        let file = File::open(&blk_file)
+            .map_err(|err| format!("Cannot open {}: {}", blk_file.display(), err))?;
+        for block in BlockFileReader::new(BufReader::new(file), magic) {
+            let block = block.map_err(|err| format!("{}: {}", blk_file.display(), err))?;
+            writer
+                .append_block(IndexedBlock::from_raw(block))
+                .map_err(|err| format!("Cannot append block: {:?}", err))?;
+
+            imported += 1;
+            if imported % PROGRESS_REPORT_INTERVAL == 0 {
+                report_progress(imported, &start);
+            }
+        }
+    }
+    writer
+        .flush()
+        .map_err(|err| format!("Cannot write blocks: {:?}", err))?;
+
+    report_progress(imported, &start);
+    Ok(())
+}
+
+fn report_progress(imported: u64, start: &Instant) {
+    let elapsed = start.elapsed();
+    let elapsed_s = elapsed.as_secs() as f64 + elapsed.subsec_millis() as f64 / 1000.0;
+    let throughput = if elapsed_s > 0.0 {
+        imported as f64 / elapsed_s
+    } else {
+        0.0
+    };
+    info!(
+        "Imported {} blocks in {:.1}s ({:.2} blocks/s)",
+        imported, elapsed_s, throughput
+    );
+}
+
+/// blk*.dat files of the directory, in name order
+fn block_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|err| format!("Cannot read {}: {}", dir.display(), err))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| format!("Cannot read {}: {}", dir.display(), err))?
+            .path();
+        let is_blk_file = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name.starts_with("blk") && name.ends_with(".dat"))
+            .unwrap_or(false);
+        if is_blk_file {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads blocks from blk*.dat-style file, where every block is prefixed
+/// with network magic and its serialized size.
+pub struct BlockFileReader<R> {
+    reader: Reader<R>,
+    magic: Magic,
+    finished: bool,
+}
+
+impl<R> BlockFileReader<R>
+where
+    R: io::Read,
+{
+    pub fn new(read: R, magic: Magic) -> Self {
+        BlockFileReader {
+            reader: Reader::from_read(read),
+            magic: magic,
+            finished: false,
+        }
+    }
+
+    fn read_block(&mut self) -> Result<Block, String> {
+        let magic: Magic = self
+            .reader
+            .read()
+            .map_err(|err| format!("Cannot read magic: {:?}", err))?;
+        if magic != self.magic {
+            return Err(format!("Unexpected network magic: {:x}", magic));
+        }
+
+        let size: u32 = self
+            .reader
+            .read()
+            .map_err(|err| format!("Cannot read block size: {:?}", err))?;
+        let mut data = vec![0u8; size as usize];
+        self.reader
+            .read_slice(&mut data)
+            .map_err(|err| format!("Cannot read block: {:?}", err))?;
+        deserialize(&data as &[u8]).map_err(|err| format!("Cannot decode block: {:?}", err))
+    }
+}
+
+impl<R> Iterator for BlockFileReader<R>
+where
+    R: io::Read,
+{
+    type Item = Result<Block, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.reader.is_finished() {
+            return None;
+        }
+
+        let result = self.read_block();
+        self.finished = result.is_err();
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::BlockFileReader;
+    use commands::export::write_block;
+    use network::Network;
+
+    fn block_file(magic: u32, blocks: &[::chain::Block]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for block in blocks {
+            write_block(&mut data, magic, block).unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn block_file_reader_reads_blocks() {
+        let magic = Network::Unitest.magic();
+        let blocks = vec![test_data::genesis(), test_data::block_h1()];
+        let data = block_file(magic, &blocks);
+
+        let read: Result<Vec<_>, _> = BlockFileReader::new(&data as &[u8], magic).collect();
+        assert_eq!(read, Ok(blocks));
+    }
+
+    #[test]
+    fn block_file_reader_rejects_foreign_magic() {
+        let data = block_file(Network::Mainnet.magic(), &[test_data::genesis()]);
+
+        let mut reader = BlockFileReader::new(&data as &[u8], Network::Unitest.magic());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,539 @@
+use chain::{Block, BlockHeader};
+use clap::ArgMatches;
+use crypto::sr25519::PK;
+use hex::{FromHex, ToHex};
+use miner::{self, BlockTemplate, MinerProgress, MinerStatus, Solution};
+use network::Network;
+use primitives::hash::H256;
+use ser::serialize;
+use serde_json::{self, Value};
+use std::cmp::min;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval between two getblocktemplate requests, used to detect tip changes.
+const TEMPLATE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Timeout of connecting to the node.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Timeout of the single request (submitblock verifies the block synchronously).
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// Delay before the first retry of the failed request.
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Maximal delay between retries of the failed request.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+/// Maximal number of attempts to submit mined block.
+const SUBMIT_ATTEMPTS: u32 = 5;
+
+pub fn miner(network: Network, matches: &ArgMatches) -> Result<(), String> {
+    let pubkey_hex = matches
+        .value_of("pubkey")
+        .expect("pubkey is required in cli.yml; qed");
+    let pubkey = parse_pubkey(pubkey_hex)?;
+    let address = match matches.value_of("rpc") {
+        Some(address) => address
+            .parse()
+            .map_err(|_| format!("Invalid JSON-RPC address: {}", address))?,
+        None => SocketAddr::new([127, 0, 0, 1].into(), network.rpc_port()),
+    };
+
+    info!("Mining for {} using node at {}", pubkey_hex, address);
+    let client = RpcClient::new(address);
+    let mut backoff = Backoff::default();
+    let progress = MinerProgress::default();
+    // template we are mining on + last evaluated SeqPoW step
+    let mut job: Option<(BlockTemplate, Solution)> = None;
+    loop {
+        let mut poll_interval = TEMPLATE_POLL_INTERVAL;
+        // report progress on the current template, so it is visible with getmininginfo
+        let iterations = job.as_ref().map(|&(_, ref solution)| solution.iterations);
+        match client.block_template(iterations) {
+            Ok(template) => {
+                backoff.reset();
+                let is_new = match job {
+                    Some((ref current, _)) => !is_same_template(current, &template),
+                    None => true,
+                };
+                if is_new {
+                    info!(
+                        "Mining block {} on top of {}",
+                        template.height,
+                        template.previous_header_hash.to_reversed_str()
+                    );
+                    progress.start(miner::target_iterations(&template));
+                    job = Some((template, miner::init(&template, &pubkey)));
+                }
+            }
+            Err(err) => {
+                let delay = backoff.next_delay();
+                warn!(
+                    "Cannot get block template from {}: {}. Retrying in {}s",
+                    address,
+                    err,
+                    delay.as_secs()
+                );
+                // keep mining the current template while the node is unreachable
+                if job.is_none() {
+                    thread::sleep(delay);
+                    continue;
+                }
+                poll_interval = delay;
+            }
+        }
+
+        let (template, solution) = job.take().expect("job is set above; qed");
+        let deadline = Instant::now() + poll_interval;
+        match mine_until(&template, &pubkey, solution, deadline, &progress) {
+            Mined::Found(solution) => {
+                let proved = miner::prove(&template, &pubkey, &solution);
+                let block = build_block(&template, &pubkey, proved);
+                info!(
+                    "Found block {} after {} iterations",
+                    block.hash().to_reversed_str(),
+                    solution.iterations
+                );
+                submit_block(&client, &block, &mut backoff);
+                // if template is still the same, continue after the found solution
+                job = Some((template, solution));
+            }
+            Mined::Timeout(solution) => {
+                log_progress(template.height, &progress.status());
+                job = Some((template, solution));
+            }
+            Mined::Exhausted => {
+                progress.stop();
+                warn!("No solution found for block {}", template.height);
+                thread::sleep(TEMPLATE_POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+enum Mined {
+    /// Valid solution (without proof) has been found.
+    Found(Solution),
+    /// Deadline has been reached. The last evaluated step is returned.
+    Timeout(Solution),
+    /// Maximal number of iterations has been reached.
+    Exhausted,
+}
+
+/// Evaluate SeqPoW steps until solution is found or deadline is reached.
+/// Progress is updated after every step.
+fn mine_until(
+    template: &BlockTemplate,
+    pubkey: &PK,
+    mut solution: Solution,
+    deadline: Instant,
+    progress: &MinerProgress,
+) -> Mined {
+    loop {
+        let (next, is_valid) = miner::solve(template, pubkey, &solution);
+        if next.iterations > u32::max_value() as u64 {
+            return Mined::Exhausted;
+        }
+        progress.update(next.iterations);
+        if is_valid {
+            return Mined::Found(next);
+        }
+        if Instant::now() >= deadline {
+            return Mined::Timeout(next);
+        }
+        solution = next;
+    }
+}
+
+fn log_progress(height: u32, status: &MinerStatus) {
+    let expected_time_to_block = match status.expected_time_to_block() {
+        Some(secs) => format!("{:.0}s", secs),
+        None => "unknown".to_owned(),
+    };
+    info!(
+        "Mining block {}: {} of ~{} iterations in {}s ({:.0} iterations/s). Expected time to block: {}",
+        height,
+        status.iterations,
+        status.target_iterations,
+        status.elapsed.as_secs(),
+        status.iterations_per_second(),
+        expected_time_to_block
+    );
+}
+
+fn build_block(template: &BlockTemplate, pubkey: &PK, solution: Solution) -> Block {
+    let header = BlockHeader {
+        version: template.version,
+        previous_header_hash: template.previous_header_hash,
+        bits: template.bits,
+        pubkey: pubkey.clone(),
+        iterations: solution.iterations as u32,
+        solution: solution.element,
+    };
+    Block::new(header, solution.proof)
+}
+
+/// Submit block, retrying on connection errors.
+fn submit_block(client: &RpcClient, block: &Block, backoff: &mut Backoff) {
+    for _ in 0..SUBMIT_ATTEMPTS {
+        match client.submit_block(block) {
+            Ok(None) => {
+                backoff.reset();
+                info!("Block {} is accepted", block.hash().to_reversed_str());
+                return;
+            }
+            Ok(Some(reason)) => {
+                backoff.reset();
+                warn!(
+                    "Block {} is rejected: {}",
+                    block.hash().to_reversed_str(),
+                    reason
+                );
+                return;
+            }
+            Err(err) => {
+                let delay = backoff.next_delay();
+                warn!(
+                    "Cannot submit block: {}. Retrying in {}s",
+                    err,
+                    delay.as_secs()
+                );
+                thread::sleep(delay);
+            }
+        }
+    }
+
+    error!(
+        "Giving up submitting block {} after {} attempts",
+        block.hash().to_reversed_str(),
+        SUBMIT_ATTEMPTS
+    );
+}
+
+fn is_same_template(a: &BlockTemplate, b: &BlockTemplate) -> bool {
+    a.previous_header_hash == b.previous_header_hash && a.version == b.version && a.bits == b.bits
+}
+
+fn parse_pubkey(hex: &str) -> Result<PK, String> {
+    let bytes: Vec<u8> = hex
+        .from_hex()
+        .map_err(|_| format!("Invalid public key: {}", hex))?;
+    PK::from_bytes(&bytes).map_err(|_| format!("Invalid public key: {}", hex))
+}
+
+/// Exponential backoff between retries of failed requests.
+struct Backoff {
+    delay: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            delay: MIN_RETRY_DELAY,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the next attempt. Every call doubles the following delay.
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.delay;
+        self.delay = min(self.delay * 2, MAX_RETRY_DELAY);
+        delay
+    }
+
+    /// Request has succeeded.
+    fn reset(&mut self) {
+        self.delay = MIN_RETRY_DELAY;
+    }
+}
+
+/// Minimal JSON-RPC over HTTP client.
+struct RpcClient {
+    address: SocketAddr,
+}
+
+impl RpcClient {
+    fn new(address: SocketAddr) -> Self {
+        RpcClient { address: address }
+    }
+
+    fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        })
+        .to_string();
+        let request = format!(
+            "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.address,
+            body.len(),
+            body
+        );
+
+        let mut stream = TcpStream::connect_timeout(&self.address, CONNECT_TIMEOUT)
+            .map_err(|err| err.to_string())?;
+        stream
+            .set_read_timeout(Some(REQUEST_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(REQUEST_TIMEOUT)))
+            .and_then(|_| stream.write_all(request.as_bytes()))
+            .map_err(|err| err.to_string())?;
+        let mut response = Vec::new();
+        stream
+            .read_to_end(&mut response)
+            .map_err(|err| err.to_string())?;
+
+        parse_rpc_response(&parse_http_response(&response)?)
+    }
+
+    /// Get block template, reporting number of iterations, evaluated for the current one.
+    fn block_template(&self, iterations: Option<u64>) -> Result<BlockTemplate, String> {
+        let request = match iterations {
+            Some(iterations) => json!({ "iterations": iterations }),
+            None => json!({}),
+        };
+        parse_block_template(&self.call("getblocktemplate", json!([request]))?)
+    }
+
+    /// Submit block. Returns rejection reason if the block is rejected by the node.
+    /// Accepted block is announced to the node peers.
+    fn submit_block(&self, block: &Block) -> Result<Option<String>, String> {
+        let data: String = serialize(block).to_hex();
+        let response = self.call("submitblock", json!([{ "data": data }]))?;
+        if response["accepted"].as_bool() == Some(true) {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            response["reason"]
+                .as_str()
+                .unwrap_or("unknown reason")
+                .to_owned(),
+        ))
+    }
+}
+
+/// Returns body of successful HTTP response.
+fn parse_http_response(response: &[u8]) -> Result<String, String> {
+    let separator =
+        find(response, b"\r\n\r\n").ok_or_else(|| "Malformed HTTP response".to_owned())?;
+    let (head, body) = (
+        String::from_utf8_lossy(&response[..separator]),
+        &response[separator + 4..],
+    );
+
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("Unexpected HTTP status: {}", status));
+    }
+
+    let is_chunked = lines.any(|line| {
+        let line = line.to_lowercase();
+        line.starts_with("transfer-encoding:") && line.contains("chunked")
+    });
+    let body = if is_chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+
+    // chunks could split multi-byte characters => body is converted only when it is complete
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Returns body of HTTP response with chunked transfer encoding.
+fn decode_chunked(mut rest: &[u8]) -> Result<Vec<u8>, String> {
+    let malformed = || "Malformed chunked HTTP response".to_owned();
+    let mut decoded = Vec::new();
+    loop {
+        let size_end = find(rest, b"\r\n").ok_or_else(malformed)?;
+        let size = String::from_utf8_lossy(&rest[..size_end]);
+        let size = usize::from_str_radix(size.trim(), 16).map_err(|_| malformed())?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk_start = size_end + 2;
+        let chunk_end = chunk_start.checked_add(size).ok_or_else(malformed)?;
+        if rest.len() < chunk_end {
+            return Err("Truncated chunked HTTP response".to_owned());
+        }
+        decoded.extend_from_slice(&rest[chunk_start..chunk_end]);
+        rest = &rest[chunk_end..];
+        if rest.starts_with(b"\r\n") {
+            rest = &rest[2..];
+        }
+    }
+}
+
+/// Returns position of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Returns result of successful JSON-RPC response.
+fn parse_rpc_response(body: &str) -> Result<Value, String> {
+    let mut response: Value =
+        serde_json::from_str(body).map_err(|err| format!("Invalid JSON-RPC response: {}", err))?;
+    if !response["error"].is_null() {
+        return Err(format!(
+            "JSON-RPC error: {}",
+            response["error"]["message"].as_str().unwrap_or("unknown")
+        ));
+    }
+    Ok(response["result"].take())
+}
+
+fn parse_block_template(template: &Value) -> Result<BlockTemplate, String> {
+    let invalid = |field: &str| format!("Invalid block template: missing {}", field);
+    let previous_header_hash: H256 = template["previousblockhash"]
+        .as_str()
+        .and_then(|hash| hash.parse().ok())
+        .ok_or_else(|| invalid("previousblockhash"))?;
+    Ok(BlockTemplate {
+        version: template["version"]
+            .as_u64()
+            .ok_or_else(|| invalid("version"))? as u32,
+        previous_header_hash: previous_header_hash.reversed(),
+        bits: (template["bits"].as_u64().ok_or_else(|| invalid("bits"))? as u32).into(),
+        height: template["height"]
+            .as_u64()
+            .ok_or_else(|| invalid("height"))? as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{
+        build_block, is_same_template, mine_until, parse_block_template, parse_http_response,
+        parse_rpc_response, Backoff, Mined, MAX_RETRY_DELAY,
+    };
+    use crypto::sr25519::PK;
+    use miner::{self, BlockTemplate, MinerProgress};
+    use primitives::bigint::{Uint, U256};
+    use serde_json::Value;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn backoff_doubles_delay() {
+        let mut backoff = Backoff::default();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(2));
+        assert_eq!(backoff.next_delay(), Duration::from_secs(4));
+        for _ in 0..10 {
+            backoff.next_delay();
+        }
+        assert_eq!(backoff.next_delay(), MAX_RETRY_DELAY);
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn http_response_is_parsed() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nbody";
+        assert_eq!(parse_http_response(response), Ok("body".to_owned()));
+
+        let response =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nbod\r\n1\r\ny\r\n0\r\n\r\n";
+        assert_eq!(parse_http_response(response), Ok("body".to_owned()));
+
+        // multi-byte character is split between chunks
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\n\"\xc3\r\n2\r\n\xa9\"\r\n0\r\n\r\n";
+        assert_eq!(parse_http_response(response), Ok("\"\u{e9}\"".to_owned()));
+
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n10\r\nbody\r\n";
+        assert!(parse_http_response(response).is_err());
+
+        let response = b"HTTP/1.1 403 Forbidden\r\n\r\n";
+        assert!(parse_http_response(response).is_err());
+        assert!(parse_http_response(b"garbage").is_err());
+    }
+
+    #[test]
+    fn rpc_response_is_parsed() {
+        assert_eq!(
+            parse_rpc_response(r#"{"jsonrpc":"2.0","result":{"accepted":true},"id":1}"#),
+            Ok(json!({"accepted": true}))
+        );
+        assert_eq!(
+            parse_rpc_response(
+                r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"invalid block"},"id":1}"#
+            ),
+            Err("JSON-RPC error: invalid block".to_owned())
+        );
+    }
+
+    #[test]
+    fn block_template_is_parsed() {
+        let template: Value = serde_json::from_str(
+            r#"{"bits":44,"coinbaseaux":null,"height":55,"mutable":null,"previousblockhash":"0000000000000000000000000000000000000000000000000000000000000001","rules":null,"target":"0000000000000000000000000000000000000000000000000000000000000000","vbavailable":null,"vbrequired":null,"version":777,"weightlimit":null}"#,
+        )
+        .unwrap();
+        let template = parse_block_template(&template).unwrap();
+        assert_eq!(template.version, 777);
+        assert_eq!(template.previous_header_hash, 1.into());
+        assert_eq!(template.bits, 44.into());
+        assert_eq!(template.height, 55);
+
+        assert!(parse_block_template(&json!({"version": 1})).is_err());
+    }
+
+    #[test]
+    fn miner_finds_and_resumes_solution() {
+        let template = BlockTemplate {
+            version: 1,
+            previous_header_hash: test_data::genesis().hash(),
+            bits: U256::max_value().into(),
+            height: 1,
+        };
+        let mut other = template;
+        assert!(is_same_template(&template, &other));
+        other.previous_header_hash = 1.into();
+        assert!(!is_same_template(&template, &other));
+
+        let pubkey = PK::from_bytes(&[0; 32]).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(600);
+        let progress = MinerProgress::default();
+        progress.start(miner::target_iterations(&template));
+        let solution = match mine_until(
+            &template,
+            &pubkey,
+            miner::init(&template, &pubkey),
+            deadline,
+            &progress,
+        ) {
+            Mined::Found(solution) => solution,
+            _ => panic!("solution is found before deadline"),
+        };
+        let status = progress.status();
+        assert!(status.enabled);
+        assert_eq!(status.iterations, solution.iterations);
+        assert_eq!(
+            status.target_iterations,
+            miner::target_iterations(&template)
+        );
+
+        let proved = miner::prove(&template, &pubkey, &solution);
+        assert!(miner::verify(&template, &pubkey, &proved));
+        let block = build_block(&template, &pubkey, proved);
+        assert_eq!(
+            block.header().previous_header_hash,
+            template.previous_header_hash
+        );
+        assert_eq!(block.header().iterations as u64, solution.iterations);
+
+        // mining continues after the found solution
+        match mine_until(&template, &pubkey, solution, deadline, &progress) {
+            Mined::Found(next) => {
+                assert!(next.iterations > block.header().iterations as u64);
+                assert_eq!(progress.status().iterations, next.iterations);
+            }
+            _ => panic!("solution is found before deadline"),
+        }
+    }
+}
@@ -1,3 +1,11 @@
+mod export;
+mod import;
+mod miner;
+mod rollback;
 mod start;
 
+pub use self::export::export;
+pub use self::import::import;
+pub use self::miner::miner;
+pub use self::rollback::rollback;
 pub use self::start::start;
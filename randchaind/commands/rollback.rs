@@ -0,0 +1,77 @@
+use clap::ArgMatches;
+use config::Config;
+use primitives::hash::H256;
+use storage::SharedStore;
+use util::init_db;
+
+pub fn rollback(cfg: Config, matches: &ArgMatches) -> Result<(), String> {
+    init_db(&cfg)?;
+
+    let target_number = match (matches.value_of("to-hash"), matches.value_of("to-height")) {
+        (Some(hash), None) => {
+            let hash: H256 = hash
+                .parse()
+                .map_err(|_| format!("Invalid block hash: {}", hash))?;
+            let hash = hash.reversed();
+            cfg.db
+                .block_number(&hash)
+                .ok_or_else(|| format!("Block {} is not in the canonical chain", hash.reversed()))?
+        }
+        (None, Some(height)) => height
+            .parse()
+            .map_err(|_| format!("Invalid block height: {}", height))?,
+        _ => return Err("Exactly one of --to-hash and --to-height must be given".into()),
+    };
+
+    let best_block_hash = rollback_to(&cfg.db, target_number)?;
+    info!(
+        "Rolled back to block {} ({})",
+        target_number,
+        best_block_hash.to_reversed_str()
+    );
+    Ok(())
+}
+
+/// Rollback best blocks, until block with given number becomes the best block
+fn rollback_to(db: &SharedStore, target_number: u32) -> Result<H256, String> {
+    let best_block = db.best_block();
+    if target_number > best_block.number {
+        return Err(format!(
+            "Cannot rollback to block {}: best block is {}",
+            target_number, best_block.number
+        ));
+    }
+
+    let mut best_block_hash = best_block.hash;
+    for _ in target_number..best_block.number {
+        best_block_hash = db
+            .rollback_best()
+            .map_err(|err| format!("Cannot rollback best block: {}", err))?;
+    }
+    Ok(best_block_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::rollback_to;
+    use db::BlockChainDatabase;
+    use std::sync::Arc;
+    use storage::SharedStore;
+
+    #[test]
+    fn rollback_to_block() {
+        let db: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::block_h0().into(),
+            test_data::block_h1().into(),
+            test_data::block_h2().into(),
+        ]));
+
+        assert!(rollback_to(&db, 3).is_err());
+        assert_eq!(rollback_to(&db, 2), Ok(test_data::block_h2().hash()));
+        assert_eq!(rollback_to(&db, 0), Ok(test_data::block_h0().hash()));
+        assert_eq!(db.best_block().number, 0);
+        assert!(db.block_hash(1).is_none());
+    }
+}
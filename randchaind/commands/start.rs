@@ -1,4 +1,6 @@
 use super::super::rpc;
+use ethcore_rpc::v1::{ChainEvents, MinerClientCore};
+use ethcore_rpc::{start_beacon, start_metrics, start_stratum, Beacon, Stratum};
 use primitives::hash::H256;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -6,10 +8,24 @@ use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Arc;
 use std::thread;
 use sync::{
-    create_local_sync_node, create_sync_connection_factory, create_sync_peers, SyncListener,
+    create_local_sync_node, create_sync_connection_factory, create_sync_peers_with_seeds,
+    MultiSyncListener, SyncListener,
 };
-use util::{init_db, node_table_path};
-use {config, p2p, PROTOCOL_MINIMUM, PROTOCOL_VERSION};
+use util::{ban_list_path, init_db, node_table_path};
+use {config, p2p, seednodes, PROTOCOL_VERSION};
+
+/// Split seed nodes into socket addresses and hostnames, which must be resolved
+fn split_seed_nodes(seeds: Vec<String>) -> (Vec<SocketAddr>, Vec<String>) {
+    let mut addresses = Vec::new();
+    let mut hosts = Vec::new();
+    for seed in seeds {
+        match seed.parse() {
+            Ok(address) => addresses.push(address),
+            Err(_) => hosts.push(seed),
+        }
+    }
+    (addresses, hosts)
+}
 
 enum BlockNotifierTask {
     NewBlock(H256),
@@ -91,6 +107,15 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
     init_db(&cfg)?;
 
     let nodes_path = node_table_path(&cfg);
+    let ban_path = ban_list_path(&cfg);
+
+    // seed nodes, given as socket addresses, need no DNS lookup => they're connected on startup
+    // other seed nodes are resolved by p2p (which skips them when proxy is used)
+    let (seed_addresses, seed_hosts) = if cfg.proxy.is_some() {
+        (Vec::new(), cfg.seednodes)
+    } else {
+        split_seed_nodes(cfg.seednodes)
+    };
 
     let p2p_cfg = p2p::Config {
         threads: cfg.p2p_threads,
@@ -98,35 +123,76 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
         outbound_connections: cfg.outbound_connections,
         connection: p2p::NetConfig {
             protocol_version: PROTOCOL_VERSION,
-            protocol_minimum: PROTOCOL_MINIMUM,
+            protocol_minimum: cfg.network.min_protocol_version(),
             magic: cfg.network.magic(),
             local_address: SocketAddr::new(cfg.host, cfg.port),
             services: cfg.services,
             user_agent: cfg.user_agent,
             start_height: 0,
-            relay: true,
+            relay: cfg.relay,
             network: cfg.network,
+            proxy: cfg.proxy,
         },
         peers: cfg.peers,
-        seeds: cfg.seednodes,
+        seeds: seed_hosts,
+        fallback_seeds: seednodes::fallback_nodes(cfg.network),
+        dns_seeds: cfg.dns_seeds,
         node_table_path: nodes_path,
+        ban_list_path: ban_path,
         preferable_services: cfg.services,
         internet_protocol: cfg.internet_protocol,
     };
 
-    let sync_peers = create_sync_peers();
+    let sync_peers = create_sync_peers_with_seeds(seed_addresses);
     let local_sync_node = create_local_sync_node(
         cfg.network,
         cfg.db.clone(),
         sync_peers.clone(),
         cfg.verification_params,
+        cfg.orphans_memory_limit,
+        cfg.relay,
     );
     let sync_connection_factory =
         create_sync_connection_factory(sync_peers.clone(), local_sync_node.clone());
 
+    let chain_events = ChainEvents::new();
+    let mut sync_listeners = vec![ChainEvents::listener(chain_events.clone())];
     if let Some(block_notify_command) = cfg.block_notify_command {
-        local_sync_node.install_sync_listener(Box::new(BlockNotifier::new(block_notify_command)));
+        sync_listeners.push(Box::new(BlockNotifier::new(block_notify_command)));
     }
+    let _beacon_server = match cfg.beacon_address {
+        Some(address) => {
+            let beacon = Arc::new(Beacon::new(cfg.db.clone(), cfg.beacon_confirmations));
+            sync_listeners.push(Beacon::listener(beacon.clone()));
+            let beacon_server = start_beacon(&address, beacon)
+                .map_err(|err| format!("Beacon server error: {}", err))?;
+            info!(target: "randchaind", "Beacon server is listening on {}", address);
+            Some(beacon_server)
+        }
+        None => None,
+    };
+    local_sync_node.install_sync_listener(Box::new(MultiSyncListener::new(sync_listeners)));
+
+    let _stratum_server = match cfg.stratum_address {
+        Some(address) => {
+            let core = MinerClientCore::new(local_sync_node.clone(), cfg.db.clone(), cfg.network);
+            let stratum_server = start_stratum(&address, Arc::new(Stratum::new(core)))
+                .map_err(|err| format!("Stratum server error: {}", err))?;
+            info!(target: "randchaind", "Stratum server is listening on {}", address);
+            Some(stratum_server)
+        }
+        None => None,
+    };
+
+    let _metrics_server = match cfg.metrics_address {
+        Some(address) => {
+            let metrics_server =
+                start_metrics(&address).map_err(|err| format!("Metrics server error: {}", err))?;
+            info!(target: "randchaind", "Metrics server is listening on {}", address);
+            Some(metrics_server)
+        }
+        None => None,
+    };
 
     let p2p =
         p2p::P2P::new(p2p_cfg, sync_connection_factory, el.handle()).map_err(|x| x.to_string())?;
@@ -135,11 +201,38 @@ pub fn start(cfg: config::Config) -> Result<(), String> {
         storage: cfg.db,
         local_sync_node: local_sync_node.clone(),
         p2p_context: p2p.context().clone(),
+        sync_peers: sync_peers.clone(),
+        chain_events: chain_events,
         remote: el.remote(),
     };
     let _rpc_server = rpc::new_http(cfg.rpc_config, rpc_deps)?;
 
     p2p.run().map_err(|_| "Failed to start p2p module")?;
+    for seed_address in sync_peers.seed_addresses() {
+        p2p::Context::connect_seednode(p2p.context().clone(), seed_address);
+    }
     el.run(p2p::forever()).unwrap();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_seed_nodes;
+
+    #[test]
+    fn seed_addresses_are_split_from_hostnames() {
+        let (addresses, hosts) = split_seed_nodes(vec![
+            "127.0.0.1:8333".into(),
+            "seed.randchain.org:8333".into(),
+            "[::1]:18333".into(),
+        ]);
+        assert_eq!(
+            addresses,
+            vec![
+                "127.0.0.1:8333".parse().unwrap(),
+                "[::1]:18333".parse().unwrap()
+            ]
+        );
+        assert_eq!(hosts, vec!["seed.randchain.org:8333".to_owned()]);
+    }
+}
@@ -1,16 +1,17 @@
 use clap;
+use ethcore_rpc::beacon;
 use message::Services;
 use network::Network;
 use p2p::InternetProtocol;
 use primitives::hash::H256;
 use rpc::HttpConfiguration as RpcHttpConfig;
 use rpc_apis::ApiSet;
-use seednodes::{mainnet_seednodes, testnet_seednodes};
+use seednodes::{mainnet_dns_seeds, mainnet_seednodes, testnet_dns_seeds, testnet_seednodes};
 use std::fs;
 use std::net;
 use storage;
-use sync::VerificationParameters;
-use util::open_db;
+use sync::{VerificationParameters, DEFAULT_ORPHANS_MEMORY_LIMIT};
+use util::{open_db, reindex_db, repair_db};
 use verification::VerificationLevel;
 use {REGTEST_USER_AGENT, USER_AGENT};
 
@@ -21,6 +22,9 @@ pub struct Config {
     pub peers: Vec<net::SocketAddr>,
     pub host: net::IpAddr,
     pub seednodes: Vec<String>, // we use String rather than SocketAddr as DNS resolver takes String
+    pub dns_seeds: Vec<String>,
+    pub relay: bool,
+    pub proxy: Option<net::SocketAddr>,
     pub quiet: bool,
     pub inbound_connections: u32,
     pub outbound_connections: u32,
@@ -30,8 +34,13 @@ pub struct Config {
     pub user_agent: String,
     pub internet_protocol: InternetProtocol,
     pub rpc_config: RpcHttpConfig,
+    pub stratum_address: Option<net::SocketAddr>,
+    pub beacon_address: Option<net::SocketAddr>,
+    pub beacon_confirmations: u32,
+    pub metrics_address: Option<net::SocketAddr>,
     pub block_notify_command: Option<String>,
     pub verification_params: VerificationParameters,
+    pub orphans_memory_limit: usize,
     pub db: storage::SharedStore,
 }
 
@@ -50,21 +59,28 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         None => None,
     };
 
-    let db = open_db(&data_dir, db_cache);
-
     let quiet = matches.is_present("quiet");
-    let network = match (matches.is_present("testnet"), matches.is_present("regtest")) {
-        (true, false) => Network::Testnet,
-        (false, true) => Network::Regtest,
-        (false, false) => Network::Mainnet,
-        (true, true) => return Err("Only one testnet option can be used".into()),
-    };
+    let network = parse_network(matches)?;
 
     let (in_connections, out_connections) = match network {
         Network::Testnet | Network::Mainnet | Network::Other(_) => (125, 8),
         Network::Regtest | Network::Unitest => (1, 0),
     };
 
+    let in_connections = match matches.value_of("inbound-connections") {
+        Some(s) => s
+            .parse()
+            .map_err(|_| "Invalid inbound connections number".to_owned())?,
+        None => in_connections,
+    };
+
+    let out_connections = match matches.value_of("outbound-connections") {
+        Some(s) => s
+            .parse()
+            .map_err(|_| "Invalid outbound connections number".to_owned())?,
+        None => out_connections,
+    };
+
     let p2p_threads = match network {
         Network::Testnet | Network::Mainnet | Network::Other(_) => 4,
         Network::Regtest | Network::Unitest => 1,
@@ -129,7 +145,7 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
             let mut addrs: Vec<String> = vec![];
             for addr_str in addrs_cfg.split(",") {
                 match addr_str.parse::<net::SocketAddr>() {
-                    Err(_) => addrs.push(format!("{}:{}", addr_str, network.dns_port())), // no port given, enforce the default port
+                    Err(_) => addrs.push(format!("{}:{}", addr_str, network.seednode_port())), // no port given, enforce the default port
                     Ok(_) => addrs.push(addr_str.to_owned()), // with port given
                 }
             }
@@ -142,6 +158,26 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         },
     };
 
+    let dns_seeds: Vec<String> = if matches.is_present("no-dnsseed") {
+        Vec::new()
+    } else {
+        let mut seeds: Vec<String> = match network {
+            Network::Mainnet => mainnet_dns_seeds().into_iter().map(Into::into).collect(),
+            Network::Testnet => testnet_dns_seeds().into_iter().map(Into::into).collect(),
+            Network::Other(_) | Network::Regtest | Network::Unitest => Vec::new(),
+        };
+        if let Some(seeds_cfg) = matches.value_of("dnsseed") {
+            for seed in seeds_cfg.split(",") {
+                if seed.contains(':') {
+                    seeds.push(seed.to_owned()); // with port given
+                } else {
+                    seeds.push(format!("{}:{}", seed, network.port())); // no port given, enforce the default port
+                }
+            }
+        }
+        seeds
+    };
+
     let only_net = match matches.value_of("only-net") {
         Some(s) => s.parse()?,
         None => InternetProtocol::default(),
@@ -157,8 +193,47 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         },
     };
 
+    let proxy = match matches.value_of("proxy") {
+        Some(s) => Some(
+            s.parse::<net::SocketAddr>()
+                .map_err(|_| "Invalid proxy address".to_owned())?,
+        ),
+        None => None,
+    };
+
     let rpc_config = parse_rpc_config(network, matches)?;
 
+    let stratum_address = match matches.value_of("stratum") {
+        Some(s) => Some(
+            s.parse::<net::SocketAddr>()
+                .map_err(|_| "Invalid stratum address".to_owned())?,
+        ),
+        None => None,
+    };
+
+    let beacon_address = match matches.value_of("beacon") {
+        Some(s) => Some(
+            s.parse::<net::SocketAddr>()
+                .map_err(|_| "Invalid beacon address".to_owned())?,
+        ),
+        None => None,
+    };
+
+    let beacon_confirmations = match matches.value_of("beacon-confirmations") {
+        Some(s) => s
+            .parse()
+            .map_err(|_| "Invalid beacon confirmations".to_owned())?,
+        None => beacon::DEFAULT_CONFIRMATIONS,
+    };
+
+    let metrics_address = match matches.value_of("metrics") {
+        Some(s) => Some(
+            s.parse::<net::SocketAddr>()
+                .map_err(|_| "Invalid metrics address".to_owned())?,
+        ),
+        None => None,
+    };
+
     let block_notify_command = match matches.value_of("blocknotify") {
         Some(s) => Some(
             s.parse()
@@ -167,26 +242,68 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         None => None,
     };
 
+    // NODE_RANDOMNESS_INDEX is not advertised until randomness queries are served over p2p
     let services = Services::default().with_network(true);
 
-    let verification_level = match matches.value_of("verification-level") {
-        Some(s) if s == "full" => VerificationLevel::Full,
-        Some(s) if s == "header" => VerificationLevel::Header,
-        Some(s) if s == "none" => VerificationLevel::NoVerification,
-        Some(s) => return Err(format!("Invalid verification level: {}", s)),
-        None => VerificationLevel::Full,
-    };
-
-    // TODO:
-    let verification_edge = match matches.value_of("verification-edge") {
-        Some(s) if verification_level != VerificationLevel::Full => {
-            let edge: H256 = s
-                .parse()
-                .map_err(|_| "Invalid verification edge".to_owned())?;
-            edge.reversed()
+    let verification_threads = match matches.value_of("verification-threads") {
+        Some(s) => s
+            .parse()
+            .map_err(|_| "Invalid verification threads".to_owned())?,
+        None => 0,
+    };
+
+    let mut verification_params = match matches.value_of("verification-level") {
+        Some("full") | None => VerificationParameters::full_from_genesis(),
+        Some(s) => {
+            let verification_level = match s {
+                "header" => VerificationLevel::Header,
+                "none" if network.is_production() => {
+                    return Err(format!(
+                        "Blocks verification can not be disabled on {} network",
+                        network.name()
+                    ))
+                }
+                "none" => VerificationLevel::NoVerification,
+                s => return Err(format!("Invalid verification level: {}", s)),
+            };
+
+            let verification_edge = match matches.value_of("verification-edge") {
+                Some(s) => {
+                    let edge: H256 = s
+                        .parse()
+                        .map_err(|_| "Invalid verification edge".to_owned())?;
+                    edge.reversed()
+                }
+                None => network.default_verification_edge(),
+            };
+
+            VerificationParameters::builder()
+                .verification_level(verification_level)
+                .verification_edge(verification_edge)
+                .build()
         }
-        _ => network.default_verification_edge(),
     };
+    verification_params.verification_threads = verification_threads;
+
+    let orphans_memory_limit = match matches.value_of("orphans-memory") {
+        Some(s) => {
+            s.parse::<usize>()
+                .map_err(|_| "Invalid orphans memory size - should be number in MB".to_owned())?
+                * 1024
+                * 1024
+        }
+        None => DEFAULT_ORPHANS_MEMORY_LIMIT,
+    };
+
+    if matches.is_present("repair-db") {
+        repair_db(&data_dir)?;
+    }
+
+    if matches.is_present("reindex") {
+        reindex_db(&data_dir, db_cache, network, verification_params.clone())?;
+    }
+
+    let db = open_db(&data_dir, db_cache);
 
     let config = Config {
         quiet: quiet,
@@ -196,6 +313,9 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         peers: peers,
         host: host,
         seednodes: seednodes,
+        dns_seeds: dns_seeds,
+        relay: !matches.is_present("no-relay"),
+        proxy: proxy,
         inbound_connections: in_connections,
         outbound_connections: out_connections,
         p2p_threads: p2p_threads,
@@ -204,17 +324,42 @@ pub fn parse(matches: &clap::ArgMatches) -> Result<Config, String> {
         user_agent: user_agent,
         internet_protocol: only_net,
         rpc_config: rpc_config,
+        stratum_address: stratum_address,
+        beacon_address: beacon_address,
+        beacon_confirmations: beacon_confirmations,
+        metrics_address: metrics_address,
         block_notify_command: block_notify_command,
-        verification_params: VerificationParameters {
-            verification_level: verification_level,
-            verification_edge: verification_edge,
-        },
+        verification_params: verification_params,
+        orphans_memory_limit: orphans_memory_limit,
         db: db,
     };
 
     Ok(config)
 }
 
+pub fn parse_network(matches: &clap::ArgMatches) -> Result<Network, String> {
+    let network = match matches.value_of("network") {
+        Some("mainnet") => Some(Network::Mainnet),
+        Some("testnet") => Some(Network::Testnet),
+        Some("regtest") => Some(Network::Regtest),
+        Some("unitest") => Some(Network::Unitest),
+        Some(s) => return Err(format!("Invalid network: {}", s)),
+        None => None,
+    };
+
+    match (
+        matches.is_present("testnet"),
+        matches.is_present("regtest"),
+        network,
+    ) {
+        (true, false, None) => Ok(Network::Testnet),
+        (false, true, None) => Ok(Network::Regtest),
+        (false, false, Some(network)) => Ok(network),
+        (false, false, None) => Ok(Network::Mainnet),
+        _ => Err("Only one testnet option can be used".into()),
+    }
+}
+
 fn parse_rpc_config(network: Network, matches: &clap::ArgMatches) -> Result<RpcHttpConfig, String> {
     let mut config = RpcHttpConfig::with_port(network.rpc_port());
     config.enabled = !matches.is_present("no-jsonrpc");
@@ -247,6 +392,16 @@ fn parse_rpc_config(network: Network, matches: &clap::ArgMatches) -> Result<RpcH
             .parse()
             .map_err(|_| "Invalid JSON RPC hosts".to_owned())?]);
     }
+    config.allow_admin = matches.is_present("allow-admin-rpc");
+    if config.allow_admin
+        && network.is_production()
+        && !matches.is_present("i-know-what-i-am-doing")
+    {
+        return Err(format!(
+            "Admin JSON-RPC methods are dangerous on {} network, use --i-know-what-i-am-doing to enable them",
+            network.name()
+        ));
+    }
 
     Ok(config)
 }
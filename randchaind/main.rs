@@ -8,14 +8,21 @@ extern crate app_dirs;
 extern crate env_logger;
 extern crate libc;
 
+extern crate rustc_hex as hex;
+#[macro_use]
+extern crate serde_json;
+
 extern crate chain;
+extern crate crypto;
 extern crate db;
 extern crate logs;
 extern crate message;
+extern crate miner;
 extern crate network;
 extern crate p2p;
 extern crate primitives;
 extern crate rpc as ethcore_rpc;
+extern crate serialization as ser;
 extern crate storage;
 extern crate sync;
 extern crate verification;
@@ -34,7 +41,6 @@ pub const APP_INFO: AppInfo = AppInfo {
     author: "RandChain",
 };
 pub const PROTOCOL_VERSION: u32 = 70_014;
-pub const PROTOCOL_MINIMUM: u32 = 70_001;
 pub const USER_AGENT: &'static str = "/Satoshi:0.12.1/";
 pub const REGTEST_USER_AGENT: &'static str = "randchaind-regtest";
 pub const LOG_INFO: &'static str = "sync=info";
@@ -51,9 +57,30 @@ fn main() {
 fn run() -> Result<(), String> {
     let yaml = load_yaml!("cli.yml");
     let matches = clap::App::from_yaml(yaml).get_matches();
+
+    // remote miner does not need the local database
+    if let ("miner", Some(miner_matches)) = matches.subcommand() {
+        init_logs(matches.is_present("quiet"));
+        return commands::miner(config::parse_network(&matches)?, miner_matches);
+    }
+
     let cfg = config::parse(&matches)?;
+    init_logs(cfg.quiet);
 
-    if !cfg.quiet {
+    if cfg.network == network::Network::Unitest {
+        warn!("Unitest network is intended for unit tests only, never use it for real nodes");
+    }
+
+    match matches.subcommand() {
+        ("import", Some(import_matches)) => commands::import(cfg, import_matches),
+        ("export", Some(export_matches)) => commands::export(cfg, export_matches),
+        ("rollback", Some(rollback_matches)) => commands::rollback(cfg, rollback_matches),
+        _ => commands::start(cfg),
+    }
+}
+
+fn init_logs(quiet: bool) {
+    if !quiet {
         if cfg!(windows) {
             logs::init(LOG_INFO, logs::DateLogFormatter);
         } else {
@@ -62,6 +89,4 @@ fn run() -> Result<(), String> {
     } else {
         env_logger::init();
     }
-
-    commands::start(cfg)
 }
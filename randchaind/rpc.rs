@@ -1,3 +1,4 @@
+use ethcore_rpc::v1::ChainEvents;
 use ethcore_rpc::{start_http, Compatibility, MetaIoHandler, Remote, Server};
 use network::Network;
 use p2p;
@@ -13,6 +14,8 @@ pub struct Dependencies {
     pub local_sync_node: sync::LocalNodeRef,
     pub storage: storage::SharedStore,
     pub p2p_context: Arc<p2p::Context>,
+    pub sync_peers: sync::PeersRef,
+    pub chain_events: Arc<ChainEvents>,
     pub remote: Remote,
 }
 
@@ -24,6 +27,7 @@ pub struct HttpConfiguration {
     pub apis: ApiSet,
     pub cors: Option<Vec<String>>,
     pub hosts: Option<Vec<String>>,
+    pub allow_admin: bool,
 }
 
 impl HttpConfiguration {
@@ -35,6 +39,7 @@ impl HttpConfiguration {
             apis: ApiSet::default(),
             cors: None,
             hosts: Some(Vec::new()),
+            allow_admin: false,
         }
     }
 }
@@ -49,7 +54,12 @@ pub fn new_http(conf: HttpConfiguration, deps: Dependencies) -> Result<Option<Se
         .parse()
         .map_err(|_| format!("Invalid JSONRPC listen host/port given: {}", url))?;
     Ok(Some(setup_http_rpc_server(
-        &addr, conf.cors, conf.hosts, conf.apis, deps,
+        &addr,
+        conf.cors,
+        conf.hosts,
+        conf.apis,
+        conf.allow_admin,
+        deps,
     )?))
 }
 
@@ -58,9 +68,10 @@ pub fn setup_http_rpc_server(
     cors_domains: Option<Vec<String>>,
     allowed_hosts: Option<Vec<String>>,
     apis: ApiSet,
+    allow_admin: bool,
     deps: Dependencies,
 ) -> Result<Server, String> {
-    let server = setup_rpc_server(apis, deps);
+    let server = setup_rpc_server(apis, allow_admin, deps);
     let start_result = start_http(url, cors_domains, allowed_hosts, server);
     match start_result {
 		Err(ref err) if err.kind() == io::ErrorKind::AddrInUse => {
@@ -71,10 +82,11 @@ pub fn setup_http_rpc_server(
 	}
 }
 
-fn setup_rpc_server(apis: ApiSet, deps: Dependencies) -> MetaIoHandler<()> {
+fn setup_rpc_server(apis: ApiSet, allow_admin: bool, deps: Dependencies) -> MetaIoHandler<()> {
     rpc_apis::setup_rpc(
         MetaIoHandler::with_compatibility(Compatibility::Both),
         apis,
+        allow_admin,
         deps,
     )
 }
@@ -52,6 +52,7 @@ impl ApiSet {
 pub fn setup_rpc(
     mut handler: MetaIoHandler<()>,
     apis: ApiSet,
+    allow_admin: bool,
     deps: Dependencies,
 ) -> MetaIoHandler<()> {
     use ethcore_rpc::v1::*;
@@ -59,17 +60,29 @@ pub fn setup_rpc(
     for api in apis.list_apis() {
         match api {
             Api::Miner => handler.extend_with(
-                MinerClient::new(MinerClientCore::new(deps.local_sync_node.clone())).to_delegate(),
+                MinerClient::new(MinerClientCore::new(
+                    deps.local_sync_node.clone(),
+                    deps.storage.clone(),
+                    deps.network,
+                ))
+                .to_delegate(),
             ),
             Api::BlockChain => handler.extend_with(
                 BlockChainClient::new(BlockChainClientCore::new(
                     deps.p2p_context.clone(),
                     deps.storage.clone(),
+                    deps.local_sync_node.clone(),
+                    deps.chain_events.clone(),
+                    allow_admin,
                 ))
                 .to_delegate(),
             ),
             Api::Network => handler.extend_with(
-                NetworkClient::new(NetworkClientCore::new(deps.p2p_context.clone())).to_delegate(),
+                NetworkClient::new(NetworkClientCore::new(
+                    deps.p2p_context.clone(),
+                    deps.sync_peers.clone(),
+                ))
+                .to_delegate(),
             ),
         }
     }
@@ -1,3 +1,6 @@
+use network::Network;
+use std::net::SocketAddr;
+
 pub fn mainnet_seednodes() -> Vec<&'static str> {
     vec![
     // TODO:
@@ -9,3 +12,38 @@ pub fn testnet_seednodes() -> Vec<&'static str> {
     // TODO:
     ]
 }
+
+/// Hostnames, resolving to addresses of mainnet nodes
+pub fn mainnet_dns_seeds() -> Vec<&'static str> {
+    vec![
+    // TODO:
+    ]
+}
+
+/// Hostnames, resolving to addresses of testnet nodes
+pub fn testnet_dns_seeds() -> Vec<&'static str> {
+    vec![
+    // TODO:
+    ]
+}
+
+/// Nodes to connect to when none of seed nodes could be resolved
+pub fn fallback_nodes(network: Network) -> Vec<SocketAddr> {
+    let nodes: Vec<&'static str> = match network {
+        Network::Mainnet => vec![
+        // TODO:
+        ],
+        Network::Testnet => vec![
+        // TODO:
+        ],
+        Network::Other(_) | Network::Regtest | Network::Unitest => Vec::new(),
+    };
+
+    nodes
+        .into_iter()
+        .map(|node| {
+            node.parse()
+                .expect("hardcoded fallback nodes are valid; qed")
+        })
+        .collect()
+}
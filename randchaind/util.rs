@@ -1,21 +1,147 @@
 use app_dirs::{app_dir, AppDataType};
 use config::Config;
 use db;
+use db::kv::KeyValueDatabase;
+use network::Network;
+use primitives::hash::H256;
 use std::fs::create_dir_all;
 use std::path::PathBuf;
 use std::sync::Arc;
+use sync::{create_sync_blocks_writer, Error as SyncError, VerificationParameters};
 use {storage, APP_INFO};
 
+/// Number of reindexed blocks between two progress reports
+const REINDEX_PROGRESS_REPORT_INTERVAL: usize = 1000;
+/// Number of imported blocks, written to the database in a single transaction
+pub const IMPORT_BATCH_SIZE: usize = 1000;
+
 pub fn open_db(data_dir: &Option<String>, db_cache: usize) -> storage::SharedStore {
-    let db_path = match *data_dir {
-        Some(ref data_dir) => custom_path(&data_dir, "db"),
-        None => app_dir(AppDataType::UserData, &APP_INFO, "db").expect("Failed to get app dir"),
-    };
     Arc::new(
-        db::BlockChainDatabase::open_at_path(db_path, db_cache).expect("Failed to open database"),
+        db::BlockChainDatabase::open_at_path(db_path(data_dir), db_cache)
+            .expect("Failed to open database"),
     )
 }
 
+pub fn repair_db(data_dir: &Option<String>) -> Result<(), String> {
+    db::BlockChainDatabase::repair(db_path(data_dir))
+        .map_err(|err| format!("Failed to repair database: {}", err))
+}
+
+/// Rebuilds canon chain indexes of the database, re-verifying all stored blocks.
+/// Blocks, which fail verification, are removed from the database together with their descendants.
+pub fn reindex_db(
+    data_dir: &Option<String>,
+    db_cache: usize,
+    network: Network,
+    verification_params: VerificationParameters,
+) -> Result<(), String> {
+    let hashes = db::BlockChainDatabase::reindex(db_path(data_dir))
+        .map_err(|err| format!("Failed to reindex database: {}", err))?;
+    let db = Arc::new(
+        db::BlockChainDatabase::open_at_path(db_path(data_dir), db_cache)
+            .map_err(|err| format!("Failed to open database: {}", err))?,
+    );
+    reindex_blocks(db, hashes, network, verification_params)
+}
+
+/// Re-verifies and canonizes stored blocks, given in order, where every block follows its parent.
+/// Only blocks, rejected by verifier, and their descendants are removed. Blocks, which could not
+/// be verified because of other errors, are kept in the database (outside of the canon chain).
+fn reindex_blocks<T>(
+    db: Arc<db::BlockChainDatabase<T>>,
+    hashes: Vec<H256>,
+    network: Network,
+    verification_params: VerificationParameters,
+) -> Result<(), String>
+where
+    T: KeyValueDatabase + 'static,
+{
+    let store: storage::SharedStore = db.clone();
+    let genesis_hash = network.genesis_block().hash().clone();
+    let mut writer = create_sync_blocks_writer(store.clone(), network, verification_params);
+    writer.set_batch_size(IMPORT_BATCH_SIZE);
+
+    info!("Reindexing {} blocks", hashes.len());
+    let total = hashes.len();
+    let mut removed = 0;
+    for (index, hash) in hashes.into_iter().enumerate() {
+        if (index + 1) % REINDEX_PROGRESS_REPORT_INTERVAL == 0 {
+            info!("Reindexed {} of {} blocks", index + 1, total);
+        }
+
+        // genesis block is not verified
+        if hash == genesis_hash {
+            db.canonize(&hash)
+                .map_err(|err| format!("Cannot canonize genesis block: {}", err))?;
+            continue;
+        }
+
+        let block = match store.block(hash.clone().into()) {
+            Some(block) => block,
+            None => continue,
+        };
+        let parent_hash = block.header.raw.previous_header_hash.clone();
+        if !store.contains_block(parent_hash.into()) {
+            warn!(
+                "Removing block {}: its parent has been removed",
+                hash.to_reversed_str()
+            );
+            remove_block(&db, &hash)?;
+            removed += 1;
+            continue;
+        }
+
+        // writer ignores known blocks => block is removed and then written back by the writer,
+        // when it passes verification
+        remove_block(&db, &hash)?;
+        let result = writer.append_block(block.clone());
+        if store.contains_block(hash.clone().into()) {
+            continue;
+        }
+
+        match result {
+            Err(SyncError::Verification(err)) => {
+                warn!("Removing block {}: {}", hash.to_reversed_str(), err);
+                removed += 1;
+            }
+            result => {
+                // block has not been rejected => keep it in the database
+                db.insert(block)
+                    .map_err(|err| format!("Cannot restore block: {}", err))?;
+                match result {
+                    Err(ref err) if !err.is_recoverable() => {
+                        return Err(format!("Cannot reindex block: {:?}", err));
+                    }
+                    Err(err) => warn!("Cannot reindex block {}: {:?}", hash.to_reversed_str(), err),
+                    Ok(()) => warn!("Block {} has not been reindexed", hash.to_reversed_str()),
+                }
+            }
+        }
+    }
+    writer
+        .flush()
+        .map_err(|err| format!("Cannot write blocks: {:?}", err))?;
+
+    info!("Reindexed {} blocks, removed {} blocks", total, removed);
+    Ok(())
+}
+
+fn remove_block<T>(db: &db::BlockChainDatabase<T>, hash: &H256) -> Result<(), String>
+where
+    T: KeyValueDatabase,
+{
+    db.take_block(hash)
+        .map(|_| ())
+        .map_err(|err| format!("Cannot remove block: {}", err))
+}
+
+fn db_path(data_dir: &Option<String>) -> PathBuf {
+    match *data_dir {
+        Some(ref data_dir) => custom_path(&data_dir, "db"),
+        None => app_dir(AppDataType::UserData, &APP_INFO, "db").expect("Failed to get app dir"),
+    }
+}
+
 pub fn node_table_path(cfg: &Config) -> PathBuf {
     let mut node_table = match cfg.data_dir {
         Some(ref data_dir) => custom_path(&data_dir, "p2p"),
@@ -25,6 +151,15 @@ pub fn node_table_path(cfg: &Config) -> PathBuf {
     node_table
 }
 
+pub fn ban_list_path(cfg: &Config) -> PathBuf {
+    let mut ban_list = match cfg.data_dir {
+        Some(ref data_dir) => custom_path(&data_dir, "p2p"),
+        None => app_dir(AppDataType::UserData, &APP_INFO, "p2p").expect("Failed to get app dir"),
+    };
+    ban_list.push("banlist.csv");
+    ban_list
+}
+
 pub fn init_db(cfg: &Config) -> Result<(), String> {
     // insert genesis block if db is empty
     let genesis_block = cfg.network.genesis_block();
@@ -52,3 +187,55 @@ fn custom_path(data_dir: &str, sub_dir: &str) -> PathBuf {
     create_dir_all(&path).expect("Failed to get app dir");
     path
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::reindex_blocks;
+    use db::BlockChainDatabase;
+    use network::Network;
+    use std::sync::Arc;
+    use storage::BlockProvider;
+    use sync::VerificationParameters;
+    use verification::VerificationLevel;
+
+    #[test]
+    fn reindex_removes_only_rejected_blocks() {
+        let db = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        // b1 is valid, b2 fails verification and b3 is a child of b2
+        let b1 = test_data::block_h1();
+        let b2 = test_data::block_builder()
+            .header()
+            .parent(b1.hash())
+            .build()
+            .build();
+        let b3 = test_data::block_builder()
+            .header()
+            .parent(b2.hash())
+            .build()
+            .build();
+        for block in vec![b1.clone(), b2.clone(), b3.clone()] {
+            db.insert(block.into()).unwrap();
+        }
+
+        reindex_blocks(
+            db.clone(),
+            vec![b1.hash(), b2.hash(), b3.hash()],
+            Network::Testnet,
+            VerificationParameters {
+                verification_level: VerificationLevel::Full,
+                verification_edge: 0u8.into(),
+                verification_threads: 0,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(db.best_block().number, 1);
+        assert_eq!(db.best_block().hash, b1.hash());
+        assert!(!db.contains_block(b2.hash().into()));
+        assert!(!db.contains_block(b3.hash().into()));
+    }
+}
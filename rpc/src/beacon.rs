@@ -0,0 +1,305 @@
+//! Randomness beacon stream.
+//!
+//! Consumers connect over TCP and receive newline-delimited JSON notifications
+//! `{"height": ..., "hash": ..., "randomnessHex": ...}` for every block of the best chain,
+//! once it is buried under the configured number of confirmations. Notifications are
+//! only sent for blocks, finalized after the consumer has connected.
+
+use parking_lot::Mutex;
+use primitives::hash::H256 as GlobalH256;
+use serde_json;
+use std::io::{self, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use storage;
+use sync::SyncListener;
+use v1::types::H256;
+
+/// Default number of confirmations, required to finalize the block randomness.
+pub const DEFAULT_CONFIRMATIONS: u32 = 6;
+/// Maximal number of notifications, queued for a single consumer. Lagging consumers are
+/// disconnected.
+const MAX_PENDING_NOTIFICATIONS: usize = 1024;
+/// Maximal number of simultaneous consumer connections.
+const MAX_CONNECTIONS: usize = 64;
+/// Consumers, which do not read a notification within this time, are disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Randomness of the finalized block.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct BeaconValue {
+    /// Block height
+    pub height: u32,
+    /// Block hash
+    pub hash: H256,
+    /// Randomness as hex
+    #[serde(rename = "randomnessHex")]
+    pub randomness_hex: String,
+}
+
+/// Randomness beacon: publishes randomness of finalized blocks to all subscribers.
+pub struct Beacon {
+    storage: storage::SharedStore,
+    confirmations: u32,
+    /// Height of the last published block.
+    last_finalized: Mutex<Option<u32>>,
+    subscribers: Mutex<Vec<SyncSender<String>>>,
+}
+
+impl Beacon {
+    pub fn new(storage: storage::SharedStore, confirmations: u32) -> Self {
+        Beacon {
+            storage: storage,
+            confirmations: confirmations,
+            last_finalized: Mutex::new(None),
+            subscribers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Listener, which publishes randomness when new best block is inserted
+    pub fn listener(beacon: Arc<Self>) -> Box<dyn SyncListener> {
+        Box::new(BeaconListener { beacon: beacon })
+    }
+
+    /// Subscribe to notifications. Subscription is cancelled when receiver is dropped.
+    pub fn subscribe(&self) -> Receiver<String> {
+        let (sender, receiver) = sync_channel(MAX_PENDING_NOTIFICATIONS);
+        self.subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Number of active subscribers.
+    pub fn subscribers_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+
+    /// Publish randomness of all blocks, which are finalized since the last call.
+    fn on_best_block_inserted(&self) {
+        let best_block_number = self.storage.best_block().number;
+        let finalized = match best_block_number.checked_sub(self.confirmations) {
+            Some(finalized) => finalized,
+            None => return,
+        };
+
+        let mut last_finalized = self.last_finalized.lock();
+        let first = match *last_finalized {
+            Some(last_finalized) if last_finalized >= finalized => return,
+            Some(last_finalized) => last_finalized + 1,
+            None => finalized,
+        };
+
+        for height in first..finalized + 1 {
+            match self.value(height) {
+                Some(value) => self.publish(&value),
+                None => {
+                    warn!(target: "beacon", "randomness of block #{} is not found", height);
+                }
+            }
+        }
+        *last_finalized = Some(finalized);
+    }
+
+    fn value(&self, height: u32) -> Option<BeaconValue> {
+        let hash: GlobalH256 = self.storage.block_hash(height)?;
+        let randomness = self.storage.randomness(height)?;
+        Some(BeaconValue {
+            height: height,
+            hash: hash.reversed().into(),
+            randomness_hex: randomness.to_string_radix(16),
+        })
+    }
+
+    fn publish(&self, value: &BeaconValue) {
+        let notification =
+            serde_json::to_string(value).expect("beacon values are always serializable; qed");
+        self.subscribers.lock().retain(|subscriber| {
+            match subscriber.try_send(notification.clone()) {
+                Ok(_) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!(target: "beacon", "disconnecting lagging beacon consumer");
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+struct BeaconListener {
+    beacon: Arc<Beacon>,
+}
+
+impl SyncListener for BeaconListener {
+    fn synchronization_state_switched(&self, _is_synchronizing: bool) {}
+
+    fn best_storage_block_inserted(&self, _block_hash: &GlobalH256) {
+        self.beacon.on_best_block_inserted();
+    }
+}
+
+/// Handle of the running beacon server.
+pub struct BeaconServer {
+    local_addr: SocketAddr,
+}
+
+impl BeaconServer {
+    /// Address, the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Start beacon server. Every consumer connection is served by a separate thread.
+/// Connections above `MAX_CONNECTIONS` are closed right after accepting.
+pub fn start_beacon(addr: &SocketAddr, beacon: Arc<Beacon>) -> io::Result<BeaconServer> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    let connections = Arc::new(AtomicUsize::new(0));
+    thread::Builder::new()
+        .name("Beacon server".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        warn!(target: "beacon", "failed to accept consumer connection: {}", err);
+                        continue;
+                    }
+                };
+
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    warn!(target: "beacon", "too many consumer connections, dropping {:?}", stream.peer_addr());
+                    continue;
+                }
+
+                let notifications = beacon.subscribe();
+                let thread_connections = connections.clone();
+                let spawned = thread::Builder::new()
+                    .name("Beacon consumer".to_owned())
+                    .spawn(move || {
+                        if let Err(err) = serve_connection(notifications, stream) {
+                            trace!(target: "beacon", "consumer connection is closed: {}", err);
+                        }
+                        thread_connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                if let Err(err) = spawned {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    warn!(target: "beacon", "failed to serve consumer connection: {}", err);
+                }
+            }
+        })?;
+
+    Ok(BeaconServer {
+        local_addr: local_addr,
+    })
+}
+
+fn serve_connection(notifications: Receiver<String>, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    for notification in notifications {
+        stream.write_all(notification.as_bytes())?;
+        stream.write_all(b"\n")?;
+        stream.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{start_beacon, Beacon, BeaconValue};
+    use db::BlockChainDatabase;
+    use serde_json;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use storage::SharedStore;
+
+    fn value(height: u32, block: &::chain::Block) -> BeaconValue {
+        BeaconValue {
+            height: height,
+            hash: block.hash().reversed().into(),
+            randomness_hex: block.header().solution.to_string_radix(16),
+        }
+    }
+
+    fn notification(receiver: &::std::sync::mpsc::Receiver<String>) -> BeaconValue {
+        let line = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn beacon_publishes_finalized_blocks() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let shared: SharedStore = storage.clone();
+        let beacon = Arc::new(Beacon::new(shared, 1));
+        let listener = Beacon::listener(beacon.clone());
+        let notifications = beacon.subscribe();
+
+        // genesis has no confirmations yet
+        listener.best_storage_block_inserted(&test_data::genesis().hash());
+        assert!(notifications.try_recv().is_err());
+
+        let b1 = test_data::block_h1();
+        storage.insert(b1.clone().into()).unwrap();
+        storage.canonize(&b1.hash()).unwrap();
+        listener.best_storage_block_inserted(&b1.hash());
+        assert_eq!(
+            notification(&notifications),
+            value(0, &test_data::genesis())
+        );
+
+        // the same block is published only once
+        listener.best_storage_block_inserted(&b1.hash());
+        assert!(notifications.try_recv().is_err());
+
+        let b2 = test_data::block_h2();
+        storage.insert(b2.clone().into()).unwrap();
+        storage.canonize(&b2.hash()).unwrap();
+        listener.best_storage_block_inserted(&b2.hash());
+        assert_eq!(notification(&notifications), value(1, &b1));
+
+        // dropped subscribers are removed
+        drop(notifications);
+        let b3 = test_data::block_h3();
+        storage.insert(b3.clone().into()).unwrap();
+        storage.canonize(&b3.hash()).unwrap();
+        listener.best_storage_block_inserted(&b3.hash());
+        assert_eq!(beacon.subscribers_count(), 0);
+    }
+
+    #[test]
+    fn beacon_server_streams_notifications() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let beacon = Arc::new(Beacon::new(storage, 0));
+        let server = start_beacon(&"127.0.0.1:0".parse().unwrap(), beacon.clone()).unwrap();
+
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        // wait until the connection is accepted
+        while beacon.subscribers_count() == 0 {
+            ::std::thread::sleep(Duration::from_millis(10));
+        }
+
+        Beacon::listener(beacon.clone()).best_storage_block_inserted(&test_data::genesis().hash());
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BeaconValue>(&line).unwrap(),
+            value(0, &test_data::genesis())
+        );
+    }
+}
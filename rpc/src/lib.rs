@@ -10,12 +10,15 @@ extern crate jsonrpc_core;
 #[macro_use]
 extern crate jsonrpc_macros;
 extern crate chain;
+extern crate crypto;
 extern crate db;
 extern crate jsonrpc_http_server;
 extern crate message;
+extern crate metrics;
 extern crate miner;
 extern crate network;
 extern crate p2p;
+extern crate parking_lot;
 extern crate primitives;
 extern crate serialization as ser;
 extern crate storage;
@@ -23,11 +26,17 @@ extern crate sync;
 extern crate tokio_core;
 extern crate verification;
 
+pub mod beacon;
+pub mod metrics_server;
 pub mod rpc_server;
+pub mod stratum;
 pub mod v1;
 
 pub use jsonrpc_core::{Compatibility, Error, MetaIoHandler};
 pub use jsonrpc_http_server::tokio_core::reactor::Remote;
 
+pub use beacon::{start_beacon, Beacon, BeaconServer};
 pub use jsonrpc_http_server::Server;
+pub use metrics_server::{start_metrics, MetricsServer};
 pub use rpc_server::start_http;
+pub use stratum::{start_stratum, Stratum, StratumServer};
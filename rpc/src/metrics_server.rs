@@ -0,0 +1,139 @@
+//! Prometheus metrics endpoint.
+//!
+//! Serves `GET /metrics` requests with node metrics in Prometheus text exposition format.
+//! Every scrape is answered on a separate connection, which is closed after the response.
+
+use metrics;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+/// Time, given to the client to send the request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Maximal number of request lines (request line + headers).
+const MAX_REQUEST_LINES: usize = 100;
+/// Content type of Prometheus text exposition format.
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Handle of the running metrics server.
+pub struct MetricsServer {
+    local_addr: SocketAddr,
+}
+
+impl MetricsServer {
+    /// Address, the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Start metrics server. Requests are served one by one, since scrapes are cheap and rare.
+pub fn start_metrics(addr: &SocketAddr) -> io::Result<MetricsServer> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    thread::Builder::new()
+        .name("Metrics server".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream.and_then(serve_connection);
+                if let Err(err) = result {
+                    trace!(target: "metrics", "failed to serve metrics request: {}", err);
+                }
+            }
+        })?;
+
+    Ok(MetricsServer {
+        local_addr: local_addr,
+    })
+}
+
+fn serve_connection(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let request_line = read_request_line(&stream)?;
+    let (status, body) = match parse_request_line(&request_line) {
+        Some(("GET", "/metrics")) => ("200 OK", metrics::render()),
+        Some(("GET", _)) => ("404 Not Found", "Not Found\n".to_owned()),
+        Some(_) => ("405 Method Not Allowed", "Method Not Allowed\n".to_owned()),
+        None => ("400 Bad Request", "Bad Request\n".to_owned()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        CONTENT_TYPE,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Read request line and skip headers. Request body is never expected.
+fn read_request_line(stream: &TcpStream) -> io::Result<String> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    for _ in 0..MAX_REQUEST_LINES {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            return Ok(request_line);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "too many request headers",
+    ))
+}
+
+/// Returns method and path (without query string) of the request.
+fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    let path = target.split('?').next()?;
+    Some((method, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request_line, start_metrics};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn request(server: &super::MetricsServer, request: &str) -> String {
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn parse_request_line_strips_query() {
+        assert_eq!(
+            parse_request_line("GET /metrics?x=1 HTTP/1.1\r\n"),
+            Some(("GET", "/metrics"))
+        );
+        assert_eq!(parse_request_line("\r\n"), None);
+    }
+
+    #[test]
+    fn metrics_server_serves_metrics() {
+        let server = start_metrics(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+        let response = request(
+            &server,
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n",
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("\r\nContent-Type: text/plain; version=0.0.4\r\n"));
+        assert!(response.contains("\n# TYPE randchain_best_block_height gauge\n"));
+
+        let response = request(&server, "GET /other HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let response = request(&server, "POST /metrics HTTP/1.1\r\n\r\n");
+        assert!(response.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"));
+    }
+}
@@ -0,0 +1,756 @@
+//! Stratum-like mining protocol server.
+//!
+//! External VDF workers connect over TCP and exchange newline-delimited JSON messages with
+//! the node. Every request is `{"id": ..., "method": ..., "params": [...]}` and every response
+//! is `{"id": ..., "result": ..., "error": null | {"code": ..., "message": ...}}`.
+//!
+//! Supported methods:
+//! - `mining.authorize` `[worker_name, pubkey_hex]` - bind the connection to the worker and
+//!   its public key, which is used in all blocks, submitted over this connection;
+//! - `mining.get_template` `[]` - get mining job: the header template of the next block;
+//! - `mining.submit` `[job_id, iterations, solution_hex, [proof_element_hex, ...]]` - submit
+//!   VDF output (randomness) and its proof for the given job;
+//! - `mining.get_stats` `[]` - get statistics of the authorized worker.
+
+use chain::{Block, BlockHeader, IndexedBlock};
+use crypto::sr25519::PK;
+use hex::FromHex;
+use miner;
+use parking_lot::Mutex;
+use rug::Integer;
+use serde_json::{self, Value};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use v1::impls::MinerClientCoreApi;
+use v1::types::{SubmitBlockResponse, H256};
+
+/// Maximal number of jobs, remembered by the server. Solutions for older jobs are stale.
+const MAX_JOBS: usize = 16;
+/// Maximal length of a single request line (4 MB).
+const MAX_LINE_LEN: u64 = 4 * 1024 * 1024;
+/// Maximal number of simultaneous worker connections.
+const MAX_CONNECTIONS: usize = 64;
+/// Maximal number of simultaneously authorized workers.
+const MAX_WORKERS: usize = 256;
+
+mod codes {
+    pub const PARSE_ERROR: i64 = -32700;
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    pub const INVALID_PARAMS: i64 = -32602;
+    pub const UNAUTHORIZED_WORKER: i64 = 24;
+    pub const TOO_MANY_WORKERS: i64 = 25;
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    id: Value,
+    result: Value,
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl ResponseError {
+    fn new(code: i64, message: &str) -> Self {
+        ResponseError {
+            code: code,
+            message: message.to_owned(),
+        }
+    }
+
+    fn invalid_params(message: &str) -> Self {
+        ResponseError::new(codes::INVALID_PARAMS, message)
+    }
+}
+
+/// Mining job, handed out to workers
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Job {
+    /// Job identifier, which must be used when submitting solution
+    pub job_id: String,
+    /// Block version
+    pub version: u32,
+    /// Hash of the parent block
+    pub previousblockhash: H256,
+    /// Compressed target of the block
+    pub bits: u32,
+    /// Height of the block
+    pub height: u32,
+}
+
+/// Statistics of the single worker
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct WorkerStats {
+    /// Number of submitted solutions
+    pub submitted: u64,
+    /// Number of solutions, accepted by the node
+    pub accepted: u64,
+    /// Number of rejected solutions
+    pub rejected: u64,
+    /// Reason of the last rejection
+    pub last_reject_reason: Option<String>,
+}
+
+/// State of the single worker connection
+#[derive(Debug, Default)]
+pub struct Session {
+    /// Name and public key of the authorized worker
+    worker: Option<(String, PK)>,
+}
+
+/// Authorized worker
+#[derive(Default)]
+struct Worker {
+    /// Number of connections, the worker is authorized on
+    connections: usize,
+    /// Worker statistics
+    stats: WorkerStats,
+}
+
+#[derive(Default)]
+struct Jobs {
+    /// Identifier of the next job
+    next_id: u64,
+    /// Recent jobs, the last one is the current
+    jobs: VecDeque<(String, miner::BlockTemplate)>,
+}
+
+impl Jobs {
+    /// Returns identifier of the job for given template, creating new job if required.
+    fn job_id(&mut self, template: miner::BlockTemplate) -> String {
+        if let Some(&(ref job_id, ref current)) = self.jobs.back() {
+            if current.previous_header_hash == template.previous_header_hash
+                && current.version == template.version
+                && current.bits == template.bits
+            {
+                return job_id.clone();
+            }
+        }
+
+        let job_id = format!("{:x}", self.next_id);
+        self.next_id += 1;
+        self.jobs.push_back((job_id.clone(), template));
+        if self.jobs.len() > MAX_JOBS {
+            self.jobs.pop_front();
+        }
+        job_id
+    }
+
+    fn template(&self, job_id: &str) -> Option<miner::BlockTemplate> {
+        self.jobs
+            .iter()
+            .find(|&&(ref id, _)| id == job_id)
+            .map(|&(_, template)| template)
+    }
+}
+
+/// Stratum protocol handler, shared by all worker connections.
+pub struct Stratum<T: MinerClientCoreApi> {
+    core: T,
+    jobs: Mutex<Jobs>,
+    workers: Mutex<HashMap<String, Worker>>,
+}
+
+impl<T> Stratum<T>
+where
+    T: MinerClientCoreApi,
+{
+    pub fn new(core: T) -> Self {
+        Stratum {
+            core: core,
+            jobs: Mutex::new(Jobs::default()),
+            workers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Statistics of all workers, authorized on currently open connections.
+    pub fn workers_stats(&self) -> HashMap<String, WorkerStats> {
+        self.workers
+            .lock()
+            .iter()
+            .map(|(name, worker)| (name.clone(), worker.stats.clone()))
+            .collect()
+    }
+
+    /// Forget the worker, authorized on the closed connection, unless it has other connections.
+    pub fn close_session(&self, session: &mut Session) {
+        if let Some((name, _)) = session.worker.take() {
+            self.release_worker(&name);
+        }
+    }
+
+    fn release_worker(&self, name: &str) {
+        let mut workers = self.workers.lock();
+        let is_last_connection = match workers.get_mut(name) {
+            Some(worker) => {
+                worker.connections -= 1;
+                worker.connections == 0
+            }
+            None => false,
+        };
+        if is_last_connection {
+            trace!(target: "stratum", "worker {} has disconnected", name);
+            workers.remove(name);
+        }
+    }
+
+    /// Handle single request line and return response line.
+    pub fn handle_line(&self, session: &mut Session, line: &str) -> String {
+        let response = match serde_json::from_str::<Request>(line) {
+            Ok(request) => {
+                let (result, error) = match self.handle(session, &request.method, &request.params) {
+                    Ok(result) => (result, None),
+                    Err(error) => (Value::Null, Some(error)),
+                };
+                Response {
+                    id: request.id,
+                    result: result,
+                    error: error,
+                }
+            }
+            Err(err) => Response {
+                id: Value::Null,
+                result: Value::Null,
+                error: Some(ResponseError::new(
+                    codes::PARSE_ERROR,
+                    &format!("invalid request: {}", err),
+                )),
+            },
+        };
+
+        serde_json::to_string(&response).expect("response is always serializable; qed")
+    }
+
+    fn handle(
+        &self,
+        session: &mut Session,
+        method: &str,
+        params: &[Value],
+    ) -> Result<Value, ResponseError> {
+        match method {
+            "mining.authorize" => self.authorize(session, params).map(Value::Bool),
+            "mining.get_template" => self.job().map(to_value),
+            "mining.submit" => self.submit(session, params).map(to_value),
+            "mining.get_stats" => self.stats(session).map(to_value),
+            _ => Err(ResponseError::new(
+                codes::METHOD_NOT_FOUND,
+                &format!("unknown method: {}", method),
+            )),
+        }
+    }
+
+    fn authorize(&self, session: &mut Session, params: &[Value]) -> Result<bool, ResponseError> {
+        let name = str_param(params, 0, "worker name")?;
+        let pubkey: Vec<u8> = str_param(params, 1, "public key")?
+            .from_hex()
+            .map_err(|_| ResponseError::invalid_params("public key is not a hex string"))?;
+        let pubkey = PK::from_bytes(&pubkey)
+            .map_err(|_| ResponseError::invalid_params("invalid public key"))?;
+
+        {
+            let mut workers = self.workers.lock();
+            if !workers.contains_key(name) && workers.len() >= MAX_WORKERS {
+                return Err(ResponseError::new(
+                    codes::TOO_MANY_WORKERS,
+                    "too many workers",
+                ));
+            }
+            workers.entry(name.to_owned()).or_default().connections += 1;
+        }
+
+        // connection could be re-authorized with another worker name
+        self.close_session(session);
+        trace!(target: "stratum", "worker {} is authorized", name);
+        session.worker = Some((name.to_owned(), pubkey));
+        Ok(true)
+    }
+
+    fn job(&self) -> Result<Job, ResponseError> {
+        let template = self
+            .core
+            .get_block_template()
+            .map_err(|err| ResponseError::new(err.code.code(), &err.message))?;
+        let job_id = self.jobs.lock().job_id(template);
+        Ok(Job {
+            job_id: job_id,
+            version: template.version,
+            previousblockhash: template.previous_header_hash.reversed().into(),
+            bits: template.bits.into(),
+            height: template.height,
+        })
+    }
+
+    fn submit(
+        &self,
+        session: &Session,
+        params: &[Value],
+    ) -> Result<SubmitBlockResponse, ResponseError> {
+        let &(ref name, ref pubkey) = authorized_worker(session)?;
+        let job_id = str_param(params, 0, "job id")?;
+        let iterations = params
+            .get(1)
+            .and_then(Value::as_u64)
+            .filter(|iterations| *iterations <= u32::max_value() as u64)
+            .ok_or_else(|| ResponseError::invalid_params("invalid iterations"))?;
+        let solution = integer_param(str_param(params, 2, "solution")?)?;
+        let proof = params
+            .get(3)
+            .and_then(Value::as_array)
+            .ok_or_else(|| ResponseError::invalid_params("missing proof"))?
+            .iter()
+            .map(|element| {
+                element
+                    .as_str()
+                    .ok_or_else(|| ResponseError::invalid_params("invalid proof"))
+                    .and_then(integer_param)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let template = self.jobs.lock().template(job_id);
+        let response = match template {
+            Some(template) => {
+                let header = BlockHeader {
+                    version: template.version,
+                    previous_header_hash: template.previous_header_hash,
+                    bits: template.bits,
+                    pubkey: pubkey.clone(),
+                    iterations: iterations as u32,
+                    solution: solution,
+                };
+                let block = IndexedBlock::from_raw(Block::new(header, proof));
+                self.core
+                    .submit_block(block)
+                    .map_err(|err| ResponseError::new(err.code.code(), &err.message))?
+            }
+            None => SubmitBlockResponse::rejected("stale"),
+        };
+
+        trace!(target: "stratum", "worker {} has submitted solution for job {}: {:?}", name, job_id, response);
+        let mut workers = self.workers.lock();
+        let stats = &mut workers.entry(name.clone()).or_default().stats;
+        stats.submitted += 1;
+        if response.accepted {
+            stats.accepted += 1;
+        } else {
+            stats.rejected += 1;
+            stats.last_reject_reason = response.reason.clone();
+        }
+
+        Ok(response)
+    }
+
+    fn stats(&self, session: &Session) -> Result<WorkerStats, ResponseError> {
+        let &(ref name, _) = authorized_worker(session)?;
+        Ok(self
+            .workers
+            .lock()
+            .get(name)
+            .map(|worker| worker.stats.clone())
+            .unwrap_or_default())
+    }
+}
+
+fn to_value<T: ::serde::Serialize>(value: T) -> Value {
+    serde_json::to_value(value).expect("stratum types are always serializable; qed")
+}
+
+fn authorized_worker(session: &Session) -> Result<&(String, PK), ResponseError> {
+    session
+        .worker
+        .as_ref()
+        .ok_or_else(|| ResponseError::new(codes::UNAUTHORIZED_WORKER, "worker is not authorized"))
+}
+
+fn str_param<'a>(params: &'a [Value], index: usize, name: &str) -> Result<&'a str, ResponseError> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| ResponseError::invalid_params(&format!("missing {}", name)))
+}
+
+fn integer_param(hex: &str) -> Result<Integer, ResponseError> {
+    Integer::from_str_radix(hex, 16)
+        .map_err(|_| ResponseError::invalid_params(&format!("invalid integer: {}", hex)))
+}
+
+/// Handle of the running stratum server.
+pub struct StratumServer {
+    local_addr: SocketAddr,
+}
+
+impl StratumServer {
+    /// Address, the server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+/// Start stratum server. Every worker connection is served by a separate thread.
+/// Connections above `MAX_CONNECTIONS` are closed right after accepting.
+pub fn start_stratum<T>(addr: &SocketAddr, stratum: Arc<Stratum<T>>) -> io::Result<StratumServer>
+where
+    T: MinerClientCoreApi,
+{
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+    let connections = Arc::new(AtomicUsize::new(0));
+    thread::Builder::new()
+        .name("Stratum server".to_owned())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(err) => {
+                        warn!(target: "stratum", "failed to accept worker connection: {}", err);
+                        continue;
+                    }
+                };
+
+                if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    warn!(target: "stratum", "too many worker connections, dropping {:?}", stream.peer_addr());
+                    continue;
+                }
+
+                let stratum = stratum.clone();
+                let thread_connections = connections.clone();
+                let spawned = thread::Builder::new()
+                    .name("Stratum worker".to_owned())
+                    .spawn(move || {
+                        if let Err(err) = serve_connection(&stratum, stream) {
+                            trace!(target: "stratum", "worker connection is closed: {}", err);
+                        }
+                        thread_connections.fetch_sub(1, Ordering::SeqCst);
+                    });
+                if let Err(err) = spawned {
+                    connections.fetch_sub(1, Ordering::SeqCst);
+                    warn!(target: "stratum", "failed to serve worker connection: {}", err);
+                }
+            }
+        })?;
+
+    Ok(StratumServer {
+        local_addr: local_addr,
+    })
+}
+
+fn serve_connection<T>(stratum: &Stratum<T>, stream: TcpStream) -> io::Result<()>
+where
+    T: MinerClientCoreApi,
+{
+    let mut session = Session::default();
+    let result = serve_session(stratum, &mut session, stream);
+    stratum.close_session(&mut session);
+    result
+}
+
+fn serve_session<T>(
+    stratum: &Stratum<T>,
+    session: &mut Session,
+    stream: TcpStream,
+) -> io::Result<()>
+where
+    T: MinerClientCoreApi,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = (&mut reader).take(MAX_LINE_LEN).read_line(&mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        if !line.ends_with('\n') && read as u64 == MAX_LINE_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request line is too long",
+            ));
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = stratum.handle_line(session, line.trim());
+        writer.write_all(response.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::{start_stratum, Session, Stratum, WorkerStats, MAX_JOBS, MAX_WORKERS};
+    use chain::IndexedBlock;
+    use hex::ToHex;
+    use jsonrpc_core::Error;
+    use miner;
+    use primitives::hash::H256;
+    use serde_json::{self, Value};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use sync;
+    use v1::impls::MinerClientCoreApi;
+    use v1::types::SubmitBlockResponse;
+
+    #[derive(Default)]
+    struct TestMinerClientCore {
+        parent: u8,
+    }
+
+    impl MinerClientCoreApi for TestMinerClientCore {
+        fn get_block_template(&self) -> Result<miner::BlockTemplate, Error> {
+            Ok(miner::BlockTemplate {
+                version: 1,
+                previous_header_hash: H256::from(self.parent),
+                bits: 44.into(),
+                height: 55,
+            })
+        }
+
+        fn submit_block(&self, block: IndexedBlock) -> Result<SubmitBlockResponse, Error> {
+            // solutions with odd iterations are invalid
+            if block.header.raw.iterations % 2 == 0 {
+                Ok(SubmitBlockResponse::accepted())
+            } else {
+                Ok(SubmitBlockResponse::rejected("bad-proof"))
+            }
+        }
+
+        fn sync_metrics(&self) -> sync::SyncMetrics {
+            unreachable!("sync metrics are not used by stratum")
+        }
+
+        fn estimate_solution_time(&self, _samples: u32) -> Result<f64, Error> {
+            Ok(0f64)
+        }
+
+        fn candidates(&self) -> Vec<IndexedBlock> {
+            Vec::new()
+        }
+
+        fn miner_status(&self) -> Result<miner::MinerStatus, Error> {
+            Ok(miner::MinerStatus::default())
+        }
+
+        fn report_miner_progress(
+            &self,
+            _template: &miner::BlockTemplate,
+            _iterations: Option<u64>,
+        ) {
+        }
+    }
+
+    fn pubkey_hex() -> String {
+        test_data::block_h1().header().pubkey.to_bytes().to_hex()
+    }
+
+    fn request(stratum: &Stratum<TestMinerClientCore>, session: &mut Session, line: &str) -> Value {
+        serde_json::from_str(&stratum.handle_line(session, line)).unwrap()
+    }
+
+    #[test]
+    fn stratum_requires_authorization() {
+        let stratum = Stratum::new(TestMinerClientCore::default());
+        let mut session = Session::default();
+
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 1, "method": "mining.submit", "params": ["0", 2, "ff", []]}"#,
+        );
+        assert_eq!(response["error"]["code"], 24);
+
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 2, "method": "mining.authorize", "params": ["worker", "zz"]}"#,
+        );
+        assert_eq!(response["error"]["code"], -32602);
+
+        let response = request(
+            &stratum,
+            &mut session,
+            &format!(
+                r#"{{"id": 3, "method": "mining.authorize", "params": ["worker", "{}"]}}"#,
+                pubkey_hex()
+            ),
+        );
+        assert_eq!(response["id"], 3);
+        assert_eq!(response["result"], true);
+        assert_eq!(response["error"], Value::Null);
+    }
+
+    #[test]
+    fn stratum_reports_errors() {
+        let stratum = Stratum::new(TestMinerClientCore::default());
+        let mut session = Session::default();
+
+        let response = request(&stratum, &mut session, "not a json");
+        assert_eq!(response["error"]["code"], -32700);
+
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 1, "method": "mining.unknown"}"#,
+        );
+        assert_eq!(response["error"]["code"], -32601);
+    }
+
+    #[test]
+    fn stratum_submits_solutions_and_counts_stats() {
+        let stratum = Stratum::new(TestMinerClientCore::default());
+        let mut session = Session::default();
+        request(
+            &stratum,
+            &mut session,
+            &format!(
+                r#"{{"id": 1, "method": "mining.authorize", "params": ["worker", "{}"]}}"#,
+                pubkey_hex()
+            ),
+        );
+
+        let job = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 2, "method": "mining.get_template"}"#,
+        );
+        assert_eq!(
+            job["result"],
+            serde_json::from_str::<Value>(
+                r#"{"job_id":"0","version":1,"previousblockhash":"0000000000000000000000000000000000000000000000000000000000000000","bits":44,"height":55}"#
+            )
+            .unwrap()
+        );
+
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 3, "method": "mining.submit", "params": ["0", 2, "ff", ["01", "02"]]}"#,
+        );
+        assert_eq!(response["result"]["accepted"], true);
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 4, "method": "mining.submit", "params": ["0", 3, "ff", ["01"]]}"#,
+        );
+        assert_eq!(response["result"]["reason"], "bad-proof");
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 5, "method": "mining.submit", "params": ["100", 2, "ff", []]}"#,
+        );
+        assert_eq!(response["result"]["reason"], "stale");
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 6, "method": "mining.submit", "params": ["0", 2, "xyz", []]}"#,
+        );
+        assert_eq!(response["error"]["code"], -32602);
+
+        let expected = WorkerStats {
+            submitted: 3,
+            accepted: 1,
+            rejected: 2,
+            last_reject_reason: Some("stale".to_owned()),
+        };
+        assert_eq!(stratum.workers_stats()["worker"], expected);
+        let response = request(
+            &stratum,
+            &mut session,
+            r#"{"id": 7, "method": "mining.get_stats"}"#,
+        );
+        assert_eq!(response["result"]["submitted"], 3);
+    }
+
+    #[test]
+    fn stratum_limits_and_evicts_workers() {
+        let stratum = Stratum::new(TestMinerClientCore::default());
+        let authorize = |session: &mut Session, name: &str| {
+            request(
+                &stratum,
+                session,
+                &format!(
+                    r#"{{"id": 1, "method": "mining.authorize", "params": ["{}", "{}"]}}"#,
+                    name,
+                    pubkey_hex()
+                ),
+            )
+        };
+
+        let mut sessions: Vec<_> = (0..MAX_WORKERS)
+            .map(|index| {
+                let mut session = Session::default();
+                assert_eq!(authorize(&mut session, &index.to_string())["result"], true);
+                session
+            })
+            .collect();
+        let mut session = Session::default();
+        assert_eq!(authorize(&mut session, "extra")["error"]["code"], 25);
+        // known worker could open another connection
+        assert_eq!(authorize(&mut session, "0")["result"], true);
+        assert_eq!(stratum.workers_stats().len(), MAX_WORKERS);
+
+        stratum.close_session(&mut sessions[0]);
+        assert!(stratum.workers_stats().contains_key("0"));
+        stratum.close_session(&mut session);
+        assert!(!stratum.workers_stats().contains_key("0"));
+
+        // re-authorization releases previous worker
+        assert_eq!(authorize(&mut sessions[1], "extra")["result"], true);
+        assert!(!stratum.workers_stats().contains_key("1"));
+        assert!(stratum.workers_stats().contains_key("extra"));
+    }
+
+    #[test]
+    fn stratum_forgets_old_jobs() {
+        let mut jobs = super::Jobs::default();
+        let mut core = TestMinerClientCore::default();
+        let first = jobs.job_id(core.get_block_template().unwrap());
+        assert_eq!(jobs.job_id(core.get_block_template().unwrap()), first);
+
+        for parent in 1..(MAX_JOBS + 1) {
+            core.parent = parent as u8;
+            jobs.job_id(core.get_block_template().unwrap());
+        }
+        assert!(jobs.template(&first).is_none());
+        assert!(jobs.template("1").is_some());
+    }
+
+    #[test]
+    fn stratum_server_serves_workers() {
+        let stratum = Arc::new(Stratum::new(TestMinerClientCore::default()));
+        let server = start_stratum(&"127.0.0.1:0".parse().unwrap(), stratum).unwrap();
+
+        let mut stream = TcpStream::connect(server.local_addr()).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        stream
+            .write_all(b"{\"id\": 1, \"method\": \"mining.get_template\"}\n")
+            .unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["height"], 55);
+    }
+}
@@ -0,0 +1,231 @@
+use jsonrpc_core::futures::sync::oneshot;
+use jsonrpc_core::futures::{future, Future};
+use jsonrpc_core::BoxFuture;
+use primitives::hash::H256;
+use std::cmp::min;
+use std::mem;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+use sync::SyncListener;
+use v1::helpers::errors::{too_many_waiting_requests, unknown};
+
+/// Max number of RPC requests, waiting for chain events at the same time.
+const MAX_WAITERS: usize = 64;
+/// Interval between checks, made by the timer thread when no requests are waiting.
+const IDLE_TIMER_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Chain events, shared between the synchronization listener and RPC handlers,
+/// which are waiting for them.
+/// Waiting requests are resolved by the listener, or by the timer thread when timeout expires,
+/// so RPC threads are never blocked.
+pub struct ChainEvents {
+    state: Mutex<ChainEventsState>,
+    /// Wakes the timer thread when new request starts waiting.
+    waiter_added: Condvar,
+}
+
+#[derive(Default)]
+struct ChainEventsState {
+    /// Is node synchronizing?
+    is_synchronizing: bool,
+    /// Requests, waiting for new best block.
+    new_block_waiters: Vec<Waiter>,
+    /// Requests, waiting for synchronization state switch.
+    sync_state_waiters: Vec<Waiter>,
+}
+
+struct Waiter {
+    deadline: Instant,
+    sender: oneshot::Sender<bool>,
+}
+
+impl ChainEventsState {
+    fn waiters_len(&self) -> usize {
+        self.new_block_waiters.len() + self.sync_state_waiters.len()
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.new_block_waiters
+            .iter()
+            .chain(self.sync_state_waiters.iter())
+            .map(|waiter| waiter.deadline)
+            .min()
+    }
+
+    /// Resolve requests, which have been waiting for too long.
+    fn expire_waiters(&mut self, now: Instant) {
+        let is_synchronizing = self.is_synchronizing;
+        expire(&mut self.new_block_waiters, now, false);
+        expire(&mut self.sync_state_waiters, now, is_synchronizing);
+    }
+}
+
+fn expire(waiters: &mut Vec<Waiter>, now: Instant, value: bool) {
+    let (expired, waiting) = mem::replace(waiters, Vec::new())
+        .into_iter()
+        .partition(|waiter| waiter.deadline <= now);
+    *waiters = waiting;
+    notify(expired, value);
+}
+
+fn notify(waiters: Vec<Waiter>, value: bool) {
+    for waiter in waiters {
+        // request could be already dropped => ignore error
+        let _ = waiter.sender.send(value);
+    }
+}
+
+impl ChainEvents {
+    /// Create chain events and start the timer thread, which lives while events are alive.
+    pub fn new() -> Arc<Self> {
+        let events = Arc::new(ChainEvents {
+            state: Mutex::new(ChainEventsState::default()),
+            waiter_added: Condvar::new(),
+        });
+        let weak_events = Arc::downgrade(&events);
+        thread::Builder::new()
+            .name("RPC chain events timer".to_owned())
+            .spawn(move || ChainEvents::timer_proc(weak_events))
+            .expect("Error creating RPC chain events timer thread");
+        events
+    }
+
+    /// Listener, which updates these events
+    pub fn listener(events: Arc<Self>) -> Box<dyn SyncListener> {
+        Box::new(ChainEventsListener { events: events })
+    }
+
+    /// Resolves when new best block is inserted. Resolves to false on timeout.
+    pub fn wait_for_new_block(&self, timeout: Duration) -> BoxFuture<bool> {
+        self.wait(timeout, |state| &mut state.new_block_waiters)
+    }
+
+    /// Resolves when synchronization state switches. Resolves to current state.
+    pub fn wait_for_sync_state_switch(&self, timeout: Duration) -> BoxFuture<bool> {
+        self.wait(timeout, |state| &mut state.sync_state_waiters)
+    }
+
+    fn wait<F>(&self, timeout: Duration, waiters: F) -> BoxFuture<bool>
+    where
+        F: FnOnce(&mut ChainEventsState) -> &mut Vec<Waiter>,
+    {
+        let mut state = self.state.lock().expect("poisoned lock");
+        if state.waiters_len() >= MAX_WAITERS {
+            return Box::new(future::err(too_many_waiting_requests()));
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        waiters(&mut state).push(Waiter {
+            deadline: Instant::now() + timeout,
+            sender: sender,
+        });
+        self.waiter_added.notify_one();
+        // sender is only dropped without sending when events are dropped
+        Box::new(receiver.map_err(|_| unknown()))
+    }
+
+    fn timer_proc(events: Weak<ChainEvents>) {
+        // if events are dropped => stop thread
+        while let Some(events) = events.upgrade() {
+            let mut state = events.state.lock().expect("poisoned lock");
+            let now = Instant::now();
+            state.expire_waiters(now);
+            // wake up at least every IDLE_TIMER_INTERVAL to check if events are dropped
+            let wait_time = state
+                .next_deadline()
+                .map_or(IDLE_TIMER_INTERVAL, |deadline| {
+                    min(deadline - now, IDLE_TIMER_INTERVAL)
+                });
+            let _ = events
+                .waiter_added
+                .wait_timeout(state, wait_time)
+                .expect("poisoned lock");
+        }
+    }
+
+    fn on_block_inserted(&self) {
+        let waiters = {
+            let mut state = self.state.lock().expect("poisoned lock");
+            mem::replace(&mut state.new_block_waiters, Vec::new())
+        };
+        notify(waiters, true);
+    }
+
+    fn on_sync_state_switched(&self, is_synchronizing: bool) {
+        let waiters = {
+            let mut state = self.state.lock().expect("poisoned lock");
+            state.is_synchronizing = is_synchronizing;
+            mem::replace(&mut state.sync_state_waiters, Vec::new())
+        };
+        notify(waiters, is_synchronizing);
+    }
+}
+
+struct ChainEventsListener {
+    events: Arc<ChainEvents>,
+}
+
+impl SyncListener for ChainEventsListener {
+    fn synchronization_state_switched(&self, is_synchronizing: bool) {
+        self.events.on_sync_state_switched(is_synchronizing);
+    }
+
+    fn best_storage_block_inserted(&self, _block_hash: &H256) {
+        self.events.on_block_inserted();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChainEvents, MAX_WAITERS};
+    use jsonrpc_core::futures::Future;
+    use std::time::Duration;
+
+    #[test]
+    fn chain_events_wait_for_new_block() {
+        let events = ChainEvents::new();
+        assert_eq!(
+            events.wait_for_new_block(Duration::from_millis(10)).wait(),
+            Ok(false)
+        );
+
+        let listener = ChainEvents::listener(events.clone());
+        let waiter = events.wait_for_new_block(Duration::from_secs(10));
+        listener.best_storage_block_inserted(&Default::default());
+        assert_eq!(waiter.wait(), Ok(true));
+    }
+
+    #[test]
+    fn chain_events_wait_for_sync_state_switch() {
+        let events = ChainEvents::new();
+        assert_eq!(
+            events
+                .wait_for_sync_state_switch(Duration::from_millis(10))
+                .wait(),
+            Ok(false)
+        );
+
+        let listener = ChainEvents::listener(events.clone());
+        let waiter = events.wait_for_sync_state_switch(Duration::from_secs(10));
+        listener.synchronization_state_switched(true);
+        assert_eq!(waiter.wait(), Ok(true));
+    }
+
+    #[test]
+    fn chain_events_limit_waiting_requests() {
+        let events = ChainEvents::new();
+        let waiters: Vec<_> = (0..MAX_WAITERS)
+            .map(|_| events.wait_for_new_block(Duration::from_secs(10)))
+            .collect();
+        assert!(events
+            .wait_for_sync_state_switch(Duration::from_secs(10))
+            .wait()
+            .is_err());
+
+        ChainEvents::listener(events.clone()).best_storage_block_inserted(&Default::default());
+        for waiter in waiters {
+            assert_eq!(waiter.wait(), Ok(true));
+        }
+    }
+}
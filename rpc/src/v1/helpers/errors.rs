@@ -4,10 +4,20 @@ mod codes {
     // NOTE [ToDr] Codes from [-32099, -32000]
     pub const UNKNOWN: i64 = -32000;
     pub const EXECUTION_ERROR: i64 = -32015;
+    pub const PEER_NOT_FOUND: i64 = -32090;
+    pub const VERIFICATION_FAILED: i64 = -32092;
+    pub const CHAIN_TOO_SHORT: i64 = -32093;
     pub const BLOCK_NOT_FOUND: i64 = -32099;
     pub const NODE_ALREADY_ADDED: i64 = -32150;
     pub const NODE_NOT_ADDED: i64 = -32151;
     pub const TOO_MANY_BLOCKS: i64 = -32152;
+    pub const ADMIN_RPC_DISABLED: i64 = -32153;
+    pub const DB_CACHE_DISABLED: i64 = -32154;
+    pub const DIFFICULTY_MISMATCH: i64 = -32155;
+    pub const NODE_NOT_BANNED: i64 = -32156;
+    pub const DB_STATS_UNAVAILABLE: i64 = -32157;
+    pub const TOO_MANY_WAITING_REQUESTS: i64 = -32158;
+    pub const CANDIDATE_POOL_FULL: i64 = -32159;
 }
 
 use jsonrpc_core::{Error, ErrorCode, Value};
@@ -85,3 +95,110 @@ pub fn too_many_blocks() -> Error {
         data: None,
     }
 }
+
+pub fn admin_rpc_disabled() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::ADMIN_RPC_DISABLED),
+        message: "Admin RPC methods are disabled, restart node with `--allow-admin-rpc`".into(),
+        data: None,
+    }
+}
+
+pub fn db_cache_disabled() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::DB_CACHE_DISABLED),
+        message: "Database is opened without blocks cache".into(),
+        data: None,
+    }
+}
+
+pub fn db_stats_unavailable() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::DB_STATS_UNAVAILABLE),
+        message: "Database is not stored on disk".into(),
+        data: None,
+    }
+}
+
+pub fn too_many_waiting_requests() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::TOO_MANY_WAITING_REQUESTS),
+        message: "Too many requests are waiting for chain events, try again later".into(),
+        data: None,
+    }
+}
+
+pub fn candidate_pool_full() -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::CANDIDATE_POOL_FULL),
+        message: "Candidates pool is full, try again later".into(),
+        data: None,
+    }
+}
+
+pub fn difficulty_mismatch<T: fmt::Debug>(data: T) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::DIFFICULTY_MISMATCH),
+        message: "Difficulty mismatch: storage may be in inconsistent state".into(),
+        data: Some(Value::String(format!("{:?}", data))),
+    }
+}
+
+pub fn peer_not_found(peer_id: u32) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::PEER_NOT_FOUND),
+        message: "Peer with given id is not connected".into(),
+        data: Some(Value::String(format!("{}", peer_id))),
+    }
+}
+
+pub fn peer_not_connected(addr: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::PEER_NOT_FOUND),
+        message: "Peer with given address is not connected".into(),
+        data: Some(Value::String(addr.into())),
+    }
+}
+
+pub fn node_not_banned(addr: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::NODE_NOT_BANNED),
+        message: "Node is not banned".into(),
+        data: Some(Value::String(addr.into())),
+    }
+}
+
+pub fn invalid_address(addr: &str) -> Error {
+    Error {
+        code: ErrorCode::InvalidParams,
+        message: "Invalid address format".into(),
+        data: Some(Value::String(addr.into())),
+    }
+}
+
+pub fn invalid_block_hex(detail: &str) -> Error {
+    Error {
+        code: ErrorCode::InvalidParams,
+        message: "Invalid hex-encoded block".into(),
+        data: Some(Value::String(detail.into())),
+    }
+}
+
+pub fn verification_failed(detail: &str) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::VERIFICATION_FAILED),
+        message: "Block verification failed".into(),
+        data: Some(Value::String(detail.into())),
+    }
+}
+
+pub fn chain_too_short(required: u32, actual: u32) -> Error {
+    Error {
+        code: ErrorCode::ServerError(codes::CHAIN_TOO_SHORT),
+        message: "Chain is too short".into(),
+        data: Some(Value::String(format!(
+            "required: {}, actual: {}",
+            required, actual
+        ))),
+    }
+}
@@ -1,2 +1,3 @@
 #[macro_use]
 pub mod errors;
+pub mod chain_events;
@@ -1,17 +1,42 @@
 use hex::ToHex;
-use jsonrpc_core::Error;
+use jsonrpc_core::futures::Future;
+use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_macros::Trailing;
+use primitives::bigint::Uint;
 use primitives::hash::H256 as GlobalH256;
 use ser::serialize;
+use std::cmp::{max, min};
 use std::sync::Arc;
+use std::time::Duration;
 use storage;
-use v1::helpers::errors::{block_at_height_not_found, block_not_found, too_many_blocks};
+use sync;
+use v1::helpers::chain_events::ChainEvents;
+use v1::helpers::errors::{
+    admin_rpc_disabled, block_at_height_not_found, block_not_found, db_cache_disabled,
+    db_stats_unavailable, execution, too_many_blocks,
+};
 use v1::traits::BlockChain;
 use v1::types::{
-    BlockMetadata, BlockchainInfo, GetBlockResponse, RawBlock, VerboseBlock, H256, U256,
+    BlockMetadata, BlockchainInfo, ChainTip, DbCacheStats, DbStats, DifficultyHistoryEntry,
+    GetBlockResponse, Randomness, RandomnessHeight, RawBlock, VerboseBlock, H256, U256,
 };
 use verification;
 
+/// Default timeout of `waitfor*` methods
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 30 * 1000;
+/// Max timeout of `waitfor*` methods
+const MAX_WAIT_TIMEOUT_MS: u64 = 60 * 1000;
+/// Max number of entries, returned by `getdifficultyhistory`
+const MAX_DIFFICULTY_HISTORY_LENGTH: u32 = 1000;
+
+/// Share of known headers, which are already verified and stored, in [0, 1]
+fn verification_progress(blocks: u32, headers: u32) -> f64 {
+    if headers == 0 {
+        return 1f64;
+    }
+    (blocks as f64 / headers as f64).min(1f64)
+}
+
 pub struct BlockChainClient<T: BlockChainClientCoreApi> {
     core: T,
 }
@@ -25,18 +50,38 @@ pub trait BlockChainClientCoreApi: Send + Sync + 'static {
     fn verbose_block(&self, hash: GlobalH256) -> Option<VerboseBlock>;
     fn blockchain_info(&self) -> BlockchainInfo;
     fn blocks(&self, u32, u32) -> Vec<BlockMetadata>;
+    fn randomness(&self, height: u32) -> Option<Randomness>;
+    fn chain_work(&self, height: u32) -> Option<U256>;
+    fn difficulty_history(&self, start: u32, num: u32) -> Vec<DifficultyHistoryEntry>;
+    fn compact_db(&self) -> Result<(), Error>;
+    fn db_cache_stats(&self) -> Option<DbCacheStats>;
+    fn db_stats(&self) -> Option<DbStats>;
+    fn wait_for_new_block(&self, timeout: Duration) -> BoxFuture<ChainTip>;
+    fn wait_for_sync_state(&self, timeout: Duration) -> BoxFuture<bool>;
 }
 
 pub struct BlockChainClientCore {
     p2p: Arc<p2p::Context>,
     storage: storage::SharedStore,
+    local_sync_node: sync::LocalNodeRef,
+    chain_events: Arc<ChainEvents>,
+    allow_admin: bool,
 }
 
 impl BlockChainClientCore {
-    pub fn new(p2p: Arc<p2p::Context>, storage: storage::SharedStore) -> Self {
+    pub fn new(
+        p2p: Arc<p2p::Context>,
+        storage: storage::SharedStore,
+        local_sync_node: sync::LocalNodeRef,
+        chain_events: Arc<ChainEvents>,
+        allow_admin: bool,
+    ) -> Self {
         BlockChainClientCore {
             p2p: p2p,
             storage: storage,
+            local_sync_node: local_sync_node,
+            chain_events: chain_events,
+            allow_admin: allow_admin,
         }
     }
 }
@@ -72,13 +117,21 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
                 None => -1,
             };
             let block_size = block.size();
+            let header_size = block.header_size();
+            let proof_size = block.proof_size();
+            debug_assert_eq!(header_size + proof_size, block_size);
 
             VerboseBlock {
                 confirmations: confirmations,
                 size: block_size as u32,
+                header_size: header_size as u32,
+                proof_size: proof_size as u32,
                 height: height,
                 difficulty: block.header.raw.bits.to_f64(),
-                chainwork: U256::default(), // TODO: read from storage
+                chainwork: height
+                    .and_then(|h| self.storage.chain_work(h))
+                    .unwrap_or_default()
+                    .into(),
                 previousblockhash: Some(block.header.raw.previous_header_hash.clone().into()),
                 nextblockhash: height
                     .and_then(|h| self.storage.block_hash(h + 1).map(|h| h.into())),
@@ -94,18 +147,30 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
     }
 
     fn blockchain_info(&self) -> BlockchainInfo {
-        // TODO RH implement
+        let best_block = self.storage.best_block();
+        let metrics = self.local_sync_node.metrics();
+        // total_headers_seen also counts the genesis block
+        let headers = max(
+            best_block.number,
+            metrics.total_headers_seen.saturating_sub(1),
+        );
         BlockchainInfo {
             chain: self.p2p.config().connection.network.name(),
-            blocks: self.storage.best_block().number,
-            headers: self.storage.best_block().number,
-            bestblockhash: self.storage.best_block().hash.to_reversed_str(),
+            blocks: best_block.number,
+            headers: headers,
+            bestblockhash: best_block.hash.to_reversed_str(),
             difficulty: self.storage.difficulty(),
             mediantime: None,
-            verificationprogress: 1, // TODO
-            initialblockdownload: 0, // TODO
-            chainwork: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
-                .to_owned(), // TODO
+            verificationprogress: verification_progress(best_block.number, headers),
+            initialblockdownload: metrics.is_synchronizing,
+            syncstate: metrics.state,
+            chainwork: format!(
+                "{:0>64}",
+                self.storage
+                    .chain_work(best_block.number)
+                    .unwrap_or_default()
+                    .to_hex()
+            ),
             size_on_disk: None,      // TODO
             pruned: false,           // TODO prune mode
             pruneheight: None,       // TODO prune mode
@@ -133,6 +198,90 @@ impl BlockChainClientCoreApi for BlockChainClientCore {
         }
         blocks
     }
+
+    fn randomness(&self, height: u32) -> Option<Randomness> {
+        let randomness = self.storage.randomness(height)?;
+        self.storage.block(height.into()).map(|block| Randomness {
+            height: height,
+            hash: block.hash().clone().into(),
+            randomness_hex: randomness.to_string_radix(16),
+            iterations: block.header.raw.iterations,
+            proof_length: block.proof.len() as u32,
+            proof_size: block.proof_size() as u32,
+        })
+    }
+
+    fn chain_work(&self, height: u32) -> Option<U256> {
+        self.storage.chain_work(height).map(Into::into)
+    }
+
+    fn difficulty_history(&self, start: u32, num: u32) -> Vec<DifficultyHistoryEntry> {
+        let mut history = vec![];
+        for height in start..start.saturating_add(num) {
+            let header = match self.storage.block_header(height.into()) {
+                Some(header) => header,
+                None => break,
+            };
+            history.push(DifficultyHistoryEntry {
+                height: height,
+                hash: header.hash.into(),
+                bits: header.raw.bits.into(),
+                difficulty: header.raw.bits.to_f64(),
+                chainwork: self.storage.chain_work(height).unwrap_or_default().into(),
+            });
+        }
+        history
+    }
+
+    fn compact_db(&self) -> Result<(), Error> {
+        if !self.allow_admin {
+            return Err(admin_rpc_disabled());
+        }
+
+        self.storage.compact().map_err(execution)
+    }
+
+    fn db_cache_stats(&self) -> Option<DbCacheStats> {
+        self.storage.cache_stats().map(|stats| DbCacheStats {
+            hits: stats.hits,
+            misses: stats.misses,
+            hit_rate: stats.hit_rate,
+            entries: stats.entries,
+            max_entries: stats.max_entries,
+        })
+    }
+
+    fn db_stats(&self) -> Option<DbStats> {
+        self.storage.database_stats().map(|stats| DbStats {
+            total_size: stats.column_sizes.iter().sum(),
+            column_sizes: stats.column_sizes,
+            is_compacting: stats.is_compacting,
+            last_compaction: stats.last_compaction,
+        })
+    }
+
+    fn wait_for_new_block(&self, timeout: Duration) -> BoxFuture<ChainTip> {
+        let storage = self.storage.clone();
+        Box::new(self.chain_events.wait_for_new_block(timeout).map(move |_| {
+            let best_block = storage.best_block();
+            ChainTip {
+                hash: best_block.hash.reversed().into(),
+                height: best_block.number,
+            }
+        }))
+    }
+
+    fn wait_for_sync_state(&self, timeout: Duration) -> BoxFuture<bool> {
+        self.chain_events.wait_for_sync_state_switch(timeout)
+    }
+}
+
+fn wait_timeout(timeout_ms: Trailing<u64>) -> Duration {
+    let timeout_ms: Option<u64> = timeout_ms.into();
+    Duration::from_millis(min(
+        timeout_ms.unwrap_or(DEFAULT_WAIT_TIMEOUT_MS),
+        MAX_WAIT_TIMEOUT_MS,
+    ))
 }
 
 impl<T> BlockChainClient<T>
@@ -200,6 +349,68 @@ where
             Ok(self.core.blocks(start, num))
         }
     }
+
+    fn randomness(&self, height: RandomnessHeight) -> Result<Randomness, Error> {
+        let height = match height {
+            RandomnessHeight::Height(height) => height,
+            RandomnessHeight::Latest => self.core.block_count(),
+        };
+        self.core
+            .randomness(height)
+            .map(|mut randomness| {
+                randomness.hash = randomness.hash.reversed();
+                randomness
+            })
+            .ok_or(block_at_height_not_found(height))
+    }
+
+    fn chain_work(&self, height: Trailing<u32>) -> Result<U256, Error> {
+        let height: Option<u32> = height.into();
+        let height = height.unwrap_or_else(|| self.core.block_count());
+        self.core
+            .chain_work(height)
+            .ok_or(block_at_height_not_found(height))
+    }
+
+    fn difficulty_history(
+        &self,
+        start: u32,
+        num: u32,
+    ) -> Result<Vec<DifficultyHistoryEntry>, Error> {
+        if num > MAX_DIFFICULTY_HISTORY_LENGTH {
+            return Err(too_many_blocks());
+        }
+
+        Ok(self
+            .core
+            .difficulty_history(start, num)
+            .into_iter()
+            .map(|mut entry| {
+                entry.hash = entry.hash.reversed();
+                entry
+            })
+            .collect())
+    }
+
+    fn compact_db(&self) -> Result<(), Error> {
+        self.core.compact_db()
+    }
+
+    fn db_cache_stats(&self) -> Result<DbCacheStats, Error> {
+        self.core.db_cache_stats().ok_or_else(db_cache_disabled)
+    }
+
+    fn db_stats(&self) -> Result<DbStats, Error> {
+        self.core.db_stats().ok_or_else(db_stats_unavailable)
+    }
+
+    fn wait_for_new_block(&self, timeout_ms: Trailing<u64>) -> BoxFuture<ChainTip> {
+        self.core.wait_for_new_block(wait_timeout(timeout_ms))
+    }
+
+    fn wait_for_sync_state(&self, timeout_ms: Trailing<u64>) -> BoxFuture<bool> {
+        self.core.wait_for_sync_state(wait_timeout(timeout_ms))
+    }
 }
 
 #[cfg(test)]
@@ -207,12 +418,18 @@ pub mod tests {
     extern crate test_data;
 
     use super::*;
+    use chain::Block;
     use db::BlockChainDatabase;
+    use hex::FromHex;
+    use jsonrpc_core::futures::future;
     use jsonrpc_core::IoHandler;
     use primitives::hash::H256 as GlobalH256;
+    use ser::deserialize;
+    use serde_json;
     use std::sync::Arc;
+    use v1::helpers::errors::unknown;
     use v1::traits::BlockChain;
-    use v1::types::{RawBlock, VerboseBlock};
+    use v1::types::{Randomness, RawBlock, VerboseBlock};
 
     #[derive(Default)]
     struct SuccessBlockChainClientCore;
@@ -246,6 +463,8 @@ pub mod tests {
                 hash: test_data::block_h2().hash().into(),
                 confirmations: 1, // h2
                 size: serialize(&test_data::block_h2()).len() as u32,
+                header_size: test_data::block_h2().header_serialized_size() as u32,
+                proof_size: test_data::block_h2().proof_serialized_size() as u32,
                 height: Some(2),
                 version: 1,
                 version_hex: "1".to_owned(),
@@ -259,6 +478,67 @@ pub mod tests {
                 nextblockhash: None,
             })
         }
+
+        fn randomness(&self, height: u32) -> Option<Randomness> {
+            Some(Randomness {
+                height: height,
+                hash: test_data::genesis().hash().into(),
+                randomness_hex: "7788".to_owned(),
+                iterations: 0,
+                proof_length: 0,
+                proof_size: 0,
+            })
+        }
+
+        fn chain_work(&self, _height: u32) -> Option<U256> {
+            Some(U256::from(0x300))
+        }
+
+        fn difficulty_history(&self, start: u32, num: u32) -> Vec<DifficultyHistoryEntry> {
+            (start..start + num)
+                .map(|height| DifficultyHistoryEntry {
+                    height: height,
+                    hash: test_data::genesis().hash().into(),
+                    bits: 0x207fffff,
+                    difficulty: 1.0,
+                    chainwork: U256::from(height + 1),
+                })
+                .collect()
+        }
+
+        fn compact_db(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn db_cache_stats(&self) -> Option<DbCacheStats> {
+            Some(DbCacheStats {
+                hits: 3,
+                misses: 1,
+                hit_rate: 0.75,
+                entries: 2,
+                max_entries: 2116,
+            })
+        }
+
+        fn db_stats(&self) -> Option<DbStats> {
+            Some(DbStats {
+                column_sizes: vec![0, 100, 20],
+                total_size: 120,
+                is_compacting: false,
+                last_compaction: Some(1600000000),
+            })
+        }
+
+        fn wait_for_new_block(&self, _timeout: Duration) -> BoxFuture<ChainTip> {
+            Box::new(future::ok(ChainTip {
+                hash: test_data::genesis().hash().reversed().into(),
+                height: 1,
+            }))
+        }
+
+        fn wait_for_sync_state(&self, _timeout: Duration) -> BoxFuture<bool> {
+            Box::new(future::ok(true))
+        }
     }
 
     impl BlockChainClientCoreApi for ErrorBlockChainClientCore {
@@ -285,6 +565,38 @@ pub mod tests {
         fn verbose_block(&self, _hash: GlobalH256) -> Option<VerboseBlock> {
             None
         }
+
+        fn randomness(&self, _height: u32) -> Option<Randomness> {
+            None
+        }
+
+        fn chain_work(&self, _height: u32) -> Option<U256> {
+            None
+        }
+
+        fn difficulty_history(&self, _start: u32, _num: u32) -> Vec<DifficultyHistoryEntry> {
+            vec![]
+        }
+
+        fn compact_db(&self) -> Result<(), Error> {
+            Err(admin_rpc_disabled())
+        }
+
+        fn db_cache_stats(&self) -> Option<DbCacheStats> {
+            None
+        }
+
+        fn db_stats(&self) -> Option<DbStats> {
+            None
+        }
+
+        fn wait_for_new_block(&self, _timeout: Duration) -> BoxFuture<ChainTip> {
+            Box::new(future::err(unknown()))
+        }
+
+        fn wait_for_sync_state(&self, _timeout: Duration) -> BoxFuture<bool> {
+            Box::new(future::err(unknown()))
+        }
     }
 
     #[test]
@@ -313,6 +625,51 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn wait_for_new_block_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "waitfornewblock",
+                    	"params": [1000],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"hash":"f2f3cc2c2507998049764c415cfc721a4336ad3297b9bc2ac916ffa240adcdb2","height":1},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn wait_for_sync_state_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "waitforsyncstate",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":true,"id":1}"#);
+    }
+
     #[test]
     fn block_count_success() {
         let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
@@ -384,6 +741,267 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn randomness_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getrandomness",
+                    	"params": ["latest"],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"height":1,"hash":"f2f3cc2c2507998049764c415cfc721a4336ad3297b9bc2ac916ffa240adcdb2","randomnessHex":"7788","iterations":0,"proofLength":0,"proofSize":0},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn randomness_error() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getrandomness",
+                    	"params": [5],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block at given height is not found","data":"5"},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn chain_work_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getchainwork",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":"300","id":1}"#);
+    }
+
+    #[test]
+    fn chain_work_error() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getchainwork",
+                    	"params": [5],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block at given height is not found","data":"5"},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn difficulty_history_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdifficultyhistory",
+                    	"params": [1, 2],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":[{"height":1,"hash":"f2f3cc2c2507998049764c415cfc721a4336ad3297b9bc2ac916ffa240adcdb2","bits":545259519,"difficulty":1.0,"chainwork":"2"},{"height":2,"hash":"f2f3cc2c2507998049764c415cfc721a4336ad3297b9bc2ac916ffa240adcdb2","bits":545259519,"difficulty":1.0,"chainwork":"3"}],"id":1}"#
+        );
+    }
+
+    #[test]
+    fn difficulty_history_too_many_blocks() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdifficultyhistory",
+                    	"params": [0, 1001],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32152,"message":"Too many blocks to respond, use smaller `num`"},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn compact_db_disabled() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "compactdb",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32153,"message":"Admin RPC methods are disabled, restart node with `--allow-admin-rpc`"},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn db_cache_stats_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdbcachestats",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"hits":3,"misses":1,"hit_rate":0.75,"entries":2,"max_entries":2116},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn db_cache_stats_disabled() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdbcachestats",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32154,"message":"Database is opened without blocks cache"},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn db_stats_success() {
+        let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdbstats",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"column_sizes":[0,100,20],"total_size":120,"is_compacting":false,"last_compaction":1600000000},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn db_stats_not_on_disk() {
+        let client = BlockChainClient::new(ErrorBlockChainClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+                    {
+                    	"jsonrpc": "2.0",
+                    	"method": "getdbstats",
+                    	"params": [],
+                    	"id": 1
+                    }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","error":{"code":-32157,"message":"Database is not stored on disk"},"id":1}"#
+        );
+    }
+
     #[test]
     fn difficulty_success() {
         let client = BlockChainClient::new(SuccessBlockChainClientCore::default());
@@ -405,7 +1023,6 @@ pub mod tests {
         assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":1.0,"id":1}"#);
     }
 
-    // TODO update tests as we changed block format
     #[test]
     fn verbose_block_contents() {
         let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
@@ -423,7 +1040,9 @@ pub mod tests {
             Some(VerboseBlock {
                 hash: test_data::block_h1().hash().into(),
                 confirmations: 2, // h1 + h2
-                size: 341,
+                size: 337,
+                header_size: 336,
+                proof_size: 1,
                 height: Some(1),
                 version: 1,
                 version_hex: "1".to_owned(),
@@ -450,7 +1069,9 @@ pub mod tests {
             Some(VerboseBlock {
                 hash: test_data::block_h2().hash().into(),
                 confirmations: 1, // h2
-                size: 341,
+                size: 337,
+                header_size: 336,
+                proof_size: 1,
                 height: Some(2),
                 version: 1,
                 version_hex: "1".to_owned(),
@@ -490,6 +1111,12 @@ pub mod tests {
              .unwrap();
         assert_eq!(&sample, expected);
 
+        // raw block includes VDF proof
+        let response: serde_json::Value = serde_json::from_str(&sample).unwrap();
+        let raw: Vec<u8> = response["result"].as_str().unwrap().from_hex().unwrap();
+        let block: Block = deserialize(&raw as &[u8]).unwrap();
+        assert_eq!(block.proof, test_data::block_h2().proof);
+
         // try without optional parameter
         let sample = handler
             .handle_request_sync(
@@ -549,7 +1176,7 @@ pub mod tests {
 
         assert_eq!(
             &sample,
-            r#"{"jsonrpc":"2.0","result":{"bits":553713663,"chainwork":"0","confirmations":1,"difficulty":1.0,"hash":"a84e37303d15d90f2d46a483b3f007efda0d876bd39ccd16b8fdd4d58adea1c5","height":2,"iterations":1,"nextblockhash":null,"previousblockhash":"8fc76690623d21e0ce7ad0479d3ea934fed2b89be57f225680fcb7e74a95a68a","pubkeyHex":"0000000000000000000000000000000000000000000000000000000000000000","randomnessHex":"59c4420c8bd35716412451248f521db0fe76eb6a25c8a42127ceea885485d549e7215bf8535c3a651bf65a858df7c19b647dd571cce6cfc81981c801824a424b744e584ce01edb73c080e8181175838b89df08a629e579d87e258ebd0e3f6dda75c8e4e1cd1534506f700be8973335a95ade2235ad4e1bbda4aa14bd3b1e30b9110d7914652a528a07b85c06810651820baa186b435bea9884b2562ac4898a876a3015072be36ba7a29d15e49479c6d5a376d69c78b68d10dbea2107187be17719c066dd117e746f09a29e17fc4b72fdc9dfaa07fc0c8786970a6a6266659a4a038ec422160484fc6a4eac82a8079065bd4a4de416762237ddf208cc632af5d6","size":341,"version":1,"versionHex":"1"},"id":1}"#
+            r#"{"jsonrpc":"2.0","result":{"bits":553713663,"chainwork":"0","confirmations":1,"difficulty":1.0,"hash":"a84e37303d15d90f2d46a483b3f007efda0d876bd39ccd16b8fdd4d58adea1c5","headerSize":336,"height":2,"iterations":1,"nextblockhash":null,"previousblockhash":"8fc76690623d21e0ce7ad0479d3ea934fed2b89be57f225680fcb7e74a95a68a","proofSize":1,"pubkeyHex":"0000000000000000000000000000000000000000000000000000000000000000","randomnessHex":"59c4420c8bd35716412451248f521db0fe76eb6a25c8a42127ceea885485d549e7215bf8535c3a651bf65a858df7c19b647dd571cce6cfc81981c801824a424b744e584ce01edb73c080e8181175838b89df08a629e579d87e258ebd0e3f6dda75c8e4e1cd1534506f700be8973335a95ade2235ad4e1bbda4aa14bd3b1e30b9110d7914652a528a07b85c06810651820baa186b435bea9884b2562ac4898a876a3015072be36ba7a29d15e49479c6d5a376d69c78b68d10dbea2107187be17719c066dd117e746f09a29e17fc4b72fdc9dfaa07fc0c8786970a6a6266659a4a038ec422160484fc6a4eac82a8079065bd4a4de416762237ddf208cc632af5d6","size":337,"version":1,"versionHex":"1"},"id":1}"#
         );
     }
 
@@ -576,4 +1203,12 @@ pub mod tests {
             r#"{"jsonrpc":"2.0","error":{"code":-32099,"message":"Block with given hash is not found","data":"000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd"},"id":1}"#
         );
     }
+
+    #[test]
+    fn verification_progress_is_bounded() {
+        assert_eq!(super::verification_progress(0, 0), 1f64);
+        assert_eq!(super::verification_progress(50, 100), 0.5);
+        assert_eq!(super::verification_progress(100, 100), 1f64);
+        assert_eq!(super::verification_progress(101, 100), 1f64);
+    }
 }
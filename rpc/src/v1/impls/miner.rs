@@ -1,12 +1,32 @@
 use chain::{Block, IndexedBlock};
+use hex::FromHex;
 use jsonrpc_core::Error;
+use jsonrpc_macros::Trailing;
 use miner;
-use ser::{deserialize, serialize};
+use network::Network;
+use parking_lot::Mutex;
+use primitives::bigint::U256;
+use primitives::hash::H256;
+use ser::deserialize;
+use storage::{self, SharedStore};
 use sync;
+use v1::helpers::errors;
 use v1::traits::Miner;
 use v1::types::{
-    BlockTemplate, BlockTemplateRequest, Bytes, SubmitBlockRequest, SubmitBlockResponse,
+    BlockTemplate, BlockTemplateRequest, Candidate, MiningInfo, SubmitBlockRequest,
+    SubmitBlockResponse, SyncInfo,
 };
+use verification::{
+    work_required, BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError,
+    VerificationLevel, Verify,
+};
+
+/// Maximal length of hex-encoded block in `submitblock` request (4 MB).
+const MAX_SUBMIT_BLOCK_HEX_LEN: usize = 4 * 1024 * 1024;
+/// Default number of VDF iterations, used to measure local VDF speed.
+const DEFAULT_ESTIMATE_SAMPLES: u32 = 100;
+/// Maximal number of VDF iterations, used to measure local VDF speed.
+const MAX_ESTIMATE_SAMPLES: u32 = 10_000;
 
 pub struct MinerClient<T: MinerClientCoreApi> {
     core: T,
@@ -15,50 +35,246 @@ pub struct MinerClient<T: MinerClientCoreApi> {
 pub trait MinerClientCoreApi: Send + Sync + 'static {
     fn get_block_template(&self) -> Result<miner::BlockTemplate, Error>;
 
-    fn submit_block(
-        &self,
-        submit_block_req: SubmitBlockRequest,
-    ) -> Result<SubmitBlockResponse, Error>;
+    fn submit_block(&self, block: IndexedBlock) -> Result<SubmitBlockResponse, Error>;
+
+    fn sync_metrics(&self) -> sync::SyncMetrics;
+
+    fn estimate_solution_time(&self, samples: u32) -> Result<f64, Error>;
+
+    fn candidates(&self) -> Vec<IndexedBlock>;
+
+    fn miner_status(&self) -> Result<miner::MinerStatus, Error>;
+
+    fn report_miner_progress(&self, template: &miner::BlockTemplate, iterations: Option<u64>);
 }
 
 pub struct MinerClientCore {
     local_sync_node: sync::LocalNodeRef,
+    storage: SharedStore,
+    network: Network,
+    /// Parent of the template, the miner is working on
+    mining_parent: Mutex<Option<H256>>,
 }
 
 impl MinerClientCore {
-    pub fn new(local_sync_node: sync::LocalNodeRef) -> Self {
-        MinerClientCore { local_sync_node }
+    pub fn new(
+        local_sync_node: sync::LocalNodeRef,
+        storage: SharedStore,
+        network: Network,
+    ) -> Self {
+        MinerClientCore {
+            local_sync_node,
+            storage,
+            network,
+            mining_parent: Mutex::new(None),
+        }
+    }
+}
+
+fn storage_error(err: storage::Error) -> Error {
+    match err {
+        storage::Error::ChainTooShort { required, actual } => {
+            errors::chain_too_short(required, actual)
+        }
+        err => errors::execution(err),
     }
 }
 
+/// Check that assembled template difficulty is close to the difficulty, required by the storage.
+fn validate_bits(
+    assembled: &miner::BlockTemplate,
+    storage: &SharedStore,
+    network: Network,
+) -> Result<(), Error> {
+    let required_bits = work_required(
+        assembled.previous_header_hash.clone(),
+        assembled.height,
+        storage.as_block_header_provider(),
+        &network,
+    )
+    .map_err(storage_error)?;
+
+    let (required, assembled_target) = match (required_bits.to_u256(), assembled.bits.to_u256()) {
+        (Ok(required), Ok(assembled_target)) => (required, assembled_target),
+        _ => return Err(errors::difficulty_mismatch(assembled.bits)),
+    };
+    // targets are allowed to differ by factor of 2
+    if assembled_target / U256::from(2u64) > required
+        || required / U256::from(2u64) > assembled_target
+    {
+        return Err(errors::difficulty_mismatch(assembled.bits));
+    }
+
+    Ok(())
+}
+
+/// Reason of the block rejection, reported by `submitblock`.
+fn reject_reason(err: &VerificationError) -> &'static str {
+    match *err {
+        VerificationError::Duplicate
+        | VerificationError::Database(storage::Error::Duplicate(_)) => "duplicate",
+        VerificationError::Database(storage::Error::UnknownParent) => "bad-parent",
+        VerificationError::Pow
+        | VerificationError::Vdf
+        | VerificationError::EmptyProof
+        | VerificationError::UnexpectedProof => "bad-proof",
+        VerificationError::Difficulty { .. } => "bad-diffbits",
+        VerificationError::OldVersionBlock => "bad-version",
+        VerificationError::RepeatedPublicKey => "bad-pubkey",
+        VerificationError::Checkpoint | VerificationError::ForkBelowCheckpoint => "checkpoint",
+        VerificationError::FuturisticTimestamp => "time-too-new",
+        VerificationError::Timestamp => "time-invalid",
+        VerificationError::NonFinalBlock => "bad-txns-nonfinal",
+        VerificationError::Database(_) => "rejected",
+    }
+}
+
+/// Synchronously verify submitted block against the current best block.
+fn verify_submitted_block(
+    block: &IndexedBlock,
+    storage: &SharedStore,
+    network: Network,
+) -> Result<(), &'static str> {
+    if storage.contains_block(block.hash().clone().into()) {
+        return Err("duplicate");
+    }
+
+    let verifier = ChainVerifier::new(storage.clone(), network);
+    verifier
+        .precheck_block(block)
+        .map_err(|err| reject_reason(&err))?;
+    // the block is valid, but it does not extend the best block => it is too late
+    if block.header.raw.previous_header_hash != storage.best_block().hash {
+        return Err("stale");
+    }
+    verifier
+        .verify(VerificationLevel::Full, block)
+        .map_err(|err| reject_reason(&err))
+}
+
 impl MinerClientCoreApi for MinerClientCore {
     // when receiving getblocktemplate request
     fn get_block_template(&self) -> Result<miner::BlockTemplate, Error> {
-        Ok(self.local_sync_node.get_block_template())
+        let block_template = self
+            .local_sync_node
+            .get_block_template()
+            .map_err(storage_error)?;
+        validate_bits(&block_template, &self.storage, self.network)?;
+        Ok(block_template)
     }
 
     // when receiving submitblock request
-    fn submit_block(
-        &self,
-        submit_block_req: SubmitBlockRequest,
-    ) -> Result<SubmitBlockResponse, Error> {
-        // Deserialise to Block
-        let data_vec: Vec<u8> = submit_block_req.data.into();
-        let blk: Block = match deserialize(&data_vec[..]) {
-            Ok(block) => block,
-            Err(_) => return Err(Error::parse_error()),
-        };
-        // Convert Block to IndexedBlock
-        let indexed_blk = IndexedBlock::from_raw(blk);
+    fn submit_block(&self, block: IndexedBlock) -> Result<SubmitBlockResponse, Error> {
         trace!(
             "received submitblock request with block hash = {:?}",
-            indexed_blk.hash()
+            block.hash()
         );
-        // commit IndexedBlock locally
+        if let Err(reason) = verify_submitted_block(&block, &self.storage, self.network) {
+            trace!("block {:?} is rejected: {}", block.hash(), reason);
+            return Ok(SubmitBlockResponse::rejected(reason));
+        }
+        // the same solution can be submitted by several miners => process it once
+        match self.local_sync_node.submit_candidate(block.clone()) {
+            Ok(()) => (),
+            Err(miner::CandidateError::Duplicate) => {
+                trace!("block {:?} is already in candidates pool", block.hash());
+                return Ok(SubmitBlockResponse::rejected("duplicate"));
+            }
+            Err(miner::CandidateError::PoolFull) => {
+                trace!(
+                    "block {:?} is dropped: candidates pool is full",
+                    block.hash()
+                );
+                return Err(errors::candidate_pool_full());
+            }
+        }
+        // commit the best candidate for the current template locally
         // TODO RH check why on_block does not work
-        self.local_sync_node.on_block(0, indexed_blk.clone());
-        Ok(SubmitBlockResponse {})
+        if let Some(candidate) = self
+            .local_sync_node
+            .best_candidate()
+            .map_err(storage_error)?
+        {
+            self.local_sync_node.on_block(0, candidate.clone());
+            // block is fully verified above => announce it without waiting for sync verification
+            self.local_sync_node.broadcast_block(candidate);
+        }
+        self.local_sync_node.miner_progress().stop();
+        Ok(SubmitBlockResponse::accepted())
+    }
+
+    fn sync_metrics(&self) -> sync::SyncMetrics {
+        self.local_sync_node.metrics()
     }
+
+    fn estimate_solution_time(&self, samples: u32) -> Result<f64, Error> {
+        let block_template = self.get_block_template()?;
+        Ok(miner::estimate_solution_time(
+            &block_template,
+            &self.network,
+            samples,
+        ))
+    }
+
+    fn candidates(&self) -> Vec<IndexedBlock> {
+        self.local_sync_node.candidates()
+    }
+
+    fn miner_status(&self) -> Result<miner::MinerStatus, Error> {
+        let mut status = self.local_sync_node.miner_progress().status();
+        // when the miner is stopped, report target of the current template
+        if !status.enabled {
+            let block_template = self
+                .local_sync_node
+                .get_block_template()
+                .map_err(storage_error)?;
+            status.target_iterations = miner::target_iterations(&block_template);
+        }
+        Ok(status)
+    }
+
+    fn report_miner_progress(&self, template: &miner::BlockTemplate, iterations: Option<u64>) {
+        update_miner_progress(
+            &self.local_sync_node.miner_progress(),
+            &mut self.mining_parent.lock(),
+            template,
+            iterations,
+        );
+    }
+}
+
+/// Update miner progress with the number of iterations, reported in `getblocktemplate` request.
+/// Progress is restarted when the miner gets template with the new parent.
+fn update_miner_progress(
+    progress: &miner::MinerProgress,
+    mining_parent: &mut Option<H256>,
+    template: &miner::BlockTemplate,
+    iterations: Option<u64>,
+) {
+    // reported iterations belong to the previous template => ignore them when tip changes
+    if mining_parent.as_ref() != Some(&template.previous_header_hash) {
+        *mining_parent = Some(template.previous_header_hash.clone());
+        progress.start(miner::target_iterations(template));
+    } else if let Some(iterations) = iterations {
+        progress.update(iterations);
+    }
+}
+
+/// Decode hex-encoded block from `submitblock` request.
+fn parse_block(hex: &str) -> Result<IndexedBlock, Error> {
+    if hex.len() > MAX_SUBMIT_BLOCK_HEX_LEN {
+        return Err(errors::invalid_block_hex(&format!(
+            "block is too large: {} hex characters",
+            hex.len()
+        )));
+    }
+
+    let data: Vec<u8> = hex
+        .from_hex()
+        .map_err(|err| errors::invalid_block_hex(&format!("invalid hex encoding: {:?}", err)))?;
+    let block: Block = deserialize(&data[..])
+        .map_err(|err| errors::invalid_block_hex(&format!("invalid block: {:?}", err)))?;
+    Ok(IndexedBlock::from_raw(block))
 }
 
 impl<T> MinerClient<T>
@@ -74,13 +290,14 @@ impl<T> Miner for MinerClient<T>
 where
     T: MinerClientCoreApi,
 {
-    fn get_block_template(&self, _request: BlockTemplateRequest) -> Result<BlockTemplate, Error> {
+    fn get_block_template(&self, request: BlockTemplateRequest) -> Result<BlockTemplate, Error> {
         let tpl: BlockTemplate = match self.core.get_block_template() {
             Ok(tpl) => {
                 trace!(
                     "getblocktemplate OK: previous_header_hash = {:?}",
                     tpl.previous_header_hash
                 );
+                self.core.report_miner_progress(&tpl, request.iterations);
                 tpl.into()
             }
             Err(err) => {
@@ -97,7 +314,8 @@ where
         &self,
         submit_block_req: SubmitBlockRequest,
     ) -> Result<SubmitBlockResponse, Error> {
-        let resp: SubmitBlockResponse = match self.core.submit_block(submit_block_req) {
+        let block = parse_block(&submit_block_req.data)?;
+        let resp: SubmitBlockResponse = match self.core.submit_block(block) {
             Ok(resp) => {
                 trace!("submitblock OK");
                 resp
@@ -109,18 +327,48 @@ where
         };
         Ok(resp)
     }
+
+    fn sync_info(&self) -> Result<SyncInfo, Error> {
+        Ok(self.core.sync_metrics().into())
+    }
+
+    fn estimate_solution_time(&self, samples: Trailing<u32>) -> Result<f64, Error> {
+        let samples: Option<u32> = samples.into();
+        let samples = samples
+            .unwrap_or(DEFAULT_ESTIMATE_SAMPLES)
+            .min(MAX_ESTIMATE_SAMPLES);
+        self.core.estimate_solution_time(samples)
+    }
+
+    fn candidates(&self) -> Result<Vec<Candidate>, Error> {
+        Ok(self.core.candidates().into_iter().map(Into::into).collect())
+    }
+
+    fn mining_info(&self) -> Result<MiningInfo, Error> {
+        self.core.miner_status().map(Into::into)
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
+    extern crate test_data;
+
     use super::*;
-    use jsonrpc_core::IoHandler;
+    use db::BlockChainDatabase;
+    use hex::ToHex;
+    use jsonrpc_core::{ErrorCode, IoHandler};
     use miner;
     use primitives::hash::H256;
+    use ser::serialize;
+    use serde_json;
+    use std::sync::Arc;
+    use std::time::Duration;
     use v1::traits::Miner;
 
     #[derive(Default)]
-    struct SuccessMinerClientCore;
+    struct SuccessMinerClientCore {
+        reported_iterations: Arc<Mutex<Vec<Option<u64>>>>,
+    }
 
     impl MinerClientCoreApi for SuccessMinerClientCore {
         fn get_block_template(&self) -> Result<miner::BlockTemplate, Error> {
@@ -132,11 +380,52 @@ pub mod tests {
             })
         }
 
-        fn submit_block(
-            &self,
-            submit_block_req: SubmitBlockRequest,
-        ) -> Result<SubmitBlockResponse, Error> {
-            Ok(SubmitBlockResponse {})
+        fn submit_block(&self, _block: IndexedBlock) -> Result<SubmitBlockResponse, Error> {
+            Ok(SubmitBlockResponse::accepted())
+        }
+
+        fn sync_metrics(&self) -> sync::SyncMetrics {
+            sync::SyncMetrics {
+                state: "synchronizing".into(),
+                scheduled: 1,
+                requested: 2,
+                verifying: 3,
+                stored: 4,
+                orphaned: 5,
+                peers_idle: 6,
+                peers_active: 7,
+                peers_unuseful: 8,
+                sync_speed_bps: 1.5,
+                verify_speed_bps: 2.5,
+                last_block_hash: H256::from(1),
+                last_block_height: 3,
+                total_headers_seen: 10,
+                is_synchronizing: true,
+                elapsed_since_last_block_secs: 42,
+                reorgs: 2,
+                last_reorg_depth: 1,
+            }
+        }
+
+        fn estimate_solution_time(&self, samples: u32) -> Result<f64, Error> {
+            Ok(samples as f64 / 8.0)
+        }
+
+        fn candidates(&self) -> Vec<IndexedBlock> {
+            vec![test_data::block_h1().into()]
+        }
+
+        fn miner_status(&self) -> Result<miner::MinerStatus, Error> {
+            Ok(miner::MinerStatus {
+                enabled: true,
+                iterations: 1_000,
+                target_iterations: 10_000,
+                elapsed: Duration::from_secs(10),
+            })
+        }
+
+        fn report_miner_progress(&self, _template: &miner::BlockTemplate, iterations: Option<u64>) {
+            self.reported_iterations.lock().push(iterations);
         }
     }
 
@@ -165,4 +454,331 @@ pub mod tests {
             r#"{"jsonrpc":"2.0","result":{"bits":44,"coinbaseaux":null,"height":55,"mutable":null,"previousblockhash":"0000000000000000000000000000000000000000000000000000000000000001","rules":null,"target":"0000000000000000000000000000000000000000000000000000000000000000","vbavailable":null,"vbrequired":null,"version":777,"weightlimit":null},"id":1}"#
         );
     }
+
+    #[test]
+    fn getblocktemplate_reports_miner_progress() {
+        let core = SuccessMinerClientCore::default();
+        let reported_iterations = core.reported_iterations.clone();
+        let client = MinerClient::new(core);
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        handler
+            .handle_request_sync(
+                r#"{"jsonrpc": "2.0", "method": "getblocktemplate", "params": [{}], "id": 1}"#,
+            )
+            .unwrap();
+        handler
+            .handle_request_sync(
+                r#"{"jsonrpc": "2.0", "method": "getblocktemplate", "params": [{"iterations": 500}], "id": 1}"#,
+            )
+            .unwrap();
+
+        assert_eq!(*reported_iterations.lock(), vec![None, Some(500)]);
+    }
+
+    #[test]
+    fn update_miner_progress_restarts_on_new_parent() {
+        let template = miner::BlockTemplate {
+            version: 1,
+            previous_header_hash: H256::from(1),
+            bits: 44.into(),
+            height: 55,
+        };
+        let progress = miner::MinerProgress::default();
+        let mut mining_parent = None;
+
+        update_miner_progress(&progress, &mut mining_parent, &template, Some(100));
+        assert!(progress.status().enabled);
+        assert_eq!(progress.status().iterations, 0);
+        assert_eq!(
+            progress.status().target_iterations,
+            miner::target_iterations(&template)
+        );
+
+        update_miner_progress(&progress, &mut mining_parent, &template, Some(100));
+        assert_eq!(progress.status().iterations, 100);
+
+        // iterations, reported for the previous template, are ignored
+        let next_template = miner::BlockTemplate {
+            previous_header_hash: H256::from(2),
+            ..template
+        };
+        update_miner_progress(&progress, &mut mining_parent, &next_template, Some(200));
+        assert_eq!(progress.status().iterations, 0);
+        assert_eq!(mining_parent, Some(H256::from(2)));
+    }
+
+    #[test]
+    fn getsyncinfo_accepted() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "getsyncinfo",
+                "params": [],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"state":"synchronizing","scheduled":1,"requested":2,"verifying":3,"stored":4,"orphaned":5,"peers_idle":6,"peers_active":7,"peers_unuseful":8,"sync_speed_bps":1.5,"verify_speed_bps":2.5,"last_block_hash":"0000000000000000000000000000000000000000000000000000000000000001","last_block_height":3,"total_headers_seen":10,"is_synchronizing":true,"elapsed_since_last_block_secs":42,"reorgs":2,"last_reorg_depth":1},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn estimatesolutiontime_accepted() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "estimatesolutiontime",
+                "params": [20],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":2.5,"id":1}"#);
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "estimatesolutiontime",
+                "params": [],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":12.5,"id":1}"#);
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "estimatesolutiontime",
+                "params": [4294967295],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+        assert_eq!(&sample, r#"{"jsonrpc":"2.0","result":1250.0,"id":1}"#);
+    }
+
+    fn submit_block_request(data: &str) -> String {
+        format!(
+            r#"{{"jsonrpc": "2.0", "method": "submitblock", "params": [{{"data": "{}"}}], "id": 1}}"#,
+            data
+        )
+    }
+
+    fn error_code(response: &str) -> i64 {
+        let response: serde_json::Value = serde_json::from_str(response).unwrap();
+        response["error"]["code"].as_i64().unwrap()
+    }
+
+    #[test]
+    fn submitblock_accepted() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let block_hex: String = serialize(&test_data::block_h1()).to_hex();
+        let sample = handler
+            .handle_request_sync(&submit_block_request(&block_hex))
+            .unwrap();
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"accepted":true,"reason":null},"id":1}"#
+        );
+    }
+
+    #[test]
+    fn submitblock_rejects_invalid_hex() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(&submit_block_request("01zz"))
+            .unwrap();
+        assert_eq!(error_code(&sample), -32602);
+        assert!(sample.contains("invalid hex encoding"));
+    }
+
+    #[test]
+    fn submitblock_rejects_invalid_block() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(&submit_block_request("0100"))
+            .unwrap();
+        assert_eq!(error_code(&sample), -32602);
+        assert!(sample.contains("invalid block"));
+    }
+
+    #[test]
+    fn submitblock_rejects_oversized_block() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let block_hex = "00".repeat(MAX_SUBMIT_BLOCK_HEX_LEN / 2 + 1);
+        let sample = handler
+            .handle_request_sync(&submit_block_request(&block_hex))
+            .unwrap();
+        assert_eq!(error_code(&sample), -32602);
+        assert!(sample.contains("block is too large"));
+    }
+
+    #[test]
+    fn validate_bits_rejects_out_of_date_bits() {
+        let storage: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let mut block_template = miner::BlockTemplate {
+            version: 1,
+            previous_header_hash: test_data::genesis().hash(),
+            bits: test_data::genesis().header().bits,
+            height: 1,
+        };
+        assert!(validate_bits(&block_template, &storage, Network::Unitest).is_ok());
+
+        block_template.bits = U256::from(1u64).into();
+        assert_eq!(
+            validate_bits(&block_template, &storage, Network::Unitest)
+                .unwrap_err()
+                .code,
+            ErrorCode::ServerError(-32155)
+        );
+    }
+
+    #[test]
+    fn submitted_block_rejection_reasons() {
+        let storage: SharedStore = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+            test_data::block_h1().into(),
+        ]));
+        let verify =
+            |block: chain::Block| verify_submitted_block(&block.into(), &storage, Network::Unitest);
+
+        // already in the chain
+        assert_eq!(verify(test_data::block_h1()), Err("duplicate"));
+        // parent is unknown
+        let orphan = test_data::ChainBuilder::from_block(test_data::block_h3())
+            .next(1)
+            .build()
+            .pop()
+            .unwrap();
+        assert_eq!(verify(orphan), Err("bad-parent"));
+        // solution without vdf proof
+        let unproved = test_data::block_builder()
+            .header()
+            .parent(test_data::block_h1().hash())
+            .bits(test_data::block_h1().header().bits)
+            .iterations(2)
+            .build()
+            .build();
+        assert_eq!(verify(unproved), Err("bad-proof"));
+        // valid, but is not built on top of the best block
+        let stale = test_data::block_builder()
+            .header()
+            .parent(test_data::genesis().hash())
+            .bits(test_data::genesis().header().bits)
+            .iterations(2)
+            .evaluated()
+            .build()
+            .proved()
+            .build();
+        assert_eq!(verify(stale), Err("stale"));
+    }
+
+    #[test]
+    fn reject_reason_matches_verification_error() {
+        assert_eq!(reject_reason(&VerificationError::Vdf), "bad-proof");
+        assert_eq!(reject_reason(&VerificationError::Duplicate), "duplicate");
+        assert_eq!(
+            reject_reason(&VerificationError::Database(storage::Error::UnknownParent)),
+            "bad-parent"
+        );
+        assert_eq!(
+            reject_reason(&VerificationError::Difficulty {
+                expected: 1.into(),
+                actual: 2.into(),
+            }),
+            "bad-diffbits"
+        );
+    }
+
+    #[test]
+    fn getcandidates_accepted() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "getcandidates",
+                "params": [],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+
+        let block: IndexedBlock = test_data::block_h1().into();
+        let pubkey_hex: String = block.header.raw.pubkey.to_bytes().to_hex();
+        let response: serde_json::Value = serde_json::from_str(&sample).unwrap();
+        let candidates = response["result"].as_array().unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            candidates[0]["iterations"].as_u64(),
+            Some(block.header.raw.iterations as u64)
+        );
+        assert_eq!(
+            candidates[0]["pubkey_hex"].as_str(),
+            Some(pubkey_hex.as_ref())
+        );
+    }
+    #[test]
+    fn getmininginfo_accepted() {
+        let client = MinerClient::new(SuccessMinerClientCore::default());
+        let mut handler = IoHandler::new();
+        handler.extend_with(client.to_delegate());
+
+        let sample = handler
+            .handle_request_sync(
+                &(r#"
+            {
+                "jsonrpc": "2.0",
+                "method": "getmininginfo",
+                "params": [],
+                "id": 1
+            }"#),
+            )
+            .unwrap();
+
+        assert_eq!(
+            &sample,
+            r#"{"jsonrpc":"2.0","result":{"enabled":true,"iterations":1000,"target_iterations":10000,"iterations_per_second":100.0,"elapsed_secs":10.0,"expected_time_to_block_secs":100.0},"id":1}"#
+        );
+    }
 }
@@ -3,5 +3,5 @@ mod miner;
 mod network;
 
 pub use self::blockchain::{BlockChainClient, BlockChainClientCore};
-pub use self::miner::{MinerClient, MinerClientCore};
+pub use self::miner::{MinerClient, MinerClientCore, MinerClientCoreApi};
 pub use self::network::{NetworkClient, NetworkClientCore};
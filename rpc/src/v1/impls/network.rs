@@ -3,19 +3,29 @@ use jsonrpc_macros::Trailing;
 use p2p;
 use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
+use sync;
 use v1::helpers::errors;
 use v1::traits::Network as NetworkRpc;
 use v1::types::Address as AddressType;
 use v1::types::Network as NetworkType;
-use v1::types::{AddNodeOperation, NetworkInfo, NodeInfo};
+use v1::types::{
+    AddNodeOperation, BannedNodeInfo, ConnectedPeerInfo, NetworkInfo, NodeInfo, SetBanOperation,
+};
 
 pub trait NetworkApi: Send + Sync + 'static {
     fn add_node(&self, socket_addr: SocketAddr) -> Result<(), p2p::NodeTableError>;
     fn remove_node(&self, socket_addr: SocketAddr) -> Result<(), p2p::NodeTableError>;
     fn connect(&self, socket_addr: SocketAddr);
+    fn connect_seednode(&self, socket_addr: SocketAddr);
+    fn disconnect(&self, socket_addr: SocketAddr) -> bool;
+    fn ban(&self, addr: IpAddr, duration_secs: i64);
+    fn unban(&self, addr: IpAddr) -> bool;
+    fn banned(&self) -> Vec<BannedNodeInfo>;
+    fn clear_banned(&self);
     fn node_info(&self, node_addr: IpAddr) -> Result<NodeInfo, p2p::NodeTableError>;
     fn nodes_info(&self) -> Vec<NodeInfo>;
     fn connection_count(&self) -> usize;
+    fn peers_info(&self) -> Vec<ConnectedPeerInfo>;
     fn net_info(&self) -> NetworkInfo;
 }
 
@@ -24,12 +34,8 @@ where
     T: NetworkApi,
 {
     fn add_node(&self, node: String, operation: AddNodeOperation) -> Result<(), Error> {
-        let addr = node.parse().map_err(|_| {
-            errors::invalid_params(
-                "node",
-                "Invalid socket address format, should be ip:port (127.0.0.1:8008)",
-            )
-        })?;
+        // should be ip:port (127.0.0.1:8008)
+        let addr = node.parse().map_err(|_| errors::invalid_address(&node))?;
         match operation {
             AddNodeOperation::Add => self
                 .api
@@ -46,17 +52,68 @@ where
         }
     }
 
+    fn add_seed_node(&self, node: String) -> Result<(), Error> {
+        // should be ip:port (127.0.0.1:8008)
+        let addr = node.parse().map_err(|_| errors::invalid_address(&node))?;
+        self.api.connect_seednode(addr);
+        Ok(())
+    }
+
+    fn disconnect_node(&self, node: String) -> Result<(), Error> {
+        // should be ip:port (127.0.0.1:8008)
+        let addr = node.parse().map_err(|_| errors::invalid_address(&node))?;
+        if self.api.disconnect(addr) {
+            Ok(())
+        } else {
+            Err(errors::peer_not_connected(&node))
+        }
+    }
+
+    fn set_ban(
+        &self,
+        node: String,
+        operation: SetBanOperation,
+        bantime: Trailing<u64>,
+    ) -> Result<(), Error> {
+        // should be ip address (127.0.0.1)
+        let addr = node.parse().map_err(|_| errors::invalid_address(&node))?;
+        match operation {
+            SetBanOperation::Add => {
+                let bantime: Option<u64> = bantime.into();
+                let bantime = bantime
+                    .map(|bantime| bantime.min(p2p::MAX_BAN_TIME_SECS as u64) as i64)
+                    .unwrap_or(p2p::DEFAULT_BAN_TIME_SECS);
+                self.api.ban(addr, bantime);
+                Ok(())
+            }
+            SetBanOperation::Remove => {
+                if self.api.unban(addr) {
+                    Ok(())
+                } else {
+                    Err(errors::node_not_banned(&node))
+                }
+            }
+        }
+    }
+
+    fn list_banned(&self) -> Result<Vec<BannedNodeInfo>, Error> {
+        Ok(self.api.banned())
+    }
+
+    fn clear_banned(&self) -> Result<(), Error> {
+        self.api.clear_banned();
+        Ok(())
+    }
+
     fn node_info(&self, _dns: bool, node_addr: Trailing<String>) -> Result<Vec<NodeInfo>, Error> {
         let node_addr: Option<String> = node_addr.into();
         Ok(match node_addr {
             None => self.api.nodes_info(),
             Some(node_addr) => {
-                let addr = node_addr.parse().map_err(|_| {
-                    errors::invalid_params(
-                        "node",
-                        "Invalid ip address format, should be ip address (127.0.0.1)",
-                    )
-                })?;
+                // should be ip address (127.0.0.1)
+                let addr = node_addr
+                    .parse()
+                    .map_err(|_| errors::invalid_address(&node_addr))?;
                 let node_info = self
                     .api
                     .node_info(addr)
@@ -70,6 +127,10 @@ where
         Ok(self.api.connection_count())
     }
 
+    fn peer_info(&self) -> Result<Vec<ConnectedPeerInfo>, Error> {
+        Ok(self.api.peers_info())
+    }
+
     fn net_info(&self) -> Result<NetworkInfo, Error> {
         Ok(self.api.net_info())
     }
@@ -90,11 +151,15 @@ where
 
 pub struct NetworkClientCore {
     p2p: Arc<p2p::Context>,
+    peers: sync::PeersRef,
 }
 
 impl NetworkClientCore {
-    pub fn new(p2p: Arc<p2p::Context>) -> Self {
-        NetworkClientCore { p2p: p2p }
+    pub fn new(p2p: Arc<p2p::Context>, peers: sync::PeersRef) -> Self {
+        NetworkClientCore {
+            p2p: p2p,
+            peers: peers,
+        }
     }
 }
 
@@ -111,6 +176,50 @@ impl NetworkApi for NetworkClientCore {
         p2p::Context::connect_normal(self.p2p.clone(), socket_addr);
     }
 
+    fn connect_seednode(&self, socket_addr: SocketAddr) {
+        p2p::Context::connect_seednode(self.p2p.clone(), socket_addr);
+    }
+
+    fn disconnect(&self, socket_addr: SocketAddr) -> bool {
+        let peers: Vec<p2p::PeerId> = self
+            .p2p
+            .connections()
+            .info()
+            .into_iter()
+            .filter(|p| p.address == socket_addr)
+            .map(|p| p.id)
+            .collect();
+        for peer in &peers {
+            self.p2p.close_channel(*peer);
+        }
+        !peers.is_empty()
+    }
+
+    fn ban(&self, addr: IpAddr, duration_secs: i64) {
+        self.p2p.ban_node(addr, duration_secs);
+        for peer in self.p2p.connections().info() {
+            if peer.address.ip() == addr {
+                self.p2p.close_channel(peer.id);
+            }
+        }
+    }
+
+    fn unban(&self, addr: IpAddr) -> bool {
+        self.p2p.unban_node(&addr)
+    }
+
+    fn banned(&self) -> Vec<BannedNodeInfo> {
+        self.p2p
+            .banned_nodes()
+            .into_iter()
+            .map(Into::into)
+            .collect()
+    }
+
+    fn clear_banned(&self) {
+        self.p2p.clear_banned_nodes();
+    }
+
     fn node_info(&self, node_addr: IpAddr) -> Result<NodeInfo, p2p::NodeTableError> {
         let exact_node = self
             .p2p
@@ -160,6 +269,30 @@ impl NetworkApi for NetworkClientCore {
         self.p2p.connections().count()
     }
 
+    fn peers_info(&self) -> Vec<ConnectedPeerInfo> {
+        let connections = self.p2p.connections();
+        let nodes = self.p2p.nodes();
+
+        connections
+            .info()
+            .into_iter()
+            .map(|p| {
+                let last_rtt_ms = connections.last_rtt_ms(p.id);
+                let banscore = nodes
+                    .iter()
+                    .find(|n| n.address() == p.address)
+                    .map(|n| n.failures())
+                    .unwrap_or(0);
+                let connection_uptime_secs = self
+                    .peers
+                    .connection_duration(p.id)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or(0);
+                ConnectedPeerInfo::new(p, last_rtt_ms, connection_uptime_secs, banscore)
+            })
+            .collect()
+    }
+
     fn net_info(&self) -> NetworkInfo {
         let cfg = self.p2p.config();
         NetworkInfo {
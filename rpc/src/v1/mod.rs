@@ -4,8 +4,9 @@ pub mod impls;
 pub mod traits;
 pub mod types;
 
+pub use self::helpers::chain_events::ChainEvents;
 pub use self::impls::{BlockChainClient, BlockChainClientCore};
-pub use self::impls::{MinerClient, MinerClientCore};
+pub use self::impls::{MinerClient, MinerClientCore, MinerClientCoreApi};
 pub use self::impls::{NetworkClient, NetworkClientCore};
 pub use self::traits::BlockChain;
 pub use self::traits::Miner;
@@ -1,7 +1,10 @@
-use jsonrpc_core::Error;
+use jsonrpc_core::{BoxFuture, Error};
 use jsonrpc_macros::Trailing;
 
-use v1::types::{BlockMetadata, BlockchainInfo, GetBlockResponse, VerboseBlock, H256};
+use v1::types::{
+    BlockMetadata, BlockchainInfo, ChainTip, DbCacheStats, DbStats, DifficultyHistoryEntry,
+    GetBlockResponse, Randomness, RandomnessHeight, VerboseBlock, H256, U256,
+};
 
 build_rpc_trait! {
     /// Parity-randchain blockchain data interface.
@@ -41,5 +44,45 @@ build_rpc_trait! {
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getblocks", "params": [0, 10], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
         #[rpc(name = "getblocks")]
         fn blocks(&self, u32, u32) -> Result<Vec<BlockMetadata>, Error>;
+
+        /// Get randomness beacon value of the block at given height (or of the best block).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getrandomness", "params": ["latest"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getrandomness")]
+        fn randomness(&self, RandomnessHeight) -> Result<Randomness, Error>;
+
+        /// Get total amount of work in the chain up to the block at given height (or up to the best block).
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getchainwork", "params": [100], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getchainwork")]
+        fn chain_work(&self, Trailing<u32>) -> Result<U256, Error>;
+
+        /// Get difficulty and chain work of a number of canon blocks, starting at given height
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getdifficultyhistory", "params": [0, 100], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getdifficultyhistory")]
+        fn difficulty_history(&self, u32, u32) -> Result<Vec<DifficultyHistoryEntry>, Error>;
+
+        /// Compact the database. Admin method, requires `--allow-admin-rpc`.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "compactdb", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "compactdb")]
+        fn compact_db(&self) -> Result<(), Error>;
+
+        /// Get statistics of the database blocks cache.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getdbcachestats", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getdbcachestats")]
+        fn db_cache_stats(&self) -> Result<DbCacheStats, Error>;
+
+        /// Get statistics of the on-disk database: approximate column sizes and compaction status.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "getdbstats", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getdbstats")]
+        fn db_stats(&self) -> Result<DbStats, Error>;
+
+        /// Wait for new best block (or timeout in milliseconds, 30s by default, 60s at most) and return the best block.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "waitfornewblock", "params": [1000], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "waitfornewblock")]
+        fn wait_for_new_block(&self, Trailing<u64>) -> BoxFuture<ChainTip>;
+
+        /// Wait for synchronization state switch (or timeout in milliseconds, 30s by default, 60s at most) and return true if node is synchronizing.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "waitforsyncstate", "params": [1000], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "waitforsyncstate")]
+        fn wait_for_sync_state(&self, Trailing<u64>) -> BoxFuture<bool>;
     }
 }
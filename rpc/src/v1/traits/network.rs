@@ -1,6 +1,8 @@
 use jsonrpc_core::Error;
 use jsonrpc_macros::Trailing;
-use v1::types::{AddNodeOperation, NetworkInfo, NodeInfo};
+use v1::types::{
+    AddNodeOperation, BannedNodeInfo, ConnectedPeerInfo, NetworkInfo, NodeInfo, SetBanOperation,
+};
 
 build_rpc_trait! {
     /// Parity-bitcoin network interface
@@ -12,6 +14,33 @@ build_rpc_trait! {
         #[rpc(name = "addnode")]
         fn add_node(&self, String, AddNodeOperation) -> Result<(), Error>;
 
+        /// Connect to the seed node to discover addresses of other nodes
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "addseednode", "params": ["127.0.0.1:8888"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "addseednode")]
+        fn add_seed_node(&self, String) -> Result<(), Error>;
+
+        /// Immediately disconnect from the peer with given address
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "disconnectnode", "params": ["127.0.0.1:8888"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "disconnectnode")]
+        fn disconnect_node(&self, String) -> Result<(), Error>;
+
+        /// Add/remove ip address to/from the ban list. Default ban time is 24 hours.
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setban", "params": ["127.0.0.1", "add"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setban", "params": ["127.0.0.1", "add", 3600], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "setban", "params": ["127.0.0.1", "remove"], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "setban")]
+        fn set_ban(&self, String, SetBanOperation, Trailing<u64>) -> Result<(), Error>;
+
+        /// List all banned ip addresses
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "listbanned", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "listbanned")]
+        fn list_banned(&self) -> Result<Vec<BannedNodeInfo>, Error>;
+
+        /// Remove all ip addresses from the ban list
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "method": "clearbanned", "params": [], "id":1 }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "clearbanned")]
+        fn clear_banned(&self) -> Result<(), Error>;
+
         /// Query node(s) info
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getaddednodeinfo", "params": [true] }' -H 'content-type: application/json' http://127.0.0.1:8332/
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getaddednodeinfo", "params": [true, "192.168.0.201"] }' -H 'content-type: application/json' http://127.0.0.1:8332/
@@ -23,6 +52,11 @@ build_rpc_trait! {
         #[rpc(name = "getconnectioncount")]
         fn connection_count(&self) -> Result<usize, Error>;
 
+        /// Query info of all connected peers
+        /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getpeerinfo", "params": [] }' -H 'content-type: application/json' http://127.0.0.1:8332/
+        #[rpc(name = "getpeerinfo")]
+        fn peer_info(&self) -> Result<Vec<ConnectedPeerInfo>, Error>;
+
         /// Query network info
         /// Example: https://github.com/bitcoin/bitcoin/blob/master/src/rpc/net.cpp#L575
         /// @curl-example: curl --data-binary '{"jsonrpc": "2.0", "id":"1", "method": "getnetworkinfo"}' -H 'content-type: application/json' http://127.0.0.1:8332/
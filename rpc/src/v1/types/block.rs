@@ -26,6 +26,12 @@ pub struct VerboseBlock {
     pub confirmations: i64,
     /// Block size
     pub size: u32,
+    /// Size of the block header
+    #[serde(rename = "headerSize")]
+    pub header_size: u32,
+    /// Size of the block VDF proof
+    #[serde(rename = "proofSize")]
+    pub proof_size: u32,
     /// Block height
     /// TODO: bitcoind always returns value, but we hold this value for main chain blocks only
     pub height: Option<u32>,
@@ -88,13 +94,15 @@ mod tests {
         let block = VerboseBlock::default();
         assert_eq!(
             serde_json::to_string(&block).unwrap(),
-            r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#
+            r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"headerSize":0,"proofSize":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#
         );
 
         let block = VerboseBlock {
             hash: H256::from(1),
             confirmations: -1,
             size: 500000,
+            header_size: 499000,
+            proof_size: 1000,
             height: Some(3513513),
             version: 1,
             version_hex: "01".to_owned(),
@@ -110,7 +118,7 @@ mod tests {
         };
         assert_eq!(
             serde_json::to_string(&block).unwrap(),
-            r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"height":3513513,"version":1,"versionHex":"01","pubkeyHex":"6969696969696969696969696969696969696969696969696969696969696969","randomnessHex":"7788","iterations":124,"bits":13513,"difficulty":555.555,"chainwork":"3","previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#
+            r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"headerSize":499000,"proofSize":1000,"height":3513513,"version":1,"versionHex":"01","pubkeyHex":"6969696969696969696969696969696969696969696969696969696969696969","randomnessHex":"7788","iterations":124,"bits":13513,"difficulty":555.555,"chainwork":"3","previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#
         );
     }
 
@@ -118,13 +126,15 @@ mod tests {
     fn verbose_block_deserialize() {
         let block = VerboseBlock::default();
         assert_eq!(
-			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"strippedsize":0,"weight":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#).unwrap(),
+			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"headerSize":0,"proofSize":0,"strippedsize":0,"weight":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#).unwrap(),
 			block);
 
         let block = VerboseBlock {
             hash: H256::from(1),
             confirmations: -1,
             size: 500000,
+            header_size: 499000,
+            proof_size: 1000,
             height: Some(3513513),
             version: 1,
             version_hex: "01".to_owned(),
@@ -139,7 +149,7 @@ mod tests {
             nextblockhash: Some(H256::from(5)),
         };
         assert_eq!(
-			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"strippedsize":444444,"weight":5236235,"height":3513513,"version":1,"versionHex":"01","pubkeyHex":"6969696969696969696969696969696969696969696969696969696969696969","randomnessHex":"7788","iterations":124,"bits":13513,"difficulty":555.555,"chainwork":"3","previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#).unwrap(),
+			serde_json::from_str::<VerboseBlock>(r#"{"hash":"0100000000000000000000000000000000000000000000000000000000000000","confirmations":-1,"size":500000,"headerSize":499000,"proofSize":1000,"strippedsize":444444,"weight":5236235,"height":3513513,"version":1,"versionHex":"01","pubkeyHex":"6969696969696969696969696969696969696969696969696969696969696969","randomnessHex":"7788","iterations":124,"bits":13513,"difficulty":555.555,"chainwork":"3","previousblockhash":"0400000000000000000000000000000000000000000000000000000000000000","nextblockhash":"0500000000000000000000000000000000000000000000000000000000000000"}"#).unwrap(),
 			block);
     }
 
@@ -155,7 +165,7 @@ mod tests {
         let verbose_response = GetBlockResponse::Verbose(block);
         assert_eq!(
             serde_json::to_string(&verbose_response).unwrap(),
-            r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#
+            r#"{"hash":"0000000000000000000000000000000000000000000000000000000000000000","confirmations":0,"size":0,"headerSize":0,"proofSize":0,"height":null,"version":0,"versionHex":"","pubkeyHex":"","randomnessHex":"","iterations":0,"bits":0,"difficulty":0.0,"chainwork":"0","previousblockhash":null,"nextblockhash":null}"#
         );
     }
 }
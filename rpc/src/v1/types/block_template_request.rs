@@ -28,6 +28,10 @@ pub struct BlockTemplateRequest {
     pub capabilities: Option<HashSet<String>>,
     /// Softfork deployments, supported by client
     pub rules: Option<HashSet<String>>,
+    /// Number of VDF iterations, evaluated by the miner for the previously returned template.
+    /// Reported by `getmininginfo`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u64>,
 }
 
 #[cfg(test)]
@@ -70,6 +74,7 @@ mod tests {
                 mode: Some(BlockTemplateRequestMode::Template),
                 capabilities: Some(vec!["a".to_owned()].into_iter().collect()),
                 rules: Some(vec!["b".to_owned()].into_iter().collect()),
+                iterations: None,
             })
             .unwrap(),
             r#"{"mode":"template","capabilities":["a"],"rules":["b"]}"#
@@ -87,6 +92,7 @@ mod tests {
                 mode: None,
                 capabilities: None,
                 rules: None,
+                iterations: None,
             }
         );
         assert_eq!(
@@ -98,6 +104,14 @@ mod tests {
                 mode: Some(BlockTemplateRequestMode::Template),
                 capabilities: Some(vec!["a".to_owned()].into_iter().collect()),
                 rules: Some(vec!["b".to_owned()].into_iter().collect()),
+                iterations: None,
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<BlockTemplateRequest>(r#"{"iterations":1000}"#).unwrap(),
+            BlockTemplateRequest {
+                iterations: Some(1000),
+                ..Default::default()
             }
         );
     }
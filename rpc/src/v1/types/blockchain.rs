@@ -1,3 +1,6 @@
+use super::hash::H256;
+use super::uint::U256;
+
 /// Information of the blockchain
 /// See https://github.com/bitcoin/bitcoin/blob/master/src/rpc/blockchain.cpp#L1411-L1518
 #[derive(Default, Serialize, Deserialize)]
@@ -8,14 +11,51 @@ pub struct BlockchainInfo {
     pub bestblockhash: String, // the hash of the currently best block
     pub difficulty: f64, // the current difficulty
     pub mediantime: Option<u32>, // median time for the current best block
-    pub verificationprogress: u32, // estimate of verification progress [0, 1]
-    pub initialblockdownload: u32, // (debug information) estimate of whether this node is in Initial Block Download mode
-    pub chainwork: String,         // total amount of work in active chain, in hexadecimal
+    pub verificationprogress: f64, // estimate of verification progress [0, 1]
+    pub initialblockdownload: bool, // (debug information) estimate of whether this node is in Initial Block Download mode
+    pub syncstate: String, // current synchronization state (Synchronizing, NearlySaturated, Saturated)
+    pub chainwork: String, // total amount of work in active chain, in hexadecimal
     pub size_on_disk: Option<u32>, // the estimated size of the block and undo files on disk
-    pub pruned: bool,              // if the blocks are subject to pruning
+    pub pruned: bool,      // if the blocks are subject to pruning
     pub pruneheight: Option<u32>, // lowest-height complete block stored (only present if pruning is enabled)
     pub automatic_pruning: Option<bool>, // whether automatic pruning is enabled (only present if pruning is enabled)
     pub prune_target_size: Option<u32>, // the target size used by pruning (only present if automatic pruning is enabled)
     pub softforks: Option<u32>,         // status of softforks TODO
     pub warnings: Option<String>,       // any network and blockchain warnings
 }
+
+/// Statistics of the database blocks cache
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbCacheStats {
+    pub hits: u64,          // number of block reads, served from the cache
+    pub misses: u64,        // number of block reads, served from the database
+    pub hit_rate: f64,      // share of block reads, served from the cache
+    pub entries: usize,     // number of blocks in the cache
+    pub max_entries: usize, // max number of blocks in the cache
+}
+
+/// Statistics of the on-disk database
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbStats {
+    pub column_sizes: Vec<u64>, // approximate size (in bytes) of every database column
+    pub total_size: u64,        // approximate size (in bytes) of all database columns
+    pub is_compacting: bool,    // is manual compaction in progress
+    pub last_compaction: Option<u64>, // unix timestamp of the last manual compaction, if any
+}
+
+/// Best block of the chain
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChainTip {
+    pub hash: H256,  // hash of the best block
+    pub height: u32, // height of the best block
+}
+
+/// Difficulty of the canon block
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyHistoryEntry {
+    pub height: u32,     // height of the block
+    pub hash: H256,      // hash of the block
+    pub bits: u32,       // compact target of the block
+    pub difficulty: f64, // difficulty of the block
+    pub chainwork: U256, // total amount of work in the chain up to the block
+}
@@ -0,0 +1,27 @@
+use super::hash::H256;
+use chain::IndexedBlock;
+use hex::ToHex;
+
+/// Solved block from the candidates pool
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// Block hash
+    pub hash: H256,
+    /// Hash of the parent block
+    pub previousblockhash: H256,
+    /// Public key of the miner, hex-encoded
+    pub pubkey_hex: String,
+    /// Number of VDF iterations
+    pub iterations: u32,
+}
+
+impl From<IndexedBlock> for Candidate {
+    fn from(block: IndexedBlock) -> Self {
+        Candidate {
+            hash: block.header.hash.reversed().into(),
+            previousblockhash: block.header.raw.previous_header_hash.reversed().into(),
+            pubkey_hex: block.header.raw.pubkey.to_bytes().to_hex::<String>(),
+            iterations: block.header.raw.iterations,
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use miner;
+
+/// Progress of the local miner
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MiningInfo {
+    /// Is the miner searching for a solution right now?
+    pub enabled: bool,
+    /// Number of VDF iterations, evaluated for the current template
+    pub iterations: u64,
+    /// Expected number of VDF iterations, required to find a solution for the current template
+    pub target_iterations: u64,
+    /// VDF speed (iterations per second)
+    pub iterations_per_second: f64,
+    /// Number of seconds, spent on the current template
+    pub elapsed_secs: f64,
+    /// Expected number of seconds to find a solution. Null if the speed is unknown
+    pub expected_time_to_block_secs: Option<f64>,
+}
+
+impl From<miner::MinerStatus> for MiningInfo {
+    fn from(status: miner::MinerStatus) -> Self {
+        MiningInfo {
+            enabled: status.enabled,
+            iterations: status.iterations,
+            target_iterations: status.target_iterations,
+            iterations_per_second: status.iterations_per_second(),
+            elapsed_secs: status.elapsed.as_secs_f64(),
+            expected_time_to_block_secs: status.expected_time_to_block(),
+        }
+    }
+}
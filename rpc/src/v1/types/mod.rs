@@ -3,19 +3,29 @@ mod block_template;
 mod block_template_request;
 mod blockchain;
 mod bytes;
+mod candidate;
 mod hash;
+mod mining_info;
 mod network;
 mod nodes;
+mod randomness;
 mod submit_block;
+mod sync_info;
 mod uint;
 
 pub use self::block::{BlockMetadata, GetBlockResponse, RawBlock, VerboseBlock};
 pub use self::block_template::BlockTemplate;
 pub use self::block_template_request::{BlockTemplateRequest, BlockTemplateRequestMode};
-pub use self::blockchain::BlockchainInfo;
+pub use self::blockchain::{BlockchainInfo, ChainTip, DbCacheStats, DbStats, DifficultyHistoryEntry};
 pub use self::bytes::Bytes;
+pub use self::candidate::Candidate;
 pub use self::hash::{H160, H256};
+pub use self::mining_info::MiningInfo;
 pub use self::network::{Address, Network, NetworkInfo};
-pub use self::nodes::{AddNodeOperation, NodeInfo};
+pub use self::nodes::{
+    AddNodeOperation, BannedNodeInfo, ConnectedPeerInfo, NodeInfo, SetBanOperation,
+};
+pub use self::randomness::{Randomness, RandomnessHeight};
 pub use self::submit_block::{SubmitBlockRequest, SubmitBlockResponse};
+pub use self::sync_info::SyncInfo;
 pub use self::uint::U256;
@@ -1,4 +1,4 @@
-use p2p::{Direction, PeerInfo};
+use p2p::{BannedNode, Direction, PeerInfo};
 use serde::de::Unexpected;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
@@ -61,6 +61,109 @@ impl From<PeerInfo> for NodeInfoAddress {
     }
 }
 
+#[derive(Debug, PartialEq)]
+pub enum SetBanOperation {
+    Add,
+    Remove,
+}
+
+impl<'a> Deserialize<'a> for SetBanOperation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct DummyVisitor;
+
+        impl<'b> Visitor<'b> for DummyVisitor {
+            type Value = SetBanOperation;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a ban operation string")
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<SetBanOperation, E>
+            where
+                E: ::serde::de::Error,
+            {
+                match value {
+                    "add" => Ok(SetBanOperation::Add),
+                    "remove" => Ok(SetBanOperation::Remove),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(DummyVisitor)
+    }
+}
+
+/// Banned address
+#[derive(Serialize)]
+pub struct BannedNodeInfo {
+    /// Banned ip address
+    address: String,
+    /// Unix timestamp, until which the address is banned
+    banned_until: i64,
+}
+
+impl From<BannedNode> for BannedNodeInfo {
+    fn from(node: BannedNode) -> Self {
+        BannedNodeInfo {
+            address: format!("{}", node.address()),
+            banned_until: node.banned_until(),
+        }
+    }
+}
+
+/// Information about connected peer
+#[derive(Serialize)]
+pub struct ConnectedPeerInfo {
+    /// Index of the peer connection
+    id: usize,
+    /// Address of the peer (ip:port)
+    addr: String,
+    /// Services, offered by the peer
+    services: u64,
+    /// Peer user agent
+    subver: String,
+    /// Protocol version, used by the peer
+    version: u32,
+    /// Is this an inbound connection?
+    inbound: bool,
+    /// Best block height of the peer at the moment of connection
+    startingheight: Option<i32>,
+    /// Round-trip time of the last answered ping, in milliseconds
+    last_rtt_ms: Option<u64>,
+    /// Number of seconds since the peer has connected
+    connection_uptime_secs: u64,
+    /// Number of failures, registered for the peer address in the node table
+    banscore: u32,
+}
+
+impl ConnectedPeerInfo {
+    pub fn new(
+        info: PeerInfo,
+        last_rtt_ms: Option<u64>,
+        connection_uptime_secs: u64,
+        banscore: u32,
+    ) -> Self {
+        ConnectedPeerInfo {
+            id: info.id,
+            addr: format!("{}", info.address),
+            services: info.version_message.services().into(),
+            subver: info.user_agent,
+            version: info.version,
+            inbound: info.direction == Direction::Inbound,
+            startingheight: info.version_message.start_height(),
+            last_rtt_ms: last_rtt_ms,
+            connection_uptime_secs: connection_uptime_secs,
+            banscore: banscore,
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct NodeInfo {
     pub addednode: String,
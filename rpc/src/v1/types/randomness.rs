@@ -0,0 +1,111 @@
+use super::hash::H256;
+use serde::de::Unexpected;
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+
+/// Height of the block, which randomness is requested
+#[derive(Debug, PartialEq)]
+pub enum RandomnessHeight {
+    /// Block at given height
+    Height(u32),
+    /// Best block
+    Latest,
+}
+
+impl<'a> Deserialize<'a> for RandomnessHeight {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        use serde::de::Visitor;
+
+        struct DummyVisitor;
+
+        impl<'b> Visitor<'b> for DummyVisitor {
+            type Value = RandomnessHeight;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a block height or 'latest'")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<RandomnessHeight, E>
+            where
+                E: ::serde::de::Error,
+            {
+                if value > u32::max_value() as u64 {
+                    return Err(E::invalid_value(Unexpected::Unsigned(value), &self));
+                }
+                Ok(RandomnessHeight::Height(value as u32))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<RandomnessHeight, E>
+            where
+                E: ::serde::de::Error,
+            {
+                match value {
+                    "latest" => Ok(RandomnessHeight::Latest),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(DummyVisitor)
+    }
+}
+
+/// Randomness beacon value
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Randomness {
+    /// Block height
+    pub height: u32,
+    /// Block hash
+    pub hash: H256,
+    /// Randomness as hex
+    #[serde(rename = "randomnessHex")]
+    pub randomness_hex: String,
+    /// Number of VDF iterations
+    pub iterations: u32,
+    /// Number of elements of the block VDF proof
+    #[serde(rename = "proofLength")]
+    pub proof_length: u32,
+    /// Size of the block VDF proof
+    #[serde(rename = "proofSize")]
+    pub proof_size: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::hash::H256;
+    use super::{Randomness, RandomnessHeight};
+    use serde_json;
+
+    #[test]
+    fn randomness_height_deserialize() {
+        assert_eq!(
+            serde_json::from_str::<RandomnessHeight>("10").unwrap(),
+            RandomnessHeight::Height(10)
+        );
+        assert_eq!(
+            serde_json::from_str::<RandomnessHeight>(r#""latest""#).unwrap(),
+            RandomnessHeight::Latest
+        );
+        assert!(serde_json::from_str::<RandomnessHeight>(r#""best""#).is_err());
+        assert!(serde_json::from_str::<RandomnessHeight>("4294967296").is_err());
+    }
+
+    #[test]
+    fn randomness_serialize() {
+        let randomness = Randomness {
+            height: 1,
+            hash: H256::from(2),
+            randomness_hex: "7788".to_owned(),
+            iterations: 3,
+            proof_length: 4,
+            proof_size: 5,
+        };
+        assert_eq!(
+            serde_json::to_string(&randomness).unwrap(),
+            r#"{"height":1,"hash":"0200000000000000000000000000000000000000000000000000000000000000","randomnessHex":"7788","iterations":3,"proofLength":4,"proofSize":5}"#
+        );
+    }
+}
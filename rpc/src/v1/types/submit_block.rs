@@ -1,14 +1,34 @@
-use super::block::RawBlock;
-
 /// SubmitBlock Request
 /// See https://github.com/btcsuite/btcd/blob/2d7825cf709fc6ac15921cecfd4d62ec78ccbba2/docs/json_rpc_api.md#submitblock and https://en.bitcoin.it/wiki/BIP_0022 for the specification
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct SubmitBlockRequest {
     // data (string, required) serialized, hex-encoded block
-    pub data: RawBlock,
+    // kept as string, so that decoding errors are reported with context
+    pub data: String,
     // params (json object, optional, default=nil) this parameter is currently **ignored**. Some miner pools provide `workid` here
 }
 
 /// SubmitBlock Response
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone)]
-pub struct SubmitBlockResponse {}
+pub struct SubmitBlockResponse {
+    // true if block has been verified and queued for import
+    pub accepted: bool,
+    // BIP22-style rejection reason: bad-proof, bad-parent, stale, duplicate, ...
+    pub reason: Option<String>,
+}
+
+impl SubmitBlockResponse {
+    pub fn accepted() -> Self {
+        SubmitBlockResponse {
+            accepted: true,
+            reason: None,
+        }
+    }
+
+    pub fn rejected(reason: &str) -> Self {
+        SubmitBlockResponse {
+            accepted: false,
+            reason: Some(reason.to_owned()),
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::hash::H256;
+use sync;
+
+/// Snapshot of the synchronization state
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncInfo {
+    /// Synchronization state: synchronizing, nearly_saturated or saturated
+    pub state: String,
+    /// Number of blocks scheduled for requesting
+    pub scheduled: u32,
+    /// Number of blocks requested from peers
+    pub requested: u32,
+    /// Number of blocks currently verifying
+    pub verifying: u32,
+    /// Number of blocks in the storage
+    pub stored: u32,
+    /// Number of orphaned blocks
+    pub orphaned: u32,
+    /// Number of useful peers without pending requests
+    pub peers_idle: u32,
+    /// Number of useful peers with pending requests
+    pub peers_active: u32,
+    /// Number of non-useful peers
+    pub peers_unuseful: u32,
+    /// Blocks synchronization speed (blocks per second)
+    pub sync_speed_bps: f64,
+    /// Blocks verification speed (blocks per second)
+    pub verify_speed_bps: f64,
+    /// Hash of the best stored block
+    pub last_block_hash: H256,
+    /// Height of the best stored block
+    pub last_block_height: u32,
+    /// Number of stored blocks + number of in-memory headers
+    pub total_headers_seen: u32,
+    /// Is synchronization in progress?
+    pub is_synchronizing: bool,
+    /// Number of seconds since the last block has been stored
+    pub elapsed_since_last_block_secs: u64,
+    /// Number of chain reorganizations since the node start
+    pub reorgs: u32,
+    /// Number of blocks, decanonized by the last chain reorganization
+    pub last_reorg_depth: u32,
+}
+
+impl From<sync::SyncMetrics> for SyncInfo {
+    fn from(metrics: sync::SyncMetrics) -> Self {
+        SyncInfo {
+            state: metrics.state,
+            scheduled: metrics.scheduled,
+            requested: metrics.requested,
+            verifying: metrics.verifying,
+            stored: metrics.stored,
+            orphaned: metrics.orphaned,
+            peers_idle: metrics.peers_idle,
+            peers_active: metrics.peers_active,
+            peers_unuseful: metrics.peers_unuseful,
+            sync_speed_bps: metrics.sync_speed_bps,
+            verify_speed_bps: metrics.verify_speed_bps,
+            last_block_hash: metrics.last_block_hash.reversed().into(),
+            last_block_height: metrics.last_block_height,
+            total_headers_seen: metrics.total_headers_seen,
+            is_synchronizing: metrics.is_synchronizing,
+            elapsed_since_last_block_secs: metrics.elapsed_since_last_block_secs,
+            reorgs: metrics.reorgs,
+            last_reorg_depth: metrics.last_reorg_depth,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Benchmarks of default and tagged `Integer` serialization.
+//!
+//! Run with `cargo bench -p serialization`.
+
+#[macro_use]
+extern crate criterion;
+extern crate rug;
+extern crate serialization as ser;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use rug::Integer;
+use ser::{deserialize, serialize, TaggedInteger};
+
+fn values() -> Vec<(&'static str, Integer)> {
+    vec![
+        // randomness of genesis blocks
+        ("small", Integer::from(8)),
+        // randomness of regular blocks is an element of 2048-bit group
+        ("full", (Integer::from(1) << 2048u32) - 1u32),
+    ]
+}
+
+fn bench_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("integer/serialize");
+    for (name, value) in values() {
+        let tagged = TaggedInteger::from(value.clone());
+        group.bench_with_input(BenchmarkId::new("default", name), &value, |b, value| {
+            b.iter(|| serialize(black_box(value)))
+        });
+        group.bench_with_input(BenchmarkId::new("tagged", name), &tagged, |b, tagged| {
+            b.iter(|| serialize(black_box(tagged)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_deserialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("integer/deserialize");
+    for (name, value) in values() {
+        let raw = serialize(&value);
+        let raw_tagged = serialize(&TaggedInteger::from(value));
+        group.bench_with_input(BenchmarkId::new("default", name), &raw, |b, raw| {
+            b.iter(|| deserialize::<_, Integer>(black_box(&raw[..])).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("tagged", name), &raw_tagged, |b, raw| {
+            b.iter(|| deserialize::<_, TaggedInteger>(black_box(&raw[..])).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_serialize, bench_deserialize);
+criterion_main!(benches);
@@ -3,6 +3,7 @@ use bytes::Bytes;
 use compact::Compact;
 use compact_integer::CompactInteger;
 use hash::{H160, H256, H264, H32, H48, H512, H520, H96};
+use primitives::bigint::{Uint, U256};
 use rug::{integer::Order, Integer};
 use std::io;
 use {Deserializable, Error, Reader, Serializable, Stream};
@@ -281,6 +282,30 @@ impl Deserializable for Compact {
     }
 }
 
+impl Serializable for U256 {
+    fn serialize(&self, stream: &mut Stream) {
+        let mut bytes = [0u8; 32];
+        self.to_big_endian(&mut bytes);
+        stream.append_slice(&bytes);
+    }
+
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        32
+    }
+}
+
+impl Deserializable for U256 {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, Error>
+    where
+        T: io::Read,
+    {
+        let mut bytes = [0u8; 32];
+        reader.read_slice(&mut bytes)?;
+        Ok(U256::from(&bytes as &[u8]))
+    }
+}
+
 impl Serializable for Integer {
     fn serialize(&self, stream: &mut Stream) {
         let digits = self.to_digits::<u8>(Order::Msf);
@@ -309,6 +334,7 @@ impl Deserializable for Integer {
 #[cfg(test)]
 mod tests {
     use bytes::Bytes;
+    use primitives::bigint::U256;
     use rug::Integer;
     use {deserialize, deserialize_iterator, serialize, Error, Reader, Stream};
 
@@ -351,6 +377,15 @@ mod tests {
         assert_eq!(stream.out(), expected);
     }
 
+    #[test]
+    fn test_u256_serialize() {
+        let value = U256::from(0x0102030405060708u64) << 192;
+        let serialized = serialize(&value);
+        assert_eq!(serialized.len(), 32);
+        assert_eq!(&serialized[..8], &[1u8, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(value, deserialize(serialized.as_ref()).unwrap());
+    }
+
     #[test]
     fn test_bytes_deserialize() {
         let raw: Bytes = "020145".into();
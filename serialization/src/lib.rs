@@ -7,6 +7,7 @@ mod impls;
 mod list;
 mod reader;
 mod stream;
+mod tagged_integer;
 
 pub use primitives::{bytes, compact, hash};
 
@@ -14,3 +15,4 @@ pub use compact_integer::CompactInteger;
 pub use list::List;
 pub use reader::{deserialize, deserialize_iterator, Deserializable, Error, ReadIterator, Reader};
 pub use stream::{serialize, serialize_list, serialized_list_size, Serializable, Stream};
+pub use tagged_integer::TaggedInteger;
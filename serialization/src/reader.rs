@@ -131,6 +131,22 @@ where
         }
     }
 
+    /// Returns next byte without consuming it.
+    pub fn peek_u8(&mut self) -> Result<u8, Error> {
+        if let Some(peeked) = self.peeked {
+            return Ok(peeked);
+        }
+
+        let mut next_buffer = [0u8];
+        match self.buffer.read(&mut next_buffer)? {
+            0 => Err(Error::UnexpectedEnd),
+            _ => {
+                self.peeked = Some(next_buffer[0]);
+                Ok(next_buffer[0])
+            }
+        }
+    }
+
     pub fn read_slice(&mut self, bytes: &mut [u8]) -> Result<(), Error> {
         io::Read::read_exact(self, bytes).map_err(|_| Error::UnexpectedEnd)
     }
@@ -229,3 +245,27 @@ where
         Ok(len)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Reader};
+
+    #[test]
+    fn test_reader_peek_u8() {
+        let buffer: &[u8] = &[0x01, 0x02];
+        let mut reader = Reader::new(buffer);
+        assert_eq!(reader.peek_u8(), Ok(0x01));
+        assert_eq!(reader.peek_u8(), Ok(0x01));
+        assert_eq!(reader.read::<u8>(), Ok(0x01));
+        assert_eq!(reader.peek_u8(), Ok(0x02));
+        assert_eq!(reader.read::<u8>(), Ok(0x02));
+        assert!(reader.is_finished());
+    }
+
+    #[test]
+    fn test_reader_peek_u8_empty() {
+        let buffer: &[u8] = &[];
+        let mut reader = Reader::new(buffer);
+        assert_eq!(reader.peek_u8(), Err(Error::UnexpectedEnd));
+    }
+}
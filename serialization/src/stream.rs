@@ -47,6 +47,22 @@ pub trait Serializable {
         // fallback implementation
         serialize(self).len()
     }
+
+    /// Expected size of serialized struct, used to preallocate buffers.
+    fn serialized_size_hint(&self) -> usize
+    where
+        Self: Sized,
+    {
+        self.serialized_size()
+    }
+
+    /// Serialize the struct into a new vector. Prefer this over `serialize(&value)`.
+    fn serialize_to_vec(&self) -> Vec<u8>
+    where
+        Self: Sized,
+    {
+        serialize(self).take()
+    }
 }
 
 /// Stream used for serialization of RandChain structures
@@ -107,3 +123,32 @@ impl Write for Stream {
         self.buffer.flush()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{serialize, Serializable};
+    use bytes::Bytes;
+    use compact_integer::CompactInteger;
+
+    #[test]
+    fn test_serialize_to_vec() {
+        assert_eq!(1u32.serialize_to_vec(), serialize(&1u32).take());
+        assert_eq!(
+            CompactInteger::from(0x1_0000u32).serialize_to_vec(),
+            serialize(&CompactInteger::from(0x1_0000u32)).take()
+        );
+        let bytes: Bytes = "0102ff".into();
+        assert_eq!(bytes.serialize_to_vec(), vec![3, 1, 2, 0xff]);
+        assert_eq!(bytes.serialize_to_vec(), serialize(&bytes).take());
+    }
+
+    #[test]
+    fn test_serialized_size_hint() {
+        let bytes: Bytes = "0102ff".into();
+        assert_eq!(bytes.serialized_size_hint(), bytes.serialize_to_vec().len());
+        assert_eq!(
+            0xffffu16.serialized_size_hint(),
+            0xffffu16.serialize_to_vec().len()
+        );
+    }
+}
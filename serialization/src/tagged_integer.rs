@@ -0,0 +1,195 @@
+//! Non-negative big integer, serialized with a single tag byte in front.
+//!
+//! Values `0..=0xfc` are stored in the tag byte itself. Larger values are stored as
+//! `0xfd || u16 length || bytes` or `0xfe || u32 length || bytes` (big-endian digits),
+//! mirroring the `CompactInteger` encoding. Small values therefore take a single byte,
+//! while the default `Integer` encoding always takes a length prefix + digits.
+
+use rug::{integer::Order, Integer};
+use std::io;
+use {Deserializable, Error as ReaderError, Reader, Serializable, Stream};
+
+/// Largest value, which is stored directly in the tag byte
+const MAX_INLINE_VALUE: u8 = 0xfc;
+/// Maximal length of the integer digits (4 MB), so that malformed length can't
+/// trigger huge allocation. Integer never exceeds the size of the block it belongs to.
+const MAX_DIGITS_LEN: usize = 4 * 1024 * 1024;
+
+/// Non-negative integer with compact tagged encoding.
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct TaggedInteger(Integer);
+
+impl From<Integer> for TaggedInteger {
+    fn from(i: Integer) -> Self {
+        TaggedInteger(i)
+    }
+}
+
+impl From<TaggedInteger> for Integer {
+    fn from(i: TaggedInteger) -> Self {
+        i.0
+    }
+}
+
+impl AsRef<Integer> for TaggedInteger {
+    fn as_ref(&self) -> &Integer {
+        &self.0
+    }
+}
+
+impl Serializable for TaggedInteger {
+    fn serialize(&self, stream: &mut Stream) {
+        if let Some(value) = self.0.to_u8() {
+            if value <= MAX_INLINE_VALUE {
+                stream.append(&value);
+                return;
+            }
+        }
+
+        let digits = self.0.to_digits::<u8>(Order::Msf);
+        if digits.len() <= 0xffff {
+            stream.append(&0xfdu8).append(&(digits.len() as u16));
+        } else {
+            stream.append(&0xfeu8).append(&(digits.len() as u32));
+        }
+        stream.append_slice(&digits);
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self.0.to_u8() {
+            Some(value) if value <= MAX_INLINE_VALUE => 1,
+            _ => {
+                let len = self.0.significant_digits::<u8>();
+                if len <= 0xffff {
+                    3 + len
+                } else {
+                    5 + len
+                }
+            }
+        }
+    }
+}
+
+impl Deserializable for TaggedInteger {
+    fn deserialize<T>(reader: &mut Reader<T>) -> Result<Self, ReaderError>
+    where
+        T: io::Read,
+    {
+        let len = match reader.read::<u8>()? {
+            value @ 0..=MAX_INLINE_VALUE => return Ok(TaggedInteger(Integer::from(value))),
+            0xfd => reader.read::<u16>()? as usize,
+            0xfe => reader.read::<u32>()? as usize,
+            _ => return Err(ReaderError::MalformedData),
+        };
+        if len > MAX_DIGITS_LEN {
+            return Err(ReaderError::MalformedData);
+        }
+
+        let mut digits = vec![0u8; len];
+        reader.read_slice(&mut digits)?;
+        let value = Integer::from_digits(&digits, Order::Msf);
+
+        // only canonical encoding is accepted
+        if value <= MAX_INLINE_VALUE
+            || digits[0] == 0
+            || (len > 0xffff) != (value.significant_digits::<u8>() > 0xffff)
+        {
+            return Err(ReaderError::MalformedData);
+        }
+
+        Ok(TaggedInteger(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TaggedInteger, MAX_DIGITS_LEN};
+    use rug::Integer;
+    use {deserialize, serialize, Error as ReaderError, Serializable};
+
+    fn tagged(value: Integer) -> TaggedInteger {
+        value.into()
+    }
+
+    #[test]
+    fn test_tagged_integer_small_values() {
+        assert_eq!(serialize(&tagged(Integer::from(0))), vec![0x00].into());
+        assert_eq!(serialize(&tagged(Integer::from(8))), vec![0x08].into());
+        assert_eq!(serialize(&tagged(Integer::from(0xfc))), vec![0xfc].into());
+        assert_eq!(
+            serialize(&tagged(Integer::from(0xfd))),
+            vec![0xfd, 0x01, 0x00, 0xfd].into()
+        );
+        assert_eq!(
+            serialize(&tagged(Integer::from(0x1234))),
+            vec![0xfd, 0x02, 0x00, 0x12, 0x34].into()
+        );
+    }
+
+    #[test]
+    fn test_tagged_integer_roundtrip() {
+        let values = vec![
+            Integer::from(0),
+            Integer::from(0xfc),
+            Integer::from(0xfd),
+            Integer::from(0xff),
+            Integer::from(Integer::u_pow_u(3, 1290)),
+            (Integer::from(1) << 2048u32) - 1u32,
+        ];
+
+        for value in values {
+            let value = tagged(value);
+            let serialized = serialize(&value);
+            assert_eq!(serialized.len(), value.serialized_size());
+            assert_eq!(
+                deserialize::<_, TaggedInteger>(&serialized[..]).unwrap(),
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_tagged_integer_non_canonical() {
+        // small value with explicit length
+        let buffer: &[u8] = &[0xfd, 0x01, 0x00, 0x08];
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(buffer).unwrap_err(),
+            ReaderError::MalformedData
+        );
+        // leading zero byte
+        let buffer: &[u8] = &[0xfd, 0x02, 0x00, 0x00, 0xfd];
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(buffer).unwrap_err(),
+            ReaderError::MalformedData
+        );
+        // unknown tag
+        let buffer: &[u8] = &[0xff];
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(buffer).unwrap_err(),
+            ReaderError::MalformedData
+        );
+        // truncated digits
+        let buffer: &[u8] = &[0xfd, 0x02, 0x00, 0x12];
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(buffer).unwrap_err(),
+            ReaderError::UnexpectedEnd
+        );
+    }
+
+    #[test]
+    fn test_tagged_integer_too_long() {
+        // length is checked before digits are allocated and read
+        let buffer: &[u8] = &[0xfe, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(buffer).unwrap_err(),
+            ReaderError::MalformedData
+        );
+
+        let mut buffer = vec![0xfe];
+        buffer.extend_from_slice(&((MAX_DIGITS_LEN + 1) as u32).to_le_bytes());
+        assert_eq!(
+            deserialize::<_, TaggedInteger>(&buffer[..]).unwrap_err(),
+            ReaderError::MalformedData
+        );
+    }
+}
@@ -13,6 +13,18 @@ pub struct SideChainOrigin {
     pub block_number: u32,
 }
 
+impl SideChainOrigin {
+    /// Number of side chain blocks, which become canonized.
+    pub fn fork_depth(&self) -> u32 {
+        self.canonized_route.len() as u32
+    }
+
+    /// Number of canon chain blocks, which become decanonized.
+    pub fn reorg_length(&self) -> u32 {
+        self.decanonized_route.len() as u32
+    }
+}
+
 impl fmt::Debug for SideChainOrigin {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("SideChainOrigin")
@@ -38,10 +50,71 @@ impl fmt::Debug for SideChainOrigin {
     }
 }
 
-#[derive(Debug)]
 pub enum BlockOrigin {
     KnownBlock,
     CanonChain { block_number: u32 },
     SideChain(SideChainOrigin),
     SideChainBecomingCanonChain(SideChainOrigin),
 }
+
+impl fmt::Debug for BlockOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BlockOrigin::KnownBlock => f.write_str("KnownBlock"),
+            BlockOrigin::CanonChain { block_number } => f
+                .debug_struct("CanonChain")
+                .field("block_number", &block_number)
+                .finish(),
+            BlockOrigin::SideChain(ref origin) => f.debug_tuple("SideChain").field(origin).finish(),
+            // reorg summary is more useful in logs than the full routes
+            BlockOrigin::SideChainBecomingCanonChain(ref origin) => f
+                .debug_struct("SideChainBecomingCanonChain")
+                .field("ancestor", &origin.ancestor)
+                .field("fork_depth", &origin.fork_depth())
+                .field("reorg_length", &origin.reorg_length())
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockOrigin, SideChainOrigin};
+    use hash::H256;
+
+    fn side_chain_origin() -> SideChainOrigin {
+        SideChainOrigin {
+            ancestor: 5,
+            canonized_route: vec![H256::from(1), H256::from(2), H256::from(3)],
+            decanonized_route: vec![H256::from(4), H256::from(5)],
+            block_number: 8,
+        }
+    }
+
+    #[test]
+    fn side_chain_origin_route_lengths() {
+        let origin = side_chain_origin();
+        assert_eq!(origin.fork_depth(), 3);
+        assert_eq!(origin.reorg_length(), 2);
+    }
+
+    #[test]
+    fn block_origin_debug() {
+        assert_eq!(format!("{:?}", BlockOrigin::KnownBlock), "KnownBlock");
+        assert_eq!(
+            format!("{:?}", BlockOrigin::CanonChain { block_number: 7 }),
+            "CanonChain { block_number: 7 }"
+        );
+        assert_eq!(
+            format!("{:?}", BlockOrigin::SideChain(side_chain_origin())),
+            format!("SideChain({:?})", side_chain_origin())
+        );
+        assert_eq!(
+            format!(
+                "{:?}",
+                BlockOrigin::SideChainBecomingCanonChain(side_chain_origin())
+            ),
+            "SideChainBecomingCanonChain { ancestor: 5, fork_depth: 3, reorg_length: 2 }"
+        );
+    }
+}
@@ -0,0 +1,14 @@
+/// Statistics of the database blocks cache
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CacheStats {
+    /// Number of block reads, served from the cache
+    pub hits: u64,
+    /// Number of block reads, served from the underlying database
+    pub misses: u64,
+    /// Share of block reads, served from the cache
+    pub hit_rate: f64,
+    /// Number of blocks in the cache
+    pub entries: usize,
+    /// Max number of blocks in the cache
+    pub max_entries: usize,
+}
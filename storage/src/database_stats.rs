@@ -0,0 +1,10 @@
+/// Statistics of the on-disk database
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DatabaseStats {
+    /// Approximate size (in bytes) of every database column
+    pub column_sizes: Vec<u64>,
+    /// Is compaction in progress?
+    pub is_compacting: bool,
+    /// Unix timestamp (in seconds) of the last completed compaction, if any
+    pub last_compaction: Option<u64>,
+}
@@ -1,3 +1,5 @@
+use primitives::hash::H256;
+
 #[derive(Debug, PartialEq, Display)]
 pub enum Error {
     /// Low level database error
@@ -12,9 +14,36 @@ pub enum Error {
     /// Uknown parent
     #[display(fmt = "Block parent is unknown")]
     UnknownParent,
+    /// Block is already in the database
+    #[display(fmt = "Block {} is already known", _0)]
+    Duplicate(H256),
     /// Ancient fork
     #[display(fmt = "Fork is too long to proceed")]
     AncientFork,
+    /// Not enough blocks in the chain
+    #[display(
+        fmt = "Chain is too short: {} blocks required, {} available",
+        required,
+        actual
+    )]
+    ChainTooShort { required: u32, actual: u32 },
+    /// Cumulative work of the canon block is not in the database
+    #[display(fmt = "Chain work of block #{} is unknown", _0)]
+    UnknownChainWork(u32),
+}
+
+impl Error {
+    /// Is the database in a state, from which we can not proceed?
+    pub fn is_fatal(&self) -> bool {
+        match *self {
+            Error::DatabaseError(_) | Error::CannotCanonize | Error::CannotDecanonize => true,
+            Error::UnknownParent
+            | Error::Duplicate(_)
+            | Error::AncientFork
+            | Error::ChainTooShort { .. }
+            | Error::UnknownChainWork(_) => false,
+        }
+    }
 }
 
 impl From<Error> for String {
@@ -22,3 +51,24 @@ impl From<Error> for String {
         format!("{}", e)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    #[test]
+    fn test_error_is_fatal() {
+        assert!(Error::DatabaseError("io".into()).is_fatal());
+        assert!(Error::CannotCanonize.is_fatal());
+        assert!(Error::CannotDecanonize.is_fatal());
+        assert!(!Error::UnknownParent.is_fatal());
+        assert!(!Error::Duplicate(Default::default()).is_fatal());
+        assert!(!Error::AncientFork.is_fatal());
+        assert!(!Error::UnknownChainWork(1).is_fatal());
+        assert!(!Error::ChainTooShort {
+            required: 2,
+            actual: 1
+        }
+        .is_fatal());
+    }
+}
@@ -2,6 +2,7 @@ extern crate bit_vec;
 extern crate elastic_array;
 extern crate lru_cache;
 extern crate parking_lot;
+extern crate rug;
 #[macro_use]
 extern crate display_derive;
 
@@ -17,6 +18,8 @@ mod block_iterator;
 mod block_origin;
 mod block_provider;
 mod block_ref;
+mod cache_stats;
+mod database_stats;
 mod duplex_store;
 mod error;
 mod store;
@@ -30,6 +33,8 @@ pub use block_iterator::BlockIterator;
 pub use block_origin::{BlockOrigin, SideChainOrigin};
 pub use block_provider::{BlockHeaderProvider, BlockProvider};
 pub use block_ref::BlockRef;
+pub use cache_stats::CacheStats;
+pub use database_stats::DatabaseStats;
 pub use duplex_store::NoopStore;
 pub use error::Error;
 pub use store::{AsSubstore, CanonStore, ConfigStore, SharedStore, Store};
@@ -1,9 +1,34 @@
 use chain::IndexedBlockHeader;
+use primitives::bigint::U256;
+use rug::Integer;
 use std::sync::Arc;
-use {BestBlock, BlockChain, BlockHeaderProvider, BlockProvider, Forkable};
+use {
+    BestBlock, BlockChain, BlockHeaderProvider, BlockProvider, CacheStats, DatabaseStats, Error,
+    Forkable,
+};
 
 pub trait CanonStore: Store + Forkable + ConfigStore {
     fn as_store(&self) -> &dyn Store;
+
+    /// Compacts underlying database
+    fn compact(&self) -> Result<(), Error>;
+
+    /// Statistics of the blocks cache. None if database is not cached.
+    fn cache_stats(&self) -> Option<CacheStats>;
+
+    /// Statistics of the on-disk database. None if database is not stored on disk.
+    fn database_stats(&self) -> Option<DatabaseStats>;
+
+    /// Keeps all following changes of the calling thread in memory until `commit_batch` is called.
+    /// Fails if the database can not buffer changes.
+    fn begin_batch(&self) -> Result<(), Error>;
+
+    /// Writes all changes of the calling thread, made since `begin_batch`, to the database
+    /// in a single transaction.
+    fn commit_batch(&self) -> Result<(), Error>;
+
+    /// Drops all changes of the calling thread, made since `begin_batch`.
+    fn discard_batch(&self);
 }
 
 /// Configuration storage interface
@@ -23,6 +48,12 @@ pub trait Store: AsSubstore {
 
     /// get blockchain difficulty
     fn difficulty(&self) -> f64;
+
+    /// get randomness beacon value of the canon block at given height
+    fn randomness(&self, number: u32) -> Option<Integer>;
+
+    /// get cumulative work of the canon chain up to the block at given height
+    fn chain_work(&self, number: u32) -> Option<U256>;
 }
 
 /// Allows casting Arc<Store> to reference to any substore type
@@ -1,4 +1,4 @@
-use super::Error;
+use super::{Error, RecoveryAction};
 use chain;
 use network::Network;
 use parking_lot::Mutex;
@@ -27,6 +27,10 @@ pub struct BlocksWriter {
     verifier: SyncVerifier<BlocksWriterSink>,
     /// Verification events receiver
     sink: Arc<Mutex<BlocksWriterSinkData>>,
+    /// Max number of blocks, written to the database in a single transaction
+    batch_size: usize,
+    /// Number of blocks in the currently open batch, if any
+    batch: Option<usize>,
 }
 
 /// Verification events receiver
@@ -58,6 +62,22 @@ impl BlocksWriter {
             orphaned_blocks_pool: OrphanBlocksPool::new(),
             verifier: verifier,
             sink: sink_data,
+            batch_size: 1,
+            batch: None,
+        }
+    }
+
+    /// Set max number of verified blocks, which are kept in memory and then written to the
+    /// database in a single transaction. Call `flush` after the last block is appended.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size;
+    }
+
+    /// Write all blocks of the currently open batch to the database
+    pub fn flush(&mut self) -> Result<(), Error> {
+        match self.batch.take() {
+            Some(_) => self.storage.commit_batch().map_err(Error::Database),
+            None => Ok(()),
         }
     }
 
@@ -75,9 +95,9 @@ impl BlocksWriter {
         if !self.storage.contains_block(storage::BlockRef::Hash(
             block.header.raw.previous_header_hash.clone(),
         )) {
-            self.orphaned_blocks_pool.insert_orphaned_block(block);
+            let evicted = self.orphaned_blocks_pool.insert_orphaned_block(None, block);
             // we can't hold many orphaned blocks in memory during import
-            if self.orphaned_blocks_pool.len() > MAX_ORPHANED_BLOCKS {
+            if !evicted.is_empty() || self.orphaned_blocks_pool.len() > MAX_ORPHANED_BLOCKS {
                 return Err(Error::TooManyOrphanBlocks);
             }
             return Ok(());
@@ -89,14 +109,125 @@ impl BlocksWriter {
             .remove_blocks_for_parent(block.hash());
         verification_queue.push_front(block);
         while let Some(block) = verification_queue.pop_front() {
+            let block_hash = block.hash().clone();
+            self.begin_batch();
             self.verifier.verify_block(block);
-            if let Some(err) = self.sink.lock().error() {
-                return Err(err);
+            let err = self.sink.lock().error();
+            match err {
+                Some(err) => match err.suggested_action() {
+                    RecoveryAction::SkipBlock => {
+                        warn!(target: "sync", "Skipping block {}: {:?}", block_hash.to_reversed_str(), err);
+                    }
+                    _ => return Err(err),
+                },
+                None => self.block_written()?,
             }
         }
 
         Ok(())
     }
+
+    /// Open new batch if batching is enabled and there's no open batch yet
+    fn begin_batch(&mut self) {
+        // blocks are written one by one if database does not support batches
+        if self.batch_size > 1 && self.batch.is_none() && self.storage.begin_batch().is_ok() {
+            self.batch = Some(0);
+        }
+    }
+
+    /// Count block, written to the open batch, and commit batch if it is full
+    fn block_written(&mut self) -> Result<(), Error> {
+        let batch_len = match self.batch.as_mut() {
+            Some(batch_len) => {
+                *batch_len += 1;
+                *batch_len
+            }
+            None => return Ok(()),
+        };
+
+        if batch_len >= self.batch_size {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Append batch of blocks, where every block is a child of the previous one.
+    /// Batch is written to the database in a single transaction: either all blocks are stored,
+    /// or the whole batch is discarded. Nothing is written if database does not support batches.
+    pub fn write_batch(&mut self, blocks: Vec<chain::IndexedBlock>) -> Vec<Result<(), Error>> {
+        // blocks of the already open batch must not be discarded together with this batch
+        if let Err(err) = self.flush() {
+            return batch_failure(blocks.len(), 0, err);
+        }
+
+        if let Err(err) = self.storage.begin_batch() {
+            return batch_failure(blocks.len(), 0, Error::Database(err));
+        }
+        let mut failure = None;
+        for (index, block) in blocks.iter().enumerate() {
+            // do not append block if it is already there
+            if self
+                .storage
+                .contains_block(storage::BlockRef::Hash(block.hash().clone()))
+            {
+                continue;
+            }
+
+            // batch blocks are never orphaned
+            if !self.storage.contains_block(storage::BlockRef::Hash(
+                block.header.raw.previous_header_hash.clone(),
+            )) {
+                failure = Some((index, Error::Database(storage::Error::UnknownParent)));
+                break;
+            }
+
+            self.verifier.verify_block(block.clone());
+            if let Some(err) = self.sink.lock().error() {
+                failure = Some((index, err));
+                break;
+            }
+        }
+
+        let (failed_index, err) = match failure {
+            Some(failure) => failure,
+            None => {
+                return match self.storage.commit_batch() {
+                    Ok(()) => blocks.iter().map(|_| Ok(())).collect(),
+                    Err(err) => batch_failure(blocks.len(), 0, Error::Database(err)),
+                };
+            }
+        };
+
+        self.storage.discard_batch();
+        // cached best block of the chain is outdated now
+        self.sink.lock().chain = Chain::new(self.storage.clone());
+        batch_failure(blocks.len(), failed_index, err)
+    }
+}
+
+/// Results of discarded batch of `len` blocks, where block at `failed_index` has failed
+fn batch_failure(len: usize, failed_index: usize, err: Error) -> Vec<Result<(), Error>> {
+    let mut err = Some(err);
+    (0..len)
+        .map(|index| {
+            if index == failed_index {
+                Err(err.take().expect("failed_index is unique; qed"))
+            } else {
+                Err(Error::Verification(format!(
+                    "Blocks batch is discarded because of block #{}",
+                    failed_index
+                )))
+            }
+        })
+        .collect()
+}
+
+impl Drop for BlocksWriter {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!(target: "sync", "Failed to write blocks batch: {:?}", err);
+        }
+    }
 }
 
 impl BlocksWriterSink {
@@ -129,8 +260,12 @@ impl BlockVerificationSink for BlocksWriterSink {
         block: chain::IndexedBlock,
     ) -> Option<Vec<VerificationTask>> {
         let mut data = self.data.lock();
-        if let Err(err) = data.chain.insert_best_block(block) {
-            data.err = Some(Error::Database(err));
+        match data.chain.insert_best_block(block) {
+            Ok(_) => (),
+            Err(storage::Error::Duplicate(hash)) => {
+                info!(target: "sync", "Block {} is already in the storage", hash.to_reversed_str());
+            }
+            Err(err) => data.err = Some(Error::Database(err)),
         }
 
         None
@@ -145,11 +280,13 @@ impl BlockVerificationSink for BlocksWriterSink {
 mod tests {
     extern crate test_data;
 
-    use super::super::Error;
+    use super::super::{Error, RecoveryAction};
     use super::{BlocksWriter, MAX_ORPHANED_BLOCKS};
+    use db::kv::{Key, KeyValueDatabase, SharedMemoryDatabase};
     use db::BlockChainDatabase;
     use network::Network;
     use std::sync::Arc;
+    use storage::{self, BlockProvider};
     use verification::VerificationLevel;
     use VerificationParameters;
 
@@ -157,6 +294,7 @@ mod tests {
         VerificationParameters {
             verification_level: VerificationLevel::Full,
             verification_edge: 0u8.into(),
+            verification_threads: 0,
         }
     }
 
@@ -262,10 +400,144 @@ mod tests {
             VerificationParameters {
                 verification_level: VerificationLevel::NoVerification,
                 verification_edge: 0u8.into(),
+                verification_threads: 0,
             },
         );
         assert_eq!(blocks_target.append_block(b1.into()), Ok(()));
         assert_eq!(blocks_target.append_block(b2.into()), Ok(()));
         assert_eq!(blocks_target.append_block(b3.into()), Ok(()));
     }
+
+    #[test]
+    fn blocks_writer_writes_batch() {
+        let db = Arc::new(BlockChainDatabase::open_with_cache(
+            SharedMemoryDatabase::default(),
+        ));
+        db.insert(test_data::genesis().into()).unwrap();
+        db.canonize(&test_data::genesis().hash()).unwrap();
+        let mut blocks_target = BlocksWriter::new(
+            db.clone(),
+            Network::Testnet,
+            VerificationParameters {
+                verification_level: VerificationLevel::NoVerification,
+                verification_edge: 0u8.into(),
+                verification_threads: 0,
+            },
+        );
+
+        let blocks = test_data::build_n_empty_blocks_from_genesis(10, 1);
+        let results = blocks_target.write_batch(blocks.iter().cloned().map(Into::into).collect());
+        assert_eq!(results.len(), 10);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(db.best_block().number, 10);
+        for (number, block) in blocks.iter().enumerate() {
+            assert_eq!(db.block_hash(number as u32 + 1), Some(block.hash()));
+        }
+    }
+
+    #[test]
+    fn blocks_writer_discards_failed_batch() {
+        let kv = SharedMemoryDatabase::default();
+        let db = Arc::new(BlockChainDatabase::open_with_cache(kv.clone()));
+        db.insert(test_data::genesis().into()).unwrap();
+        db.canonize(&test_data::genesis().hash()).unwrap();
+        let mut blocks_target = BlocksWriter::new(
+            db.clone(),
+            Network::Testnet,
+            VerificationParameters {
+                verification_level: VerificationLevel::NoVerification,
+                verification_edge: 0u8.into(),
+                verification_threads: 0,
+            },
+        );
+
+        let mut blocks = test_data::build_n_empty_blocks_from_genesis(10, 1);
+        // block with unknown parent in the middle of the batch
+        blocks[5] = test_data::block_builder()
+            .header()
+            .parent(42.into())
+            .build()
+            .build();
+        let results = blocks_target.write_batch(blocks.iter().cloned().map(Into::into).collect());
+        assert_eq!(results.len(), 10);
+        assert_eq!(
+            results[5],
+            Err(Error::Database(storage::Error::UnknownParent))
+        );
+        assert!(results.iter().all(Result::is_err));
+        assert_eq!(db.best_block().number, 0);
+        for block in &blocks {
+            assert!(!db.contains_block(block.hash().into()));
+            assert!(kv
+                .get(&Key::Block(block.hash()))
+                .unwrap()
+                .into_option()
+                .is_none());
+        }
+
+        // writer is still usable after discarded batch
+        blocks_target
+            .append_block(blocks[0].clone().into())
+            .expect("Expecting no error");
+        assert_eq!(db.best_block().number, 1);
+    }
+
+    #[test]
+    fn blocks_writer_writes_blocks_in_batches() {
+        let kv = SharedMemoryDatabase::default();
+        let db = Arc::new(BlockChainDatabase::open_with_cache(kv.clone()));
+        db.insert(test_data::genesis().into()).unwrap();
+        db.canonize(&test_data::genesis().hash()).unwrap();
+
+        let mut blocks_target = BlocksWriter::new(
+            db.clone(),
+            Network::Testnet,
+            VerificationParameters {
+                verification_level: VerificationLevel::NoVerification,
+                verification_edge: 0u8.into(),
+                verification_threads: 0,
+            },
+        );
+        blocks_target.set_batch_size(100);
+
+        let blocks = test_data::build_n_empty_blocks_from_genesis(150, 1);
+        for block in blocks.iter().cloned() {
+            blocks_target.append_block(block.into()).unwrap();
+        }
+        assert_eq!(db.best_block().number, 150);
+
+        // first batch is written, second is still in memory
+        let is_written = |number| {
+            kv.get(&Key::BlockHash(number))
+                .unwrap()
+                .into_option()
+                .is_some()
+        };
+        assert!(is_written(100));
+        assert!(!is_written(101));
+
+        blocks_target.flush().unwrap();
+        assert!(is_written(150));
+    }
+
+    #[test]
+    fn sync_error_suggested_action() {
+        assert_eq!(
+            Error::TooManyOrphanBlocks.suggested_action(),
+            RecoveryAction::Retry
+        );
+        assert!(Error::TooManyOrphanBlocks.is_recoverable());
+
+        let err = Error::Database(storage::Error::AncientFork);
+        assert_eq!(err.suggested_action(), RecoveryAction::SkipBlock);
+        assert!(err.is_recoverable());
+
+        let err = Error::Database(storage::Error::CannotCanonize);
+        assert_eq!(err.suggested_action(), RecoveryAction::Shutdown);
+        assert!(!err.is_recoverable());
+
+        let err = Error::Verification("invalid proof".into());
+        assert_eq!(err.suggested_action(), RecoveryAction::Disconnect);
+        assert!(!err.is_recoverable());
+    }
 }
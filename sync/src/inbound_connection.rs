@@ -97,10 +97,6 @@ impl InboundSyncConnection for InboundConnection {
     }
 
     fn on_headers(&self, message: types::Headers) {
-        // if headers are empty - just ignore this message
-        if message.headers.is_empty() {
-            return;
-        }
         // if there are too many headers => possible DOS
         if message.headers.len() > types::HEADERS_MAX_HEADERS_LEN {
             self.peers.dos(
@@ -213,5 +209,6 @@ pub mod tests {
         }
         fn ignored(&self, _id: RequestId) {}
         fn close(&self) {}
+        fn ban(&self) {}
     }
 }
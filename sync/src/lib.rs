@@ -7,8 +7,10 @@ extern crate storage;
 extern crate log;
 extern crate bit_vec;
 extern crate futures;
+extern crate heapsize;
 extern crate linked_hash_map;
 extern crate message;
+extern crate metrics;
 extern crate miner;
 extern crate murmur3;
 extern crate network;
@@ -36,11 +38,15 @@ mod synchronization_verifier;
 mod types;
 mod utils;
 
+pub use synchronization_chain::ReorgInfo;
+pub use synchronization_client_core::SyncMetrics;
 pub use types::LocalNodeRef;
 pub use types::PeersRef;
+pub use utils::DEFAULT_ORPHANS_MEMORY_LIMIT;
 
 use network::Network;
 use primitives::hash::H256;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
 
@@ -55,7 +61,42 @@ pub enum Error {
     Verification(String),
 }
 
-#[derive(Debug)]
+/// What to do after sync error has occured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryAction {
+    /// Try again later.
+    Retry,
+    /// Skip this block and continue with others.
+    SkipBlock,
+    /// Disconnect from the peer, which has provided the block.
+    Disconnect,
+    /// Stop the node.
+    Shutdown,
+}
+
+impl Error {
+    /// Is it possible to continue after this error?
+    pub fn is_recoverable(&self) -> bool {
+        match *self {
+            Error::TooManyOrphanBlocks => true,
+            Error::Database(ref err) => !err.is_fatal(),
+            Error::Verification(_) => false,
+        }
+    }
+
+    /// Suggested way to recover from this error.
+    pub fn suggested_action(&self) -> RecoveryAction {
+        match *self {
+            // will be resolved when parent blocks arrive
+            Error::TooManyOrphanBlocks => RecoveryAction::Retry,
+            Error::Database(ref err) if err.is_fatal() => RecoveryAction::Shutdown,
+            Error::Database(_) => RecoveryAction::SkipBlock,
+            Error::Verification(_) => RecoveryAction::Disconnect,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 /// Verification parameters.
 pub struct VerificationParameters {
     /// Blocks verification level.
@@ -63,6 +104,86 @@ pub struct VerificationParameters {
     /// Blocks verification edge: all blocks before this are validated using verification_level.
     /// All blocks after this (inclusive) are validated using VerificationLevel::Full level.
     pub verification_edge: H256,
+    /// Number of threads, verifying blocks during synchronization.
+    /// 0 means that blocks are verified on the global rayon thread pool.
+    pub verification_threads: usize,
+}
+
+impl VerificationParameters {
+    /// Fully verify all blocks, starting from genesis.
+    pub fn full_from_genesis() -> Self {
+        VerificationParameters {
+            verification_level: verification::VerificationLevel::Full,
+            verification_edge: H256::from(0),
+            verification_threads: 0,
+        }
+    }
+
+    /// Do not verify blocks at all. Edge is never reached.
+    pub fn no_verification() -> Self {
+        VerificationParameters {
+            verification_level: verification::VerificationLevel::NoVerification,
+            verification_edge: H256::from([0xff; 32]),
+            verification_threads: 0,
+        }
+    }
+
+    /// Create builder for custom verification parameters.
+    pub fn builder() -> VerificationParametersBuilder {
+        VerificationParametersBuilder::default()
+    }
+}
+
+/// Verification parameters builder.
+#[derive(Debug)]
+pub struct VerificationParametersBuilder {
+    verification_level: verification::VerificationLevel,
+    verification_edge: H256,
+    verification_threads: usize,
+}
+
+impl Default for VerificationParametersBuilder {
+    fn default() -> Self {
+        let VerificationParameters {
+            verification_level,
+            verification_edge,
+            verification_threads,
+        } = VerificationParameters::full_from_genesis();
+        VerificationParametersBuilder {
+            verification_level: verification_level,
+            verification_edge: verification_edge,
+            verification_threads: verification_threads,
+        }
+    }
+}
+
+impl VerificationParametersBuilder {
+    /// Set blocks verification level.
+    pub fn verification_level(mut self, level: verification::VerificationLevel) -> Self {
+        self.verification_level = level;
+        self
+    }
+
+    /// Set blocks verification edge.
+    pub fn verification_edge(mut self, edge: H256) -> Self {
+        self.verification_edge = edge;
+        self
+    }
+
+    /// Set number of blocks verification threads.
+    pub fn verification_threads(mut self, threads: usize) -> Self {
+        self.verification_threads = threads;
+        self
+    }
+
+    /// Build verification parameters.
+    pub fn build(self) -> VerificationParameters {
+        VerificationParameters {
+            verification_level: self.verification_level,
+            verification_edge: self.verification_edge,
+            verification_threads: self.verification_threads,
+        }
+    }
 }
 
 /// Synchronization events listener
@@ -71,6 +192,58 @@ pub trait SyncListener: Send + 'static {
     fn synchronization_state_switched(&self, is_synchronizing: bool);
     /// Called when new best storage block is inserted
     fn best_storage_block_inserted(&self, block_hash: &H256);
+    /// Called when best chain is switched to the side chain
+    fn on_chain_reorganized(&self, _reorg: &ReorgInfo) {}
+    /// Called when synchronization session with peer is started
+    fn on_peer_connected(&self, _peer_index: types::PeerIndex, _addr: String) {}
+    /// Called when synchronization session with peer is stopped
+    fn on_peer_disconnected(&self, _peer_index: types::PeerIndex) {}
+}
+
+/// Synchronization events listener, which forwards all events to multiple listeners
+pub struct MultiSyncListener {
+    listeners: Vec<types::SyncListenerRef>,
+}
+
+impl MultiSyncListener {
+    /// Create new listener, forwarding events to all given listeners
+    pub fn new(listeners: Vec<types::SyncListenerRef>) -> Self {
+        MultiSyncListener {
+            listeners: listeners,
+        }
+    }
+}
+
+impl SyncListener for MultiSyncListener {
+    fn synchronization_state_switched(&self, is_synchronizing: bool) {
+        for listener in &self.listeners {
+            listener.synchronization_state_switched(is_synchronizing);
+        }
+    }
+
+    fn best_storage_block_inserted(&self, block_hash: &H256) {
+        for listener in &self.listeners {
+            listener.best_storage_block_inserted(block_hash);
+        }
+    }
+
+    fn on_chain_reorganized(&self, reorg: &ReorgInfo) {
+        for listener in &self.listeners {
+            listener.on_chain_reorganized(reorg);
+        }
+    }
+
+    fn on_peer_connected(&self, peer_index: types::PeerIndex, addr: String) {
+        for listener in &self.listeners {
+            listener.on_peer_connected(peer_index, addr.clone());
+        }
+    }
+
+    fn on_peer_disconnected(&self, peer_index: types::PeerIndex) {
+        for listener in &self.listeners {
+            listener.on_peer_disconnected(peer_index);
+        }
+    }
 }
 
 /// Create blocks writer.
@@ -89,12 +262,21 @@ pub fn create_sync_peers() -> PeersRef {
     Arc::new(PeersImpl::default())
 }
 
+/// Create synchronization peers with addresses of seed peers, which are connected on startup
+pub fn create_sync_peers_with_seeds(seeds: Vec<SocketAddr>) -> PeersRef {
+    use synchronization_peers::PeersImpl;
+
+    Arc::new(PeersImpl::with_seeds(seeds))
+}
+
 /// Creates local sync node for given `db`
 pub fn create_local_sync_node(
     network: Network,
     db: storage::SharedStore,
     peers: PeersRef,
     verification_params: VerificationParameters,
+    orphans_memory_limit: usize,
+    relay: bool,
 ) -> LocalNodeRef {
     use local_node::LocalNode as SyncNode;
     use synchronization_chain::Chain as SyncChain;
@@ -104,13 +286,15 @@ pub fn create_local_sync_node(
     };
     use synchronization_executor::LocalSynchronizationTaskExecutor as SyncExecutor;
     use synchronization_server::ServerImpl;
-    use synchronization_verifier::AsyncVerifier;
+    use synchronization_verifier::{AsyncHeadersVerifier, AsyncVerifier};
     use types::SynchronizationStateRef;
     use utils::SynchronizationState;
 
     let sync_client_config = SynchronizationConfig {
         // during regtests, peer is providing us with bad blocks => we shouldn't close connection because of this
         close_connection_on_bad_block: network != Network::Regtest,
+        orphans_memory_limit: orphans_memory_limit,
+        relay: relay,
     };
 
     let sync_state = SynchronizationStateRef::new(SynchronizationState::with_storage(db.clone()));
@@ -129,16 +313,16 @@ pub fn create_local_sync_node(
         peers.clone(),
         sync_executor.clone(),
         sync_chain,
-        chain_verifier.clone(),
     );
     let verifier_sink = Arc::new(CoreVerificationSink::new(sync_client_core.clone()));
+    let headers_verifier = AsyncHeadersVerifier::new(chain_verifier.clone(), verifier_sink.clone());
     let verifier = AsyncVerifier::new(
         chain_verifier,
         db.clone(),
         verifier_sink,
         verification_params,
     );
-    let sync_client = SynchronizationClient::new(sync_client_core, verifier);
+    let sync_client = SynchronizationClient::new(sync_client_core, verifier, headers_verifier);
     Arc::new(SyncNode::new(
         network,
         db,
@@ -146,6 +330,7 @@ pub fn create_local_sync_node(
         sync_state,
         sync_client,
         sync_server,
+        sync_executor,
     ))
 }
 
@@ -1,18 +1,27 @@
 use chain::{IndexedBlock, IndexedBlockHeader};
-use futures::{finished, lazy};
+use futures::Future;
+use message::common::InventoryVector;
 use message::types;
 use miner::BlockAssembler;
 use miner::BlockTemplate;
+use miner::MinerProgress;
+use miner::{CandidateError, CandidatePool};
 use network::Network;
+use parking_lot::Mutex;
+use primitives::hash::H256;
 use std::sync::Arc;
+use storage;
 use synchronization_client::Client;
+use synchronization_client_core::SyncMetrics;
+use synchronization_executor::{Task, TaskExecutor};
 use synchronization_peers::{BlockAnnouncementType, TransactionAnnouncementType};
 use synchronization_server::{Server, ServerTask};
 use time;
 use types::{
-    ClientRef, PeerIndex, PeersRef, RequestId, ServerRef, StorageRef, SyncListenerRef,
-    SynchronizationStateRef,
+    ClientRef, EmptyBoxFuture, PeerIndex, PeersRef, RequestId, ServerRef, StorageRef,
+    SyncListenerRef, SynchronizationStateRef,
 };
+use utils::notify_executor;
 
 /// Local synchronization node
 pub struct LocalNode<U: Server, V: Client> {
@@ -28,6 +37,12 @@ pub struct LocalNode<U: Server, V: Client> {
     client: ClientRef<V>,
     /// Synchronization server
     server: ServerRef<U>,
+    /// Synchronization tasks executor
+    executor: Arc<dyn TaskExecutor>,
+    /// Solved blocks, built on top of the best block
+    candidates: Mutex<CandidatePool>,
+    /// Progress of the local miner
+    miner_progress: Arc<MinerProgress>,
 }
 
 impl<U, V> LocalNode<U, V>
@@ -44,6 +59,7 @@ where
         state: SynchronizationStateRef,
         client: ClientRef<V>,
         server: ServerRef<U>,
+        executor: Arc<dyn TaskExecutor>,
     ) -> Self {
         LocalNode {
             network: network,
@@ -52,6 +68,9 @@ where
             state: state,
             client: client,
             server: server,
+            executor: executor,
+            candidates: Mutex::new(CandidatePool::default()),
+            miner_progress: Arc::new(MinerProgress::default()),
         }
     }
 
@@ -64,16 +83,17 @@ where
     pub fn on_connect(&self, peer_index: PeerIndex, peer_name: String, version: types::Version) {
         trace!(target: "sync", "Starting new sync session with peer#{}: {}", peer_index, peer_name);
 
-        // light clients may not want transactions broadcasting until filter for connection is set
+        // low-bandwidth peers may opt out of announcements and poll us for new headers instead
         if !version.relay_transactions() {
             self.peers.set_transaction_announcement_type(
                 peer_index,
                 TransactionAnnouncementType::DoNotAnnounce,
             );
+            self.peers.set_relay(peer_index, false);
         }
 
         // start synchronization session with peer
-        self.client.on_connect(peer_index);
+        self.client.on_connect(peer_index, peer_name);
     }
 
     /// When peer disconnects
@@ -128,15 +148,21 @@ where
 
         // simulating randchaind for passing tests: if we are in nearly-saturated state
         // and peer, which has just provided a new blocks to us, is asking for headers
-        // => do not serve getheaders until we have fully process his blocks + wait until headers are served before returning
+        // => do not serve getheaders until we have fully process his blocks
         let server = Arc::downgrade(&self.server);
         let server_task = ServerTask::GetHeaders(peer_index, message, id);
-        let lazy_server_task = lazy(move || {
-            server.upgrade().map(|s| s.execute(server_task));
-            finished::<(), ()>(())
+        let deferred_server_task = self.peer_blocks_verified(peer_index).map(move |_| {
+            if let Some(server) = server.upgrade() {
+                server.execute(server_task);
+            }
         });
-        self.client
-            .after_peer_nearly_blocks_verified(peer_index, Box::new(lazy_server_task));
+        notify_executor::spawn(Box::new(deferred_server_task));
+    }
+
+    /// Future, which resolves when all blocks, which have been provided by the peer
+    /// while we are in nearly-saturated state, are verified.
+    pub fn peer_blocks_verified(&self, peer_index: PeerIndex) -> EmptyBoxFuture {
+        self.client.after_peer_nearly_blocks_verified(peer_index)
     }
 
     /// When peer asks us to announce new blocks using headers message
@@ -147,15 +173,62 @@ where
     }
 
     /// Get block template for mining
-    pub fn get_block_template(&self) -> BlockTemplate {
+    pub fn get_block_template(&self) -> Result<BlockTemplate, storage::Error> {
         let block_assembler = BlockAssembler {};
         block_assembler.create_new_block(&self.storage, &self.network)
     }
 
+    /// Remember verified solved block in the candidates pool.
+    pub fn submit_candidate(&self, block: IndexedBlock) -> Result<(), CandidateError> {
+        let mut candidates = self.candidates.lock();
+        candidates.remove_stale(&self.storage.best_block().hash);
+        candidates.insert(block)
+    }
+
+    /// Get best pooled candidate, which solves current block template
+    pub fn best_candidate(&self) -> Result<Option<IndexedBlock>, storage::Error> {
+        let block_template = self.get_block_template()?;
+        let block_assembler = BlockAssembler {};
+        Ok(block_assembler.candidate_for(&self.candidates.lock(), &block_template))
+    }
+
+    /// Get all candidates, built on top of the best block
+    pub fn candidates(&self) -> Vec<IndexedBlock> {
+        let mut candidates = self.candidates.lock();
+        candidates.remove_stale(&self.storage.best_block().hash);
+        candidates.candidates().to_vec()
+    }
+
+    /// Get progress of the local miner
+    pub fn miner_progress(&self) -> Arc<MinerProgress> {
+        self.miner_progress.clone()
+    }
+
+    /// Announce new block to all connected peers, respecting their announcement preferences
+    pub fn broadcast_block(&self, block: IndexedBlock) {
+        trace!(target: "sync", "Broadcasting block {}", block.hash().to_reversed_str());
+        self.executor.execute(Task::RelayNewBlock(block));
+    }
+
+    /// Announce new block hash to all connected peers using `inventory` message only.
+    /// Peers, which do not know the block yet, will request it with `getdata`.
+    pub fn broadcast_inv(&self, hash: H256) {
+        trace!(target: "sync", "Broadcasting block inventory {}", hash.to_reversed_str());
+        for peer_index in self.peers.enumerate() {
+            let inventory = types::Inv::with_inventory(vec![InventoryVector::block(hash.clone())]);
+            self.executor.execute(Task::Inventory(peer_index, inventory));
+        }
+    }
+
     /// Install synchronization events listener
     pub fn install_sync_listener(&self, listener: SyncListenerRef) {
         self.client.install_sync_listener(listener);
     }
+
+    /// Get snapshot of synchronization metrics
+    pub fn metrics(&self) -> SyncMetrics {
+        self.client.metrics()
+    }
 }
 
 #[cfg(test)]
@@ -163,21 +236,26 @@ pub mod tests {
     extern crate test_data;
 
     use super::LocalNode;
+    use chain::IndexedBlock;
     use db::BlockChainDatabase;
+    use inbound_connection::tests::DummyOutboundSyncConnection;
     use message::common::{InventoryType, InventoryVector};
     use message::types;
+    use message::Services;
+    use miner::CandidateError;
     use network::Network;
     use std::sync::Arc;
     use synchronization_chain::Chain;
     use synchronization_client::SynchronizationClient;
     use synchronization_client_core::{Config, CoreVerificationSink, SynchronizationClientCore};
     use synchronization_executor::tests::DummyTaskExecutor;
+    use synchronization_executor::Task;
     use synchronization_peers::PeersImpl;
     use synchronization_server::tests::DummyServer;
     use synchronization_server::ServerTask;
-    use synchronization_verifier::tests::DummyVerifier;
+    use synchronization_verifier::tests::{DummyHeadersVerifier, DummyVerifier};
     use types::SynchronizationStateRef;
-    use utils::SynchronizationState;
+    use utils::{SynchronizationState, DEFAULT_ORPHANS_MEMORY_LIMIT};
     use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
 
     fn create_local_node(
@@ -185,7 +263,10 @@ pub mod tests {
     ) -> (
         Arc<DummyTaskExecutor>,
         Arc<DummyServer>,
-        LocalNode<DummyServer, SynchronizationClient<DummyTaskExecutor, DummyVerifier>>,
+        LocalNode<
+            DummyServer,
+            SynchronizationClient<DummyTaskExecutor, DummyVerifier, DummyHeadersVerifier>,
+        >,
     ) {
         let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
             test_data::genesis().into(),
@@ -198,6 +279,8 @@ pub mod tests {
         let server = Arc::new(DummyServer::new());
         let config = Config {
             close_connection_on_bad_block: true,
+            orphans_memory_limit: DEFAULT_ORPHANS_MEMORY_LIMIT,
+            relay: true,
         };
         let chain_verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Mainnet));
         let client_core = SynchronizationClientCore::new(
@@ -206,14 +289,17 @@ pub mod tests {
             sync_peers.clone(),
             executor.clone(),
             chain,
-            chain_verifier,
         );
+        let sink = Arc::new(CoreVerificationSink::new(client_core.clone()));
+        let mut headers_verifier = DummyHeadersVerifier::default();
+        headers_verifier.set_sink(sink.clone());
+        headers_verifier.set_verifier(chain_verifier);
         let mut verifier = match verifier {
             Some(verifier) => verifier,
             None => DummyVerifier::default(),
         };
-        verifier.set_sink(Arc::new(CoreVerificationSink::new(client_core.clone())));
-        let client = SynchronizationClient::new(client_core, verifier);
+        verifier.set_sink(sink);
+        let client = SynchronizationClient::new(client_core, verifier, headers_verifier);
         let local_node = LocalNode::new(
             Network::Mainnet,
             storage,
@@ -221,6 +307,7 @@ pub mod tests {
             sync_state,
             client,
             server.clone(),
+            executor.clone(),
         );
         (executor, server, local_node)
     }
@@ -252,4 +339,69 @@ pub mod tests {
             )]
         );
     }
+
+    #[test]
+    fn local_node_broadcasts_block() {
+        let (executor, _, local_node) = create_local_node(None);
+        for peer_index in 1..4 {
+            local_node.peers.insert(
+                peer_index,
+                Services::default(),
+                DummyOutboundSyncConnection::new(),
+            );
+        }
+
+        let block = test_data::block_h1();
+        local_node.broadcast_block(block.clone().into());
+        assert_eq!(
+            executor.take_tasks(),
+            vec![Task::RelayNewBlock(block.clone().into())]
+        );
+
+        local_node.broadcast_inv(block.hash());
+        let tasks = executor.take_tasks();
+        assert_eq!(tasks.len(), 3);
+        for peer_index in 1..4 {
+            assert!(tasks.contains(&Task::Inventory(
+                peer_index,
+                types::Inv::with_inventory(vec![InventoryVector::block(block.hash())])
+            )));
+        }
+    }
+
+    #[test]
+    fn local_node_remembers_sendheaders() {
+        let (_, _, local_node) = create_local_node(None);
+        for peer_index in 1..3 {
+            local_node.peers.insert(
+                peer_index,
+                Services::default(),
+                DummyOutboundSyncConnection::new(),
+            );
+        }
+
+        local_node.on_sendheaders(2, types::SendHeaders);
+        assert!(!local_node.peers.supports_send_headers(1));
+        assert!(local_node.peers.supports_send_headers(2));
+    }
+
+    #[test]
+    fn local_node_pools_candidates() {
+        let (_, _, local_node) = create_local_node(None);
+        let block1: IndexedBlock = test_data::block_h1().into();
+        assert_eq!(local_node.submit_candidate(block1.clone()), Ok(()));
+        assert_eq!(
+            local_node.submit_candidate(block1.clone()),
+            Err(CandidateError::Duplicate)
+        );
+        assert_eq!(local_node.candidates(), vec![block1.clone()]);
+        assert_eq!(local_node.best_candidate(), Ok(Some(block1)));
+
+        // candidates, which are not built on top of the best block, are stale
+        assert_eq!(
+            local_node.submit_candidate(test_data::block_h2().into()),
+            Ok(())
+        );
+        assert_eq!(local_node.candidates().len(), 1);
+    }
 }
@@ -21,6 +21,49 @@ const NUMBER_OF_QUEUES: usize = 3;
 pub struct BlockInsertionResult {
     /// Hashes of blocks, which were canonized during this insertion procedure. Order matters
     pub canonized_blocks_hashes: Vec<H256>,
+    /// Chain reorganization, caused by this insertion procedure
+    pub reorg: Option<ReorgInfo>,
+}
+
+/// Chain reorganization information
+#[derive(Clone, PartialEq)]
+pub struct ReorgInfo {
+    /// Height of the newest common ancestor of both chains
+    pub ancestor: u32,
+    /// Hashes of blocks, which were decanonized. Ordered from oldest to newest
+    pub decanonized_blocks_hashes: Vec<H256>,
+    /// Hashes of blocks, which were canonized. Ordered from oldest to newest
+    pub canonized_blocks_hashes: Vec<H256>,
+    /// Number of blocks, which were decanonized
+    pub decanonized_depth: u32,
+    /// Number of blocks, which were canonized
+    pub canonized_depth: u32,
+}
+
+impl fmt::Debug for ReorgInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReorgInfo")
+            .field("ancestor", &self.ancestor)
+            .field(
+                "decanonized_blocks_hashes",
+                &self
+                    .decanonized_blocks_hashes
+                    .iter()
+                    .map(H256::reversed)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "canonized_blocks_hashes",
+                &self
+                    .canonized_blocks_hashes
+                    .iter()
+                    .map(H256::reversed)
+                    .collect::<Vec<_>>(),
+            )
+            .field("decanonized_depth", &self.decanonized_depth)
+            .field("canonized_depth", &self.canonized_depth)
+            .finish()
+    }
 }
 
 impl fmt::Debug for BlockInsertionResult {
@@ -34,6 +77,7 @@ impl fmt::Debug for BlockInsertionResult {
                     .map(H256::reversed)
                     .collect::<Vec<_>>(),
             )
+            .field("reorg", &self.reorg)
             .finish()
     }
 }
@@ -43,6 +87,7 @@ impl BlockInsertionResult {
     pub fn with_canonized_blocks(canonized_blocks_hashes: Vec<H256>) -> Self {
         BlockInsertionResult {
             canonized_blocks_hashes: canonized_blocks_hashes,
+            reorg: None,
         }
     }
 }
@@ -162,6 +207,15 @@ impl Chain {
         }
     }
 
+    /// Returns true if there are no blocks of given state
+    pub fn is_blocks_state_empty(&self, state: BlockState) -> bool {
+        match state {
+            // genesis block is always stored
+            BlockState::Stored => false,
+            _ => self.hash_chain.is_empty_at(state.to_queue_index()),
+        }
+    }
+
     /// Get n best blocks of given state
     pub fn best_n_of_blocks_state(&self, state: BlockState, n: BlockHeight) -> Vec<H256> {
         match state {
@@ -242,6 +296,29 @@ impl Chain {
         self.headers_chain.by_hash(hash)
     }
 
+    /// Get cumulative work (total number of VDF iterations) of the chain ending with given block.
+    /// Walks back from `tip_hash` until genesis or the block with already known cumulative work.
+    pub fn total_work_for_chain(&self, tip_hash: &H256) -> u64 {
+        let mut total_work = 0u64;
+        let mut hash = tip_hash.clone();
+        loop {
+            if let Some(known_work) = self.headers_chain.total_work(&hash) {
+                return total_work + known_work;
+            }
+
+            match self.block_header_by_hash(&hash) {
+                Some(header) => {
+                    total_work += header.raw.iterations as u64;
+                    if hash == self.genesis_block_hash {
+                        return total_work;
+                    }
+                    hash = header.raw.previous_header_hash;
+                }
+                None => return total_work,
+            }
+        }
+    }
+
     /// Get block state
     pub fn block_state(&self, hash: &H256) -> BlockState {
         match self.hash_chain.contains_in(hash) {
@@ -280,13 +357,26 @@ impl Chain {
         block_locator_hashes
     }
 
-    /// Schedule blocks hashes for requesting
-    pub fn schedule_blocks_headers(&mut self, headers: Vec<IndexedBlockHeader>) {
+    /// Schedule blocks hashes for requesting.
+    /// Returns false if headers chain capacity has been exceeded and some headers were not scheduled
+    pub fn schedule_blocks_headers(&mut self, mut headers: Vec<IndexedBlockHeader>) -> bool {
+        let room = self
+            .headers_chain
+            .capacity()
+            .saturating_sub(self.headers_chain.len());
+        let is_scheduled_all = headers.len() <= room;
+        if !is_scheduled_all {
+            warn!(target: "sync", "Headers chain capacity {} is exceeded. Ignoring {} headers",
+                self.headers_chain.capacity(), headers.len() - room);
+            headers.truncate(room);
+        }
+
         self.hash_chain.push_back_n_at(
             SCHEDULED_QUEUE,
             headers.iter().map(|h| h.hash.clone()).collect(),
         );
         self.headers_chain.insert_n(headers);
+        is_scheduled_all
     }
 
     /// Moves n blocks from scheduled queue to requested queue
@@ -297,6 +387,17 @@ impl Chain {
         scheduled
     }
 
+    /// Moves requested blocks back to the front of scheduled queue
+    pub fn reschedule_blocks_hashes(&mut self, hashes: Vec<H256>) {
+        let hashes: Vec<_> = hashes
+            .into_iter()
+            .filter(|hash| {
+                self.hash_chain.remove_at(REQUESTED_QUEUE, hash) != HashPosition::Missing
+            })
+            .collect();
+        self.hash_chain.push_front_n_at(SCHEDULED_QUEUE, hashes);
+    }
+
     /// Add block to verifying queue
     pub fn verify_block(&mut self, header: IndexedBlockHeader) {
         // insert header to the in-memory chain in case when it is not already there (non-headers-first sync)
@@ -336,11 +437,13 @@ impl Chain {
             self.storage.block_hash(self.storage.best_block().number)
         );
         let block_origin = self.storage.block_origin(&block.header)?;
+        let total_work = self.total_work_for_chain(&block.header.raw.previous_header_hash)
+            + block.header.raw.iterations as u64;
         trace!(target: "sync", "insert_best_block {:?} origin: {:?}", block.hash().reversed(), block_origin);
         match block_origin {
             storage::BlockOrigin::KnownBlock => {
-                // there should be no known blocks at this point
-                unreachable!();
+                // block has been inserted while it was verified
+                Err(storage::Error::Duplicate(block.hash().clone()))
             }
             // case 1: block has been added to the main branch
             storage::BlockOrigin::CanonChain { .. } => {
@@ -352,14 +455,18 @@ impl Chain {
 
                 // remove inserted block + handle possible reorganization in headers chain
                 // TODO: mk, not sure if we need both of those params
-                self.headers_chain
-                    .block_inserted_to_storage(block.hash(), &self.best_storage_block.hash);
+                self.headers_chain.block_inserted_to_storage(
+                    block.hash(),
+                    &self.best_storage_block.hash,
+                    total_work,
+                );
 
                 // double check
                 assert_eq!(self.best_storage_block.hash, block.hash().clone());
 
                 Ok(BlockInsertionResult {
                     canonized_blocks_hashes: vec![*block.hash()],
+                    reorg: None,
                 })
             }
             // case 2: block has been added to the side branch with reorganization to this branch
@@ -374,13 +481,24 @@ impl Chain {
 
                 // remove inserted block + handle possible reorganization in headers chain
                 // TODO: mk, not sure if we need both of those params
-                self.headers_chain
-                    .block_inserted_to_storage(block.hash(), &self.best_storage_block.hash);
+                self.headers_chain.block_inserted_to_storage(
+                    block.hash(),
+                    &self.best_storage_block.hash,
+                    total_work,
+                );
 
                 let mut canonized_blocks_hashes = origin.canonized_route.clone();
                 canonized_blocks_hashes.push(*block.hash());
+                let reorg = ReorgInfo {
+                    ancestor: origin.ancestor,
+                    decanonized_blocks_hashes: origin.decanonized_route.clone(),
+                    canonized_blocks_hashes: canonized_blocks_hashes.clone(),
+                    decanonized_depth: origin.decanonized_route.len() as u32,
+                    canonized_depth: canonized_blocks_hashes.len() as u32,
+                };
                 let result = BlockInsertionResult {
                     canonized_blocks_hashes: canonized_blocks_hashes,
+                    reorg: Some(reorg),
                 };
 
                 trace!(target: "sync", "result: {:?}", result);
@@ -394,8 +512,11 @@ impl Chain {
 
                 // remove inserted block + handle possible reorganization in headers chain
                 // TODO: mk, not sure if it's needed here at all
-                self.headers_chain
-                    .block_inserted_to_storage(&block_hash, &self.best_storage_block.hash);
+                self.headers_chain.block_inserted_to_storage(
+                    &block_hash,
+                    &self.best_storage_block.hash,
+                    total_work,
+                );
 
                 // no transactions were accepted
                 // no transactions to reverify
@@ -601,7 +722,7 @@ impl fmt::Debug for Chain {
 mod tests {
     extern crate test_data;
 
-    use super::{BlockState, Chain};
+    use super::{BlockState, Chain, ReorgInfo};
     use chain::IndexedBlockHeader;
     use db::BlockChainDatabase;
     use primitives::hash::H256;
@@ -733,6 +854,85 @@ mod tests {
         assert_eq!(db.best_block().number, 1);
     }
 
+    #[test]
+    fn chain_reschedule_blocks_hashes() {
+        let db = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let mut chain = Chain::new(db);
+
+        let blocks = test_data::build_n_empty_blocks_from_genesis(4, 0);
+        let hashes: Vec<_> = blocks.iter().map(|b| b.hash()).collect();
+        chain.schedule_blocks_headers(blocks.into_iter().map(|b| b.block_header.into()).collect());
+        assert_eq!(chain.request_blocks_hashes(3), hashes[0..3].to_vec());
+
+        // unknown hashes are ignored, requested are moved to the front of scheduled queue
+        chain.reschedule_blocks_hashes(vec![hashes[1].clone(), hashes[2].clone(), H256::from(0)]);
+        assert_eq!(chain.block_state(&hashes[0]), BlockState::Requested);
+        assert_eq!(chain.block_state(&hashes[1]), BlockState::Scheduled);
+        assert_eq!(chain.request_blocks_hashes(3), hashes[1..4].to_vec());
+    }
+
+    #[test]
+    fn chain_total_work() {
+        let genesis = test_data::genesis();
+        let db = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let mut chain = Chain::new(db);
+        let genesis_work = genesis.block_header.iterations as u64;
+        assert_eq!(chain.total_work_for_chain(&genesis.hash()), genesis_work);
+
+        let blocks = test_data::build_n_empty_blocks_from(3, 10, &genesis.block_header);
+        chain
+            .insert_best_block(blocks[0].clone().into())
+            .expect("Error inserting new block");
+        assert_eq!(
+            chain.total_work_for_chain(&blocks[0].hash()),
+            genesis_work + 10
+        );
+
+        // headers, which are not yet in storage, are also counted
+        chain.schedule_blocks_headers(vec![
+            blocks[1].block_header.clone().into(),
+            blocks[2].block_header.clone().into(),
+        ]);
+        assert_eq!(
+            chain.total_work_for_chain(&blocks[2].hash()),
+            genesis_work + 10 + 11 + 12
+        );
+    }
+
+    #[test]
+    fn chain_reorg_info() {
+        let genesis = test_data::genesis();
+        let db = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let mut chain = Chain::new(db);
+        let fork1 = test_data::build_n_empty_blocks_from(1, 10, &genesis.block_header);
+        let fork2 = test_data::build_n_empty_blocks_from(2, 20, &genesis.block_header);
+
+        let result = chain.insert_best_block(fork1[0].clone().into()).unwrap();
+        assert_eq!(result.reorg, None);
+
+        // fork2 has the same length, but more work
+        let result = chain.insert_best_block(fork2[0].clone().into()).unwrap();
+        assert_eq!(
+            result.reorg,
+            Some(ReorgInfo {
+                ancestor: 0,
+                decanonized_blocks_hashes: vec![fork1[0].hash()],
+                canonized_blocks_hashes: vec![fork2[0].hash()],
+                decanonized_depth: 1,
+                canonized_depth: 1,
+            })
+        );
+
+        let result = chain.insert_best_block(fork2[1].clone().into()).unwrap();
+        assert_eq!(result.reorg, None);
+    }
+
     #[test]
     fn chain_block_locator_hashes() {
         let db = Arc::new(BlockChainDatabase::init_test_chain(vec![
@@ -2,9 +2,9 @@ use chain::{IndexedBlock, IndexedBlockHeader};
 use message::types;
 use parking_lot::Mutex;
 use std::sync::Arc;
-use synchronization_client_core::{ClientCore, SynchronizationClientCore};
+use synchronization_client_core::{ClientCore, SyncMetrics, SynchronizationClientCore};
 use synchronization_executor::TaskExecutor;
-use synchronization_verifier::Verifier;
+use synchronization_verifier::{HeadersVerifier, Verifier};
 use types::{ClientCoreRef, EmptyBoxFuture, PeerIndex, SyncListenerRef};
 
 #[cfg_attr(feature = "cargo-clippy", allow(doc_markdown))]
@@ -121,33 +121,37 @@ use types::{ClientCoreRef, EmptyBoxFuture, PeerIndex, SyncListenerRef};
 
 /// Synchronization client trait
 pub trait Client: Send + Sync + 'static {
-    fn on_connect(&self, peer_index: PeerIndex);
+    fn on_connect(&self, peer_index: PeerIndex, peer_name: String);
     fn on_disconnect(&self, peer_index: PeerIndex);
     fn on_inventory(&self, peer_index: PeerIndex, message: types::Inv);
     fn on_headers(&self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>);
     fn on_block(&self, peer_index: PeerIndex, block: IndexedBlock);
     fn on_notfound(&self, peer_index: PeerIndex, message: types::NotFound);
-    fn after_peer_nearly_blocks_verified(&self, peer_index: PeerIndex, future: EmptyBoxFuture);
+    fn after_peer_nearly_blocks_verified(&self, peer_index: PeerIndex) -> EmptyBoxFuture;
     fn install_sync_listener(&self, listener: SyncListenerRef);
+    fn metrics(&self) -> SyncMetrics;
 }
 
 /// Synchronization client facade
-pub struct SynchronizationClient<T: TaskExecutor, U: Verifier> {
+pub struct SynchronizationClient<T: TaskExecutor, U: Verifier, V: HeadersVerifier> {
     /// Verification mutex
     verification_lock: Mutex<()>,
     /// Client core
     core: ClientCoreRef<SynchronizationClientCore<T>>,
     /// Verifier
     verifier: U,
+    /// Headers verifier
+    headers_verifier: V,
 }
 
-impl<T, U> Client for SynchronizationClient<T, U>
+impl<T, U, V> Client for SynchronizationClient<T, U, V>
 where
     T: TaskExecutor,
     U: Verifier,
+    V: HeadersVerifier,
 {
-    fn on_connect(&self, peer_index: PeerIndex) {
-        self.core.lock().on_connect(peer_index);
+    fn on_connect(&self, peer_index: PeerIndex, peer_name: String) {
+        self.core.lock().on_connect(peer_index, peer_name);
     }
 
     fn on_disconnect(&self, peer_index: PeerIndex) {
@@ -159,7 +163,12 @@ where
     }
 
     fn on_headers(&self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>) {
-        self.core.lock().on_headers(peer_index, headers);
+        // headers are verified without holding the core lock
+        let headers_to_verify = self.core.lock().on_headers(peer_index, headers);
+        if let Some(headers_to_verify) = headers_to_verify {
+            self.headers_verifier
+                .verify_headers(peer_index, headers_to_verify);
+        }
     }
 
     fn on_block(&self, peer_index: PeerIndex, block: IndexedBlock) {
@@ -192,28 +201,38 @@ where
         self.core.lock().on_notfound(peer_index, message);
     }
 
-    fn after_peer_nearly_blocks_verified(&self, peer_index: PeerIndex, future: EmptyBoxFuture) {
+    fn after_peer_nearly_blocks_verified(&self, peer_index: PeerIndex) -> EmptyBoxFuture {
         self.core
             .lock()
-            .after_peer_nearly_blocks_verified(peer_index, future);
+            .after_peer_nearly_blocks_verified(peer_index)
     }
 
     fn install_sync_listener(&self, listener: SyncListenerRef) {
         self.core.lock().install_sync_listener(listener);
     }
+
+    fn metrics(&self) -> SyncMetrics {
+        self.core.lock().metrics()
+    }
 }
 
-impl<T, U> SynchronizationClient<T, U>
+impl<T, U, V> SynchronizationClient<T, U, V>
 where
     T: TaskExecutor,
     U: Verifier,
+    V: HeadersVerifier,
 {
     /// Create new synchronization client
-    pub fn new(core: ClientCoreRef<SynchronizationClientCore<T>>, verifier: U) -> Arc<Self> {
+    pub fn new(
+        core: ClientCoreRef<SynchronizationClientCore<T>>,
+        verifier: U,
+        headers_verifier: V,
+    ) -> Arc<Self> {
         Arc::new(SynchronizationClient {
             verification_lock: Mutex::new(()),
             core: core,
             verifier: verifier,
+            headers_verifier: headers_verifier,
         })
     }
 }
@@ -1,13 +1,16 @@
 use chain::{IndexedBlock, IndexedBlockHeader};
-use futures::Future;
-use message::common::{InventoryType, InventoryVector};
+use futures::sync::oneshot;
+use futures::{future, Future};
+use message::common::{InventoryType, InventoryVector, Services};
 use message::types;
+use metrics;
 use parking_lot::Mutex;
 use primitives::hash::H256;
 use std::cmp::{max, min};
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
+use storage;
 #[cfg(test)]
 use synchronization_chain::Information as ChainInformation;
 use synchronization_chain::{BlockInsertionResult, BlockState, Chain};
@@ -16,14 +19,15 @@ use synchronization_manager::ManagementWorker;
 #[cfg(test)]
 use synchronization_peers_tasks::Information as PeersTasksInformation;
 use synchronization_peers_tasks::PeersTasks;
-use synchronization_verifier::{BlockVerificationSink, VerificationSink, VerificationTask};
+use synchronization_verifier::{
+    BlockVerificationSink, HeadersVerificationSink, VerificationSink, VerificationTask,
+};
 use time::precise_time_s;
 use types::{
     BlockHeight, ClientCoreRef, EmptyBoxFuture, PeerIndex, PeersRef, SyncListenerRef,
     SynchronizationStateRef,
 };
-use utils::{AverageSpeedMeter, HashPosition, MessageBlockHeadersProvider, OrphanBlocksPool};
-use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
+use utils::{AverageSpeedMeter, HashPosition, OrphanBlocksPool};
 
 /// Approximate maximal number of blocks hashes in scheduled queue.
 const MAX_SCHEDULED_HASHES: BlockHeight = 4 * 1024;
@@ -34,7 +38,7 @@ const MAX_VERIFYING_BLOCKS: BlockHeight = 256;
 /// Minimum number of blocks to request from peer
 const MIN_BLOCKS_IN_REQUEST: BlockHeight = 32;
 /// Maximum number of blocks to request from peer
-const MAX_BLOCKS_IN_REQUEST: BlockHeight = 128;
+pub const MAX_BLOCKS_IN_REQUEST: BlockHeight = 128;
 /// Number of blocks to receive since synchronization start to begin duplicating blocks requests
 const NEAR_EMPTY_VERIFICATION_QUEUE_THRESHOLD_BLOCKS: usize = 20;
 /// Number of seconds left before verification queue will be empty to count it as 'near empty queue'
@@ -64,19 +68,64 @@ pub struct Information {
     pub orphaned_blocks: usize,
 }
 
+/// Snapshot of synchronization metrics, taken under the client core lock.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncMetrics {
+    /// Current synchronization state.
+    pub state: String,
+    /// Number of blocks hashes currently scheduled for requesting.
+    pub scheduled: u32,
+    /// Number of blocks hashes currently requested from peers.
+    pub requested: u32,
+    /// Number of blocks currently verifying.
+    pub verifying: u32,
+    /// Number of blocks in the storage.
+    pub stored: u32,
+    /// Number of currently orphaned blocks.
+    pub orphaned: u32,
+    /// Number of useful peers without pending requests.
+    pub peers_idle: u32,
+    /// Number of useful peers with pending requests.
+    pub peers_active: u32,
+    /// Number of peers, marked as non-useful.
+    pub peers_unuseful: u32,
+    /// Blocks synchronization speed (blocks per second).
+    pub sync_speed_bps: f64,
+    /// Blocks verification speed (blocks per second).
+    pub verify_speed_bps: f64,
+    /// Hash of the best storage block.
+    pub last_block_hash: H256,
+    /// Height of the best storage block.
+    pub last_block_height: u32,
+    /// Number of stored blocks + number of in-memory headers.
+    pub total_headers_seen: u32,
+    /// Is synchronization in progress?
+    pub is_synchronizing: bool,
+    /// Number of seconds since the last block has been inserted to the storage.
+    pub elapsed_since_last_block_secs: u64,
+    /// Number of chain reorganizations since the node start.
+    pub reorgs: u32,
+    /// Number of blocks, decanonized by the last chain reorganization.
+    pub last_reorg_depth: u32,
+}
+
 /// Synchronization client trait
 pub trait ClientCore {
-    fn on_connect(&mut self, peer_index: PeerIndex);
+    fn on_connect(&mut self, peer_index: PeerIndex, peer_name: String);
     fn on_disconnect(&mut self, peer_index: PeerIndex);
     fn on_inventory(&self, peer_index: PeerIndex, message: types::Inv);
-    fn on_headers(&mut self, peer_index: PeerIndex, message: Vec<IndexedBlockHeader>);
+    fn on_headers(
+        &mut self,
+        peer_index: PeerIndex,
+        message: Vec<IndexedBlockHeader>,
+    ) -> Option<Vec<IndexedBlockHeader>>;
     fn on_block(
         &mut self,
         peer_index: PeerIndex,
         block: IndexedBlock,
     ) -> Option<VecDeque<IndexedBlock>>;
     fn on_notfound(&mut self, peer_index: PeerIndex, message: types::NotFound);
-    fn after_peer_nearly_blocks_verified(&mut self, peer_index: PeerIndex, future: EmptyBoxFuture);
+    fn after_peer_nearly_blocks_verified(&mut self, peer_index: PeerIndex) -> EmptyBoxFuture;
     fn install_sync_listener(&mut self, listener: SyncListenerRef);
     fn execute_synchronization_tasks(
         &mut self,
@@ -91,6 +140,10 @@ pub trait ClientCore {
 pub struct Config {
     /// If true, connection to peer who has provided us with bad block is closed
     pub close_connection_on_bad_block: bool,
+    /// Max memory, used by orphaned blocks (in bytes)
+    pub orphans_memory_limit: usize,
+    /// If false, peers do not announce new blocks to us => new headers are polled periodically
+    pub relay: bool,
 }
 
 /// Synchronization client.
@@ -111,14 +164,15 @@ pub struct SynchronizationClientCore<T: TaskExecutor> {
     chain: Chain,
     /// Orphaned blocks pool.
     orphaned_blocks_pool: OrphanBlocksPool,
-    /// Chain verifier
-    chain_verifier: Arc<ChainVerifier>,
     /// Verify block headers?
     verify_headers: bool,
+    /// Peers, which headers are currently verifying.
+    /// Value is true if peer has sent more headers while previous were verifying.
+    verifying_headers_peers: HashMap<PeerIndex, bool>,
     /// Verifying blocks by peer
     verifying_blocks_by_peer: HashMap<H256, PeerIndex>,
-    /// Verifying blocks futures
-    verifying_blocks_futures: HashMap<PeerIndex, (HashSet<H256>, Vec<EmptyBoxFuture>)>,
+    /// Verifying blocks of every peer and senders, which are notified when all these blocks are verified
+    verifying_blocks_futures: HashMap<PeerIndex, (HashSet<H256>, Vec<oneshot::Sender<()>>)>,
     /// Hashes of items we do not want to relay after verification is completed
     do_not_relay: HashSet<H256>,
     /// Block processing speed meter
@@ -131,6 +185,10 @@ pub struct SynchronizationClientCore<T: TaskExecutor> {
     listener: Option<SyncListenerRef>,
     /// Time of last duplicated blocks request.
     last_dup_time: f64,
+    /// Number of chain reorganizations since the node start.
+    reorgs: u32,
+    /// Number of blocks, decanonized by the last chain reorganization.
+    last_reorg_depth: u32,
 }
 
 /// Verification sink for synchronization client core
@@ -168,8 +226,6 @@ pub struct BlocksRequestLimits {
 enum BlocksHeadersVerificationResult {
     /// Skip these blocks headers
     Skip,
-    /// Error during verification of header with given index
-    Error(usize),
     /// Successful verification
     Success,
 }
@@ -195,13 +251,21 @@ impl State {
             _ => false,
         }
     }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            State::Synchronizing(_, _) => "synchronizing",
+            State::NearlySaturated => "nearly_saturated",
+            State::Saturated => "saturated",
+        }
+    }
 }
 
 impl<T> ClientCore for SynchronizationClientCore<T>
 where
     T: TaskExecutor,
 {
-    fn on_connect(&mut self, peer_index: PeerIndex) {
+    fn on_connect(&mut self, peer_index: PeerIndex, peer_name: String) {
         // ask peer for its block headers to find our best common block
         let block_locator_hashes = self.chain.block_locator_hashes();
         self.executor.execute(Task::GetHeaders(
@@ -211,13 +275,22 @@ where
         // unuseful until respond with headers message
         self.peers_tasks.unuseful_peer(peer_index);
         self.peers_tasks.on_headers_requested(peer_index);
+
+        if let Some(ref listener) = self.listener {
+            listener.on_peer_connected(peer_index, peer_name);
+        }
     }
 
     fn on_disconnect(&mut self, peer_index: PeerIndex) {
         // sync tasks from this peers must be executed by other peers
         let peer_tasks = self.peers_tasks.reset_blocks_tasks(peer_index);
         self.peers_tasks.disconnect(peer_index);
+        self.verifying_headers_peers.remove(&peer_index);
         self.execute_synchronization_tasks(Some(peer_tasks), None);
+
+        if let Some(ref listener) = self.listener {
+            listener.on_peer_disconnected(peer_index);
+        }
     }
 
     fn on_inventory(&self, peer_index: PeerIndex, message: types::Inv) {
@@ -272,15 +345,30 @@ where
     }
 
     /// Try to queue synchronization of unknown blocks when blocks headers are received.
-    fn on_headers(&mut self, peer_index: PeerIndex, mut headers: Vec<IndexedBlockHeader>) {
-        assert!(
-            !headers.is_empty(),
-            "This must be checked in incoming connection"
-        );
-
+    /// Returns unknown headers, which must be verified before scheduling.
+    fn on_headers(
+        &mut self,
+        peer_index: PeerIndex,
+        mut headers: Vec<IndexedBlockHeader>,
+    ) -> Option<Vec<IndexedBlockHeader>> {
         // update peers to select next tasks
         self.peers_tasks.on_headers_received(peer_index);
 
+        // empty `headers` message means that peer has no new headers for us
+        if headers.is_empty() {
+            trace!(target: "sync", "Peer#{} has no new headers", peer_index);
+            self.peers_tasks.useful_peer(peer_index);
+            return None;
+        }
+
+        // only one `headers` message of every peer is verified at a time
+        // => ignore these headers and ask peer again when verification is completed
+        if let Some(has_ignored_headers) = self.verifying_headers_peers.get_mut(&peer_index) {
+            trace!(target: "sync", "Ignoring {} headers from peer#{} - previous headers are still verifying", headers.len(), peer_index);
+            *has_ignored_headers = true;
+            return None;
+        }
+
         // headers are ordered
         // => if we know nothing about headers[0].parent
         // => all headers are also unknown to us
@@ -300,7 +388,7 @@ where
                 self.peers.misbehaving(peer_index, "Too many failures.");
             }
 
-            return;
+            return None;
         }
 
         // find first unknown header position
@@ -320,7 +408,7 @@ where
 						trace!(target: "sync", "Ignoring {} known headers from peer#{}", headers.len(), peer_index);
 						// but this peer is still useful for synchronization
 						self.peers_tasks.useful_peer(peer_index);
-						return;
+						return None;
 					},
 				}
             }
@@ -342,47 +430,24 @@ where
                     last_known_hash.to_reversed_str()
                 ),
             );
-            return;
+            return None;
         }
-        match self.verify_headers(
+        match self.verify_headers_chain(
             peer_index,
             last_known_hash,
             &headers[first_unknown_index..num_headers],
         ) {
-            BlocksHeadersVerificationResult::Error(error_index) => self
-                .chain
-                .mark_dead_end_block(&headers[first_unknown_index + error_index].hash),
-            BlocksHeadersVerificationResult::Skip => (),
+            BlocksHeadersVerificationResult::Skip => None,
             BlocksHeadersVerificationResult::Success => {
-                // report progress
-                let num_new_headers = num_headers - first_unknown_index;
-                trace!(target: "sync", "New {} headers from peer#{}. First {:?}, last: {:?}",
-                    num_new_headers,
-                    peer_index,
-                    headers[first_unknown_index].hash.to_reversed_str(),
-                    headers[num_headers - 1].hash.to_reversed_str()
-                );
-
-                // prepare new headers array
                 let new_headers = headers.split_off(first_unknown_index);
-                self.chain.schedule_blocks_headers(new_headers);
-
-                // switch to synchronization state
-                if !self.state.is_synchronizing() {
-                    if self.chain.length_of_blocks_state(BlockState::Scheduled)
-                        + self.chain.length_of_blocks_state(BlockState::Requested)
-                        == 1
-                    {
-                        self.switch_to_nearly_saturated_state();
-                    } else {
-                        self.switch_to_synchronization_state();
-                    }
+                if self.verify_headers {
+                    // do not ask this peer for new headers until these are verified
+                    self.verifying_headers_peers.insert(peer_index, false);
+                    return Some(new_headers);
                 }
 
-                // this peers has supplied us with new headers => useful indeed
-                self.peers_tasks.useful_peer(peer_index);
-                // and execute tasks
-                self.execute_synchronization_tasks(None, None);
+                self.schedule_blocks_headers(peer_index, new_headers);
+                None
             }
         }
     }
@@ -470,7 +535,10 @@ where
                                 .orphaned_blocks_pool
                                 .contains_unknown_block(&block.header.hash)
                             {
-                                self.orphaned_blocks_pool.insert_unknown_block(block);
+                                let evicted = self
+                                    .orphaned_blocks_pool
+                                    .insert_unknown_block(Some(peer_index), block);
+                                self.on_orphans_evicted(peer_index, evicted);
                             }
                         }
                     }
@@ -521,7 +589,10 @@ where
                         // remember peer as useful
                         self.peers_tasks.useful_peer(peer_index);
                         // remember as orphan block
-                        self.orphaned_blocks_pool.insert_orphaned_block(block);
+                        let evicted = self
+                            .orphaned_blocks_pool
+                            .insert_orphaned_block(Some(peer_index), block);
+                        self.on_orphans_evicted(peer_index, evicted);
                     }
                 }
             }
@@ -573,21 +644,23 @@ where
         }
     }
 
-    /// Execute after last block from this peer in NearlySaturated state is verified.
-    /// If there are no verifying blocks from this peer or we are not in the NearlySaturated state => execute immediately.
-    fn after_peer_nearly_blocks_verified(&mut self, peer_index: PeerIndex, future: EmptyBoxFuture) {
+    /// Future, which resolves after last block from this peer in NearlySaturated state is verified.
+    /// If there are no verifying blocks from this peer or we are not in the NearlySaturated state => resolves immediately.
+    fn after_peer_nearly_blocks_verified(&mut self, peer_index: PeerIndex) -> EmptyBoxFuture {
         // if we are currently synchronizing => no need to wait
         if self.state.is_synchronizing() {
-            future.wait().expect("no-error future");
-            return;
+            return Box::new(future::ok(()));
         }
 
         // we have to wait until all previous peer requests are server
         match self.verifying_blocks_futures.entry(peer_index) {
             Entry::Occupied(mut entry) => {
-                entry.get_mut().1.push(future);
+                let (sender, receiver) = oneshot::channel();
+                entry.get_mut().1.push(sender);
+                // sender is dropped when verification is abandoned => do not wait anymore
+                Box::new(receiver.then(|_| Ok(())))
             }
-            _ => future.wait().expect("no-error future"),
+            _ => Box::new(future::ok(())),
         }
     }
 
@@ -608,12 +681,8 @@ where
         // display information if processed many blocks || enough time has passed since sync start
         self.print_synchronization_information();
 
-        // prepare limits. TODO: must be updated using current retrieval && verification speed && blocks size
-        let mut limits = BlocksRequestLimits::default();
-        if self.chain.length_of_blocks_state(BlockState::Stored) > 150_000 {
-            limits.min_blocks_in_request = 8;
-            limits.max_blocks_in_request = 16;
-        }
+        // prepare limits. Size of every peer request is adapted to the peer latency && speed below
+        let limits = BlocksRequestLimits::default();
 
         // if some blocks requests are forced => we should ask peers even if there are no idle peers
         let verifying_hashes_len = self.chain.length_of_blocks_state(BlockState::Verifying);
@@ -626,9 +695,11 @@ where
                 return;
             }
 
-            let forced_tasks =
+            let (forced_tasks, not_requested) =
                 self.prepare_blocks_requests_tasks(&limits, useful_peers, forced_blocks_requests);
             tasks.extend(forced_tasks);
+            // blocks, which do not fit into peers requests, are requested later
+            self.chain.reschedule_blocks_hashes(not_requested);
         }
 
         // if some blocks requests are marked as last [i.e. blocks are potentialy wrong] => ask peers anyway
@@ -636,7 +707,7 @@ where
             let useful_peers = self.peers_tasks.useful_peers();
             if !useful_peers.is_empty() {
                 // if empty => not a problem, just forget these blocks
-                let forced_tasks = self.prepare_blocks_requests_tasks(
+                let (forced_tasks, _) = self.prepare_blocks_requests_tasks(
                     &limits,
                     useful_peers,
                     final_blocks_requests,
@@ -646,18 +717,28 @@ where
         }
 
         let mut blocks_requests: Option<Vec<H256>> = None;
-        let blocks_idle_peers: Vec<_> = self
-            .peers_tasks
-            .idle_peers_for_blocks()
-            .iter()
-            .cloned()
-            .collect();
+        let mut new_blocks_requests: Option<(Vec<(PeerIndex, usize)>, Vec<H256>)> = None;
+        // prefer peers with best score for new blocks requests
+        // peers, which have not advertised NODE_NETWORK service, can't serve blocks
+        let blocks_idle_peers: Vec<_> = {
+            let idle_peers = self.peers_tasks.idle_peers_for_blocks();
+            let limited_peers = self.limited_peers();
+            self.peers_tasks
+                .top_k_peers(self.peers_tasks.all_peers().len())
+                .into_iter()
+                .filter(|peer_index| idle_peers.contains(peer_index))
+                .filter(|peer_index| !limited_peers.contains(peer_index))
+                .collect()
+        };
         {
             // check if we can query some blocks headers
+            // (peers, which headers are still verifying, are not asked for new headers)
+            let verifying_headers_peers = &self.verifying_headers_peers;
             let headers_idle_peers: Vec<_> = self
                 .peers_tasks
                 .idle_peers_for_headers()
                 .iter()
+                .filter(|peer_index| !verifying_headers_peers.contains_key(peer_index))
                 .cloned()
                 .collect();
             if !headers_idle_peers.is_empty() {
@@ -773,34 +854,53 @@ where
                         < MAX_REQUESTED_BLOCKS + MAX_VERIFYING_BLOCKS
                         && scheduled_hashes_len != 0
                     {
-                        let chunk_size = min(
+                        // peers with unmeasured speed are asked for equal share of scheduled blocks
+                        let default_chunk_size = min(
                             limits.max_blocks_in_request,
                             max(
                                 scheduled_hashes_len / blocks_idle_peers_len,
                                 limits.min_blocks_in_request,
                             ),
                         );
-                        let hashes_to_request_len = chunk_size * blocks_idle_peers_len;
+                        // every peer is asked for the number of blocks it is expected to deliver
+                        // (idle peers are already sorted by score => earliest blocks are
+                        // requested from the best peers)
+                        let peers_chunks: Vec<_> = blocks_idle_peers
+                            .iter()
+                            .map(|peer_index| {
+                                let chunk_size = self
+                                    .peers_tasks
+                                    .blocks_request_size(
+                                        *peer_index,
+                                        limits.min_blocks_in_request,
+                                        limits.max_blocks_in_request,
+                                    )
+                                    .unwrap_or(default_chunk_size);
+                                (*peer_index, chunk_size as usize)
+                            })
+                            .collect();
+                        let hashes_to_request_len = peers_chunks
+                            .iter()
+                            .map(|&(_, chunk_size)| chunk_size as BlockHeight)
+                            .sum();
                         let hashes_to_request =
                             self.chain.request_blocks_hashes(hashes_to_request_len);
-                        match blocks_requests {
-                            Some(ref mut blocks_requests) => {
-                                blocks_requests.extend(hashes_to_request)
-                            }
-                            None => blocks_requests = Some(hashes_to_request),
-                        }
+                        new_blocks_requests = Some((peers_chunks, hashes_to_request));
                     }
                 }
             }
         }
 
         // append blocks requests tasks
+        // (duplicated requests, which do not fit into peers requests, are not duplicated at all)
         if let Some(blocks_requests) = blocks_requests {
-            tasks.extend(self.prepare_blocks_requests_tasks(
-                &limits,
-                blocks_idle_peers,
-                blocks_requests,
-            ));
+            let (duplicated_tasks, _) =
+                self.prepare_blocks_requests_tasks(&limits, blocks_idle_peers, blocks_requests);
+            tasks.extend(duplicated_tasks);
+        }
+        if let Some((peers_chunks, mut hashes)) = new_blocks_requests {
+            tasks.extend(self.prepare_sized_blocks_requests_tasks(peers_chunks, &mut hashes));
+            assert_eq!(hashes.len(), 0);
         }
 
         // execute synchronization tasks
@@ -812,8 +912,8 @@ where
     fn try_switch_to_saturated_state(&mut self) -> bool {
         let switch_to_saturated = {
             // requested block is received => move to saturated state if there are no more blocks
-            self.chain.length_of_blocks_state(BlockState::Scheduled) == 0
-                && self.chain.length_of_blocks_state(BlockState::Requested) == 0
+            self.chain.is_blocks_state_empty(BlockState::Scheduled)
+                && self.chain.is_blocks_state_empty(BlockState::Requested)
         };
 
         if switch_to_saturated {
@@ -835,6 +935,35 @@ where
 
 impl<T> VerificationSink for CoreVerificationSink<T> where T: TaskExecutor {}
 
+impl<T> HeadersVerificationSink for CoreVerificationSink<T>
+where
+    T: TaskExecutor,
+{
+    /// Process successful headers verification
+    fn on_headers_verification_success(
+        &self,
+        peer_index: PeerIndex,
+        headers: Vec<IndexedBlockHeader>,
+    ) {
+        self.core
+            .lock()
+            .on_headers_verification_success(peer_index, headers)
+    }
+
+    /// Process failed headers verification
+    fn on_headers_verification_error(
+        &self,
+        peer_index: PeerIndex,
+        err: &str,
+        headers: Vec<IndexedBlockHeader>,
+        error_index: usize,
+    ) {
+        self.core
+            .lock()
+            .on_headers_verification_error(peer_index, err, headers, error_index)
+    }
+}
+
 impl<T> BlockVerificationSink for CoreVerificationSink<T>
 where
     T: TaskExecutor,
@@ -850,6 +979,49 @@ where
     }
 }
 
+/// Split window of `total` blocks between peers (sorted from fastest to slowest), proportionally
+/// to their download speed. Every peer is asked for at least `min_chunk` blocks (while there are
+/// blocks left) and for at most `max_chunk` blocks, so that the earliest blocks are always requested
+/// from the fastest peers. Peers with unknown speed are treated as average ones.
+/// Blocks, which do not fit into peers chunks, are not included in the result.
+fn blocks_window_chunks(
+    speeds: &[Option<f64>],
+    total: usize,
+    min_chunk: usize,
+    max_chunk: usize,
+) -> Vec<usize> {
+    if speeds.is_empty() {
+        return Vec::new();
+    }
+
+    let measured: Vec<f64> = speeds.iter().filter_map(|s| *s).collect();
+    let average_speed = if measured.is_empty() {
+        1f64
+    } else {
+        measured.iter().sum::<f64>() / measured.len() as f64
+    };
+    let speeds: Vec<f64> = speeds.iter().map(|s| s.unwrap_or(average_speed)).collect();
+    let total_speed: f64 = speeds.iter().sum();
+
+    let mut remaining = total;
+    let mut chunks: Vec<usize> = speeds
+        .iter()
+        .map(|speed| {
+            let share = (total as f64 * speed / total_speed) as usize;
+            let chunk = min(remaining, min(max(share, min_chunk), max_chunk));
+            remaining -= chunk;
+            chunk
+        })
+        .collect();
+    // rounding leftovers are requested from the fastest peers
+    for chunk in &mut chunks {
+        let leftover = min(remaining, max_chunk - *chunk);
+        *chunk += leftover;
+        remaining -= leftover;
+    }
+    chunks
+}
+
 impl<T> SynchronizationClientCore<T>
 where
     T: TaskExecutor,
@@ -861,7 +1033,6 @@ where
         peers: PeersRef,
         executor: Arc<T>,
         chain: Chain,
-        chain_verifier: Arc<ChainVerifier>,
     ) -> ClientCoreRef<Self> {
         let sync = Arc::new(Mutex::new(SynchronizationClientCore {
             shared_state: shared_state,
@@ -871,9 +1042,9 @@ where
             management_worker: None,
             executor: executor,
             chain: chain,
-            orphaned_blocks_pool: OrphanBlocksPool::new(),
-            chain_verifier: chain_verifier,
+            orphaned_blocks_pool: OrphanBlocksPool::with_memory_limit(config.orphans_memory_limit),
             verify_headers: true,
+            verifying_headers_peers: HashMap::new(),
             verifying_blocks_by_peer: HashMap::new(),
             verifying_blocks_futures: HashMap::new(),
             do_not_relay: HashSet::new(),
@@ -882,6 +1053,8 @@ where
             config: config,
             listener: None,
             last_dup_time: 0f64,
+            reorgs: 0,
+            last_reorg_depth: 0,
         }));
 
         {
@@ -904,6 +1077,75 @@ where
         }
     }
 
+    /// Get snapshot of synchronization metrics.
+    pub fn metrics(&self) -> SyncMetrics {
+        let chain = self.chain.information();
+        let peers_tasks = self.peers_tasks.information();
+        let best_block = self.chain.best_storage_block();
+        SyncMetrics {
+            state: self.state.name().to_owned(),
+            scheduled: chain.scheduled,
+            requested: chain.requested,
+            verifying: chain.verifying,
+            stored: chain.stored,
+            orphaned: self.orphaned_blocks_pool.len() as u32,
+            peers_idle: peers_tasks.idle as u32,
+            peers_active: peers_tasks.active as u32,
+            peers_unuseful: peers_tasks.unuseful as u32,
+            sync_speed_bps: self.sync_speed_meter.speed(),
+            verify_speed_bps: self.block_speed_meter.speed(),
+            last_block_hash: best_block.hash,
+            last_block_height: best_block.number,
+            total_headers_seen: chain.stored + chain.headers.total,
+            is_synchronizing: self.state.is_synchronizing(),
+            elapsed_since_last_block_secs: self.shared_state.elapsed_since_last_block_secs(),
+            reorgs: self.reorgs,
+            last_reorg_depth: self.last_reorg_depth,
+        }
+    }
+
+    /// Publish synchronization state to node metrics.
+    pub fn update_metrics(&self) {
+        let sync = self.metrics();
+        metrics::BEST_BLOCK_HEIGHT.set(sync.last_block_height as i64);
+        metrics::PEERS.set((sync.peers_idle + sync.peers_active + sync.peers_unuseful) as i64);
+        metrics::SYNCHRONIZING.set(sync.is_synchronizing as i64);
+        metrics::SCHEDULED_BLOCKS.set(sync.scheduled as i64);
+        metrics::REQUESTED_BLOCKS.set(sync.requested as i64);
+        metrics::VERIFYING_BLOCKS.set(sync.verifying as i64);
+        metrics::ORPHAN_BLOCKS.set(sync.orphaned as i64);
+    }
+
+    /// Do peers announce new blocks to us?
+    pub fn is_relay_enabled(&self) -> bool {
+        self.config.relay
+    }
+
+    /// Number of seconds since the last block has been inserted to the storage
+    pub fn elapsed_since_last_block_secs(&self) -> u64 {
+        self.shared_state.elapsed_since_last_block_secs()
+    }
+
+    /// Ask all known peers for headers, starting from our best block.
+    /// If `preferred_peer` is given, it is asked first.
+    pub fn request_headers_from_all_peers(&mut self, preferred_peer: Option<PeerIndex>) {
+        let block_locator_hashes: Vec<H256> = self.chain.block_locator_hashes();
+        let mut peers: Vec<PeerIndex> = self.peers_tasks.all_peers().iter().cloned().collect();
+        if let Some(position) =
+            preferred_peer.and_then(|preferred| peers.iter().position(|p| *p == preferred))
+        {
+            let preferred_peer = peers.remove(position);
+            peers.insert(0, preferred_peer);
+        }
+
+        for peer in peers {
+            self.executor.execute(Task::GetHeaders(
+                peer,
+                types::GetHeaders::with_block_locator_hashes(block_locator_hashes.clone()),
+            ));
+        }
+    }
+
     /// Get synchronization state
     pub fn state(&self) -> State {
         self.state
@@ -970,17 +1212,14 @@ where
         }
     }
 
-    /// Verify and select unknown headers for scheduling
-    fn verify_headers(
+    /// Check that unknown headers are linked && select them for verification
+    fn verify_headers_chain(
         &mut self,
         peer_index: PeerIndex,
         last_known_hash: H256,
         headers: &[IndexedBlockHeader],
     ) -> BlocksHeadersVerificationResult {
-        // validate blocks headers before scheduling
         let mut last_known_hash = &last_known_hash;
-        let mut headers_provider =
-            MessageBlockHeadersProvider::new(&self.chain, self.chain.best_block_header().number);
         for (header_index, header) in headers.iter().enumerate() {
             // check that this header is direct child of previous header
             if &header.raw.previous_header_hash != last_known_hash {
@@ -1013,31 +1252,7 @@ where
                 }
             }
 
-            // verify header
-            if self.verify_headers {
-                if let Err(error) = self.chain_verifier.verify_block_header(
-                    &headers_provider,
-                    &header.hash,
-                    &header.raw,
-                ) {
-                    if self.config.close_connection_on_bad_block {
-                        self.peers.misbehaving(
-                            peer_index,
-                            &format!(
-                                "Error verifying header {} from `headers`: {:?}",
-                                header.hash.to_reversed_str(),
-                                error
-                            ),
-                        );
-                    } else {
-                        warn!(target: "sync", "Error verifying header {} from `headers` message: {:?}", header.hash.to_reversed_str(), error);
-                    }
-                    return BlocksHeadersVerificationResult::Error(header_index);
-                }
-            }
-
             last_known_hash = &header.hash;
-            headers_provider.append_header(header.hash.clone(), header.clone());
         }
 
         BlocksHeadersVerificationResult::Success
@@ -1048,34 +1263,51 @@ where
         limits: &BlocksRequestLimits,
         mut peers: Vec<PeerIndex>,
         mut hashes: Vec<H256>,
-    ) -> Vec<Task> {
-        use std::mem::swap;
-
+    ) -> (Vec<Task>, Vec<H256>) {
         // ask fastest peers for hashes at the beginning of `hashes`
         self.peers_tasks.sort_peers_for_blocks(&mut peers);
 
-        let chunk_size = min(
-            limits.max_blocks_in_request,
-            max(hashes.len() as BlockHeight, limits.min_blocks_in_request),
+        // split window between peers, proportionally to their measured speed
+        let speeds: Vec<_> = peers
+            .iter()
+            .map(|peer| self.peers_tasks.blocks_speed(*peer))
+            .collect();
+        let chunks = blocks_window_chunks(
+            &speeds,
+            hashes.len(),
+            limits.min_blocks_in_request as usize,
+            limits.max_blocks_in_request as usize,
+        );
+
+        // hashes, which are left in `hashes`, do not fit into peers requests
+        let tasks = self.prepare_sized_blocks_requests_tasks(
+            peers.into_iter().zip(chunks).collect(),
+            &mut hashes,
         );
-        let last_peer_index = peers.len() - 1;
+        (tasks, hashes)
+    }
+
+    /// Ask every peer for given number of blocks, taken from the beginning of `hashes`.
+    fn prepare_sized_blocks_requests_tasks(
+        &mut self,
+        peers_chunks: Vec<(PeerIndex, usize)>,
+        hashes: &mut Vec<H256>,
+    ) -> Vec<Task> {
+        use std::mem::swap;
+
         let mut tasks: Vec<Task> = Vec::new();
         let inv_type = InventoryType::MessageBlock;
-        for (peer_index, peer) in peers.into_iter().enumerate() {
-            // we have to request all blocks => we will request last peer for all remaining blocks
-            let peer_chunk_size = if peer_index == last_peer_index {
-                hashes.len()
-            } else {
-                min(hashes.len(), chunk_size as usize)
-            };
+        for (peer, peer_chunk_size) in peers_chunks {
+            let peer_chunk_size = min(peer_chunk_size, hashes.len());
             if peer_chunk_size == 0 {
                 break;
             }
 
             let mut chunk_hashes = hashes.split_off(peer_chunk_size);
-            swap(&mut chunk_hashes, &mut hashes);
+            swap(&mut chunk_hashes, hashes);
 
             // remember that peer is asked for these blocks
+            trace!(target: "sync", "Requesting {} blocks from peer#{} (score: {:.2})", chunk_hashes.len(), peer, self.peers_tasks.score(peer));
             self.peers_tasks.on_blocks_requested(peer, &chunk_hashes);
 
             // request blocks. If block is believed to have witness - ask for witness
@@ -1091,7 +1323,6 @@ where
             tasks.push(Task::GetData(peer, getdata));
         }
 
-        assert_eq!(hashes.len(), 0);
         tasks
     }
 
@@ -1123,6 +1354,18 @@ where
         self.state = State::NearlySaturated;
     }
 
+    /// Connected peers, which have not advertised NODE_NETWORK service.
+    fn limited_peers(&self) -> HashSet<PeerIndex> {
+        let full_peers = self
+            .peers
+            .peers_with_services(Services::default().with_network(true));
+        self.peers
+            .enumerate()
+            .into_iter()
+            .filter(|peer_index| !full_peers.contains(peer_index))
+            .collect()
+    }
+
     /// Switch to saturated state
     fn switch_to_saturated_state(&mut self) {
         if self.state.is_saturated() {
@@ -1154,15 +1397,7 @@ where
 
         // finally - ask all known peers for their best blocks inventory, in case if some peer
         // has lead us to the fork
-        {
-            let block_locator_hashes: Vec<H256> = self.chain.block_locator_hashes();
-            for peer in self.peers_tasks.all_peers() {
-                self.executor.execute(Task::GetHeaders(
-                    *peer,
-                    types::GetHeaders::with_block_locator_hashes(block_locator_hashes.clone()),
-                ));
-            }
-        }
+        self.request_headers_from_all_peers(None);
     }
 
     fn on_block_verification_success(
@@ -1196,6 +1431,17 @@ where
                 // update shared state
                 self.shared_state
                     .update_best_storage_block_height(self.chain.best_storage_block().number);
+                self.shared_state.update_last_block_time();
+
+                // notify listener about reorganization before the new best block
+                if let Some(ref reorg) = insert_result.reorg {
+                    info!(target: "sync", "Chain reorganization at #{}: {} blocks decanonized, {} blocks canonized", reorg.ancestor, reorg.decanonized_depth, reorg.canonized_depth);
+                    self.reorgs += 1;
+                    self.last_reorg_depth = reorg.decanonized_depth;
+                    if let Some(ref listener) = self.listener {
+                        listener.on_chain_reorganized(reorg);
+                    }
+                }
 
                 // notify listener
                 if let Some(best_block_hash) = insert_result.canonized_blocks_hashes.last() {
@@ -1205,7 +1451,7 @@ where
                 }
 
                 // awake threads, waiting for this block insertion
-                self.awake_waiting_threads(&block_hash);
+                self.awake_waiting_futures(&block_hash);
 
                 // continue with synchronization
                 self.execute_synchronization_tasks(None, None);
@@ -1224,6 +1470,13 @@ where
                 // Vec::with_capacity(insert_result.transactions_to_reverify.len());
                 Some(verification_tasks)
             }
+            Err(storage::Error::Duplicate(_)) => {
+                // block has been inserted by someone else (i.e. it has been submitted
+                // by miner && received from peer at the same time) => nothing to do
+                info!(target: "sync", "Block {} is already in the storage", block_hash.to_reversed_str());
+                self.awake_waiting_futures(&block_hash);
+                Some(Vec::new())
+            }
             Err(e) => {
                 // process as irrecoverable failure
                 panic!(
@@ -1261,14 +1514,170 @@ where
         self.chain.mark_dead_end_block(hash);
 
         // awake threads, waiting for this block insertion
-        self.awake_waiting_threads(hash);
+        self.awake_waiting_futures(hash);
 
         // start new tasks
         self.execute_synchronization_tasks(None, None);
     }
 
-    /// Execute futures, which were waiting for this block verification
-    fn awake_waiting_threads(&mut self, hash: &H256) {
+    fn on_headers_verification_success(
+        &mut self,
+        peer_index: PeerIndex,
+        headers: Vec<IndexedBlockHeader>,
+    ) {
+        // headers from disconnected peer are ignored
+        let has_ignored_headers = match self.verifying_headers_peers.remove(&peer_index) {
+            Some(has_ignored_headers) => has_ignored_headers,
+            None => return,
+        };
+
+        self.schedule_verified_headers(peer_index, headers);
+
+        // ask for headers, which have been ignored while verifying
+        if has_ignored_headers {
+            self.request_ignored_headers(peer_index);
+        }
+    }
+
+    fn on_headers_verification_error(
+        &mut self,
+        peer_index: PeerIndex,
+        err: &str,
+        headers: Vec<IndexedBlockHeader>,
+        error_index: usize,
+    ) {
+        let hash = &headers[error_index].hash;
+        if let Some(has_ignored_headers) = self.verifying_headers_peers.remove(&peer_index) {
+            if self.config.close_connection_on_bad_block {
+                self.peers.misbehaving(
+                    peer_index,
+                    &format!(
+                        "Error verifying header {} from `headers`: {}",
+                        hash.to_reversed_str(),
+                        err
+                    ),
+                );
+            } else {
+                warn!(target: "sync", "Error verifying header {} from `headers` message: {}", hash.to_reversed_str(), err);
+                if has_ignored_headers {
+                    self.request_ignored_headers(peer_index);
+                }
+            }
+        }
+
+        // mark failed header as dead end (this branch won't be synchronized)
+        self.chain.mark_dead_end_block(hash);
+    }
+
+    /// Schedule headers, which have been verified by headers verifier
+    fn schedule_verified_headers(
+        &mut self,
+        peer_index: PeerIndex,
+        mut headers: Vec<IndexedBlockHeader>,
+    ) {
+        // chain could have been changed while headers were verified => only schedule unknown headers
+        let first_unknown_index = match headers
+            .iter()
+            .position(|header| self.chain.block_state(&header.hash) == BlockState::Unknown)
+        {
+            Some(first_unknown_index) => first_unknown_index,
+            None => {
+                trace!(target: "sync", "Ignoring {} verified headers from peer#{} - all are known", headers.len(), peer_index);
+                self.peers_tasks.useful_peer(peer_index);
+                return;
+            }
+        };
+        let parent_hash = &headers[first_unknown_index].raw.previous_header_hash;
+        match self.chain.block_state(parent_hash) {
+            BlockState::Unknown | BlockState::DeadEnd => {
+                trace!(target: "sync", "Ignoring {} verified headers from peer#{} - parent {} is not in the chain", headers.len(), peer_index, parent_hash.to_reversed_str());
+                return;
+            }
+            _ => (),
+        }
+
+        let new_headers = headers.split_off(first_unknown_index);
+        self.schedule_blocks_headers(peer_index, new_headers);
+    }
+
+    /// Ask peer for headers, which have been ignored while its previous headers were verifying
+    fn request_ignored_headers(&mut self, peer_index: PeerIndex) {
+        // peer could have been already asked when verified headers were scheduled
+        if !self
+            .peers_tasks
+            .idle_peers_for_headers()
+            .contains(&peer_index)
+        {
+            return;
+        }
+
+        let block_locator_hashes = self.chain.block_locator_hashes();
+        self.executor.execute(Task::GetHeaders(
+            peer_index,
+            types::GetHeaders::with_block_locator_hashes(block_locator_hashes),
+        ));
+        self.peers_tasks.on_headers_requested(peer_index);
+    }
+
+    /// Forget orphaned blocks, evicted from the full orphans pool && penalize peer,
+    /// which is flooding us with orphaned blocks
+    fn on_orphans_evicted(&mut self, peer_index: PeerIndex, evicted: Vec<H256>) {
+        if evicted.is_empty() {
+            return;
+        }
+
+        trace!(target: "sync", "Evicted {} orphaned blocks. Orphans pool uses {} bytes", evicted.len(), self.orphaned_blocks_pool.memory_usage());
+        for hash in &evicted {
+            self.chain.forget_block(hash);
+        }
+
+        if self.orphaned_blocks_pool.is_flooding_peer(peer_index) {
+            warn!(target: "sync", "Peer#{} is flooding us with orphaned blocks", peer_index);
+            if self.peers_tasks.penalize(peer_index) {
+                self.peers
+                    .misbehaving(peer_index, "Too many orphaned blocks.");
+            }
+        }
+    }
+
+    /// Schedule verified headers && request blocks
+    fn schedule_blocks_headers(&mut self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>) {
+        // report progress
+        trace!(target: "sync", "New {} headers from peer#{}. First {:?}, last: {:?}",
+            headers.len(),
+            peer_index,
+            headers[0].hash.to_reversed_str(),
+            headers[headers.len() - 1].hash.to_reversed_str()
+        );
+
+        let is_scheduled_all = self.chain.schedule_blocks_headers(headers);
+
+        // switch to synchronization state
+        if !self.state.is_synchronizing() {
+            if self.chain.length_of_blocks_state(BlockState::Scheduled)
+                + self.chain.length_of_blocks_state(BlockState::Requested)
+                == 1
+            {
+                self.switch_to_nearly_saturated_state();
+            } else {
+                self.switch_to_synchronization_state();
+            }
+        }
+
+        if is_scheduled_all {
+            // this peers has supplied us with new headers => useful indeed
+            self.peers_tasks.useful_peer(peer_index);
+        } else {
+            // we can't hold more headers in memory => do not ask this peer for new
+            // headers until it'll become useful again
+            self.peers_tasks.unuseful_peer(peer_index);
+        }
+        // and execute tasks
+        self.execute_synchronization_tasks(None, None);
+    }
+
+    /// Resolve futures, which were waiting for this block verification
+    fn awake_waiting_futures(&mut self, hash: &H256) {
         // find a peer, which has supplied us with this block
         if let Entry::Occupied(block_entry) = self.verifying_blocks_by_peer.entry(hash.clone()) {
             let peer_index = *block_entry.get();
@@ -1277,11 +1686,12 @@ where
                 let is_last_block = {
                     let &mut (ref mut waiting, ref mut futures) = entry.get_mut();
                     waiting.remove(hash);
-                    // if this is the last block => awake waiting threads
+                    // if this is the last block => awake waiting futures
                     let is_last_block = waiting.is_empty();
                     if is_last_block {
-                        for future in futures.drain(..) {
-                            future.wait().expect("no-error future");
+                        for sender in futures.drain(..) {
+                            // receiver may be already dropped
+                            let _ = sender.send(());
                         }
                     }
                     is_last_block
@@ -1313,9 +1723,12 @@ pub mod tests {
     extern crate test_data;
 
     use super::super::SyncListener;
-    use super::{ClientCore, Config, CoreVerificationSink, SynchronizationClientCore};
+    use super::{
+        blocks_window_chunks, ClientCore, Config, CoreVerificationSink, SynchronizationClientCore,
+    };
     use chain::Block;
     use db::BlockChainDatabase;
+    use futures::Future;
     use inbound_connection::tests::DummyOutboundSyncConnection;
     use message::common::InventoryVector;
     use message::{types, Services};
@@ -1323,20 +1736,28 @@ pub mod tests {
     use parking_lot::Mutex;
     use primitives::hash::H256;
     use std::sync::Arc;
-    use synchronization_chain::Chain;
+    use synchronization_chain::{BlockState, Chain, ReorgInfo};
     use synchronization_client::{Client, SynchronizationClient};
     use synchronization_executor::tests::DummyTaskExecutor;
     use synchronization_executor::Task;
     use synchronization_peers::PeersImpl;
-    use synchronization_verifier::tests::DummyVerifier;
+    use synchronization_verifier::tests::{DummyHeadersVerifier, DummyVerifier};
     use types::{ClientCoreRef, PeerIndex, StorageRef, SynchronizationStateRef};
-    use utils::SynchronizationState;
+    use utils::{SynchronizationState, DEFAULT_ORPHANS_MEMORY_LIMIT};
     use verification::BackwardsCompatibleChainVerifier as ChainVerifier;
 
+    #[derive(Debug, PartialEq)]
+    enum PeerEvent {
+        Connected(PeerIndex, String),
+        Disconnected(PeerIndex),
+    }
+
     #[derive(Default)]
     struct DummySyncListenerData {
         pub is_synchronizing: bool,
         pub best_blocks: Vec<H256>,
+        pub reorgs: Vec<ReorgInfo>,
+        pub peer_events: Vec<PeerEvent>,
     }
 
     struct DummySyncListener {
@@ -1357,6 +1778,24 @@ pub mod tests {
         fn best_storage_block_inserted(&self, block_hash: &H256) {
             self.data.lock().best_blocks.push(block_hash.clone());
         }
+
+        fn on_chain_reorganized(&self, reorg: &ReorgInfo) {
+            self.data.lock().reorgs.push(reorg.clone());
+        }
+
+        fn on_peer_connected(&self, peer_index: PeerIndex, addr: String) {
+            self.data
+                .lock()
+                .peer_events
+                .push(PeerEvent::Connected(peer_index, addr));
+        }
+
+        fn on_peer_disconnected(&self, peer_index: PeerIndex) {
+            self.data
+                .lock()
+                .peer_events
+                .push(PeerEvent::Disconnected(peer_index));
+        }
     }
 
     fn create_sync(
@@ -1365,7 +1804,7 @@ pub mod tests {
     ) -> (
         Arc<DummyTaskExecutor>,
         ClientCoreRef<SynchronizationClientCore<DummyTaskExecutor>>,
-        Arc<SynchronizationClient<DummyTaskExecutor, DummyVerifier>>,
+        Arc<SynchronizationClient<DummyTaskExecutor, DummyVerifier, DummyHeadersVerifier>>,
     ) {
         let sync_peers = Arc::new(PeersImpl::default());
         let storage = match storage {
@@ -1380,6 +1819,8 @@ pub mod tests {
         let executor = DummyTaskExecutor::new();
         let config = Config {
             close_connection_on_bad_block: true,
+            orphans_memory_limit: DEFAULT_ORPHANS_MEMORY_LIMIT,
+            relay: true,
         };
 
         let chain_verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
@@ -1389,17 +1830,20 @@ pub mod tests {
             sync_peers.clone(),
             executor.clone(),
             chain,
-            chain_verifier.clone(),
         );
         {
             client_core.lock().set_verify_headers(false);
         }
+        let sink = Arc::new(CoreVerificationSink::new(client_core.clone()));
+        let mut headers_verifier = DummyHeadersVerifier::default();
+        headers_verifier.set_sink(sink.clone());
+        headers_verifier.set_verifier(chain_verifier.clone());
         let mut verifier = verifier.unwrap_or_default();
-        verifier.set_sink(Arc::new(CoreVerificationSink::new(client_core.clone())));
+        verifier.set_sink(sink);
         verifier.set_storage(storage);
         verifier.set_verifier(chain_verifier);
 
-        let client = SynchronizationClient::new(client_core.clone(), verifier);
+        let client = SynchronizationClient::new(client_core.clone(), verifier, headers_verifier);
         (executor, client_core, client)
     }
 
@@ -1431,7 +1875,7 @@ pub mod tests {
     fn synchronization_request_inventory_on_sync_start() {
         let (executor, _, sync) = create_sync(None, None);
         // start sync session
-        sync.on_connect(0);
+        sync.on_connect(0, "test".into());
         // => ask for inventory
         let tasks = executor.take_tasks();
         assert_eq!(tasks, vec![request_block_headers_genesis(0)]);
@@ -1445,6 +1889,21 @@ pub mod tests {
         assert_eq!(info.orphaned_blocks, 0);
     }
 
+    #[test]
+    fn on_headers_empty() {
+        let (executor, core, sync) = create_sync(None, None);
+        core.lock()
+            .peers
+            .insert(1, Services::default(), DummyOutboundSyncConnection::new());
+
+        sync.on_headers(1, vec![]);
+
+        assert!(executor.take_tasks().is_empty());
+        assert_eq!(core.lock().information().chain.scheduled, 0);
+        assert_eq!(core.lock().information().chain.requested, 0);
+        assert!(core.lock().peers.enumerate().contains(&1));
+    }
+
     #[test]
     fn synchronization_in_order_block_path_nearly_saturated() {
         let (executor, core, sync) = create_sync(None, None);
@@ -1491,6 +1950,70 @@ pub mod tests {
         assert_eq!(core.lock().information().peers_tasks.active, 0);
     }
 
+    #[test]
+    fn synchronization_requests_blocks_from_full_peers_only() {
+        let (executor, core, sync) = create_sync(None, None);
+        core.lock()
+            .peers
+            .insert(1, Services::default(), DummyOutboundSyncConnection::new());
+        core.lock().peers.insert(
+            2,
+            Services::default().with_network(true),
+            DummyOutboundSyncConnection::new(),
+        );
+
+        // limited peer is asked for headers, but not for blocks
+        let block1: Block = test_data::block_h1();
+        sync.on_headers(1, vec![block1.block_header.clone().into()]);
+        assert_eq!(
+            executor.take_tasks(),
+            vec![request_block_headers_genesis_and(1, vec![block1.hash()])]
+        );
+        assert_eq!(core.lock().information().chain.scheduled, 1);
+
+        // full peer is asked for scheduled blocks
+        sync.on_headers(2, vec![block1.block_header.clone().into()]);
+        sync.on_disconnect(1);
+        let tasks = executor.take_tasks();
+        assert!(tasks.contains(&request_blocks(2, vec![block1.hash()])));
+        assert_eq!(core.lock().information().chain.requested, 1);
+    }
+
+    #[test]
+    fn synchronization_metrics_are_consistent() {
+        let (_, core, sync) = create_sync(None, None);
+
+        sync.on_headers(
+            5,
+            vec![
+                test_data::block_h1().block_header.into(),
+                test_data::block_h2().block_header.into(),
+            ],
+        );
+        let metrics = core.lock().metrics();
+        assert_eq!(metrics.state, core.lock().state().name());
+        assert_eq!(metrics.requested, 2);
+        assert_eq!(metrics.stored, 1);
+        assert_eq!(metrics.peers_active, 1);
+        assert_eq!(
+            metrics.scheduled + metrics.requested + metrics.verifying + metrics.stored,
+            metrics.total_headers_seen
+        );
+
+        sync.on_block(5, test_data::block_h1().into());
+        sync.on_block(5, test_data::block_h2().into());
+        let metrics = core.lock().metrics();
+        assert_eq!(metrics.requested, 0);
+        assert_eq!(metrics.stored, 3);
+        assert_eq!(metrics.orphaned, 0);
+        assert_eq!(metrics.last_block_height, 2);
+        assert_eq!(metrics.last_block_hash, test_data::block_h2().hash());
+        assert_eq!(
+            metrics.scheduled + metrics.requested + metrics.verifying + metrics.stored,
+            metrics.total_headers_seen
+        );
+    }
+
     #[test]
     fn synchronization_out_of_order_block_path() {
         let (_, core, sync) = create_sync(None, None);
@@ -1844,11 +2367,12 @@ pub mod tests {
             assert_eq!(chain.best_storage_block().number, 2);
         }
 
+        // fork2 has the same length, but more work
         sync.on_block(2, fork2[1].clone().into());
         {
             let mut core = core.lock();
             let chain = core.chain();
-            assert_eq!(chain.best_storage_block().hash, fork1[1].hash());
+            assert_eq!(chain.best_storage_block().hash, fork2[1].hash());
             assert_eq!(chain.best_storage_block().number, 2);
         }
 
@@ -2435,6 +2959,71 @@ pub mod tests {
         assert!(!core.lock().peers.enumerate().contains(&0));
     }
 
+    #[test]
+    fn headers_are_scheduled_after_verification() {
+        let (executor, core, sync) = create_sync(None, None);
+        core.lock().set_verify_headers(true);
+
+        let block1: Block = test_data::block_h1();
+        sync.on_headers(5, vec![block1.block_header.clone().into()]);
+        let tasks = executor.take_tasks();
+        assert_eq!(
+            tasks,
+            vec![
+                request_block_headers_genesis_and(5, vec![block1.hash()]),
+                request_blocks(5, vec![block1.hash()])
+            ]
+        );
+        assert_eq!(core.lock().information().chain.requested, 1);
+    }
+
+    #[test]
+    fn headers_received_while_verifying_are_requested_again() {
+        let (executor, core, _) = create_sync(None, None);
+        core.lock().set_verify_headers(true);
+
+        let block1: Block = test_data::block_h1();
+        let block2: Block = test_data::block_h2();
+        let headers1 = core
+            .lock()
+            .on_headers(5, vec![block1.block_header.clone().into()])
+            .expect("headers are passed to verifier");
+        assert!(core
+            .lock()
+            .on_headers(5, vec![block2.block_header.clone().into()])
+            .is_none());
+        assert_eq!(core.lock().verifying_headers_peers.get(&5), Some(&true));
+
+        // verified headers are known => peer is not asked when headers are scheduled
+        core.lock().chain.mark_dead_end_block(&block1.hash());
+        executor.take_tasks();
+        core.lock().on_headers_verification_success(5, headers1);
+        assert_eq!(
+            executor.take_tasks(),
+            vec![request_block_headers_genesis(5)]
+        );
+        assert!(core.lock().verifying_headers_peers.is_empty());
+    }
+
+    #[test]
+    fn collection_closed_on_providing_header_with_wrong_pow() {
+        let mut header = test_data::block_h1().block_header;
+        header.bits = 0.into();
+        let hash = header.hash();
+
+        let (_, core, sync) = create_sync(None, None);
+        core.lock().set_verify_headers(true);
+        core.lock()
+            .peers
+            .insert(0, Services::default(), DummyOutboundSyncConnection::new());
+        assert!(core.lock().peers.enumerate().contains(&0));
+
+        sync.on_headers(0, vec![header.into()]);
+
+        assert!(!core.lock().peers.enumerate().contains(&0));
+        assert_eq!(core.lock().chain().block_state(&hash), BlockState::DeadEnd);
+    }
+
     #[test]
     fn collection_closed_on_providing_dead_end_block() {
         let genesis = test_data::genesis();
@@ -2589,4 +3178,146 @@ pub mod tests {
         assert_eq!(data.lock().is_synchronizing, false);
         assert_eq!(data.lock().best_blocks.len(), 3);
     }
+
+    #[test]
+    fn after_peer_nearly_blocks_verified_resolves_without_verifying_blocks() {
+        let (_, _, sync) = create_sync(None, None);
+        assert_eq!(sync.after_peer_nearly_blocks_verified(0).wait(), Ok(()));
+    }
+
+    #[test]
+    fn sync_listener_reorg_calls() {
+        let genesis = test_data::genesis();
+        let (_, core, sync) = create_sync(None, None);
+
+        let data = Arc::new(Mutex::new(DummySyncListenerData::default()));
+        sync.install_sync_listener(Box::new(DummySyncListener::new(data.clone())));
+
+        let fork1 = test_data::build_n_empty_blocks_from(1, 10, &genesis.block_header);
+        let fork2 = test_data::build_n_empty_blocks_from(1, 20, &genesis.block_header);
+        sync.on_headers(0, vec![fork1[0].block_header.clone().into()]);
+        sync.on_headers(1, vec![fork2[0].block_header.clone().into()]);
+
+        sync.on_block(0, fork1[0].clone().into());
+        assert!(data.lock().reorgs.is_empty());
+        assert_eq!(core.lock().metrics().reorgs, 0);
+
+        // fork2 has the same length, but more work
+        sync.on_block(1, fork2[0].clone().into());
+        assert_eq!(
+            data.lock().reorgs,
+            vec![ReorgInfo {
+                ancestor: 0,
+                decanonized_blocks_hashes: vec![fork1[0].hash()],
+                canonized_blocks_hashes: vec![fork2[0].hash()],
+                decanonized_depth: 1,
+                canonized_depth: 1,
+            }]
+        );
+        assert_eq!(
+            data.lock().best_blocks,
+            vec![fork1[0].hash(), fork2[0].hash()]
+        );
+        let metrics = core.lock().metrics();
+        assert_eq!(metrics.reorgs, 1);
+        assert_eq!(metrics.last_reorg_depth, 1);
+    }
+
+    #[test]
+    fn sync_listener_peer_calls() {
+        let (_, _, sync) = create_sync(None, None);
+
+        let data = Arc::new(Mutex::new(DummySyncListenerData::default()));
+        sync.install_sync_listener(Box::new(DummySyncListener::new(data.clone())));
+
+        sync.on_connect(0, "127.0.0.1:8333".into());
+        sync.on_connect(1, "127.0.0.1:8334".into());
+        sync.on_disconnect(0);
+
+        assert_eq!(
+            data.lock().peer_events,
+            vec![
+                PeerEvent::Connected(0, "127.0.0.1:8333".into()),
+                PeerEvent::Connected(1, "127.0.0.1:8334".into()),
+                PeerEvent::Disconnected(0),
+            ]
+        );
+    }
+
+    #[test]
+    fn synchronization_requests_headers_from_all_peers() {
+        let (executor, core, sync) = create_sync(None, None);
+
+        sync.on_connect(1, "1".into());
+        sync.on_connect(2, "2".into());
+        executor.take_tasks();
+
+        core.lock().request_headers_from_all_peers(None);
+        let tasks = executor.take_tasks();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.contains(&request_block_headers_genesis(1)));
+        assert!(tasks.contains(&request_block_headers_genesis(2)));
+
+        core.lock().request_headers_from_all_peers(Some(2));
+        assert_eq!(
+            executor.take_tasks(),
+            vec![
+                request_block_headers_genesis(2),
+                request_block_headers_genesis(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn blocks_window_is_split_by_peers_speed() {
+        // small window => fastest peer is asked for all blocks
+        assert_eq!(
+            blocks_window_chunks(&[None, None], 10, 32, 128),
+            vec![10, 0]
+        );
+        // unknown speeds => window is split equally
+        assert_eq!(
+            blocks_window_chunks(&[None, None], 100, 8, 128),
+            vec![50, 50]
+        );
+        // fast peer is asked for more blocks than the slow one
+        assert_eq!(
+            blocks_window_chunks(&[Some(30f64), Some(10f64)], 100, 8, 128),
+            vec![75, 25]
+        );
+        // fastest peer is asked for at least min_chunk blocks
+        assert_eq!(
+            blocks_window_chunks(&[None, None], 20, 16, 128),
+            vec![16, 4]
+        );
+        // peer with unknown speed is treated as average one
+        assert_eq!(
+            blocks_window_chunks(&[Some(30f64), None, Some(10f64)], 90, 8, 128),
+            vec![45, 30, 15]
+        );
+        // rounding leftovers are requested from the fastest peer
+        assert_eq!(
+            blocks_window_chunks(&[None, None, None], 100, 8, 128),
+            vec![34, 33, 33]
+        );
+    }
+
+    #[test]
+    fn blocks_window_chunks_are_limited() {
+        // fast peer is asked for at most max_chunk blocks, slow peer is asked for min_chunk blocks
+        assert_eq!(
+            blocks_window_chunks(&[Some(100f64), Some(1f64)], 160, 32, 128),
+            vec![128, 32]
+        );
+        // leftovers are requested from the fastest peers, up to max_chunk blocks
+        assert_eq!(
+            blocks_window_chunks(&[Some(100f64), Some(1f64), Some(1f64)], 200, 32, 128),
+            vec![128, 40, 32]
+        );
+        // blocks, which do not fit into max_chunk of every peer, are not requested
+        assert_eq!(
+            blocks_window_chunks(&[Some(100f64), Some(1f64)], 300, 32, 128),
+            vec![128, 128]
+        );
+    }
 }
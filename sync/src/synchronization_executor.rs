@@ -125,8 +125,11 @@ impl LocalSynchronizationTaskExecutor {
                         None,
                     );
                 }
-                BlockAnnouncementType::DoNotAnnounce => (),
+                BlockAnnouncementType::DoNotAnnounce => continue,
             }
+            // do not announce the same block twice
+            self.peers
+                .hash_known_as(peer_index, block.hash().clone(), KnownHashType::Block);
         }
     }
 }
@@ -216,6 +219,8 @@ pub mod tests {
         let c2 = DummyOutboundSyncConnection::new();
         peers.insert(2, Services::default(), c2.clone());
         peers.set_block_announcement_type(2, BlockAnnouncementType::SendHeaders);
+        assert!(!peers.supports_send_headers(1));
+        assert!(peers.supports_send_headers(2));
 
         executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
         assert_eq!(
@@ -230,4 +235,41 @@ pub mod tests {
             1
         );
     }
+
+    #[test]
+    fn relay_new_block_once() {
+        let peers = Arc::new(PeersImpl::default());
+        let executor = LocalSynchronizationTaskExecutor::new(peers.clone());
+
+        let c1 = DummyOutboundSyncConnection::new();
+        peers.insert(1, Services::default(), c1.clone());
+
+        executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
+        executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
+        assert_eq!(
+            *c1.messages
+                .lock()
+                .entry("inventory".to_owned())
+                .or_insert(0),
+            1
+        );
+    }
+
+    #[test]
+    fn do_not_relay_new_block_when_peer_opted_out() {
+        let peers = Arc::new(PeersImpl::default());
+        let executor = LocalSynchronizationTaskExecutor::new(peers.clone());
+
+        let c1 = DummyOutboundSyncConnection::new();
+        peers.insert(1, Services::default(), c1.clone());
+        let c2 = DummyOutboundSyncConnection::new();
+        peers.insert(2, Services::default(), c2.clone());
+        peers.set_block_announcement_type(2, BlockAnnouncementType::SendHeaders);
+        peers.set_relay(1, false);
+        peers.set_relay(2, false);
+
+        executor.execute(Task::RelayNewBlock(test_data::genesis().into()));
+        assert!(c1.messages.lock().is_empty());
+        assert!(c2.messages.lock().is_empty());
+    }
 }
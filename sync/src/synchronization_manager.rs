@@ -25,6 +25,14 @@ const DEFAULT_TRUSTED_PEER_HEADERS_FAILURE_INTERVAL_MS: u32 = 20 * 1000;
 const DEFAULT_UNKNOWN_BLOCK_REMOVAL_TIME_MS: u32 = 20 * 60 * 1000;
 /// Maximal number of orphaned blocks
 const DEFAULT_UNKNOWN_BLOCKS_MAX_LEN: usize = 16;
+/// Time after which orphaned block is considered to be from a dead fork (in seconds)
+const ORPHAN_MAX_AGE_S: f64 = 60_f64 * 60_f64;
+/// Interval between two stalled synchronization checks (in seconds)
+const STALL_CHECK_INTERVAL_S: f64 = 5_f64 * 60_f64;
+/// Time without new blocks, after which synchronization is considered stalled (in seconds)
+const STALL_TIMEOUT_S: u64 = 60 * 60;
+/// Interval between headers requests, when peers do not announce new blocks to us (in seconds)
+const HEADERS_POLL_INTERVAL_S: f64 = 60_f64;
 
 /// Synchronization management worker
 pub struct ManagementWorker {
@@ -59,6 +67,8 @@ impl ManagementWorker {
     ) {
         let peers_config = ManagePeersConfig::default();
         let unknown_config = ManageUnknownBlocksConfig::default();
+        let mut last_stall_check = precise_time_s();
+        let mut last_headers_poll = precise_time_s();
 
         loop {
             let mut lock = is_stopping.lock();
@@ -88,6 +98,7 @@ impl ManagementWorker {
             let mut core = core.lock();
             // trace synchronization state
             core.print_synchronization_information();
+            core.update_metrics();
             // execute management tasks if not saturated
             if core.state().is_synchronizing() || core.state().is_nearly_saturated() {
                 let (blocks_to_request, blocks_to_forget) = manage_synchronization_peers_blocks(
@@ -114,7 +125,35 @@ impl ManagementWorker {
                     core.peers(),
                     core.peers_tasks(),
                 );
+
+                // re-request headers if no blocks were inserted for too long
+                let now = precise_time_s();
+                if now - last_stall_check >= STALL_CHECK_INTERVAL_S {
+                    last_stall_check = now;
+                    if manage_stalled_synchronization(
+                        STALL_TIMEOUT_S,
+                        core.elapsed_since_last_block_secs(),
+                    ) {
+                        // long-lived peers are more likely to be on the canonical chain
+                        let preferred_peer = core
+                            .peers()
+                            .longest_connected_peer()
+                            .map(|(peer_index, _)| peer_index);
+                        core.request_headers_from_all_peers(preferred_peer);
+                    }
+                }
             } else {
+                // peers do not announce new blocks when relay is disabled => poll them for headers
+                let now = precise_time_s();
+                if manage_headers_polling(
+                    core.is_relay_enabled(),
+                    HEADERS_POLL_INTERVAL_S,
+                    now - last_headers_poll,
+                ) {
+                    last_headers_poll = now;
+                    core.request_headers_from_all_peers(None);
+                }
+
                 // only remove orphaned blocks when not in synchronization state
                 if let Some(orphans_to_remove) =
                     manage_unknown_orphaned_blocks(&unknown_config, core.orphaned_blocks_pool())
@@ -124,6 +163,14 @@ impl ManagementWorker {
                     }
                 }
             }
+
+            // remove orphaned blocks, which are waiting for parent for too long
+            let old_orphans = core
+                .orphaned_blocks_pool()
+                .evict_old_orphans(ORPHAN_MAX_AGE_S);
+            for old_orphan in old_orphans {
+                core.chain().forget_block(&old_orphan);
+            }
         }
 
         trace!(target: "sync", "Stopping sync management thread");
@@ -277,6 +324,28 @@ pub fn manage_synchronization_peers_headers(
     }
 }
 
+/// Check if synchronization has stalled, i.e. no blocks were inserted for too long
+pub fn manage_stalled_synchronization(
+    stall_timeout_s: u64,
+    elapsed_since_last_block_secs: u64,
+) -> bool {
+    if elapsed_since_last_block_secs <= stall_timeout_s {
+        return false;
+    }
+
+    warn!(target: "sync", "No new blocks for {} seconds. Synchronization appears stalled, requesting headers from all peers.", elapsed_since_last_block_secs);
+    true
+}
+
+/// Check if peers must be polled for new headers, because they do not announce new blocks to us
+pub fn manage_headers_polling(
+    relay: bool,
+    poll_interval_s: f64,
+    elapsed_since_last_poll_s: f64,
+) -> bool {
+    !relay && elapsed_since_last_poll_s >= poll_interval_s
+}
+
 /// Manage unknown orphaned blocks
 pub fn manage_unknown_orphaned_blocks(
     config: &ManageUnknownBlocksConfig,
@@ -325,8 +394,9 @@ mod tests {
     extern crate test_data;
 
     use super::{
+        manage_headers_polling, manage_stalled_synchronization,
         manage_synchronization_peers_blocks, manage_unknown_orphaned_blocks, ManagePeersConfig,
-        ManageUnknownBlocksConfig,
+        ManageUnknownBlocksConfig, HEADERS_POLL_INTERVAL_S, STALL_TIMEOUT_S,
     };
     use primitives::hash::H256;
     use std::sync::Arc;
@@ -397,7 +467,7 @@ mod tests {
         };
         let mut pool = OrphanBlocksPool::new();
         let block = test_data::genesis();
-        pool.insert_unknown_block(block.into());
+        pool.insert_unknown_block(None, block.into());
         assert_eq!(manage_unknown_orphaned_blocks(&config, &mut pool), None);
         assert_eq!(pool.len(), 1);
     }
@@ -413,7 +483,7 @@ mod tests {
         let mut pool = OrphanBlocksPool::new();
         let block = test_data::genesis();
         let block_hash = block.hash();
-        pool.insert_unknown_block(block.into());
+        pool.insert_unknown_block(None, block.into());
         sleep(Duration::from_millis(1));
 
         assert_eq!(
@@ -433,12 +503,44 @@ mod tests {
         let block1 = test_data::genesis();
         let block1_hash = block1.hash();
         let block2 = test_data::block_h2();
-        pool.insert_unknown_block(block1.into());
-        pool.insert_unknown_block(block2.into());
+        pool.insert_unknown_block(None, block1.into());
+        pool.insert_unknown_block(None, block2.into());
         assert_eq!(
             manage_unknown_orphaned_blocks(&config, &mut pool),
             Some(vec![block1_hash])
         );
         assert_eq!(pool.len(), 1);
     }
+
+    #[test]
+    fn manage_stalled_synchronization_after_timeout() {
+        assert!(!manage_stalled_synchronization(STALL_TIMEOUT_S, 0));
+        assert!(!manage_stalled_synchronization(
+            STALL_TIMEOUT_S,
+            STALL_TIMEOUT_S
+        ));
+        assert!(manage_stalled_synchronization(
+            STALL_TIMEOUT_S,
+            STALL_TIMEOUT_S + 1
+        ));
+    }
+
+    #[test]
+    fn manage_headers_polling_when_relay_is_disabled() {
+        assert!(!manage_headers_polling(
+            true,
+            HEADERS_POLL_INTERVAL_S,
+            HEADERS_POLL_INTERVAL_S * 10f64
+        ));
+        assert!(!manage_headers_polling(
+            false,
+            HEADERS_POLL_INTERVAL_S,
+            0f64
+        ));
+        assert!(manage_headers_polling(
+            false,
+            HEADERS_POLL_INTERVAL_S,
+            HEADERS_POLL_INTERVAL_S
+        ));
+    }
 }
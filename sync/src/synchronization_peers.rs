@@ -4,11 +4,13 @@ use p2p::OutboundSyncConnectionRef;
 use parking_lot::RwLock;
 use primitives::hash::H256;
 use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
 use types::PeerIndex;
 use utils::{ConnectionFilter, KnownHashType};
 
 /// Block announcement type
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockAnnouncementType {
     /// Send inventory message with block hash [default behavior]
     SendInventory,
@@ -31,8 +33,14 @@ pub enum TransactionAnnouncementType {
 pub trait Peers: Send + Sync + PeersContainer + PeersFilters + PeersOptions {
     /// Require peers services.
     fn require_peer_services(&self, services: Services);
+    /// Get services, advertised by peer in version message
+    fn services(&self, peer_index: PeerIndex) -> Option<Services>;
+    /// Get peers, which have advertised all given services
+    fn peers_with_services(&self, services: Services) -> Vec<PeerIndex>;
     /// Get peer connection
     fn connection(&self, peer_index: PeerIndex) -> Option<OutboundSyncConnectionRef>;
+    /// Get addresses of seed peers, which are connected on startup
+    fn seed_addresses(&self) -> Vec<SocketAddr>;
 }
 
 /// Connected peers container
@@ -50,8 +58,12 @@ pub trait PeersContainer {
     fn remove(&self, peer_index: PeerIndex);
     /// Close and remove peer connection due to misbehaving
     fn misbehaving(&self, peer_index: PeerIndex, reason: &str);
-    /// Close and remove peer connection due to detected DOS attempt
+    /// Ban, close and remove peer connection due to detected DOS attempt
     fn dos(&self, peer_index: PeerIndex, reason: &str);
+    /// Get time since connection to the peer has been established
+    fn connection_duration(&self, peer_index: PeerIndex) -> Option<Duration>;
+    /// Get peer, which is connected for the longest time
+    fn longest_connected_peer(&self) -> Option<(PeerIndex, Duration)>;
 }
 
 /// Filters for peers connections
@@ -71,6 +83,8 @@ pub trait PeersFilters {
 
 /// Options for peers connections
 pub trait PeersOptions {
+    /// Has peer asked us to announce new blocks with headers (BIP 130)?
+    fn supports_send_headers(&self, peer_index: PeerIndex) -> bool;
     /// Set up new block announcement type for the connection
     fn set_block_announcement_type(
         &self,
@@ -83,6 +97,8 @@ pub trait PeersOptions {
         peer_index: PeerIndex,
         announcement_type: TransactionAnnouncementType,
     );
+    /// Enable or disable new blocks relay for the connection
+    fn set_relay(&self, peer_index: PeerIndex, relay: bool);
 }
 
 /// Single connected peer data
@@ -97,6 +113,8 @@ struct Peer {
     pub block_announcement_type: BlockAnnouncementType,
     /// Transaction announcement type
     pub transaction_announcement_type: TransactionAnnouncementType,
+    /// Time when connection has been established
+    pub connected_at: Instant,
 }
 
 /// Default implementation of connectd peers container
@@ -105,6 +123,17 @@ pub struct PeersImpl {
     /// All connected peers. Most of times this field is accessed, it is accessed in read mode.
     /// So this lock shouldn't be a performance problem.
     peers: RwLock<HashMap<PeerIndex, Peer>>,
+    /// Addresses of seed peers.
+    seed_addresses: Vec<SocketAddr>,
+}
+
+impl PeersImpl {
+    pub fn with_seeds(seed_addresses: Vec<SocketAddr>) -> Self {
+        PeersImpl {
+            peers: RwLock::default(),
+            seed_addresses: seed_addresses,
+        }
+    }
 }
 
 impl Peer {
@@ -115,6 +144,7 @@ impl Peer {
             filter: ConnectionFilter::default(),
             block_announcement_type: BlockAnnouncementType::SendInventory,
             transaction_announcement_type: TransactionAnnouncementType::SendInventory,
+            connected_at: Instant::now(),
         }
     }
 }
@@ -126,7 +156,7 @@ impl Peers for PeersImpl {
         let mut peers = self.peers.write();
         for peer_index in peers
             .iter()
-            .filter(|&(_, p)| p.services.includes(&services))
+            .filter(|&(_, p)| !p.services.includes(&services))
             .map(|(p, _)| *p)
             .collect::<Vec<_>>()
         {
@@ -140,12 +170,29 @@ impl Peers for PeersImpl {
         }
     }
 
+    fn services(&self, peer_index: PeerIndex) -> Option<Services> {
+        self.peers.read().get(&peer_index).map(|peer| peer.services)
+    }
+
+    fn peers_with_services(&self, services: Services) -> Vec<PeerIndex> {
+        self.peers
+            .read()
+            .iter()
+            .filter(|&(_, peer)| peer.services.includes(&services))
+            .map(|(peer_index, _)| *peer_index)
+            .collect()
+    }
+
     fn connection(&self, peer_index: PeerIndex) -> Option<OutboundSyncConnectionRef> {
         self.peers
             .read()
             .get(&peer_index)
             .map(|peer| peer.connection.clone())
     }
+
+    fn seed_addresses(&self) -> Vec<SocketAddr> {
+        self.seed_addresses.clone()
+    }
 }
 
 impl PeersContainer for PeersImpl {
@@ -182,10 +229,25 @@ impl PeersContainer for PeersImpl {
 
     fn dos(&self, peer_index: PeerIndex, reason: &str) {
         if let Some(peer) = self.peers.write().remove(&peer_index) {
-            warn!(target: "sync", "Disconnecting from peer#{} due to DoS: {}", peer_index, reason);
-            peer.connection.close();
+            warn!(target: "sync", "Banning peer#{} due to DoS: {}", peer_index, reason);
+            peer.connection.ban();
         }
     }
+
+    fn connection_duration(&self, peer_index: PeerIndex) -> Option<Duration> {
+        self.peers
+            .read()
+            .get(&peer_index)
+            .map(|peer| peer.connected_at.elapsed())
+    }
+
+    fn longest_connected_peer(&self) -> Option<(PeerIndex, Duration)> {
+        self.peers
+            .read()
+            .iter()
+            .min_by_key(|&(_, peer)| peer.connected_at)
+            .map(|(peer_index, peer)| (*peer_index, peer.connected_at.elapsed()))
+    }
 }
 
 impl PeersFilters for PeersImpl {
@@ -220,6 +282,14 @@ impl PeersFilters for PeersImpl {
 }
 
 impl PeersOptions for PeersImpl {
+    fn supports_send_headers(&self, peer_index: PeerIndex) -> bool {
+        self.peers
+            .read()
+            .get(&peer_index)
+            .map(|peer| peer.block_announcement_type == BlockAnnouncementType::SendHeaders)
+            .unwrap_or(false)
+    }
+
     fn set_block_announcement_type(
         &self,
         peer_index: PeerIndex,
@@ -239,4 +309,89 @@ impl PeersOptions for PeersImpl {
             peer.transaction_announcement_type = announcement_type;
         }
     }
+
+    fn set_relay(&self, peer_index: PeerIndex, relay: bool) {
+        if let Some(peer) = self.peers.write().get_mut(&peer_index) {
+            peer.filter.set_relay(relay);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Peers, PeersContainer, PeersImpl};
+    use inbound_connection::tests::DummyOutboundSyncConnection;
+    use message::Services;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn peers_connection_duration() {
+        let peers = PeersImpl::default();
+        assert_eq!(peers.connection_duration(1), None);
+        assert_eq!(peers.longest_connected_peer(), None);
+
+        peers.insert(1, Services::default(), DummyOutboundSyncConnection::new());
+        sleep(Duration::from_millis(10));
+        peers.insert(2, Services::default(), DummyOutboundSyncConnection::new());
+
+        let duration1 = peers.connection_duration(1).unwrap();
+        let duration2 = peers.connection_duration(2).unwrap();
+        assert!(duration1 >= Duration::from_millis(10));
+        assert!(duration1 > duration2);
+
+        let (peer_index, duration) = peers.longest_connected_peer().unwrap();
+        assert_eq!(peer_index, 1);
+        assert!(duration >= duration1);
+
+        peers.remove(1);
+        assert_eq!(peers.connection_duration(1), None);
+        assert_eq!(peers.longest_connected_peer().unwrap().0, 2);
+    }
+
+    #[test]
+    fn require_peer_services_disconnects_insufficient_peers() {
+        let peers = PeersImpl::default();
+        let network = Services::default().with_network(true);
+        peers.insert(1, Services::default(), DummyOutboundSyncConnection::new());
+        peers.insert(
+            2,
+            network.with_compact_blocks(true),
+            DummyOutboundSyncConnection::new(),
+        );
+
+        peers.require_peer_services(network);
+        assert_eq!(peers.enumerate(), vec![2]);
+    }
+
+    #[test]
+    fn peers_with_services() {
+        let peers = PeersImpl::default();
+        let randomness_index = Services::default().with_randomness_index(true);
+        peers.insert(1, Services::default(), DummyOutboundSyncConnection::new());
+        peers.insert(
+            2,
+            randomness_index.with_compact_blocks(true),
+            DummyOutboundSyncConnection::new(),
+        );
+
+        assert_eq!(peers.services(1), Some(Services::default()));
+        assert_eq!(peers.services(3), None);
+        assert_eq!(peers.peers_with_services(randomness_index), vec![2]);
+        let mut all_peers = peers.peers_with_services(Services::default());
+        all_peers.sort();
+        assert_eq!(all_peers, vec![1, 2]);
+    }
+
+    #[test]
+    fn peers_seed_addresses() {
+        assert!(PeersImpl::default().seed_addresses().is_empty());
+
+        let seeds = vec![
+            "127.0.0.1:8333".parse().unwrap(),
+            "127.0.0.2:8333".parse().unwrap(),
+        ];
+        let peers = PeersImpl::with_seeds(seeds.clone());
+        assert_eq!(peers.seed_addresses(), seeds);
+    }
 }
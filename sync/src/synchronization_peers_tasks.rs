@@ -4,7 +4,7 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use time::precise_time_s;
-use types::PeerIndex;
+use types::{BlockHeight, PeerIndex};
 use utils::AverageSpeedMeter;
 
 /// Max peer failures # before excluding from sync process
@@ -13,6 +13,18 @@ const MAX_PEER_FAILURES: usize = 4;
 const MAX_BLOCKS_FAILURES: usize = 6;
 /// Number of blocks to inspect while calculating average response time
 const BLOCKS_TO_INSPECT: usize = 32;
+/// Number of blocks requests to inspect while calculating average request latency
+const BLOCKS_REQUESTS_TO_INSPECT: usize = 8;
+/// Blocks requests are sized so that peer is expected to respond within this number of seconds
+const TARGET_BLOCKS_REQUEST_DURATION_S: f64 = 10_f64;
+/// Score of the peer component, which has not been measured yet
+const NEUTRAL_SCORE: f64 = 0.5;
+/// Number of seconds after last failure, after which peer failure is forgotten
+const FAILURE_FORGET_INTERVAL_S: f64 = 600_f64;
+/// Number of seconds of peer inactivity, after which its score is decayed
+const SCORE_DECAY_INTERVAL_S: f64 = 60_f64;
+/// Score decay per every SCORE_DECAY_INTERVAL_S of peer inactivity
+const SCORE_DECAY: f64 = 0.1;
 
 /// Information on synchronization peers
 pub struct Information {
@@ -79,8 +91,18 @@ pub struct PeerStats {
     failures: usize,
     /// Average block response time meter
     speed: AverageSpeedMeter,
+    /// Average time between blocks request and the first block of response
+    latency: AverageSpeedMeter,
     /// Peer trust level.
     trust: TrustLevel,
+    /// Number of blocks requested from peer
+    blocks_requested: usize,
+    /// Number of requested blocks received from peer
+    blocks_received: usize,
+    /// Time of last peer response
+    last_activity: f64,
+    /// Time of last peer failure
+    last_failure: Option<f64>,
 }
 
 /// Block statistics
@@ -124,26 +146,93 @@ impl PeersTasks {
         &self.idle_for_blocks
     }
 
-    /// Sort peers for blocks request
+    /// Sort peers for blocks request (best score first)
     pub fn sort_peers_for_blocks(&self, peers: &mut Vec<PeerIndex>) {
+        let now = precise_time_s();
         peers.sort_by(|left, right| {
-            let left_speed = self
-                .stats
-                .get(left)
-                .map(|s| s.speed.speed())
-                .unwrap_or(0f64);
-            let right_speed = self
-                .stats
-                .get(right)
-                .map(|s| s.speed.speed())
-                .unwrap_or(0f64);
-            // larger speed => better
-            right_speed
-                .partial_cmp(&left_speed)
+            let left_score = self.score_at(*left, now);
+            let right_score = self.score_at(*right, now);
+            // larger score => better
+            right_score
+                .partial_cmp(&left_score)
                 .unwrap_or(Ordering::Equal)
         })
     }
 
+    /// Get peer score: 0.0 for the worst peer, 1.0 for the best one.
+    pub fn score(&self, peer_index: PeerIndex) -> f64 {
+        self.score_at(peer_index, precise_time_s())
+    }
+
+    /// Get scores of all useful peers, sorted by score (best first).
+    pub fn score_all_peers(&self) -> Vec<(PeerIndex, f64)> {
+        let now = precise_time_s();
+        let mut scores: Vec<_> = self
+            .all
+            .difference(&self.unuseful)
+            .map(|peer_index| (*peer_index, self.score_at(*peer_index, now)))
+            .collect();
+        scores.sort_by(|left, right| {
+            // larger score => better
+            right
+                .1
+                .partial_cmp(&left.1)
+                .unwrap_or(Ordering::Equal)
+                .then(left.0.cmp(&right.0))
+        });
+        scores
+    }
+
+    /// Get at most `k` useful peers with the best score (best first).
+    pub fn top_k_peers(&self, k: usize) -> Vec<PeerIndex> {
+        self.score_all_peers()
+            .into_iter()
+            .take(k)
+            .map(|(peer_index, _)| peer_index)
+            .collect()
+    }
+
+    /// Get measured blocks download speed of the peer (blocks per second).
+    pub fn blocks_speed(&self, peer_index: PeerIndex) -> Option<f64> {
+        self.stats
+            .get(&peer_index)
+            .filter(|s| s.speed.inspected_items_len() != 0)
+            .map(|s| s.speed.speed())
+            .filter(|speed| *speed > 0f64)
+    }
+
+    /// Get measured latency of the peer blocks requests (seconds).
+    pub fn blocks_latency(&self, peer_index: PeerIndex) -> Option<f64> {
+        self.stats
+            .get(&peer_index)
+            .filter(|s| s.latency.inspected_items_len() != 0)
+            .map(|s| s.latency.speed())
+            .filter(|requests_per_second| *requests_per_second > 0f64)
+            .map(|requests_per_second| 1f64 / requests_per_second)
+    }
+
+    /// Get number of blocks to request from the peer, so that it is expected to respond
+    /// within `TARGET_BLOCKS_REQUEST_DURATION_S`. None if peer speed has not been measured yet.
+    pub fn blocks_request_size(
+        &self,
+        peer_index: PeerIndex,
+        min_blocks: BlockHeight,
+        max_blocks: BlockHeight,
+    ) -> Option<BlockHeight> {
+        self.blocks_speed(peer_index).map(|speed| {
+            let latency = self.blocks_latency(peer_index).unwrap_or(0f64);
+            adaptive_blocks_request_size(speed, latency, min_blocks, max_blocks)
+        })
+    }
+
+    /// Get peer score at given time.
+    fn score_at(&self, peer_index: PeerIndex, now: f64) -> f64 {
+        self.stats
+            .get(&peer_index)
+            .map(|s| s.score_at(now))
+            .unwrap_or(0f64)
+    }
+
     /// Get active headers requests, sorted by last response time (oldest first).
     pub fn ordered_headers_requests(&self) -> &LinkedHashMap<PeerIndex, HeadersRequest> {
         &self.headers_requests
@@ -232,7 +321,12 @@ impl PeersTasks {
                 br.failures -= 1;
             }
             br.trust = TrustLevel::Trusted;
-            br.speed.checkpoint()
+            br.blocks_received += 1;
+            br.last_activity = precise_time_s();
+            br.speed.checkpoint();
+            // only the first block of the request is measured
+            br.latency.checkpoint();
+            br.latency.stop();
         });
 
         // if it hasn't been last requested block => just return
@@ -267,6 +361,9 @@ impl PeersTasks {
         if !self.blocks_requests.contains_key(&peer_index) {
             self.idle_for_headers.insert(peer_index);
         }
+        if let Some(stats) = self.stats.get_mut(&peer_index) {
+            stats.last_activity = precise_time_s();
+        }
     }
 
     /// Blocks have been requested from peer.
@@ -281,6 +378,10 @@ impl PeersTasks {
         if !self.blocks_requests.contains_key(&peer_index) {
             self.blocks_requests
                 .insert(peer_index, BlocksRequest::new());
+            // start request latency meter
+            if let Some(stats) = self.stats.get_mut(&peer_index) {
+                stats.latency.start();
+            }
         }
         self.blocks_requests
             .get_mut(&peer_index)
@@ -288,8 +389,11 @@ impl PeersTasks {
             .blocks
             .extend(blocks_hashes.iter().cloned());
 
-        // no more requested blocks => pause requests speed meter
-        self.stats.get_mut(&peer_index).map(|br| br.speed.start());
+        // start requests speed meter
+        self.stats.get_mut(&peer_index).map(|br| {
+            br.blocks_requested += blocks_hashes.len();
+            br.speed.start()
+        });
     }
 
     /// Headers hashave been requested from peer.
@@ -347,6 +451,7 @@ impl PeersTasks {
         self.stats
             .get_mut(&peer_index)
             .map(|s| {
+                s.last_failure = Some(precise_time_s());
                 if s.trust == TrustLevel::Trusted {
                     s.failures += 1;
                     s.failures > MAX_PEER_FAILURES
@@ -400,7 +505,12 @@ impl PeerStats {
         PeerStats {
             failures: 0,
             speed: AverageSpeedMeter::with_inspect_items(BLOCKS_TO_INSPECT),
+            latency: AverageSpeedMeter::with_inspect_items(BLOCKS_REQUESTS_TO_INSPECT),
             trust: TrustLevel::Suspicious,
+            blocks_requested: 0,
+            blocks_received: 0,
+            last_activity: precise_time_s(),
+            last_failure: None,
         }
     }
 
@@ -412,6 +522,51 @@ impl PeerStats {
     pub fn set_trust(&mut self, trust: TrustLevel) {
         self.trust = trust;
     }
+
+    /// Compute peer score at given time.
+    fn score_at(&self, now: f64) -> f64 {
+        // faster responses => better
+        let latency_score = if self.speed.inspected_items_len() == 0 {
+            NEUTRAL_SCORE
+        } else {
+            let speed = self.speed.speed();
+            speed / (speed + 1f64)
+        };
+        // more requested blocks delivered => better
+        let delivery_score = if self.blocks_requested == 0 {
+            NEUTRAL_SCORE
+        } else {
+            (self.blocks_received as f64 / self.blocks_requested as f64).min(1f64)
+        };
+        // more time since last failure => better
+        let failure_score = match self.last_failure {
+            Some(last_failure) => ((now - last_failure) / FAILURE_FORGET_INTERVAL_S)
+                .max(0f64)
+                .min(1f64),
+            None => 1f64,
+        };
+        // inactive peer => decay score
+        let inactivity_intervals = ((now - self.last_activity) / SCORE_DECAY_INTERVAL_S)
+            .max(0f64)
+            .floor();
+
+        let score = (latency_score + delivery_score + failure_score) / 3f64
+            - SCORE_DECAY * inactivity_intervals;
+        score.max(0f64).min(1f64)
+    }
+}
+
+/// Number of blocks, which peer with given download speed (blocks per second) and request
+/// latency (seconds) is expected to deliver within `TARGET_BLOCKS_REQUEST_DURATION_S`.
+fn adaptive_blocks_request_size(
+    speed: f64,
+    latency: f64,
+    min_blocks: BlockHeight,
+    max_blocks: BlockHeight,
+) -> BlockHeight {
+    let transfer_duration = (TARGET_BLOCKS_REQUEST_DURATION_S - latency).max(0f64);
+    let blocks = (speed * transfer_duration).min(max_blocks as f64) as BlockHeight;
+    blocks.max(min_blocks).min(max_blocks)
 }
 
 impl fmt::Debug for Information {
@@ -426,7 +581,10 @@ impl fmt::Debug for Information {
 
 #[cfg(test)]
 mod tests {
-    use super::{PeersTasks, MAX_BLOCKS_FAILURES, MAX_PEER_FAILURES};
+    use super::{
+        adaptive_blocks_request_size, PeersTasks, MAX_BLOCKS_FAILURES, MAX_PEER_FAILURES,
+        SCORE_DECAY, SCORE_DECAY_INTERVAL_S,
+    };
     use primitives::hash::H256;
     use types::PeerIndex;
 
@@ -663,4 +821,79 @@ mod tests {
         assert_eq!(peers_for_blocks[0], 2);
         assert_eq!(peers_for_blocks[1], 1);
     }
+
+    #[test]
+    fn peer_score_ordering() {
+        let mut peers = PeersTasks::default();
+        let hashes = vec![H256::from(1), H256::from(2), H256::from(3), H256::from(4)];
+        for peer_index in 1..4 {
+            peers.useful_peer(peer_index);
+            peers.on_blocks_requested(peer_index, &hashes);
+        }
+
+        // peer#1 has delivered all requested blocks
+        for hash in &hashes {
+            peers.on_block_received(1, hash);
+        }
+        // peer#2 has delivered half of requested blocks
+        peers.on_block_received(2, &hashes[0]);
+        peers.on_block_received(2, &hashes[1]);
+        // peer#3 has delivered all requested blocks, but has failed recently
+        for hash in &hashes {
+            peers.on_block_received(3, hash);
+        }
+        peers.penalize(3);
+
+        assert!(peers.score(1) > peers.score(2));
+        assert!(peers.score(2) > peers.score(3));
+        assert_eq!(peers.top_k_peers(3), vec![1, 2, 3]);
+        assert_eq!(peers.top_k_peers(1), vec![1]);
+        assert_eq!(peers.score(4), 0f64);
+
+        // unuseful peers are not selected
+        peers.on_blocks_failure(peers.reset_blocks_tasks(2));
+        peers.unuseful_peer(2);
+        assert_eq!(peers.top_k_peers(3), vec![1, 3]);
+    }
+
+    #[test]
+    fn peer_score_decay() {
+        let mut peers = PeersTasks::default();
+        peers.useful_peer(1);
+        peers.useful_peer(2);
+
+        let now = peers.get_peer_stats(1).unwrap().last_activity;
+        let initial_score = peers.score_at(1, now);
+        assert!(initial_score > 0f64 && initial_score <= 1f64);
+
+        // score is decayed after every SCORE_DECAY_INTERVAL_S of inactivity
+        let decayed_score = peers.score_at(1, now + SCORE_DECAY_INTERVAL_S);
+        assert!((initial_score - decayed_score - SCORE_DECAY).abs() < 1e-9);
+        assert_eq!(
+            peers.score_at(1, now + 100f64 * SCORE_DECAY_INTERVAL_S),
+            0f64
+        );
+
+        // failed peer recovers over time
+        peers.get_peer_stats_mut(2).unwrap().last_failure = Some(now);
+        peers.get_peer_stats_mut(2).unwrap().last_activity = now + 600f64;
+        assert!(peers.score_at(2, now + 300f64) < peers.score_at(2, now + 600f64));
+    }
+
+    #[test]
+    fn peer_adaptive_blocks_request_size() {
+        // fast peer => max request
+        assert_eq!(adaptive_blocks_request_size(100f64, 0.1f64, 32, 128), 128);
+        // average peer => as much as it delivers in target duration
+        assert_eq!(adaptive_blocks_request_size(8f64, 2f64, 32, 128), 64);
+        // slow peer || huge latency => min request
+        assert_eq!(adaptive_blocks_request_size(0.5f64, 1f64, 32, 128), 32);
+        assert_eq!(adaptive_blocks_request_size(100f64, 60f64, 32, 128), 32);
+
+        // unmeasured peer => unknown size
+        let mut peers = PeersTasks::default();
+        peers.useful_peer(1);
+        assert_eq!(peers.blocks_request_size(1, 32, 128), None);
+        assert_eq!(peers.blocks_latency(1), None);
+    }
 }
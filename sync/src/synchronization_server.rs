@@ -6,9 +6,26 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
+use synchronization_client_core::MAX_BLOCKS_IN_REQUEST;
 use synchronization_executor::{Task, TaskExecutor};
+use time::precise_time_s;
 use types::{BlockHeight, ExecutorRef, PeerIndex, PeersRef, RequestId, StorageRef};
 
+/// Number of blocks per second, single peer is allowed to request
+const MAX_SERVED_BLOCKS_PER_SECOND: f64 = 20_f64;
+/// Number of blocks, single peer is allowed to request at once (two full blocks requests)
+const MAX_SERVED_BLOCKS_BURST: f64 = 2_f64 * MAX_BLOCKS_IN_REQUEST as f64;
+/// Number of headers (and blocks hashes) per second, single peer is allowed to request
+const MAX_SERVED_HEADERS_PER_SECOND: f64 = 4_000_f64;
+/// Number of headers (and blocks hashes), single peer is allowed to request at once
+const MAX_SERVED_HEADERS_BURST: f64 = 20_000_f64;
+/// Number of requests, dropped because of exceeded serving limits, after which peer is disconnected
+const MAX_DROPPED_REQUESTS: u32 = 5;
+/// Time (in seconds) without dropped requests, after which dropped requests are forgotten
+const DROPPED_REQUESTS_RESET_INTERVAL_S: f64 = 600_f64;
+/// Number of pending requests from single peer, after which peer is banned
+const MAX_PEER_PENDING_TASKS: usize = 256;
+
 /// Synchronization server task
 #[derive(Debug, PartialEq)]
 pub enum ServerTask {
@@ -34,6 +51,7 @@ pub trait Server: Send + Sync + 'static {
 
 /// Synchronization requests server
 pub struct ServerImpl {
+    peers: PeersRef,
     queue_ready: Arc<Condvar>,
     queue: Arc<Mutex<ServerQueue>>,
     worker_thread: Option<thread::JoinHandle<()>>,
@@ -45,6 +63,8 @@ struct ServerQueue {
     queue_ready: Arc<Condvar>,
     peers_queue: VecDeque<usize>,
     tasks_queue: HashMap<usize, VecDeque<ServerTask>>,
+    /// Peers, disconnected since last check of worker thread
+    disconnected_peers: Vec<PeerIndex>,
 }
 
 /// Server tasks executor
@@ -58,15 +78,57 @@ where
     executor: ExecutorRef<T>,
     /// Storage reference
     storage: StorageRef,
+    /// Serving limits for every peer
+    serving_stats: HashMap<PeerIndex, PeerServingStats>,
+}
+
+/// Serving limits of single peer
+#[derive(Debug, Clone, Copy)]
+struct PeerServingStats {
+    /// Limits number of served blocks
+    blocks: TokenBucket,
+    /// Limits number of served headers and blocks hashes
+    headers: TokenBucket,
+    /// Number of requests, dropped because of exceeded limits
+    dropped_requests: u32,
+    /// Time of last dropped request
+    last_dropped_request: f64,
+}
+
+/// Token bucket, limiting number of served items
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    /// Number of tokens, added every second
+    rate: f64,
+    /// Max number of tokens
+    burst: f64,
+    /// Number of tokens at the moment of last update
+    tokens: f64,
+    /// Time of last update
+    last_update: f64,
 }
 
 impl Server for ServerImpl {
     fn execute(&self, task: ServerTask) {
-        self.queue.lock().add_task(task);
+        let peer_index = task.peer_index();
+        {
+            let mut queue = self.queue.lock();
+            if queue.peer_tasks_len(peer_index) < MAX_PEER_PENDING_TASKS {
+                queue.add_task(task);
+                return;
+            }
+
+            queue.remove_peer_tasks(peer_index);
+        }
+
+        self.peers.dos(peer_index, "too many pending requests");
     }
 
     fn on_disconnect(&self, peer_index: PeerIndex) {
-        self.queue.lock().remove_peer_tasks(peer_index);
+        let mut queue = self.queue.lock();
+        queue.remove_peer_tasks(peer_index);
+        queue.disconnected_peers.push(peer_index);
+        self.queue_ready.notify_one();
     }
 }
 
@@ -84,10 +146,11 @@ impl ServerTask {
 
 impl ServerImpl {
     pub fn new<T: TaskExecutor>(peers: PeersRef, storage: StorageRef, executor: Arc<T>) -> Self {
-        let executor = ServerTaskExecutor::new(peers, storage, executor);
+        let executor = ServerTaskExecutor::new(peers.clone(), storage, executor);
         let queue_ready = Arc::new(Condvar::new());
         let queue = Arc::new(Mutex::new(ServerQueue::new(queue_ready.clone())));
         let mut server = ServerImpl {
+            peers: peers,
             queue_ready: queue_ready.clone(),
             queue: queue.clone(),
             worker_thread: None,
@@ -101,7 +164,7 @@ impl ServerImpl {
     fn server_worker<T: TaskExecutor>(
         queue_ready: Arc<Condvar>,
         queue: Arc<Mutex<ServerQueue>>,
-        executor: ServerTaskExecutor<T>,
+        mut executor: ServerTaskExecutor<T>,
     ) {
         loop {
             let task = {
@@ -110,6 +173,10 @@ impl ServerImpl {
                     break;
                 }
 
+                for peer_index in queue.disconnected_peers.drain(..) {
+                    executor.on_disconnect(peer_index);
+                }
+
                 queue.next_task().or_else(|| {
                     queue_ready.wait(&mut queue);
                     queue.next_task()
@@ -142,6 +209,7 @@ impl ServerQueue {
             queue_ready: queue_ready,
             peers_queue: VecDeque::new(),
             tasks_queue: HashMap::new(),
+            disconnected_peers: Vec::new(),
         }
     }
 
@@ -207,6 +275,13 @@ impl ServerQueue {
         self.queue_ready.notify_one();
     }
 
+    pub fn peer_tasks_len(&self, peer_index: PeerIndex) -> usize {
+        self.tasks_queue
+            .get(&peer_index)
+            .map(VecDeque::len)
+            .unwrap_or(0)
+    }
+
     pub fn remove_peer_tasks(&mut self, peer_index: PeerIndex) {
         if self.tasks_queue.remove(&peer_index).is_some() {
             let position = self.peers_queue.iter().position(|p| p == &peer_index)
@@ -216,6 +291,51 @@ impl ServerQueue {
     }
 }
 
+impl PeerServingStats {
+    pub fn new(now: f64) -> Self {
+        PeerServingStats {
+            blocks: TokenBucket::new(MAX_SERVED_BLOCKS_PER_SECOND, MAX_SERVED_BLOCKS_BURST, now),
+            headers: TokenBucket::new(MAX_SERVED_HEADERS_PER_SECOND, MAX_SERVED_HEADERS_BURST, now),
+            dropped_requests: 0,
+            last_dropped_request: now,
+        }
+    }
+
+    /// Account dropped request and return number of recently dropped requests.
+    /// Counter is reset when peer has not exceeded its limits for a long time.
+    pub fn on_request_dropped(&mut self, now: f64) -> u32 {
+        if now - self.last_dropped_request > DROPPED_REQUESTS_RESET_INTERVAL_S {
+            self.dropped_requests = 0;
+        }
+        self.dropped_requests += 1;
+        self.last_dropped_request = now;
+        self.dropped_requests
+    }
+}
+
+impl TokenBucket {
+    pub fn new(rate: f64, burst: f64, now: f64) -> Self {
+        TokenBucket {
+            rate: rate,
+            burst: burst,
+            tokens: burst,
+            last_update: now,
+        }
+    }
+
+    /// Number of tokens available at given time
+    pub fn tokens(&self, now: f64) -> f64 {
+        let elapsed = (now - self.last_update).max(0f64);
+        (self.tokens + elapsed * self.rate).min(self.burst)
+    }
+
+    /// Consume given number of tokens at given time
+    pub fn consume(&mut self, count: usize, now: f64) {
+        self.tokens = (self.tokens(now) - count as f64).max(0f64);
+        self.last_update = now;
+    }
+}
+
 impl<TExecutor> ServerTaskExecutor<TExecutor>
 where
     TExecutor: TaskExecutor,
@@ -225,10 +345,73 @@ where
             peers: peers,
             storage: storage,
             executor: executor,
+            serving_stats: HashMap::new(),
         }
     }
 
-    pub fn execute(&self, task: ServerTask) -> Option<ServerTask> {
+    /// Check if peer has not yet exceeded its blocks serving limit
+    pub fn can_serve_blocks(&self, peer_index: PeerIndex) -> bool {
+        self.serving_stats
+            .get(&peer_index)
+            .map(|stats| stats.blocks.tokens(precise_time_s()) >= 1f64)
+            .unwrap_or(true)
+    }
+
+    /// Check if peer has enough headers serving limit left to be served with the full response
+    pub fn can_serve_headers(&self, peer_index: PeerIndex) -> bool {
+        self.serving_stats
+            .get(&peer_index)
+            .map(|stats| {
+                stats.headers.tokens(precise_time_s())
+                    >= types::GETHEADERS_MAX_RESPONSE_HEADERS as f64
+            })
+            .unwrap_or(true)
+    }
+
+    /// Account blocks, served to the peer
+    pub fn on_blocks_served(&mut self, peer_index: PeerIndex, count: usize) {
+        let now = precise_time_s();
+        self.peer_serving_stats(peer_index, now)
+            .blocks
+            .consume(count, now);
+    }
+
+    /// Account headers (or blocks hashes), served to the peer
+    pub fn on_headers_served(&mut self, peer_index: PeerIndex, count: usize) {
+        let now = precise_time_s();
+        self.peer_serving_stats(peer_index, now)
+            .headers
+            .consume(count, now);
+    }
+
+    /// Called when peer request is dropped, because peer has exceeded its serving limit.
+    /// Peer, which keeps sending requests after that, is disconnected.
+    pub fn on_request_dropped(&mut self, peer_index: PeerIndex, request: &str) {
+        let now = precise_time_s();
+        let dropped_requests = self
+            .peer_serving_stats(peer_index, now)
+            .on_request_dropped(now);
+
+        if dropped_requests >= MAX_DROPPED_REQUESTS {
+            self.peers
+                .misbehaving(peer_index, &format!("too many '{}' requests", request));
+        } else {
+            warn!(target: "sync", "Dropping '{}' request from peer#{}: serving limit exceeded", request, peer_index);
+        }
+    }
+
+    /// Forget serving limits of disconnected peer
+    pub fn on_disconnect(&mut self, peer_index: PeerIndex) {
+        self.serving_stats.remove(&peer_index);
+    }
+
+    fn peer_serving_stats(&mut self, peer_index: PeerIndex, now: f64) -> &mut PeerServingStats {
+        self.serving_stats
+            .entry(peer_index)
+            .or_insert_with(|| PeerServingStats::new(now))
+    }
+
+    pub fn execute(&mut self, task: ServerTask) -> Option<ServerTask> {
         match task {
             ServerTask::GetData(peer_index, message) => {
                 return self.serve_get_data(peer_index, message)
@@ -265,7 +448,7 @@ where
     }
 
     fn serve_reversed_get_data(
-        &self,
+        &mut self,
         peer_index: PeerIndex,
         mut message: types::GetData,
         mut notfound: types::NotFound,
@@ -283,6 +466,18 @@ where
 
         match next_item.inv_type {
             common::InventoryType::MessageBlock => {
+                if !self.can_serve_blocks(peer_index) {
+                    // let peer know that it should request the rest of blocks from other peers
+                    notfound.inventory.push(next_item);
+                    notfound
+                        .inventory
+                        .extend(message.inventory.into_iter().rev());
+                    self.executor.execute(Task::NotFound(peer_index, notfound));
+                    self.on_request_dropped(peer_index, "getdata");
+                    return None;
+                }
+                self.on_blocks_served(peer_index, 1);
+
                 if let Some(block) = self.storage.block(next_item.hash.clone().into()) {
                     trace!(target: "sync", "'getblocks' response to peer#{} is ready with block {}", peer_index, next_item.hash.to_reversed_str());
                     self.executor.execute(Task::Block(peer_index, block));
@@ -296,7 +491,12 @@ where
         Some(ServerTask::ReversedGetData(peer_index, message, notfound))
     }
 
-    fn serve_get_blocks(&self, peer_index: PeerIndex, message: types::GetBlocks) {
+    fn serve_get_blocks(&mut self, peer_index: PeerIndex, message: types::GetBlocks) {
+        if !self.can_serve_headers(peer_index) {
+            self.on_request_dropped(peer_index, "getblocks");
+            return;
+        }
+
         if let Some(block_height) =
             self.locate_best_common_block(&message.hash_stop, &message.block_locator_hashes)
         {
@@ -308,6 +508,7 @@ where
                 .take_while(|block_hash| block_hash != &message.hash_stop)
                 .map(common::InventoryVector::block)
                 .collect();
+            self.on_headers_served(peer_index, inventory.len());
             // empty inventory messages are invalid according to regtests, while empty headers messages are valid
             if !inventory.is_empty() {
                 trace!(target: "sync", "'getblocks' response to peer#{} is ready with {} hashes", peer_index, inventory.len());
@@ -326,11 +527,17 @@ where
     }
 
     fn serve_get_headers(
-        &self,
+        &mut self,
         peer_index: PeerIndex,
         message: types::GetHeaders,
         request_id: RequestId,
     ) {
+        if !self.can_serve_headers(peer_index) {
+            self.executor.execute(Task::Ignore(peer_index, request_id));
+            self.on_request_dropped(peer_index, "getheaders");
+            return;
+        }
+
         if let Some(block_height) =
             self.locate_best_common_block(&message.hash_stop, &message.block_locator_hashes)
         {
@@ -345,6 +552,7 @@ where
                 .map(Option::unwrap)
                 .map(|h| h.raw)
                 .collect();
+            self.on_headers_served(peer_index, headers.len());
             // empty inventory messages are invalid according to regtests, while empty headers messages are valid
             trace!(target: "sync", "'getheaders' response to peer#{} is ready with {} headers", peer_index, headers.len());
             self.executor.execute(Task::Headers(
@@ -399,14 +607,21 @@ where
 pub mod tests {
     extern crate test_data;
 
-    use super::{Server, ServerImpl, ServerTask};
+    use super::{
+        PeerServingStats, Server, ServerImpl, ServerTask, ServerTaskExecutor, TokenBucket,
+        DROPPED_REQUESTS_RESET_INTERVAL_S, MAX_DROPPED_REQUESTS, MAX_SERVED_BLOCKS_BURST,
+        MAX_SERVED_BLOCKS_PER_SECOND, MAX_SERVED_HEADERS_BURST,
+    };
     use db::BlockChainDatabase;
+    use inbound_connection::tests::DummyOutboundSyncConnection;
     use message::common::{InventoryType, InventoryVector};
     use message::types;
+    use message::Services;
     use parking_lot::Mutex;
     use primitives::hash::H256;
     use std::mem::replace;
     use std::sync::Arc;
+    use synchronization_client_core::MAX_BLOCKS_IN_REQUEST;
     use synchronization_executor::tests::DummyTaskExecutor;
     use synchronization_executor::Task;
     use synchronization_peers::PeersImpl;
@@ -667,4 +882,125 @@ pub mod tests {
             )]
         );
     }
+
+    #[test]
+    fn server_limits_served_blocks_per_peer() {
+        let (storage, executor, peers, _) = create_synchronization_server();
+        let mut server_executor = ServerTaskExecutor::new(peers, storage, executor);
+
+        let mut served = 0;
+        for _ in 0..2 * MAX_SERVED_BLOCKS_BURST as usize {
+            if server_executor.can_serve_blocks(0) {
+                server_executor.on_blocks_served(0, 1);
+                served += 1;
+            }
+        }
+        assert_eq!(served, MAX_SERVED_BLOCKS_BURST as usize);
+        // peer is able to request full blocks request at once
+        assert!(served >= MAX_BLOCKS_IN_REQUEST as usize);
+        // other peers are not affected
+        assert!(server_executor.can_serve_blocks(1));
+        // other limits are not affected
+        assert!(server_executor.can_serve_headers(0));
+    }
+
+    #[test]
+    fn server_responds_notfound_when_blocks_limit_exceeded() {
+        let (storage, executor, peers, _) = create_synchronization_server();
+        let mut server_executor = ServerTaskExecutor::new(peers, storage, executor.clone());
+        server_executor.on_blocks_served(0, MAX_SERVED_BLOCKS_BURST as usize - 1);
+
+        let inventory = vec![
+            InventoryVector::block(test_data::genesis().hash()),
+            InventoryVector::block(H256::from(1)),
+            InventoryVector::block(H256::from(2)),
+        ];
+        let mut task = Some(ServerTask::GetData(
+            0,
+            types::GetData::with_inventory(inventory.clone()),
+        ));
+        while let Some(next_task) = task {
+            task = server_executor.execute(next_task);
+        }
+
+        // first block is served, the rest is reported as not found
+        assert_eq!(
+            executor.take_tasks(),
+            vec![
+                Task::Block(0, test_data::genesis().into()),
+                Task::NotFound(0, types::NotFound::with_inventory(inventory[1..].to_vec())),
+            ]
+        );
+    }
+
+    #[test]
+    fn server_ignores_getheaders_when_headers_limit_exceeded() {
+        let (storage, executor, peers, _) = create_synchronization_server();
+        let mut server_executor = ServerTaskExecutor::new(peers, storage, executor.clone());
+        server_executor.on_headers_served(0, MAX_SERVED_HEADERS_BURST as usize);
+
+        let dummy_id = 6;
+        server_executor.execute(ServerTask::GetHeaders(
+            0,
+            types::GetHeaders {
+                version: 0,
+                block_locator_hashes: vec![test_data::genesis().hash()],
+                hash_stop: H256::default(),
+            },
+            dummy_id,
+        ));
+        assert_eq!(executor.take_tasks(), vec![Task::Ignore(0, dummy_id)]);
+    }
+
+    #[test]
+    fn server_disconnects_peer_after_too_many_dropped_requests() {
+        let (storage, executor, peers, _) = create_synchronization_server();
+        peers.insert(0, Services::default(), DummyOutboundSyncConnection::new());
+        let mut server_executor = ServerTaskExecutor::new(peers.clone(), storage, executor);
+
+        for _ in 0..MAX_DROPPED_REQUESTS - 1 {
+            server_executor.on_request_dropped(0, "getdata");
+        }
+        assert_eq!(peers.enumerate(), vec![0]);
+
+        server_executor.on_request_dropped(0, "getdata");
+        assert!(peers.enumerate().is_empty());
+    }
+
+    #[test]
+    fn server_forgets_dropped_requests_after_quiet_interval() {
+        let mut stats = PeerServingStats::new(0f64);
+        for i in 0..MAX_DROPPED_REQUESTS - 1 {
+            assert_eq!(stats.on_request_dropped(i as f64), i + 1);
+        }
+
+        let quiet_time = MAX_DROPPED_REQUESTS as f64 + DROPPED_REQUESTS_RESET_INTERVAL_S;
+        assert_eq!(stats.on_request_dropped(quiet_time), 1);
+    }
+
+    #[test]
+    fn server_forgets_serving_stats_of_disconnected_peer() {
+        let (storage, executor, peers, _) = create_synchronization_server();
+        let mut server_executor = ServerTaskExecutor::new(peers, storage, executor);
+        server_executor.on_blocks_served(0, 10);
+        server_executor.on_blocks_served(1, 10);
+        assert_eq!(server_executor.serving_stats.len(), 2);
+
+        server_executor.on_disconnect(0);
+        assert!(!server_executor.serving_stats.contains_key(&0));
+        assert!(server_executor.serving_stats.contains_key(&1));
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let mut bucket =
+            TokenBucket::new(MAX_SERVED_BLOCKS_PER_SECOND, MAX_SERVED_BLOCKS_BURST, 0f64);
+        for _ in 0..MAX_SERVED_BLOCKS_BURST as usize {
+            bucket.consume(1, 0f64);
+        }
+        assert!(bucket.tokens(0f64) < 1f64);
+        // 20 blocks per second
+        assert_eq!(bucket.tokens(0.5f64), 10f64);
+        assert_eq!(bucket.tokens(1000f64), MAX_SERVED_BLOCKS_BURST);
+    }
 }
@@ -1,19 +1,28 @@
-use chain::IndexedBlock;
+use chain::{IndexedBlock, IndexedBlockHeader};
 use network::Network;
 use parking_lot::Mutex;
 use primitives::hash::H256;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender};
 use std::sync::Arc;
 use std::thread;
-use types::StorageRef;
+use types::{PeerIndex, StorageRef};
 use verification::{
     BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError,
     VerificationLevel, Verify as VerificationVerify,
 };
 use VerificationParameters;
 
+/// Max number of blocks, verified in single batch
+const MAX_VERIFICATION_BATCH_SIZE: usize = 32;
+/// Min number of blocks, checked in parallel. Smaller batches are checked block after block
+/// (proof of every block is still verified on multiple threads).
+const MIN_PARALLEL_VERIFICATION_BATCH_SIZE: usize = 4;
+/// Max number of `headers` messages, waiting for verification. Every peer has at most one
+/// message verifying, so the queue only fills up when many peers are sending headers at once.
+const MAX_HEADERS_VERIFICATION_QUEUE_SIZE: usize = 64;
+
 /// Block verification events sink
 pub trait BlockVerificationSink: Send + Sync + 'static {
     /// When block verification has completed successfully.
@@ -25,6 +34,24 @@ pub trait BlockVerificationSink: Send + Sync + 'static {
 /// Verification events sink
 pub trait VerificationSink: BlockVerificationSink {}
 
+/// Headers verification events sink
+pub trait HeadersVerificationSink: Send + Sync + 'static {
+    /// When all headers from peer `headers` message are verified successfully.
+    fn on_headers_verification_success(
+        &self,
+        peer_index: PeerIndex,
+        headers: Vec<IndexedBlockHeader>,
+    );
+    /// When verification of header with given index has failed.
+    fn on_headers_verification_error(
+        &self,
+        peer_index: PeerIndex,
+        err: &str,
+        headers: Vec<IndexedBlockHeader>,
+        error_index: usize,
+    );
+}
+
 /// Verification thread tasks
 #[derive(Debug)]
 pub enum VerificationTask {
@@ -34,12 +61,27 @@ pub enum VerificationTask {
     Stop,
 }
 
+/// Headers verification thread tasks
+#[derive(Debug)]
+pub enum HeadersVerificationTask {
+    /// Verify headers from peer `headers` message
+    VerifyHeaders(PeerIndex, Vec<IndexedBlockHeader>),
+    /// Stop headers verification thread
+    Stop,
+}
+
 /// Synchronization verifier
 pub trait Verifier: Send + Sync + 'static {
     /// Verify block
     fn verify_block(&self, block: IndexedBlock);
 }
 
+/// Synchronization headers verifier
+pub trait HeadersVerifier: Send + Sync + 'static {
+    /// Verify headers, received from peer
+    fn verify_headers(&self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>);
+}
+
 /// Asynchronous synchronization verifier
 pub struct AsyncVerifier {
     /// Verification work transmission channel.
@@ -48,10 +90,45 @@ pub struct AsyncVerifier {
     verification_worker_thread: Option<thread::JoinHandle<()>>,
 }
 
+/// Asynchronous headers verifier. Headers are verified on the dedicated thread (proof of work
+/// of headers is checked in parallel), so that large `headers` messages do not stall processing
+/// of other messages.
+pub struct AsyncHeadersVerifier {
+    /// Verification work transmission channel.
+    verification_work_sender: Mutex<SyncSender<HeadersVerificationTask>>,
+    /// Verification thread.
+    verification_worker_thread: Option<thread::JoinHandle<()>>,
+}
+
+/// Verification worker task: index of the block in the batch, verification level, block number
+/// and the block.
+type WorkerTask = (usize, VerificationLevel, Option<u32>, IndexedBlock);
+
+/// Verification worker result: index of the block in the batch, verification level, the block
+/// and result of its context-free verification.
+type WorkerResult = (
+    usize,
+    VerificationLevel,
+    IndexedBlock,
+    Result<(), VerificationError>,
+);
+
+/// Pool of verification worker threads, fed by the sync verification thread.
+struct VerificationWorkers {
+    /// Tasks transmission channel.
+    task_sender: Option<Sender<WorkerTask>>,
+    /// Results receiving channel.
+    result_receiver: Receiver<WorkerResult>,
+    /// Worker threads.
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
 /// Chain verifier wrapper to deal with verification parameters.
 pub struct ChainVerifierWrapper {
     /// Original verifier.
     pub verifier: Arc<ChainVerifier>,
+    /// Storage reference.
+    storage: StorageRef,
     /// Verification parameters.
     verification_params: VerificationParameters,
     /// Is verification edge passed.
@@ -70,6 +147,7 @@ impl ChainVerifierWrapper {
         );
         ChainVerifierWrapper {
             verifier: verifier,
+            storage: storage.clone(),
             verification_params: verification_params,
             enforce_full_verification: enforce_full_verification,
         }
@@ -77,6 +155,80 @@ impl ChainVerifierWrapper {
 
     /// Verify block.
     pub fn verify_block(&self, block: &IndexedBlock) -> Result<(), VerificationError> {
+        let verification_level = self.verification_level(block);
+        // run cheap checks first, so that malformed blocks are rejected without verifying the VDF
+        if verification_level != VerificationLevel::NoVerification {
+            self.verifier.precheck_block(block)?;
+        }
+        self.verifier.verify(verification_level, block)
+    }
+
+    /// Select verification levels and numbers of the batch blocks. Parent of every block is
+    /// either in the storage, or precedes the block in the batch.
+    pub fn batch_verification_params(
+        &self,
+        blocks: &[IndexedBlock],
+    ) -> Vec<(VerificationLevel, Option<u32>)> {
+        let mut batch_numbers: HashMap<H256, u32> = HashMap::new();
+        blocks
+            .iter()
+            .map(|block| {
+                let parent_hash = &block.header.raw.previous_header_hash;
+                let block_number = batch_numbers
+                    .get(parent_hash)
+                    .cloned()
+                    .or_else(|| self.storage.block_number(parent_hash))
+                    .map(|parent_number| parent_number + 1);
+                if let Some(block_number) = block_number {
+                    batch_numbers.insert(block.hash().clone(), block_number);
+                }
+                // levels are selected in blocks order => verification edge is respected
+                (self.verification_level(block), block_number)
+            })
+            .collect()
+    }
+
+    /// Run checks, which do not depend on the storage state, for all blocks of the batch.
+    /// Blocks are checked in parallel if verification queue is deep enough.
+    /// Results are returned in the same order as blocks.
+    pub fn batch_verify_context_free(
+        &self,
+        blocks: &[IndexedBlock],
+        params: &[(VerificationLevel, Option<u32>)],
+    ) -> Vec<Result<(), VerificationError>> {
+        if blocks.len() < MIN_PARALLEL_VERIFICATION_BATCH_SIZE {
+            return blocks
+                .iter()
+                .zip(params)
+                .map(|(block, &(verification_level, block_number))| {
+                    self.verifier
+                        .verify_block_context_free(verification_level, block_number, block)
+                })
+                .collect();
+        }
+
+        let blocks: Vec<_> = blocks
+            .iter()
+            .zip(params)
+            .map(|(block, &(verification_level, block_number))| {
+                (verification_level, block_number, block)
+            })
+            .collect();
+        self.verifier.batch_verify_context_free(&blocks)
+    }
+
+    /// Run checks, which depend on the storage state. Block must be already checked by
+    /// `batch_verify_context_free` and its parent must be already inserted into the storage.
+    pub fn accept_block(
+        &self,
+        verification_level: VerificationLevel,
+        block: &IndexedBlock,
+    ) -> Result<(), VerificationError> {
+        self.verifier.accept_block(verification_level, block)
+    }
+
+    /// Select verification level for the block.
+    fn verification_level(&self, block: &IndexedBlock) -> VerificationLevel {
         let enforce_full_verification =
             if block.hash() == &self.verification_params.verification_edge {
                 self.enforce_full_verification
@@ -85,18 +237,129 @@ impl ChainVerifierWrapper {
             } else {
                 self.enforce_full_verification.load(Ordering::Relaxed)
             };
-        let verification_level = if enforce_full_verification {
+        if enforce_full_verification {
             VerificationLevel::Full
         } else {
             self.verification_params.verification_level
-        };
-
-        self.verifier.verify(verification_level, block)
+        }
     }
 }
 
 impl VerificationTask {}
 
+impl VerificationWorkers {
+    /// Spawn `threads` verification workers.
+    fn new(verifier: Arc<ChainVerifierWrapper>, threads: usize) -> Self {
+        let (task_sender, task_receiver) = channel();
+        let (result_sender, result_receiver) = channel();
+        let task_receiver = Arc::new(Mutex::new(task_receiver));
+        let threads = (0..threads)
+            .map(|index| {
+                let verifier = verifier.clone();
+                let task_receiver = task_receiver.clone();
+                let result_sender = result_sender.clone();
+                thread::Builder::new()
+                    .name(format!("Sync verification worker #{}", index))
+                    .spawn(move || {
+                        VerificationWorkers::worker_proc(verifier, task_receiver, result_sender)
+                    })
+                    .expect("Error creating sync verification worker thread")
+            })
+            .collect();
+
+        VerificationWorkers {
+            task_sender: Some(task_sender),
+            result_receiver: result_receiver,
+            threads: threads,
+        }
+    }
+
+    /// Thread procedure of verification worker
+    fn worker_proc(
+        verifier: Arc<ChainVerifierWrapper>,
+        task_receiver: Arc<Mutex<Receiver<WorkerTask>>>,
+        result_sender: Sender<WorkerResult>,
+    ) {
+        loop {
+            // receiver is unlocked before verification starts
+            let task = task_receiver.lock().recv();
+            let (index, verification_level, block_number, block) = match task {
+                Ok(task) => task,
+                Err(_) => break,
+            };
+
+            let result = verifier.verifier.verify_block_context_free(
+                verification_level,
+                block_number,
+                &block,
+            );
+            if result_sender
+                .send((index, verification_level, block, result))
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Run context-free checks of blocks on worker threads. Results are passed to `on_result`
+    /// in blocks order, as soon as all previous blocks are checked.
+    /// Stops when `on_result` returns false.
+    fn verify<F>(
+        &self,
+        blocks: Vec<IndexedBlock>,
+        params: Vec<(VerificationLevel, Option<u32>)>,
+        mut on_result: F,
+    ) -> bool
+    where
+        F: FnMut(VerificationLevel, IndexedBlock, Result<(), VerificationError>) -> bool,
+    {
+        let task_sender = self
+            .task_sender
+            .as_ref()
+            .expect("task_sender is only taken when workers are dropped; qed");
+        let blocks_len = blocks.len();
+        for (index, (block, (verification_level, block_number))) in
+            blocks.into_iter().zip(params).enumerate()
+        {
+            task_sender
+                .send((index, verification_level, block_number, block))
+                .expect("Verification workers have the same lifetime as `VerificationWorkers`");
+        }
+
+        let mut pending: Vec<_> = (0..blocks_len).map(|_| None).collect();
+        let mut next_index = 0;
+        while next_index != blocks_len {
+            let (index, verification_level, block, result) = match self.result_receiver.recv() {
+                Ok(result) => result,
+                Err(_) => return false,
+            };
+            pending[index] = Some((verification_level, block, result));
+
+            while let Some((verification_level, block, result)) =
+                pending.get_mut(next_index).and_then(Option::take)
+            {
+                next_index += 1;
+                if !on_result(verification_level, block, result) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+impl Drop for VerificationWorkers {
+    fn drop(&mut self) {
+        // workers are stopping when tasks channel is closed
+        self.task_sender.take();
+        for thread in self.threads.drain(..) {
+            thread.join().expect("Clean shutdown.");
+        }
+    }
+}
+
 impl AsyncVerifier {
     /// Create new async verifier
     pub fn new<T: VerificationSink>(
@@ -112,11 +375,20 @@ impl AsyncVerifier {
                 thread::Builder::new()
                     .name("Sync verification thread".to_string())
                     .spawn(move || {
-                        let verifier =
-                            ChainVerifierWrapper::new(verifier, &storage, verification_params);
+                        let verification_threads = verification_params.verification_threads;
+                        let verifier = Arc::new(ChainVerifierWrapper::new(
+                            verifier,
+                            &storage,
+                            verification_params,
+                        ));
+                        let workers = match verification_threads {
+                            0 => None,
+                            threads => Some(VerificationWorkers::new(verifier.clone(), threads)),
+                        };
                         AsyncVerifier::verification_worker_proc(
                             sink,
                             verifier,
+                            workers,
                             verification_work_receiver,
                         )
                     })
@@ -128,11 +400,21 @@ impl AsyncVerifier {
     /// Thread procedure for handling verification tasks
     fn verification_worker_proc<T: VerificationSink>(
         sink: Arc<T>,
-        verifier: ChainVerifierWrapper,
+        verifier: Arc<ChainVerifierWrapper>,
+        workers: Option<VerificationWorkers>,
         work_receiver: Receiver<VerificationTask>,
     ) {
         while let Ok(task) = work_receiver.recv() {
-            if !AsyncVerifier::execute_single_task(&sink, &verifier, task) {
+            // drain all pending tasks to verify them at once
+            let mut tasks = vec![task];
+            while tasks.len() < MAX_VERIFICATION_BATCH_SIZE {
+                match work_receiver.try_recv() {
+                    Ok(task) => tasks.push(task),
+                    Err(_) => break,
+                }
+            }
+
+            if !AsyncVerifier::execute_tasks_batch(&sink, &verifier, workers.as_ref(), tasks) {
                 break;
             }
         }
@@ -140,6 +422,85 @@ impl AsyncVerifier {
         trace!(target: "sync", "Stopping sync verification thread");
     }
 
+    /// Execute batch of verification tasks
+    fn execute_tasks_batch<T: VerificationSink>(
+        sink: &Arc<T>,
+        verifier: &ChainVerifierWrapper,
+        workers: Option<&VerificationWorkers>,
+        tasks: Vec<VerificationTask>,
+    ) -> bool {
+        let mut blocks: Vec<IndexedBlock> = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            match task {
+                VerificationTask::VerifyBlock(block) => blocks.push(block),
+                VerificationTask::Stop => {
+                    AsyncVerifier::execute_blocks_batch(sink, verifier, workers, blocks);
+                    return false;
+                }
+            }
+        }
+
+        AsyncVerifier::execute_blocks_batch(sink, verifier, workers, blocks)
+    }
+
+    /// Verify blocks && notify sink in blocks order. Blocks of the batch could be children
+    /// of each other, so context-free checks are run in parallel for the whole batch, and
+    /// then blocks are accepted one by one, after their parents are inserted.
+    fn execute_blocks_batch<T: VerificationSink>(
+        sink: &Arc<T>,
+        verifier: &ChainVerifierWrapper,
+        workers: Option<&VerificationWorkers>,
+        blocks: Vec<IndexedBlock>,
+    ) -> bool {
+        if blocks.is_empty() {
+            return true;
+        }
+
+        trace!(target: "sync", "Verifying batch of {} blocks", blocks.len());
+        let params = verifier.batch_verification_params(&blocks);
+        if let Some(workers) = workers {
+            return workers.verify(blocks, params, |verification_level, block, result| {
+                let result = result.and_then(|_| verifier.accept_block(verification_level, &block));
+                AsyncVerifier::on_block_verified(sink, verifier, block, result)
+            });
+        }
+
+        let results = verifier.batch_verify_context_free(&blocks, &params);
+        for ((block, (verification_level, _)), result) in
+            blocks.into_iter().zip(params).zip(results)
+        {
+            let result = result.and_then(|_| verifier.accept_block(verification_level, &block));
+            if !AsyncVerifier::on_block_verified(sink, verifier, block, result) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Notify sink about block verification result
+    fn on_block_verified<T: VerificationSink>(
+        sink: &Arc<T>,
+        verifier: &ChainVerifierWrapper,
+        block: IndexedBlock,
+        result: Result<(), VerificationError>,
+    ) -> bool {
+        match result {
+            Ok(_) => {
+                if let Some(tasks) = sink.on_block_verification_success(block) {
+                    for task in tasks {
+                        if !AsyncVerifier::execute_single_task(sink, verifier, task) {
+                            return false;
+                        }
+                    }
+                }
+            }
+            Err(e) => sink.on_block_verification_error(&format!("{:?}", e), block.hash()),
+        }
+
+        true
+    }
+
     /// Execute single verification task
     pub fn execute_single_task<T: VerificationSink>(
         sink: &Arc<T>,
@@ -199,6 +560,89 @@ impl Verifier for AsyncVerifier {
     }
 }
 
+impl AsyncHeadersVerifier {
+    /// Create new async headers verifier
+    pub fn new<T: HeadersVerificationSink>(verifier: Arc<ChainVerifier>, sink: Arc<T>) -> Self {
+        let (verification_work_sender, verification_work_receiver) =
+            sync_channel(MAX_HEADERS_VERIFICATION_QUEUE_SIZE);
+        AsyncHeadersVerifier {
+            verification_work_sender: Mutex::new(verification_work_sender),
+            verification_worker_thread: Some(
+                thread::Builder::new()
+                    .name("Sync headers verification thread".to_string())
+                    .spawn(move || {
+                        AsyncHeadersVerifier::verification_worker_proc(
+                            sink,
+                            verifier,
+                            verification_work_receiver,
+                        )
+                    })
+                    .expect("Error creating sync headers verification thread"),
+            ),
+        }
+    }
+
+    /// Thread procedure for handling headers verification tasks
+    fn verification_worker_proc<T: HeadersVerificationSink>(
+        sink: Arc<T>,
+        verifier: Arc<ChainVerifier>,
+        work_receiver: Receiver<HeadersVerificationTask>,
+    ) {
+        while let Ok(task) = work_receiver.recv() {
+            match task {
+                HeadersVerificationTask::VerifyHeaders(peer_index, headers) => {
+                    AsyncHeadersVerifier::execute_single_task(&sink, &verifier, peer_index, headers)
+                }
+                HeadersVerificationTask::Stop => break,
+            }
+        }
+
+        trace!(target: "sync", "Stopping sync headers verification thread");
+    }
+
+    /// Verify headers && notify sink
+    pub fn execute_single_task<T: HeadersVerificationSink>(
+        sink: &Arc<T>,
+        verifier: &ChainVerifier,
+        peer_index: PeerIndex,
+        headers: Vec<IndexedBlockHeader>,
+    ) {
+        trace!(target: "sync", "Verifying {} headers from peer#{}", headers.len(), peer_index);
+        match verifier.batch_verify_headers(&headers) {
+            Ok(_) => sink.on_headers_verification_success(peer_index, headers),
+            Err((error_index, e)) => sink.on_headers_verification_error(
+                peer_index,
+                &format!("{:?}", e),
+                headers,
+                error_index,
+            ),
+        }
+    }
+}
+
+impl Drop for AsyncHeadersVerifier {
+    fn drop(&mut self) {
+        if let Some(join_handle) = self.verification_worker_thread.take() {
+            {
+                let verification_work_sender = self.verification_work_sender.lock();
+                // ignore send error here <= destructing anyway
+                let _ = verification_work_sender.send(HeadersVerificationTask::Stop);
+            }
+            join_handle.join().expect("Clean shutdown.");
+        }
+    }
+}
+
+impl HeadersVerifier for AsyncHeadersVerifier {
+    /// Verify headers
+    fn verify_headers(&self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>) {
+        self.verification_work_sender
+            .lock()
+            .send(HeadersVerificationTask::VerifyHeaders(peer_index, headers))
+            .expect("Verification thread have the same lifetime as `AsyncHeadersVerifier`");
+    }
+}
+
 /// Synchronous synchronization verifier
 pub struct SyncVerifier<T: VerificationSink> {
     /// Verifier
@@ -252,19 +696,27 @@ pub mod tests {
     extern crate test_data;
 
     use super::{
-        AsyncVerifier, BlockVerificationSink, ChainVerifierWrapper, VerificationTask, Verifier,
+        AsyncHeadersVerifier, AsyncVerifier, BlockVerificationSink, ChainVerifierWrapper,
+        HeadersVerificationSink, HeadersVerifier, VerificationSink, VerificationTask,
+        VerificationWorkers, Verifier, MAX_VERIFICATION_BATCH_SIZE,
+        MIN_PARALLEL_VERIFICATION_BATCH_SIZE,
     };
-    use chain::IndexedBlock;
+    use chain::{IndexedBlock, IndexedBlockHeader};
     use db::BlockChainDatabase;
     use network::Network;
+    use parking_lot::Mutex;
     use primitives::hash::H256;
     use std::collections::{HashMap, HashSet};
     use std::sync::atomic::Ordering;
     use std::sync::Arc;
+    use storage::Error as DBError;
     use synchronization_client_core::CoreVerificationSink;
     use synchronization_executor::tests::DummyTaskExecutor;
-    use types::StorageRef;
-    use verification::{BackwardsCompatibleChainVerifier as ChainVerifier, VerificationLevel};
+    use types::{PeerIndex, StorageRef};
+    use verification::{
+        BackwardsCompatibleChainVerifier as ChainVerifier, Error as VerificationError,
+        VerificationLevel,
+    };
     use VerificationParameters;
 
     #[derive(Default)]
@@ -292,6 +744,7 @@ pub mod tests {
                 VerificationParameters {
                     verification_level: VerificationLevel::Full,
                     verification_edge: 0u8.into(),
+                    verification_threads: 0,
                 },
             ));
         }
@@ -327,6 +780,87 @@ pub mod tests {
         }
     }
 
+    #[derive(Default)]
+    pub struct DummyHeadersVerifier {
+        sink: Option<Arc<CoreVerificationSink<DummyTaskExecutor>>>,
+        verifier: Option<Arc<ChainVerifier>>,
+    }
+
+    impl DummyHeadersVerifier {
+        pub fn set_sink(&mut self, sink: Arc<CoreVerificationSink<DummyTaskExecutor>>) {
+            self.sink = Some(sink);
+        }
+
+        pub fn set_verifier(&mut self, verifier: Arc<ChainVerifier>) {
+            self.verifier = Some(verifier);
+        }
+    }
+
+    impl HeadersVerifier for DummyHeadersVerifier {
+        fn verify_headers(&self, peer_index: PeerIndex, headers: Vec<IndexedBlockHeader>) {
+            match (self.sink.as_ref(), self.verifier.as_ref()) {
+                (Some(sink), Some(verifier)) => {
+                    AsyncHeadersVerifier::execute_single_task(sink, verifier, peer_index, headers)
+                }
+                _ => panic!("call set_sink and set_verifier"),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingHeadersSink {
+        verified: Mutex<Vec<(PeerIndex, usize)>>,
+        failed: Mutex<Vec<(PeerIndex, usize)>>,
+    }
+
+    impl HeadersVerificationSink for RecordingHeadersSink {
+        fn on_headers_verification_success(
+            &self,
+            peer_index: PeerIndex,
+            headers: Vec<IndexedBlockHeader>,
+        ) {
+            self.verified.lock().push((peer_index, headers.len()));
+        }
+
+        fn on_headers_verification_error(
+            &self,
+            peer_index: PeerIndex,
+            _err: &str,
+            _headers: Vec<IndexedBlockHeader>,
+            error_index: usize,
+        ) {
+            self.failed.lock().push((peer_index, error_index));
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        /// When set, verified blocks are inserted into the storage
+        storage: Option<StorageRef>,
+        verified: Mutex<Vec<H256>>,
+        failed: Mutex<Vec<H256>>,
+    }
+
+    impl BlockVerificationSink for RecordingSink {
+        fn on_block_verification_success(
+            &self,
+            block: IndexedBlock,
+        ) -> Option<Vec<VerificationTask>> {
+            self.verified.lock().push(block.hash().clone());
+            if let Some(ref storage) = self.storage {
+                storage.insert(block.clone()).unwrap();
+                storage.canonize(block.hash()).unwrap();
+            }
+            None
+        }
+
+        fn on_block_verification_error(&self, _err: &str, hash: &H256) {
+            self.failed.lock().push(hash.clone());
+        }
+    }
+
+    impl VerificationSink for RecordingSink {}
+
     #[test]
     fn verifier_wrapper_switches_to_full_mode() {
         let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
@@ -342,6 +876,7 @@ pub mod tests {
                 VerificationParameters {
                     verification_level: VerificationLevel::NoVerification,
                     verification_edge: test_data::genesis().hash(),
+                    verification_threads: 0,
                 }
             )
             .enforce_full_verification
@@ -356,6 +891,7 @@ pub mod tests {
             VerificationParameters {
                 verification_level: VerificationLevel::NoVerification,
                 verification_edge: test_data::block_h1().hash(),
+                verification_threads: 0,
             },
         );
         assert_eq!(
@@ -385,8 +921,319 @@ pub mod tests {
             VerificationParameters {
                 verification_level: VerificationLevel::NoVerification,
                 verification_edge: 1.into(),
+                verification_threads: 0,
             },
         );
         assert_eq!(wrapper.verify_block(&bad_block), Ok(()));
     }
+
+    #[test]
+    fn verification_parameters_presets() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+
+        let params = VerificationParameters::full_from_genesis();
+        assert_eq!(params.verification_level, VerificationLevel::Full);
+        assert_eq!(params.verification_edge, H256::from(0));
+
+        // full level does not depend on the edge
+        let wrapper = ChainVerifierWrapper::new(verifier.clone(), &storage, params);
+        let block: IndexedBlock = test_data::block_h1().into();
+        assert_eq!(
+            wrapper.enforce_full_verification.load(Ordering::Relaxed),
+            false
+        );
+        assert_eq!(wrapper.verification_level(&block), VerificationLevel::Full);
+
+        let params = VerificationParameters::no_verification();
+        assert_eq!(params.verification_level, VerificationLevel::NoVerification);
+        assert_eq!(params.verification_edge, H256::from([0xff; 32]));
+
+        // edge is never reached when nothing is verified
+        let wrapper = ChainVerifierWrapper::new(verifier.clone(), &storage, params);
+        assert_eq!(wrapper.verify_block(&block), Ok(()));
+        assert_eq!(
+            wrapper.enforce_full_verification.load(Ordering::Relaxed),
+            false
+        );
+
+        // builder overrides defaults
+        let wrapper = ChainVerifierWrapper::new(
+            verifier,
+            &storage,
+            VerificationParameters::builder()
+                .verification_level(VerificationLevel::Header)
+                .verification_edge(test_data::genesis().hash())
+                .build(),
+        );
+        assert_eq!(
+            wrapper.enforce_full_verification.load(Ordering::Relaxed),
+            true
+        );
+    }
+
+    #[test]
+    fn verifier_wrapper_batch_verify() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+        let wrapper = ChainVerifierWrapper::new(
+            verifier,
+            &storage,
+            VerificationParameters::full_from_genesis(),
+        );
+
+        // every second block has no proof
+        let good_block: IndexedBlock = test_data::block_h1().into();
+        let bad_block = block_without_proof();
+        let blocks: Vec<_> = (0..MAX_VERIFICATION_BATCH_SIZE)
+            .map(|i| {
+                if i % 2 == 0 {
+                    good_block.clone()
+                } else {
+                    bad_block.clone()
+                }
+            })
+            .collect();
+
+        let params = wrapper.batch_verification_params(&blocks);
+        let results = wrapper.batch_verify_context_free(&blocks, &params);
+        assert_eq!(results.len(), MAX_VERIFICATION_BATCH_SIZE);
+        for (i, result) in results.into_iter().enumerate() {
+            assert_eq!(result.is_ok(), i % 2 == 0);
+        }
+    }
+
+    #[test]
+    fn verifier_wrapper_batch_verify_short_queue() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+        let wrapper = ChainVerifierWrapper::new(
+            verifier,
+            &storage,
+            VerificationParameters::full_from_genesis(),
+        );
+
+        let blocks: Vec<IndexedBlock> = vec![block_without_proof(), test_data::block_h1().into()];
+        assert!(blocks.len() < MIN_PARALLEL_VERIFICATION_BATCH_SIZE);
+        let params = wrapper.batch_verification_params(&blocks);
+        let results = wrapper.batch_verify_context_free(&blocks, &params);
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_err());
+        assert_eq!(results[1], Ok(()));
+    }
+
+    /// Block with 4 iterations, which requires 2 proof elements.
+    fn block_without_proof() -> IndexedBlock {
+        let mut block = test_data::block_h1();
+        block.block_header.iterations = 4;
+        block.proof.clear();
+        block.into()
+    }
+
+    /// Chain of `len` valid blocks on top of genesis.
+    fn build_chain(len: usize) -> Vec<IndexedBlock> {
+        let mut parent_hash = test_data::genesis().hash();
+        (0..len)
+            .map(|_| {
+                let block: IndexedBlock = test_data::block_builder()
+                    .header()
+                    .parent(parent_hash.clone())
+                    .version(1)
+                    .iterations(1)
+                    .evaluated()
+                    .build()
+                    .proved()
+                    .build()
+                    .into();
+                parent_hash = block.hash().clone();
+                block
+            })
+            .collect()
+    }
+
+    #[test]
+    fn verifier_wrapper_checks_chain_blocks_in_parallel() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+        let wrapper = ChainVerifierWrapper::new(
+            verifier,
+            &storage,
+            VerificationParameters::full_from_genesis(),
+        );
+
+        // parents of all blocks, except the first one, are not yet in the storage
+        let blocks = build_chain(MAX_VERIFICATION_BATCH_SIZE);
+        let params = wrapper.batch_verification_params(&blocks);
+        let expected_params: Vec<_> = (1..MAX_VERIFICATION_BATCH_SIZE as u32 + 1)
+            .map(|number| (VerificationLevel::Full, Some(number)))
+            .collect();
+        assert_eq!(params, expected_params);
+
+        // but the whole chain is checked at once
+        let results = wrapper.batch_verify_context_free(&blocks, &params);
+        assert_eq!(results.len(), MAX_VERIFICATION_BATCH_SIZE);
+        assert!(results.iter().all(Result::is_ok));
+
+        // and then blocks are accepted one by one
+        assert_eq!(
+            wrapper.accept_block(VerificationLevel::Full, &blocks[1]),
+            Err(VerificationError::Database(DBError::UnknownParent))
+        );
+        assert_eq!(
+            wrapper.accept_block(VerificationLevel::Full, &blocks[0]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn async_verifier_verifies_chain_in_single_batch() {
+        // with and without verification workers
+        for &threads in &[0, 4] {
+            let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+                test_data::genesis().into(),
+            ]));
+            let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+            let wrapper = Arc::new(ChainVerifierWrapper::new(
+                verifier,
+                &storage,
+                VerificationParameters::full_from_genesis(),
+            ));
+            let workers = match threads {
+                0 => None,
+                threads => Some(VerificationWorkers::new(wrapper.clone(), threads)),
+            };
+            let sink = Arc::new(RecordingSink {
+                storage: Some(storage.clone()),
+                ..Default::default()
+            });
+
+            let blocks = build_chain(MAX_VERIFICATION_BATCH_SIZE);
+            let hashes: Vec<_> = blocks.iter().map(|b| b.hash().clone()).collect();
+            let tasks = blocks
+                .into_iter()
+                .map(VerificationTask::VerifyBlock)
+                .collect();
+            assert!(AsyncVerifier::execute_tasks_batch(
+                &sink,
+                &wrapper,
+                workers.as_ref(),
+                tasks
+            ));
+
+            assert_eq!(*sink.verified.lock(), hashes);
+            assert!(sink.failed.lock().is_empty());
+            assert_eq!(
+                storage.best_block().number,
+                MAX_VERIFICATION_BATCH_SIZE as u32
+            );
+        }
+    }
+
+    #[test]
+    fn async_verifier_notifies_sink_in_tasks_order() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+        let sink = Arc::new(RecordingSink::default());
+        let async_verifier = AsyncVerifier::new(
+            verifier,
+            storage,
+            sink.clone(),
+            VerificationParameters::no_verification(),
+        );
+
+        let blocks: Vec<IndexedBlock> = (0..MAX_VERIFICATION_BATCH_SIZE as u32)
+            .map(|i| {
+                test_data::block_builder()
+                    .header()
+                    .parent(test_data::genesis().hash())
+                    .iterations(i + 1)
+                    .build()
+                    .build()
+                    .into()
+            })
+            .collect();
+        let hashes: Vec<_> = blocks.iter().map(|b| b.hash().clone()).collect();
+        for block in blocks {
+            async_verifier.verify_block(block);
+        }
+
+        // verification thread processes all pending tasks before stopping
+        drop(async_verifier);
+        assert_eq!(*sink.verified.lock(), hashes);
+        assert!(sink.failed.lock().is_empty());
+    }
+
+    #[test]
+    fn async_verifier_workers_notify_sink_in_tasks_order() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage.clone(), Network::Unitest));
+        let sink = Arc::new(RecordingSink::default());
+        let async_verifier = AsyncVerifier::new(
+            verifier,
+            storage,
+            sink.clone(),
+            VerificationParameters::builder()
+                .verification_threads(4)
+                .build(),
+        );
+
+        // every second block has no proof
+        let good_block: IndexedBlock = test_data::block_h1().into();
+        let bad_blocks: Vec<IndexedBlock> = (0..MAX_VERIFICATION_BATCH_SIZE as u32 / 2)
+            .map(|i| {
+                test_data::block_builder()
+                    .header()
+                    .parent(test_data::genesis().hash())
+                    .iterations(i + 2)
+                    .build()
+                    .build()
+                    .into()
+            })
+            .collect();
+        let bad_hashes: Vec<_> = bad_blocks.iter().map(|b| b.hash().clone()).collect();
+        for block in bad_blocks {
+            async_verifier.verify_block(good_block.clone());
+            async_verifier.verify_block(block);
+        }
+
+        drop(async_verifier);
+        assert_eq!(
+            *sink.verified.lock(),
+            vec![good_block.hash().clone(); MAX_VERIFICATION_BATCH_SIZE / 2]
+        );
+        assert_eq!(*sink.failed.lock(), bad_hashes);
+    }
+
+    #[test]
+    fn async_headers_verifier_notifies_sink() {
+        let storage: StorageRef = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = Arc::new(ChainVerifier::new(storage, Network::Unitest));
+        let sink = Arc::new(RecordingHeadersSink::default());
+        let headers_verifier = AsyncHeadersVerifier::new(verifier, sink.clone());
+
+        let good_header: IndexedBlockHeader = test_data::block_h1().block_header.into();
+        let mut bad_header = test_data::block_h1().block_header;
+        bad_header.bits = 0.into();
+        headers_verifier.verify_headers(0, vec![good_header.clone()]);
+        headers_verifier.verify_headers(1, vec![good_header, bad_header.into()]);
+
+        // verification thread processes all pending tasks before stopping
+        drop(headers_verifier);
+        assert_eq!(*sink.verified.lock(), vec![(0, 1)]);
+        assert_eq!(*sink.failed.lock(), vec![(1, 1)]);
+    }
 }
@@ -8,7 +8,7 @@ use synchronization_client::SynchronizationClient;
 use synchronization_executor::LocalSynchronizationTaskExecutor;
 use synchronization_peers::Peers;
 use synchronization_server::ServerImpl;
-use synchronization_verifier::AsyncVerifier;
+use synchronization_verifier::{AsyncHeadersVerifier, AsyncVerifier};
 use utils::SynchronizationState;
 
 pub use utils::BlockHeight;
@@ -45,7 +45,14 @@ pub type ServerRef<T> = Arc<T>;
 
 /// Reference to local node
 pub type LocalNodeRef = Arc<
-    LocalNode<ServerImpl, SynchronizationClient<LocalSynchronizationTaskExecutor, AsyncVerifier>>,
+    LocalNode<
+        ServerImpl,
+        SynchronizationClient<
+            LocalSynchronizationTaskExecutor,
+            AsyncVerifier,
+            AsyncHeadersVerifier,
+        >,
+    >,
 >;
 
 /// Synchronization events listener reference
@@ -1,8 +1,12 @@
 use super::{HashPosition, HashQueue};
 use chain::IndexedBlockHeader;
+use linked_hash_map::LinkedHashMap;
 use primitives::hash::H256;
 use std::collections::HashMap;
 
+/// Default maximal number of headers, kept in memory
+pub const DEFAULT_HEADERS_CAPACITY: usize = 8192;
+
 /// Best headers chain information
 #[derive(Debug)]
 pub struct Information {
@@ -22,15 +26,27 @@ pub struct BestHeadersChain {
     headers: HashMap<H256, IndexedBlockHeader>,
     /// Best chain
     best: HashQueue,
+    /// Cumulative work (total number of VDF iterations from genesis) by block hash.
+    /// At most `capacity` recently inserted entries are kept.
+    total_work: LinkedHashMap<H256, u64>,
+    /// Maximal number of headers in the chain
+    capacity: usize,
 }
 
 impl BestHeadersChain {
     /// Create new best headers chain
     pub fn new(storage_best_hash: H256) -> Self {
+        BestHeadersChain::with_capacity(storage_best_hash, DEFAULT_HEADERS_CAPACITY)
+    }
+
+    /// Create new best headers chain, holding at most `capacity` headers
+    pub fn with_capacity(storage_best_hash: H256, capacity: usize) -> Self {
         BestHeadersChain {
             storage_best_hash: storage_best_hash,
             headers: HashMap::new(),
             best: HashQueue::new(),
+            total_work: LinkedHashMap::new(),
+            capacity: capacity,
         }
     }
 
@@ -42,6 +58,21 @@ impl BestHeadersChain {
         }
     }
 
+    /// Get number of headers in the chain
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Get maximal number of headers in the chain
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns true if no more headers can be inserted
+    pub fn is_capacity_exceeded(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
     /// Get header from main chain at given position
     pub fn at(&self, height: u32) -> Option<IndexedBlockHeader> {
         self.best
@@ -59,6 +90,20 @@ impl BestHeadersChain {
         self.best.position(hash)
     }
 
+    /// Get cached cumulative work of the chain ending with given block
+    pub fn total_work(&self, hash: &H256) -> Option<u64> {
+        self.total_work.get(hash).cloned()
+    }
+
+    /// Cache cumulative work of the chain ending with given block
+    pub fn set_total_work(&mut self, hash: H256, total_work: u64) {
+        self.total_work.insert(hash, total_work);
+        // evicted work is recomputed from headers when required
+        while self.total_work.len() > self.capacity {
+            self.total_work.pop_front();
+        }
+    }
+
     /// Get all direct child blocks hashes of given block hash
     pub fn children(&self, hash: &H256) -> Vec<H256> {
         self.best
@@ -76,15 +121,21 @@ impl BestHeadersChain {
             .expect("storage_best_hash is always known")
     }
 
-    /// Insert new block header
-    pub fn insert(&mut self, header: IndexedBlockHeader) {
+    /// Insert new block header. Returns false if header has not been inserted
+    pub fn insert(&mut self, header: IndexedBlockHeader) -> bool {
+        if self.is_capacity_exceeded() {
+            return false;
+        }
+
         // append to the best chain
         if self.best_block_hash() == header.raw.previous_header_hash {
             let header_hash = header.hash.clone();
             self.headers.insert(header_hash.clone(), header);
             self.best.push_back(header_hash);
-            return;
+            return true;
         }
+
+        false
     }
 
     /// Insert new blocks headers
@@ -97,6 +148,7 @@ impl BestHeadersChain {
     /// Remove block header with given hash and all its children
     pub fn remove(&mut self, hash: &H256) {
         if self.headers.remove(hash).is_some() {
+            self.total_work.remove(hash);
             match self.best.remove(hash) {
                 HashPosition::Front => self.clear(),
                 HashPosition::Inside(position) => self.clear_after(position),
@@ -113,11 +165,17 @@ impl BestHeadersChain {
     }
 
     /// Called when new blocks is inserted to storage
-    pub fn block_inserted_to_storage(&mut self, hash: &H256, storage_best_hash: &H256) {
+    pub fn block_inserted_to_storage(
+        &mut self,
+        hash: &H256,
+        storage_best_hash: &H256,
+        total_work: u64,
+    ) {
         if self.best.front().map(|h| &h == hash).unwrap_or(false) {
             self.best.pop_front();
             self.headers.remove(hash);
         }
+        self.set_total_work(hash.clone(), total_work);
         self.storage_best_hash = storage_best_hash.clone();
     }
 
@@ -133,8 +191,9 @@ impl BestHeadersChain {
             self.clear()
         } else {
             while self.best.len() > position {
-                self.headers
-                    .remove(&self.best.pop_back().expect("len() > position; qed"));
+                let hash = self.best.pop_back().expect("len() > position; qed");
+                self.headers.remove(&hash);
+                self.total_work.remove(&hash);
             }
         }
     }
@@ -145,6 +204,7 @@ mod tests {
     extern crate test_data;
 
     use super::BestHeadersChain;
+    use chain::IndexedBlockHeader;
     use primitives::hash::H256;
 
     #[test]
@@ -177,7 +237,7 @@ mod tests {
         chain.insert(b181.clone().into());
         assert_eq!(chain.information().best, 0);
         assert_eq!(chain.information().total, 0);
-        chain.block_inserted_to_storage(&b181.hash(), &b181.hash());
+        chain.block_inserted_to_storage(&b181.hash(), &b181.hash(), 0);
         assert_eq!(chain.information().best, 0);
         assert_eq!(chain.information().total, 0);
         chain.insert(b182.into());
@@ -253,12 +313,53 @@ mod tests {
         assert_eq!(chain.at(0), Some(b1.clone().into()));
         assert_eq!(chain.at(1), Some(b2.clone().into()));
 
-        chain.block_inserted_to_storage(&b1.hash(), &b1.hash());
+        chain.block_inserted_to_storage(&b1.hash(), &b1.hash(), b1.iterations as u64);
 
         assert_eq!(chain.at(0), Some(b2.into()));
+        assert_eq!(chain.total_work(&b1.hash()), Some(b1.iterations as u64));
         assert_eq!(chain.at(1), None);
 
         assert_eq!(chain.information().best, 1);
         assert_eq!(chain.information().total, 1);
     }
+
+    #[test]
+    fn best_chain_capacity() {
+        let mut chain = BestHeadersChain::with_capacity(test_data::genesis().hash(), 2);
+        let headers: Vec<IndexedBlockHeader> = test_data::build_n_empty_blocks_from_genesis(3, 1)
+            .into_iter()
+            .map(|b| b.block_header.into())
+            .collect();
+        assert_eq!(chain.capacity(), 2);
+        assert!(chain.insert(headers[0].clone()));
+        assert!(!chain.is_capacity_exceeded());
+        assert!(chain.insert(headers[1].clone()));
+        assert!(chain.is_capacity_exceeded());
+        assert!(!chain.insert(headers[2].clone()));
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain.information().total, 2);
+        assert_eq!(chain.best_block_hash(), headers[1].hash);
+
+        // space is freed when block is moved to the storage
+        chain.block_inserted_to_storage(&headers[0].hash, &headers[0].hash, 2);
+        assert!(chain.insert(headers[2].clone()));
+        assert_eq!(chain.len(), 2);
+    }
+
+    #[test]
+    fn best_chain_total_work_is_bounded() {
+        let mut chain = BestHeadersChain::with_capacity(test_data::genesis().hash(), 2);
+        let headers: Vec<IndexedBlockHeader> = test_data::build_n_empty_blocks_from_genesis(3, 1)
+            .into_iter()
+            .map(|b| b.block_header.into())
+            .collect();
+        chain.block_inserted_to_storage(&headers[0].hash, &headers[0].hash, 1);
+        chain.block_inserted_to_storage(&headers[1].hash, &headers[1].hash, 3);
+        chain.block_inserted_to_storage(&headers[2].hash, &headers[2].hash, 6);
+
+        // the oldest entry is evicted
+        assert_eq!(chain.total_work(&headers[0].hash), None);
+        assert_eq!(chain.total_work(&headers[1].hash), Some(3));
+        assert_eq!(chain.total_work(&headers[2].hash), Some(6));
+    }
 }
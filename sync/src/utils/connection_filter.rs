@@ -6,9 +6,16 @@ use utils::{KnownHashFilter, KnownHashType};
 pub struct ConnectionFilter {
     /// Known hashes filter
     known_hash_filter: KnownHashFilter,
+    /// Peer has opted out of new blocks announcements
+    no_relay: bool,
 }
 
 impl ConnectionFilter {
+    /// Enable or disable new blocks announcements
+    pub fn set_relay(&mut self, relay: bool) {
+        self.no_relay = !relay;
+    }
+
     /// Add known item hash
     pub fn hash_known_as(&mut self, hash: H256, hash_type: KnownHashType) {
         self.known_hash_filter.insert(hash, hash_type);
@@ -21,7 +28,7 @@ impl ConnectionFilter {
 
     /// Check if block should be sent to this connection
     pub fn filter_block(&self, block_hash: &H256) -> bool {
-        self.known_hash_filter.filter_block(block_hash)
+        !self.no_relay && self.known_hash_filter.filter_block(block_hash)
     }
 }
 
@@ -44,4 +51,13 @@ pub mod tests {
         assert!(!filter.filter_block(&test_data::block_h1().hash()));
         assert!(filter.filter_block(&test_data::genesis().hash()));
     }
+
+    #[test]
+    fn filter_rejects_block_when_relay_disabled() {
+        let mut filter = ConnectionFilter::default();
+        filter.set_relay(false);
+        assert!(!filter.filter_block(&test_data::genesis().hash()));
+        filter.set_relay(true);
+        assert!(filter.filter_block(&test_data::genesis().hash()));
+    }
 }
@@ -35,10 +35,10 @@ impl HashQueue {
         }
     }
 
-    /// Clears the queue
-    pub fn clear(&mut self) {
+    /// Clears the queue. Returns removed elements.
+    pub fn clear(&mut self) -> Vec<H256> {
         self.set.clear();
-        self.queue.clear();
+        self.queue.drain(..).collect()
     }
 
     /// Returns len of the given queue.
@@ -46,6 +46,11 @@ impl HashQueue {
         self.queue.len() as u32
     }
 
+    /// Returns true if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
     /// Returns front element from the given queue.
     pub fn front(&self) -> Option<H256> {
         self.queue.front().cloned()
@@ -138,6 +143,16 @@ impl HashQueue {
         }
     }
 
+    /// Adds elements to the front of the queue, preserving their order.
+    pub fn push_front_n(&mut self, hashes: Vec<H256>) {
+        for hash in hashes.into_iter().rev() {
+            if !self.set.insert(hash.clone()) {
+                panic!("must be checked by caller");
+            }
+            self.queue.push_front(hash);
+        }
+    }
+
     /// Removes element from the queue, returning its position.
     pub fn remove(&mut self, hash: &H256) -> HashPosition {
         if !self.set.remove(hash) {
@@ -198,6 +213,11 @@ impl HashQueueChain {
         self.chain[queue_index].len()
     }
 
+    /// Returns true if the given queue is empty.
+    pub fn is_empty_at(&self, queue_index: usize) -> bool {
+        self.chain[queue_index].is_empty()
+    }
+
     /// Returns element at the given position
     pub fn at(&self, mut index: u32) -> Option<H256> {
         for queue in &self.chain {
@@ -283,6 +303,11 @@ impl HashQueueChain {
         self.chain[queue_index].push_back_n(hashes)
     }
 
+    /// Push a number of hashes onto the front of the given queue.
+    pub fn push_front_n_at(&mut self, queue_index: usize, hashes: Vec<H256>) {
+        self.chain[queue_index].push_front_n(hashes)
+    }
+
     /// Remove hash from given queue.
     pub fn remove_at(&mut self, queue_index: usize, hash: &H256) -> HashPosition {
         self.chain[queue_index].remove(hash)
@@ -382,6 +407,8 @@ mod tests {
         assert_eq!(chain.len_of(1), 2);
         assert_eq!(chain.len_of(2), 1);
         assert_eq!(chain.len_of(3), 0);
+        assert!(!chain.is_empty_at(0));
+        assert!(chain.is_empty_at(3));
         assert_eq!(chain.front_at(0), Some(H256::from(0)));
         assert_eq!(chain.front_at(1), Some(H256::from(3)));
         assert_eq!(chain.front_at(2), Some(H256::from(5)));
@@ -413,4 +440,34 @@ mod tests {
         assert_eq!(queue.pop_front_n(3), vec![H256::from(0), H256::from(1)]);
         assert_eq!(queue.pop_front_n(3), vec![]);
     }
+
+    #[test]
+    fn hash_queue_contains() {
+        let mut queue = HashQueue::new();
+        assert!(queue.is_empty());
+        queue.push_back_n(vec![H256::from(0), H256::from(1)]);
+        assert!(!queue.is_empty());
+        assert!(queue.contains(&H256::from(0)));
+        assert!(queue.contains(&H256::from(1)));
+        assert!(!queue.contains(&H256::from(2)));
+
+        queue.pop_front();
+        assert!(!queue.contains(&H256::from(0)));
+        assert!(queue.contains(&H256::from(1)));
+    }
+
+    #[test]
+    fn hash_queue_clear() {
+        let mut queue = HashQueue::new();
+        assert_eq!(queue.clear(), vec![]);
+
+        queue.push_back_n(vec![H256::from(0), H256::from(1), H256::from(2)]);
+        assert_eq!(
+            queue.clear(),
+            vec![H256::from(0), H256::from(1), H256::from(2)]
+        );
+        assert!(queue.is_empty());
+        assert_eq!(queue.len(), 0);
+        assert!(!queue.contains(&H256::from(0)));
+    }
 }
@@ -3,7 +3,7 @@ mod best_headers_chain;
 mod connection_filter;
 mod hash_queue;
 mod known_hash_filter;
-mod message_block_headers_provider;
+pub mod notify_executor;
 mod orphan_blocks_pool;
 mod synchronization_state;
 
@@ -12,8 +12,7 @@ pub use self::best_headers_chain::{BestHeadersChain, Information as BestHeadersC
 pub use self::connection_filter::ConnectionFilter;
 pub use self::hash_queue::{HashPosition, HashQueue, HashQueueChain};
 pub use self::known_hash_filter::{KnownHashFilter, KnownHashType};
-pub use self::message_block_headers_provider::MessageBlockHeadersProvider;
-pub use self::orphan_blocks_pool::OrphanBlocksPool;
+pub use self::orphan_blocks_pool::{OrphanBlocksPool, DEFAULT_ORPHANS_MEMORY_LIMIT};
 pub use self::synchronization_state::SynchronizationState;
 
 /// Block height type
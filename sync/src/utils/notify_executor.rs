@@ -0,0 +1,74 @@
+use futures::executor::{self, Notify, NotifyHandle, Spawn};
+use futures::Async;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use types::EmptyBoxFuture;
+
+/// Future, which is polled from the thread, which has notified it.
+struct NotifiedFuture {
+    /// The future itself. None when it is completed
+    future: Mutex<Option<Spawn<EmptyBoxFuture>>>,
+}
+
+/// Notification handle of the `NotifiedFuture`
+struct NotifiedFutureHandle(Arc<NotifiedFuture>);
+
+/// Run future without dedicated thread or event loop: it is polled immediately and then
+/// every time it is notified, in the notifying thread. So the future must never block.
+pub fn spawn(future: EmptyBoxFuture) {
+    let future = Arc::new(NotifiedFuture {
+        future: Mutex::new(Some(executor::spawn(future))),
+    });
+    NotifiedFuture::poll(&future);
+}
+
+impl NotifiedFuture {
+    fn poll(this: &Arc<NotifiedFuture>) {
+        let mut future = this.future.lock();
+        let is_completed = match *future {
+            Some(ref mut future) => {
+                let handle = NotifyHandle::from(Arc::new(NotifiedFutureHandle(this.clone())));
+                match future.poll_future_notify(&handle, 0) {
+                    Ok(Async::NotReady) => false,
+                    Ok(Async::Ready(())) | Err(()) => true,
+                }
+            }
+            None => false,
+        };
+
+        // drop completed future, breaking the reference cycle through the notify handle
+        if is_completed {
+            *future = None;
+        }
+    }
+}
+
+impl Notify for NotifiedFutureHandle {
+    fn notify(&self, _id: usize) {
+        NotifiedFuture::poll(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::spawn;
+    use futures::sync::oneshot;
+    use futures::Future;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn notified_future_is_executed_after_notification() {
+        let executed = Arc::new(AtomicBool::new(false));
+        let executed_clone = executed.clone();
+        let (sender, receiver) = oneshot::channel::<()>();
+        spawn(Box::new(receiver.then(move |_| {
+            executed_clone.store(true, Ordering::SeqCst);
+            Ok(())
+        })));
+        assert!(!executed.load(Ordering::SeqCst));
+
+        sender.send(()).unwrap();
+        assert!(executed.load(Ordering::SeqCst));
+    }
+}
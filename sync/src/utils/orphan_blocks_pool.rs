@@ -1,9 +1,15 @@
 use chain::IndexedBlock;
+use heapsize::HeapSizeOf;
 use linked_hash_map::LinkedHashMap;
 use primitives::hash::H256;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::mem;
 use time;
+use types::PeerIndex;
+
+/// Default limit of memory, used by orphaned blocks (in bytes).
+pub const DEFAULT_ORPHANS_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
 
 #[derive(Debug)]
 /// Storage for blocks, for which we have no parent yet.
@@ -13,14 +19,34 @@ pub struct OrphanBlocksPool {
     orphaned_blocks: HashMap<H256, HashMap<H256, IndexedBlock>>,
     /// Blocks that we have received without requesting with receiving time.
     unknown_blocks: LinkedHashMap<H256, f64>,
+    /// Insertion time of every orphaned block, in the insertion order.
+    insertion_times: LinkedHashMap<H256, f64>,
+    /// Memory, used by every orphaned block + peer, which has provided this block.
+    blocks_memory: HashMap<H256, (usize, Option<PeerIndex>)>,
+    /// Memory, used by orphaned blocks of every peer.
+    peers_memory: HashMap<PeerIndex, usize>,
+    /// Memory, used by all orphaned blocks.
+    memory_usage: usize,
+    /// When memory usage exceeds this limit, the oldest orphaned blocks are evicted.
+    memory_limit: usize,
 }
 
 impl OrphanBlocksPool {
     /// Create new pool
     pub fn new() -> Self {
+        OrphanBlocksPool::with_memory_limit(DEFAULT_ORPHANS_MEMORY_LIMIT)
+    }
+
+    /// Create new pool, which holds at most `memory_limit` bytes of orphaned blocks
+    pub fn with_memory_limit(memory_limit: usize) -> Self {
         OrphanBlocksPool {
             orphaned_blocks: HashMap::new(),
             unknown_blocks: LinkedHashMap::new(),
+            insertion_times: LinkedHashMap::new(),
+            blocks_memory: HashMap::new(),
+            peers_memory: HashMap::new(),
+            memory_usage: 0,
+            memory_limit: memory_limit,
         }
     }
 
@@ -29,6 +55,22 @@ impl OrphanBlocksPool {
         self.orphaned_blocks.len()
     }
 
+    /// Get memory, used by all orphaned blocks (in bytes)
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Get memory, used by orphaned blocks of given peer (in bytes)
+    pub fn peer_memory_usage(&self, peer_index: PeerIndex) -> usize {
+        self.peers_memory.get(&peer_index).cloned().unwrap_or(0)
+    }
+
+    /// Is peer flooding us with orphaned blocks? Single peer should not occupy more than
+    /// a half of the pool.
+    pub fn is_flooding_peer(&self, peer_index: PeerIndex) -> bool {
+        self.peer_memory_usage(peer_index) > self.memory_limit / 2
+    }
+
     /// Check if block with given hash is stored as unknown in this pool
     pub fn contains_unknown_block(&self, hash: &H256) -> bool {
         self.unknown_blocks.contains_key(hash)
@@ -39,22 +81,112 @@ impl OrphanBlocksPool {
         &self.unknown_blocks
     }
 
-    /// Insert orphaned block, for which we have already requested its parent block
-    pub fn insert_orphaned_block(&mut self, block: IndexedBlock) {
+    /// Get age (in seconds) of the oldest orphaned block
+    pub fn oldest_orphan_age_s(&self) -> Option<f64> {
+        let now = time::precise_time_s();
+        self.insertion_times
+            .front()
+            .map(|(_, insertion_time)| now - insertion_time)
+    }
+
+    /// Remove orphaned blocks, which are waiting for parent for at least `max_age_s` seconds + all dependent blocks
+    pub fn evict_old_orphans(&mut self, max_age_s: f64) -> Vec<H256> {
+        self.evict_orphans_inserted_before(time::precise_time_s() - max_age_s)
+    }
+
+    fn evict_orphans_inserted_before(&mut self, time: f64) -> Vec<H256> {
+        let orphans_to_remove: HashSet<H256> = self
+            .insertion_times
+            .iter()
+            .take_while(|&(_, insertion_time)| *insertion_time <= time)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        if orphans_to_remove.is_empty() {
+            return Vec::new();
+        }
+
+        self.remove_blocks(&orphans_to_remove)
+    }
+
+    /// Insert orphaned block, for which we have already requested its parent block.
+    /// Returns hashes of blocks, evicted from the pool to stay within the memory limit.
+    pub fn insert_orphaned_block(
+        &mut self,
+        peer_index: Option<PeerIndex>,
+        block: IndexedBlock,
+    ) -> Vec<H256> {
+        // the same block could be provided by several peers
+        if self.blocks_memory.contains_key(&block.header.hash) {
+            return Vec::new();
+        }
+
+        let block_memory = mem::size_of::<IndexedBlock>() + block.heap_size_of_children();
+        self.memory_usage += block_memory;
+        if let Some(peer_index) = peer_index {
+            *self.peers_memory.entry(peer_index).or_insert(0) += block_memory;
+        }
+        self.blocks_memory
+            .insert(block.header.hash.clone(), (block_memory, peer_index));
+        self.insertion_times
+            .insert(block.header.hash.clone(), time::precise_time_s());
         self.orphaned_blocks
             .entry(block.header.raw.previous_header_hash.clone())
             .or_insert_with(HashMap::new)
             .insert(block.header.hash.clone(), block);
+
+        self.evict_orphans_over_limit()
     }
 
-    /// Insert unknown block, for which we know nothing about its parent block
-    pub fn insert_unknown_block(&mut self, block: IndexedBlock) {
+    /// Insert unknown block, for which we know nothing about its parent block.
+    /// Returns hashes of blocks, evicted from the pool to stay within the memory limit.
+    pub fn insert_unknown_block(
+        &mut self,
+        peer_index: Option<PeerIndex>,
+        block: IndexedBlock,
+    ) -> Vec<H256> {
         let previous_value = self
             .unknown_blocks
             .insert(block.header.hash.clone(), time::precise_time_s());
         assert_eq!(previous_value, None);
 
-        self.insert_orphaned_block(block);
+        self.insert_orphaned_block(peer_index, block)
+    }
+
+    /// Remove the oldest orphaned blocks + all dependent blocks, until memory usage is within the limit
+    fn evict_orphans_over_limit(&mut self) -> Vec<H256> {
+        if self.memory_usage <= self.memory_limit {
+            return Vec::new();
+        }
+
+        let mut evicted: Vec<H256> = Vec::new();
+        while self.memory_usage > self.memory_limit {
+            let oldest = match self.insertion_times.front() {
+                Some((hash, _)) => hash.clone(),
+                None => break,
+            };
+
+            let mut orphans_to_remove = HashSet::new();
+            orphans_to_remove.insert(oldest);
+            evicted.extend(self.remove_blocks(&orphans_to_remove));
+        }
+        evicted
+    }
+
+    /// Forget everything about block, removed from the pool
+    fn on_block_removed(&mut self, hash: &H256) {
+        self.unknown_blocks.remove(hash);
+        self.insertion_times.remove(hash);
+        if let Some((block_memory, peer_index)) = self.blocks_memory.remove(hash) {
+            self.memory_usage -= block_memory;
+            if let Some(peer_index) = peer_index {
+                if let Entry::Occupied(mut entry) = self.peers_memory.entry(peer_index) {
+                    *entry.get_mut() -= block_memory;
+                    if *entry.get() == 0 {
+                        entry.remove();
+                    }
+                }
+            }
+        }
     }
 
     /// Remove all blocks, which are not-unknown
@@ -79,7 +211,7 @@ impl OrphanBlocksPool {
             if let Entry::Occupied(entry) = self.orphaned_blocks.entry(parent_hash) {
                 let (_, orphaned) = entry.remove_entry();
                 for orphaned_hash in orphaned.keys() {
-                    self.unknown_blocks.remove(orphaned_hash);
+                    self.on_block_removed(orphaned_hash);
                 }
                 queue.extend(orphaned.keys().cloned());
                 removed.extend(orphaned.into_iter().map(|(_, b)| b));
@@ -100,7 +232,7 @@ impl OrphanBlocksPool {
         });
 
         for block in &removed {
-            self.unknown_blocks.remove(block);
+            self.on_block_removed(block);
         }
         // also delete all children
         for hash in hashes.iter() {
@@ -120,8 +252,16 @@ mod tests {
     extern crate test_data;
 
     use super::OrphanBlocksPool;
+    use chain::{Block, IndexedBlock};
     use primitives::hash::H256;
     use std::collections::HashSet;
+    use std::mem;
+
+    fn block_memory(block: &Block) -> usize {
+        let mut pool = OrphanBlocksPool::new();
+        pool.insert_orphaned_block(None, block.clone().into());
+        pool.memory_usage()
+    }
 
     #[test]
     fn orphan_block_pool_empty_on_start() {
@@ -135,7 +275,7 @@ mod tests {
         let b1 = test_data::block_h1();
         let b1_hash = b1.hash();
 
-        pool.insert_orphaned_block(b1.into());
+        pool.insert_orphaned_block(None, b1.into());
 
         assert_eq!(pool.len(), 1);
         assert!(!pool.contains_unknown_block(&b1_hash));
@@ -148,7 +288,7 @@ mod tests {
         let b1 = test_data::block_h1();
         let b1_hash = b1.hash();
 
-        pool.insert_unknown_block(b1.into());
+        pool.insert_unknown_block(None, b1.into());
 
         assert_eq!(pool.len(), 1);
         assert!(pool.contains_unknown_block(&b1_hash));
@@ -163,8 +303,8 @@ mod tests {
         let b2 = test_data::block_h169();
         let b2_hash = b2.hash();
 
-        pool.insert_orphaned_block(b1.into());
-        pool.insert_unknown_block(b2.into());
+        pool.insert_orphaned_block(None, b1.into());
+        pool.insert_unknown_block(None, b2.into());
 
         assert_eq!(pool.len(), 2);
         assert!(!pool.contains_unknown_block(&b1_hash));
@@ -189,9 +329,9 @@ mod tests {
         let b3 = test_data::block_h2();
         let b3_hash = b3.hash();
 
-        pool.insert_orphaned_block(b1.into());
-        pool.insert_unknown_block(b2.into());
-        pool.insert_orphaned_block(b3.into());
+        pool.insert_orphaned_block(None, b1.into());
+        pool.insert_unknown_block(None, b2.into());
+        pool.insert_orphaned_block(None, b3.into());
 
         let removed = pool.remove_blocks_for_parent(&test_data::genesis().hash());
         assert_eq!(removed.len(), 2);
@@ -218,11 +358,11 @@ mod tests {
         let b4_hash = b4.hash();
         let b5 = test_data::block_h181();
 
-        pool.insert_orphaned_block(b1.into());
-        pool.insert_orphaned_block(b2.into());
-        pool.insert_orphaned_block(b3.into());
-        pool.insert_orphaned_block(b4.into());
-        pool.insert_orphaned_block(b5.into());
+        pool.insert_orphaned_block(None, b1.into());
+        pool.insert_orphaned_block(None, b2.into());
+        pool.insert_orphaned_block(None, b3.into());
+        pool.insert_orphaned_block(None, b4.into());
+        pool.insert_orphaned_block(None, b5.into());
 
         let mut blocks_to_remove: HashSet<H256> = HashSet::new();
         blocks_to_remove.insert(b1_hash.clone());
@@ -237,4 +377,132 @@ mod tests {
 
         assert_eq!(pool.len(), 1);
     }
+
+    #[test]
+    fn orphan_block_pool_oldest_orphan_age() {
+        let mut pool = OrphanBlocksPool::new();
+        assert_eq!(pool.oldest_orphan_age_s(), None);
+
+        pool.insert_orphaned_block(None, test_data::block_h1().into());
+        let age = pool.oldest_orphan_age_s().unwrap();
+        assert!(age >= 0f64 && age < 60f64);
+    }
+
+    #[test]
+    fn orphan_block_pool_does_not_evict_young_orphans() {
+        let mut pool = OrphanBlocksPool::new();
+        pool.insert_orphaned_block(None, test_data::block_h1().into());
+
+        assert_eq!(pool.evict_old_orphans(3600f64), vec![]);
+        let insertion_time = pool.insertion_times[&test_data::block_h1().hash()];
+        assert_eq!(
+            pool.evict_orphans_inserted_before(insertion_time - 1f64),
+            vec![]
+        );
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn orphan_block_pool_evicts_orphans_at_threshold() {
+        let mut pool = OrphanBlocksPool::new();
+        let b1 = test_data::block_h1();
+        let b1_hash = b1.hash();
+        pool.insert_orphaned_block(None, b1.into());
+
+        let insertion_time = pool.insertion_times[&b1_hash];
+        assert_eq!(
+            pool.evict_orphans_inserted_before(insertion_time),
+            vec![b1_hash]
+        );
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.oldest_orphan_age_s(), None);
+    }
+
+    #[test]
+    fn orphan_block_pool_evicts_orphan_children() {
+        let mut pool = OrphanBlocksPool::new();
+        let b1 = test_data::block_h1();
+        let b1_hash = b1.hash();
+        let b2 = test_data::block_h2();
+        let b2_hash = b2.hash();
+
+        pool.insert_orphaned_block(None, b1.into());
+        let insertion_time = pool.insertion_times[&b1_hash];
+        pool.insert_orphaned_block(None, b2.into());
+        // child block is younger than the threshold, but its parent is evicted
+        pool.insertion_times
+            .insert(b2_hash.clone(), insertion_time + 10f64);
+
+        let removed = pool.evict_orphans_inserted_before(insertion_time);
+        assert_eq!(removed, vec![b1_hash, b2_hash]);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(pool.oldest_orphan_age_s(), None);
+    }
+
+    #[test]
+    fn orphan_block_pool_accounts_memory_usage() {
+        let mut pool = OrphanBlocksPool::new();
+        let b1: IndexedBlock = test_data::block_h1().into();
+        let b1_hash = b1.hash().clone();
+        let b2: IndexedBlock = test_data::block_h169().into();
+        let b2_hash = b2.hash().clone();
+
+        pool.insert_orphaned_block(Some(1), b1.clone());
+        let b1_memory = pool.memory_usage();
+        assert!(b1_memory >= mem::size_of::<IndexedBlock>());
+        assert_eq!(pool.peer_memory_usage(1), b1_memory);
+
+        // the same block from other peer is ignored
+        pool.insert_orphaned_block(Some(2), b1);
+        assert_eq!(pool.memory_usage(), b1_memory);
+        assert_eq!(pool.peer_memory_usage(2), 0);
+
+        pool.insert_unknown_block(None, b2);
+        assert!(pool.memory_usage() > b1_memory);
+        assert_eq!(pool.peer_memory_usage(1), b1_memory);
+
+        let mut blocks_to_remove: HashSet<H256> = HashSet::new();
+        blocks_to_remove.insert(b1_hash);
+        blocks_to_remove.insert(b2_hash);
+        pool.remove_blocks(&blocks_to_remove);
+        assert_eq!(pool.memory_usage(), 0);
+        assert_eq!(pool.peer_memory_usage(1), 0);
+    }
+
+    #[test]
+    fn orphan_block_pool_evicts_oldest_orphans_over_memory_limit() {
+        let b1 = test_data::block_h1();
+        let b1_hash = b1.hash();
+        let b2 = test_data::block_h2();
+        let b2_hash = b2.hash();
+        let b3 = test_data::block_h169();
+        let b3_memory = block_memory(&b3);
+        let memory_limit = block_memory(&b1) + block_memory(&b2) + b3_memory - 1;
+        let mut pool = OrphanBlocksPool::with_memory_limit(memory_limit);
+
+        assert_eq!(pool.insert_orphaned_block(Some(1), b1.into()), vec![]);
+        assert_eq!(pool.insert_orphaned_block(Some(1), b2.into()), vec![]);
+        pool.insertion_times.insert(b1_hash.clone(), 0f64);
+        pool.insertion_times.insert(b2_hash.clone(), 1f64);
+
+        // the oldest block is evicted along with its child
+        assert_eq!(
+            pool.insert_orphaned_block(Some(2), b3.into()),
+            vec![b1_hash, b2_hash]
+        );
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool.memory_usage(), b3_memory);
+        assert_eq!(pool.peer_memory_usage(1), 0);
+        assert_eq!(pool.peer_memory_usage(2), b3_memory);
+    }
+
+    #[test]
+    fn orphan_block_pool_detects_flooding_peer() {
+        let b1 = test_data::block_h1();
+        let mut pool = OrphanBlocksPool::with_memory_limit(block_memory(&b1) * 3 / 2);
+
+        pool.insert_orphaned_block(Some(1), b1.into());
+        assert!(pool.is_flooding_peer(1));
+        assert!(!pool.is_flooding_peer(2));
+    }
 }
@@ -1,6 +1,7 @@
 use super::super::types::{BlockHeight, StorageRef};
 use p2p::InboundSyncConnectionState;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // AtomicU32 is unstable => using AtomicUsize here
 
@@ -12,6 +13,8 @@ pub struct SynchronizationState {
     is_synchronizing: AtomicBool,
     /// Height of best block in the storage
     best_storage_block_height: AtomicUsize,
+    /// UNIX timestamp (in seconds) of the last block insertion
+    last_block_time: AtomicU64,
 }
 
 impl SynchronizationState {
@@ -20,6 +23,7 @@ impl SynchronizationState {
         SynchronizationState {
             is_synchronizing: AtomicBool::new(false),
             best_storage_block_height: AtomicUsize::new(best_storage_block_height as usize),
+            last_block_time: AtomicU64::new(unix_timestamp()),
         }
     }
 
@@ -39,6 +43,22 @@ impl SynchronizationState {
         self.best_storage_block_height
             .store(height as usize, Ordering::SeqCst);
     }
+
+    pub fn update_last_block_time(&self) {
+        self.last_block_time
+            .store(unix_timestamp(), Ordering::SeqCst);
+    }
+
+    pub fn elapsed_since_last_block_secs(&self) -> u64 {
+        unix_timestamp().saturating_sub(self.last_block_time.load(Ordering::SeqCst))
+    }
+}
+
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
 }
 
 impl InboundSyncConnectionState for SynchronizationState {
@@ -1,8 +1,170 @@
-#[derive(Debug, Default, Clone)]
-pub struct ChainBuilder {}
+//! Chain builder
+
+use super::genesis;
+use block::block_builder;
+use chain::Block;
+use primitives::compact::Compact;
+use std::collections::HashSet;
+
+/// Builds chains of test blocks, where every block is a child of the previous one.
+///
+/// Heights are counted from the first block of the builder, i.e. the first block
+/// (genesis by default) is at height 0.
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    blocks: Vec<Block>,
+    bits: Compact,
+    validate: bool,
+}
+
+impl Default for ChainBuilder {
+    fn default() -> Self {
+        ChainBuilder::from_block(genesis())
+    }
+}
 
 impl ChainBuilder {
+    /// Start building chain from the genesis block.
     pub fn new() -> ChainBuilder {
-        ChainBuilder {}
+        ChainBuilder::default()
+    }
+
+    /// Start building chain from arbitrary existing block.
+    pub fn from_block(block: Block) -> ChainBuilder {
+        ChainBuilder {
+            blocks: vec![block],
+            bits: Compact::max_value(),
+            validate: true,
+        }
+    }
+
+    /// Set bits for all blocks appended after this call.
+    pub fn with_bits(mut self, bits: Compact) -> Self {
+        self.bits = bits;
+        self
+    }
+
+    /// Append `n` blocks, using current bits.
+    pub fn next(mut self, n: u32) -> Self {
+        for _ in 0..n {
+            let parent = self
+                .blocks
+                .last()
+                .expect("chain builder always has at least one block; qed")
+                .hash();
+            let block = block_builder()
+                .header()
+                .parent(parent)
+                .bits(self.bits)
+                .iterations(1)
+                .evaluated()
+                .build()
+                .proved()
+                .build();
+            self.blocks.push(block);
+        }
+        self
+    }
+
+    /// Append `n` blocks with given bits.
+    pub fn next_with_bits(self, n: u32, bits: Compact) -> Self {
+        self.with_bits(bits).next(n)
+    }
+
+    /// Do not check chain consistency when building.
+    pub fn skip_validation(mut self) -> Self {
+        self.validate = false;
+        self
+    }
+
+    /// Panic if blocks are not linked to each other or if there are duplicate blocks.
+    pub fn assert_valid_chain(&self) {
+        for (index, pair) in self.blocks.windows(2).enumerate() {
+            assert_eq!(
+                pair[1].header().previous_header_hash,
+                pair[0].hash(),
+                "block #{} is not a child of block #{}",
+                index + 1,
+                index
+            );
+        }
+
+        let mut hashes = HashSet::new();
+        for (index, block) in self.blocks.iter().enumerate() {
+            assert!(
+                hashes.insert(block.hash()),
+                "block #{} is a duplicate",
+                index
+            );
+        }
+    }
+
+    /// Height of the last block in the chain.
+    pub fn height(&self) -> u32 {
+        self.blocks.len() as u32 - 1
+    }
+
+    /// Return all blocks of the chain, starting with the first one.
+    /// In debug builds the chain is checked with `assert_valid_chain` unless
+    /// `skip_validation` has been called.
+    pub fn build(self) -> Vec<Block> {
+        if cfg!(debug_assertions) && self.validate {
+            self.assert_valid_chain();
+        }
+        self.blocks
+    }
+
+    /// Append blocks until the chain reaches `target_height` and return all blocks.
+    pub fn build_at_height(self, target_height: u32) -> Vec<Block> {
+        let height = self.height();
+        assert!(
+            target_height >= height,
+            "chain is already higher than target height"
+        );
+        self.next(target_height - height).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChainBuilder;
+    use primitives::compact::Compact;
+
+    #[test]
+    fn chain_builder_builds_linked_chain() {
+        let blocks = ChainBuilder::new()
+            .next(2)
+            .next_with_bits(2, Compact::new(0x1d00ffff))
+            .build();
+
+        assert_eq!(blocks.len(), 5);
+        assert_eq!(blocks[0], ::genesis());
+        for pair in blocks.windows(2) {
+            assert_eq!(pair[1].header().previous_header_hash, pair[0].hash());
+        }
+        assert_eq!(blocks[2].header().bits, Compact::max_value());
+        assert_eq!(blocks[4].header().bits, Compact::new(0x1d00ffff));
+    }
+
+    #[test]
+    fn chain_builder_builds_at_height() {
+        let blocks = ChainBuilder::from_block(::block_h1()).build_at_height(3);
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[0], ::block_h1());
+    }
+
+    #[test]
+    #[should_panic(expected = "block #2 is not a child of block #1")]
+    fn chain_builder_rejects_broken_chain() {
+        let mut builder = ChainBuilder::new().next(1);
+        builder.blocks.push(::block_h2());
+        builder.assert_valid_chain();
+    }
+
+    #[test]
+    fn chain_builder_skips_validation() {
+        let mut builder = ChainBuilder::new().next(1).skip_validation();
+        builder.blocks.push(::block_h2());
+        assert_eq!(builder.build().len(), 3);
     }
 }
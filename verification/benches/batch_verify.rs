@@ -0,0 +1,53 @@
+//! Benchmarks of sequential and parallel (batched) blocks verification.
+//!
+//! Run with `cargo bench -p verification`.
+
+#[macro_use]
+extern crate criterion;
+extern crate chain;
+extern crate db;
+extern crate network;
+extern crate test_data;
+extern crate verification;
+
+use chain::IndexedBlock;
+use criterion::{black_box, Criterion, Throughput};
+use db::BlockChainDatabase;
+use network::Network;
+use std::sync::Arc;
+use verification::{BackwardsCompatibleChainVerifier as ChainVerifier, VerificationLevel, Verify};
+
+/// Number of blocks, verified in single batch by the sync verification thread.
+const BATCH_SIZE: usize = 32;
+
+fn bench_batch_verify(c: &mut Criterion) {
+    let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+        test_data::genesis().into(),
+    ]));
+    let verifier = ChainVerifier::new(storage, Network::Unitest);
+    let block: IndexedBlock = test_data::block_h1().into();
+    let blocks = vec![block; BATCH_SIZE];
+    let batch: Vec<_> = blocks
+        .iter()
+        .map(|block| (VerificationLevel::Full, Some(1), block))
+        .collect();
+
+    let mut group = c.benchmark_group("verify");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let results: Vec<_> = blocks
+                .iter()
+                .map(|block| verifier.verify(VerificationLevel::Full, block))
+                .collect();
+            black_box(results)
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| black_box(verifier.batch_verify_context_free(&batch)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_batch_verify);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
 use canon::CanonHeader;
+use checkpoints::check_checkpoint;
 use error::Error;
 use network::Network;
 use storage::BlockHeaderProvider;
@@ -8,6 +9,8 @@ use work::work_required;
 pub struct HeaderAcceptor<'a> {
     pub version: HeaderVersion<'a>,
     pub work: HeaderWork<'a>,
+    pub pubkey: HeaderPublicKey<'a>,
+    pub checkpoint: HeaderCheckpoint<'a>,
     // pub median_timestamp: HeaderMedianTimestamp<'a>,
 }
 
@@ -21,6 +24,8 @@ impl<'a> HeaderAcceptor<'a> {
     ) -> Self {
         HeaderAcceptor {
             work: HeaderWork::new(header, store, height, network),
+            pubkey: HeaderPublicKey::new(header, store, network),
+            checkpoint: HeaderCheckpoint::new(header, height, network),
             // median_timestamp: HeaderMedianTimestamp::new(header, store),
             version: HeaderVersion::new(header, height, network),
         }
@@ -29,6 +34,8 @@ impl<'a> HeaderAcceptor<'a> {
     pub fn check(&self) -> Result<(), Error> {
         self.version.check()?;
         self.work.check()?;
+        self.pubkey.check()?;
+        self.checkpoint.check()?;
         // self.median_timestamp.check()?;
         Ok(())
     }
@@ -81,7 +88,7 @@ impl<'a> HeaderWork<'a> {
 
     fn check(&self) -> Result<(), Error> {
         let previous_header_hash = self.header.raw.previous_header_hash.clone();
-        let work = work_required(previous_header_hash, self.height, self.store, self.network);
+        let work = work_required(previous_header_hash, self.height, self.store, self.network)?;
         if work == self.header.raw.bits {
             Ok(())
         } else {
@@ -93,6 +100,62 @@ impl<'a> HeaderWork<'a> {
     }
 }
 
+/// Consecutive blocks must not be mined with the same public key
+pub struct HeaderPublicKey<'a> {
+    header: CanonHeader<'a>,
+    store: &'a dyn BlockHeaderProvider,
+    network: &'a Network,
+}
+
+impl<'a> HeaderPublicKey<'a> {
+    fn new(
+        header: CanonHeader<'a>,
+        store: &'a dyn BlockHeaderProvider,
+        network: &'a Network,
+    ) -> Self {
+        HeaderPublicKey {
+            header: header,
+            store: store,
+            network: network,
+        }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        if !self.network.enforces_pubkey_uniqueness() {
+            return Ok(());
+        }
+
+        let parent_hash = self.header.raw.previous_header_hash.clone();
+        match self.store.block_header(parent_hash.into()) {
+            Some(ref parent_header) if parent_header.raw.pubkey == self.header.raw.pubkey => {
+                Err(Error::RepeatedPublicKey)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Block at checkpoint height must have checkpoint hash
+pub struct HeaderCheckpoint<'a> {
+    header: CanonHeader<'a>,
+    height: u32,
+    network: &'a Network,
+}
+
+impl<'a> HeaderCheckpoint<'a> {
+    fn new(header: CanonHeader<'a>, height: u32, network: &'a Network) -> Self {
+        HeaderCheckpoint {
+            header: header,
+            height: height,
+            network: network,
+        }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        check_checkpoint(self.network, self.height, &self.header.hash)
+    }
+}
+
 // pub struct HeaderMedianTimestamp<'a> {
 //     header: CanonHeader<'a>,
 //     store: &'a dyn BlockHeaderProvider,
@@ -116,3 +179,37 @@ impl<'a> HeaderWork<'a> {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::HeaderPublicKey;
+    use canon::CanonHeader;
+    use chain::IndexedBlockHeader;
+    use crypto::sr25519::create_keypair;
+    use db::BlockChainDatabase;
+    use error::Error;
+    use network::Network;
+
+    #[test]
+    fn header_pubkey_not_same_as_parent() {
+        let storage = BlockChainDatabase::init_test_chain(vec![test_data::genesis().into()]);
+        let genesis = test_data::genesis();
+        let mut header = genesis.header().clone();
+        header.previous_header_hash = genesis.hash();
+        let same_pubkey = IndexedBlockHeader::from_raw(header.clone());
+        header.pubkey = create_keypair(&[1; 32]).1;
+        let other_pubkey = IndexedBlockHeader::from_raw(header);
+
+        let check = |header: &IndexedBlockHeader, network: &Network| {
+            HeaderPublicKey::new(CanonHeader::new(header), &storage, network).check()
+        };
+        assert_eq!(
+            check(&same_pubkey, &Network::Mainnet),
+            Err(Error::RepeatedPublicKey)
+        );
+        assert_eq!(check(&other_pubkey, &Network::Mainnet), Ok(()));
+        assert_eq!(check(&same_pubkey, &Network::Regtest), Ok(()));
+    }
+}
@@ -3,10 +3,13 @@
 use accept_chain::ChainAcceptor;
 use canon::CanonBlock;
 use chain::{BlockHeader, IndexedBlock, IndexedBlockHeader};
+use checkpoints::{check_fork, last_checkpoint_height};
 use error::Error;
 use hash::H256;
 use network::Network;
-use storage::{BlockHeaderProvider, BlockOrigin, SharedStore};
+use rayon::prelude::*;
+use storage::{BlockHeaderProvider, BlockOrigin, Error as DBError, SharedStore};
+use verify_block::BlockVerifier;
 use verify_chain::ChainVerifier;
 use verify_header::HeaderVerifier;
 use {VerificationLevel, Verify};
@@ -24,18 +27,101 @@ impl BackwardsCompatibleChainVerifier {
         }
     }
 
-    fn verify_block(
+    /// Cheap checks, which allow to reject malformed blocks before verifying the VDF.
+    pub fn precheck_block(&self, block: &IndexedBlock) -> Result<(), Error> {
+        self.precheck_block_context_free(block)?;
+
+        let header = &block.header.raw;
+        if !header.previous_header_hash.is_zero()
+            && !self
+                .store
+                .contains_block(header.previous_header_hash.clone().into())
+        {
+            return Err(Error::Database(DBError::UnknownParent));
+        }
+
+        Ok(())
+    }
+
+    /// Cheap checks, which do not depend on the database state.
+    fn precheck_block_context_free(&self, block: &IndexedBlock) -> Result<(), Error> {
+        let header = &block.header.raw;
+        if header.version < 1 {
+            return Err(Error::OldVersionBlock);
+        }
+
+        if header.bits == 0.into() {
+            return Err(Error::Pow);
+        }
+
+        BlockVerifier::new(block).check_proof()
+    }
+
+    /// Checks, which only depend on the block itself (including the VDF). So blocks of the same
+    /// chain could be checked in parallel, before their parents are inserted into the database.
+    /// `block_number` is the height of the block, if it is known.
+    pub fn verify_block_context_free(
         &self,
         verification_level: VerificationLevel,
+        block_number: Option<u32>,
         block: &IndexedBlock,
     ) -> Result<(), Error> {
         if verification_level == VerificationLevel::NoVerification {
             return Ok(());
         }
 
-        // first run pre-verification
+        // cheap checks first, so that malformed blocks are rejected without verifying the VDF
+        self.precheck_block_context_free(block)?;
+
         let chain_verifier = ChainVerifier::new(block, self.network);
-        chain_verifier.check()?;
+        match self.checkpoint_verification_level(verification_level, block_number) {
+            VerificationLevel::Full => chain_verifier.check(),
+            _ => chain_verifier.check_without_vdf(),
+        }
+    }
+
+    /// Run `verify_block_context_free` for blocks in parallel.
+    /// Results are returned in the same order as blocks.
+    pub fn batch_verify_context_free(
+        &self,
+        blocks: &[(VerificationLevel, Option<u32>, &IndexedBlock)],
+    ) -> Vec<Result<(), Error>> {
+        blocks
+            .par_iter()
+            .map(|&(verification_level, block_number, block)| {
+                self.verify_block_context_free(verification_level, block_number, block)
+            })
+            .collect()
+    }
+
+    fn verify_block(
+        &self,
+        verification_level: VerificationLevel,
+        block: &IndexedBlock,
+    ) -> Result<(), Error> {
+        let block_number = self
+            .store
+            .block_number(&block.header.raw.previous_header_hash)
+            .map(|parent_number| parent_number + 1);
+        self.verify_block_context_free(verification_level, block_number, block)?;
+        self.accept_block(verification_level, block)
+    }
+
+    /// Checks, which depend on the database state. Must be called after the block has passed
+    /// `verify_block_context_free` and its parent has been inserted into the database.
+    pub fn accept_block(
+        &self,
+        verification_level: VerificationLevel,
+        block: &IndexedBlock,
+    ) -> Result<(), Error> {
+        if verification_level == VerificationLevel::NoVerification {
+            assert!(
+                !self.network.is_production(),
+                "blocks verification can not be disabled on {} network",
+                self.network.name()
+            );
+            return Ok(());
+        }
 
         assert_eq!(
             Some(self.store.best_block().hash),
@@ -64,6 +150,7 @@ impl BackwardsCompatibleChainVerifier {
                 chain_acceptor.check()?;
             }
             BlockOrigin::SideChain(origin) => {
+                check_fork(&self.network, origin.ancestor)?;
                 let block_number = origin.block_number;
                 let fork = self.store.fork(origin)?;
                 let header_provider = fork.store().as_block_header_provider();
@@ -72,6 +159,7 @@ impl BackwardsCompatibleChainVerifier {
                 chain_acceptor.check()?;
             }
             BlockOrigin::SideChainBecomingCanonChain(origin) => {
+                check_fork(&self.network, origin.ancestor)?;
                 let block_number = origin.block_number;
                 let fork = self.store.fork(origin)?;
                 let header_provider = fork.store().as_block_header_provider();
@@ -88,6 +176,24 @@ impl BackwardsCompatibleChainVerifier {
         Ok(())
     }
 
+    /// Blocks of the canon chain below the last checkpoint are verified using `VerificationLevel::Header`.
+    fn checkpoint_verification_level(
+        &self,
+        verification_level: VerificationLevel,
+        block_number: Option<u32>,
+    ) -> VerificationLevel {
+        if verification_level != VerificationLevel::Full {
+            return verification_level;
+        }
+
+        match block_number {
+            Some(block_number) if block_number < last_checkpoint_height(&self.network) => {
+                VerificationLevel::Header
+            }
+            _ => VerificationLevel::Full,
+        }
+    }
+
     pub fn verify_block_header(
         &self,
         _block_header_provider: &dyn BlockHeaderProvider,
@@ -102,6 +208,22 @@ impl BackwardsCompatibleChainVerifier {
         // let header_verifier = HeaderVerifier::new(&header, self.network, current_time);
         header_verifier.check()
     }
+
+    /// Verify headers of single `headers` message in parallel. Headers are checked independently,
+    /// so on failure the index of the first invalid header is returned along with the error.
+    pub fn batch_verify_headers(
+        &self,
+        headers: &[IndexedBlockHeader],
+    ) -> Result<(), (usize, Error)> {
+        let results: Vec<_> = headers
+            .par_iter()
+            .map(|header| HeaderVerifier::new(header, self.network).check())
+            .collect();
+        results
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(index, result)| result.map_err(|error| (index, error)))
+    }
 }
 
 impl Verify for BackwardsCompatibleChainVerifier {
@@ -129,6 +251,88 @@ mod tests {
     use storage::Error as DBError;
     use {Error, VerificationLevel, Verify};
 
+    #[test]
+    fn precheck_block() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = ChainVerifier::new(storage, Network::Unitest);
+        assert_eq!(
+            verifier.precheck_block(&test_data::block_h1().into()),
+            Ok(())
+        );
+
+        let mut block = test_data::block_h1();
+        block.block_header.version = 0;
+        assert_eq!(
+            verifier.precheck_block(&block.into()),
+            Err(Error::OldVersionBlock)
+        );
+
+        let mut block = test_data::block_h1();
+        block.block_header.bits = 0.into();
+        assert_eq!(verifier.precheck_block(&block.into()), Err(Error::Pow));
+
+        // 4 iterations require 2 proof elements
+        let mut block = test_data::block_h1();
+        block.block_header.iterations = 4;
+        block.proof.clear();
+        assert_eq!(
+            verifier.precheck_block(&block.into()),
+            Err(Error::EmptyProof)
+        );
+
+        assert_eq!(
+            verifier.precheck_block(&test_data::block_h2().into()),
+            Err(Error::Database(DBError::UnknownParent))
+        );
+    }
+
+    #[test]
+    fn batch_verify_context_free() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = ChainVerifier::new(storage, Network::Unitest);
+        let b1 = test_data::block_h1().into();
+        // parent of b2 is not yet in the database
+        let b2 = test_data::block_h2().into();
+        let mut b3 = test_data::block_h3();
+        b3.proof.clear();
+        b3.block_header.iterations = 4;
+        let b3 = b3.into();
+        assert_eq!(
+            verifier.batch_verify_context_free(&[
+                (VerificationLevel::Full, Some(1), &b1),
+                (VerificationLevel::Full, Some(2), &b2),
+                (VerificationLevel::Full, Some(3), &b3),
+                (VerificationLevel::NoVerification, Some(3), &b3),
+            ]),
+            vec![Ok(()), Ok(()), Err(Error::EmptyProof), Ok(())]
+        );
+        assert_eq!(
+            verifier.accept_block(VerificationLevel::Full, &b2),
+            Err(Error::Database(DBError::UnknownParent))
+        );
+        assert_eq!(verifier.accept_block(VerificationLevel::Full, &b1), Ok(()));
+    }
+
+    #[test]
+    fn batch_verify_headers() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let verifier = ChainVerifier::new(storage, Network::Unitest);
+        let h1 = test_data::block_h1().block_header;
+        let mut h2 = test_data::block_h2().block_header;
+        h2.bits = 0.into();
+        assert_eq!(verifier.batch_verify_headers(&[h1.clone().into()]), Ok(()));
+        assert_eq!(
+            verifier.batch_verify_headers(&[h1.clone().into(), h2.into(), h1.into()]),
+            Err((1, Error::Pow))
+        );
+    }
+
     #[test]
     fn verify_orphan() {
         let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
@@ -151,4 +355,28 @@ mod tests {
         let verifier = ChainVerifier::new(storage, Network::Unitest);
         assert!(verifier.verify(VerificationLevel::Full, &b1.into()).is_ok());
     }
+
+    #[test]
+    #[should_panic(expected = "blocks verification can not be disabled on main network")]
+    fn verify_none_on_production_network() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let b1 = test_data::block_h1();
+        let verifier = ChainVerifier::new(storage, Network::Mainnet);
+        let _ = verifier.verify(VerificationLevel::NoVerification, &b1.into());
+    }
+
+    #[test]
+    fn verify_none_on_test_network() {
+        let storage = Arc::new(BlockChainDatabase::init_test_chain(vec![
+            test_data::genesis().into(),
+        ]));
+        let b2 = test_data::block_h2();
+        let verifier = ChainVerifier::new(storage, Network::Testnet);
+        assert_eq!(
+            verifier.verify(VerificationLevel::NoVerification, &b2.into()),
+            Ok(())
+        );
+    }
 }
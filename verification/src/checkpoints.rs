@@ -0,0 +1,65 @@
+//! Hard-coded checkpoints of the network
+
+use error::Error;
+use hash::H256;
+use network::Network;
+
+/// Hash of the checkpoint block at given height, if any.
+pub fn checkpoint(network: &Network, height: u32) -> Option<H256> {
+    network
+        .checkpoints()
+        .into_iter()
+        .find(|&(checkpoint_height, _)| checkpoint_height == height)
+        .map(|(_, hash)| hash)
+}
+
+/// Height of the last checkpoint.
+pub fn last_checkpoint_height(network: &Network) -> u32 {
+    network
+        .checkpoints()
+        .last()
+        .map(|&(height, _)| height)
+        .unwrap_or(0)
+}
+
+/// Block at given height must match the checkpoint at this height (if any).
+pub fn check_checkpoint(network: &Network, height: u32, hash: &H256) -> Result<(), Error> {
+    match checkpoint(network, height) {
+        Some(ref checkpoint_hash) if checkpoint_hash != hash => Err(Error::Checkpoint),
+        _ => Ok(()),
+    }
+}
+
+/// Side chain, which forks after block `ancestor`, must not fork below the last checkpoint.
+pub fn check_fork(network: &Network, ancestor: u32) -> Result<(), Error> {
+    if ancestor < last_checkpoint_height(network) {
+        Err(Error::ForkBelowCheckpoint)
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_checkpoint, check_fork, checkpoint, last_checkpoint_height};
+    use error::Error;
+    use hash::H256;
+    use network::Network;
+
+    #[test]
+    fn genesis_checkpoint() {
+        let network = Network::Unitest;
+        let genesis_hash = *network.genesis_block().hash();
+        assert_eq!(checkpoint(&network, 0), Some(genesis_hash));
+        assert_eq!(checkpoint(&network, 1), None);
+        assert_eq!(last_checkpoint_height(&network), 0);
+
+        assert_eq!(check_checkpoint(&network, 0, &genesis_hash), Ok(()));
+        assert_eq!(
+            check_checkpoint(&network, 0, &H256::from(1)),
+            Err(Error::Checkpoint)
+        );
+        assert_eq!(check_checkpoint(&network, 1, &H256::from(1)), Ok(()));
+        assert_eq!(check_fork(&network, 0), Ok(()));
+    }
+}
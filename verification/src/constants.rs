@@ -1,18 +1,51 @@
 //! Consenus constants
 
-pub const BLOCK_MAX_FUTURE: i64 = 2 * 60 * 60; // 2 hours
-pub const COINBASE_MATURITY: u32 = 100; // 2 hours
-pub const MIN_COINBASE_SIZE: usize = 2;
-pub const MAX_COINBASE_SIZE: usize = 100;
+/// Block reward of the first blocks (50 coins).
+pub const INITIAL_BLOCK_REWARD_SATOSHI: u64 = 50 * 100 * 1000 * 1000;
+/// Number of blocks between two block reward halvings.
+pub const SUBSIDY_HALVING_INTERVAL: u32 = 210_000;
 
+/// Maximal factor, by which difficulty can change at single retarget.
 pub const RETARGETING_FACTOR: u32 = 4;
+/// Target time between two blocks (10 minutes).
 pub const TARGET_SPACING_SECONDS: u32 = 10 * 60;
+/// Time without blocks, after which testnet allows minimal difficulty block.
 pub const DOUBLE_SPACING_SECONDS: u32 = 2 * TARGET_SPACING_SECONDS;
+/// Target time between two retargets (2 weeks).
 pub const TARGET_TIMESPAN_SECONDS: u32 = 2 * 7 * 24 * 60 * 60;
 
-// The upper and lower bounds for retargeting timespan
+/// Lower bound for retargeting timespan.
 pub const MIN_TIMESPAN: u32 = TARGET_TIMESPAN_SECONDS / RETARGETING_FACTOR;
+/// Upper bound for retargeting timespan.
 pub const MAX_TIMESPAN: u32 = TARGET_TIMESPAN_SECONDS * RETARGETING_FACTOR;
 
-// Target number of blocks, 2 weaks, 2016
+/// Number of blocks between two retargets (2016).
 pub const RETARGETING_INTERVAL: u32 = TARGET_TIMESPAN_SECONDS / TARGET_SPACING_SECONDS;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constants_are_not_zero() {
+        let constants = [
+            ("INITIAL_BLOCK_REWARD_SATOSHI", INITIAL_BLOCK_REWARD_SATOSHI),
+            ("SUBSIDY_HALVING_INTERVAL", SUBSIDY_HALVING_INTERVAL as u64),
+            ("RETARGETING_FACTOR", RETARGETING_FACTOR as u64),
+            ("TARGET_SPACING_SECONDS", TARGET_SPACING_SECONDS as u64),
+            ("DOUBLE_SPACING_SECONDS", DOUBLE_SPACING_SECONDS as u64),
+            ("TARGET_TIMESPAN_SECONDS", TARGET_TIMESPAN_SECONDS as u64),
+            ("MIN_TIMESPAN", MIN_TIMESPAN as u64),
+            ("MAX_TIMESPAN", MAX_TIMESPAN as u64),
+            ("RETARGETING_INTERVAL", RETARGETING_INTERVAL as u64),
+        ];
+        for &(name, value) in constants.iter() {
+            assert!(value != 0, "{} is zero", name);
+        }
+    }
+
+    #[test]
+    fn retargeting_interval() {
+        assert_eq!(RETARGETING_INTERVAL, 2016);
+    }
+}
@@ -10,6 +10,10 @@ pub enum Error {
     Pow,
     /// Invalid vdf proof
     Vdf,
+    /// Block has empty vdf proof, but its iterations require non-empty one
+    EmptyProof,
+    /// Number of vdf proof elements does not match block iterations
+    UnexpectedProof,
     /// Futuristic timestamp
     FuturisticTimestamp,
     /// Invalid timestamp
@@ -20,6 +24,12 @@ pub enum Error {
     NonFinalBlock,
     /// Old version block.
     OldVersionBlock,
+    /// Block is mined with the same public key as its parent
+    RepeatedPublicKey,
+    /// Block hash does not match the checkpoint at the same height
+    Checkpoint,
+    /// Side chain forks from the main chain below the last checkpoint
+    ForkBelowCheckpoint,
     /// Database error
     Database(DBError),
 }
@@ -65,12 +65,14 @@ extern crate chain;
 extern crate crypto;
 #[cfg(test)]
 extern crate db;
+extern crate metrics;
 extern crate network;
 extern crate primitives;
 extern crate serialization as ser;
 extern crate storage;
 
 mod canon;
+pub mod checkpoints;
 pub mod constants;
 mod error;
 mod timestamp;
@@ -104,7 +106,8 @@ pub use chain_verifier::BackwardsCompatibleChainVerifier;
 pub use error::Error;
 // pub use timestamp::{median_timestamp, median_timestamp_inclusive};
 pub use work::{
-    block_reward_satoshi, is_valid_proof_of_work, is_valid_proof_of_work_hash, work_required,
+    block_reward_satoshi, cumulative_iterations, is_valid_proof_of_work,
+    is_valid_proof_of_work_hash, work_required,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -112,7 +115,7 @@ pub use work::{
 pub enum VerificationLevel {
     /// Full verification.
     Full,
-    /// Transaction scripts are not checked.
+    /// VDF proofs are not checked. Used automatically for blocks below the last checkpoint.
     Header,
     /// No verification at all.
     NoVerification,
@@ -17,7 +17,7 @@ use storage::{BlockAncestors, BlockHeaderProvider};
 //     store: &dyn BlockHeaderProvider,
 // ) -> u32 {
 //     let mut timestamps: Vec<_> = BlockAncestors::new(previous_header_hash.clone().into(), store)
-//         .take(11)
+//         .take(MEDIAN_TIMESTAMP_SPAN)
 //         .map(|header| header.raw.time)
 //         .collect();
 
@@ -1,6 +1,7 @@
 use chain::IndexedBlock;
 use crypto::{dhash256, vdf};
 use error::Error;
+use metrics;
 use primitives::bytes::Bytes;
 use rug::{integer::Order, Integer};
 use ser::Stream;
@@ -32,19 +33,48 @@ pub fn h_g(block: &IndexedBlock) -> Integer {
 }
 
 pub struct BlockVerifier<'a> {
+    pub proof: BlockProof<'a>,
     pub vdf: BlockVDF<'a>,
 }
 
 impl<'a> BlockVerifier<'a> {
     pub fn new(block: &'a IndexedBlock) -> Self {
         BlockVerifier {
+            proof: BlockProof::new(block),
             vdf: BlockVDF::new(block),
         }
     }
 
     pub fn check(&self) -> Result<(), Error> {
+        self.proof.check()?;
         self.vdf.check()
     }
+
+    /// Check proof presence only, without verifying the VDF
+    pub fn check_proof(&self) -> Result<(), Error> {
+        self.proof.check()
+    }
+}
+
+/// Proof must contain exactly as many elements as `vdf::prove` produces for block iterations.
+/// So blocks with less than 2 iterations (e.g. genesis) have empty proof.
+pub struct BlockProof<'a> {
+    block: &'a IndexedBlock,
+}
+
+impl<'a> BlockProof<'a> {
+    fn new(block: &'a IndexedBlock) -> Self {
+        BlockProof { block: block }
+    }
+
+    fn check(&self) -> Result<(), Error> {
+        let expected_len = vdf::proof_len(self.block.header.raw.iterations as u64);
+        match self.block.proof.len() {
+            len if len == expected_len => Ok(()),
+            0 => Err(Error::EmptyProof),
+            _ => Err(Error::UnexpectedProof),
+        }
+    }
 }
 
 pub struct BlockVDF<'a> {
@@ -59,14 +89,75 @@ impl<'a> BlockVDF<'a> {
     fn check(&self) -> Result<(), Error> {
         let g = h_g(self.block);
 
-        match vdf::verify(
-            &g,
-            &self.block.header.raw.solution,
-            self.block.header.raw.iterations as u64,
-            &self.block.proof,
-        ) {
+        let is_valid = metrics::VDF_VERIFY.time(|| {
+            vdf::verify(
+                &g,
+                &self.block.header.raw.solution,
+                self.block.header.raw.iterations as u64,
+                &self.block.proof,
+            )
+        });
+        match is_valid {
             false => Err(Error::Vdf),
             true => Ok(()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    extern crate test_data;
+
+    use super::BlockProof;
+    use chain::{Block, IndexedBlock};
+    use error::Error;
+    use rug::Integer;
+
+    fn block(iterations: u32, proof: Vec<Integer>) -> IndexedBlock {
+        let mut header = test_data::genesis().header().clone();
+        header.iterations = iterations;
+        IndexedBlock::from_raw(Block::new(header, proof))
+    }
+
+    #[test]
+    fn block_proof_zero_iterations_empty_proof() {
+        let block = block(0, vec![]);
+        assert_eq!(BlockProof::new(&block).check(), Ok(()));
+    }
+
+    #[test]
+    fn block_proof_zero_iterations_non_empty_proof() {
+        let block = block(0, vec![Integer::from(1)]);
+        assert_eq!(BlockProof::new(&block).check(), Err(Error::UnexpectedProof));
+    }
+
+    #[test]
+    fn block_proof_one_iteration_empty_proof() {
+        let block = block(1, vec![]);
+        assert_eq!(BlockProof::new(&block).check(), Ok(()));
+    }
+
+    #[test]
+    fn block_proof_one_iteration_non_empty_proof() {
+        let block = block(1, vec![Integer::from(1)]);
+        assert_eq!(BlockProof::new(&block).check(), Err(Error::UnexpectedProof));
+    }
+
+    #[test]
+    fn block_proof_many_iterations_empty_proof() {
+        let block = block(4, vec![]);
+        assert_eq!(BlockProof::new(&block).check(), Err(Error::EmptyProof));
+    }
+
+    #[test]
+    fn block_proof_many_iterations_proof_len() {
+        let proof = block(4, vec![Integer::from(1), Integer::from(2)]);
+        assert_eq!(BlockProof::new(&proof).check(), Ok(()));
+
+        let short_proof = block(4, vec![Integer::from(1)]);
+        assert_eq!(
+            BlockProof::new(&short_proof).check(),
+            Err(Error::UnexpectedProof)
+        );
+    }
+}
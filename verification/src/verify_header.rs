@@ -1,5 +1,4 @@
 use chain::IndexedBlockHeader;
-use constants::BLOCK_MAX_FUTURE;
 use error::Error;
 use network::Network;
 use primitives::compact::Compact;
@@ -1,14 +1,14 @@
-use chain::IndexedBlockHeader;
+use chain::{BlockHeader, IndexedBlockHeader};
 use network::Network;
 use primitives::bigint::U256;
 use primitives::compact::Compact;
 use primitives::hash::H256;
 use std::cmp;
-use storage::{BlockHeaderProvider, BlockRef};
+use storage::{BlockAncestors, BlockHeaderProvider, BlockRef, Error as DBError};
 
 use constants::{
-    DOUBLE_SPACING_SECONDS, MAX_TIMESPAN, MIN_TIMESPAN, RETARGETING_INTERVAL,
-    TARGET_TIMESPAN_SECONDS,
+    DOUBLE_SPACING_SECONDS, INITIAL_BLOCK_REWARD_SATOSHI, MAX_TIMESPAN, MIN_TIMESPAN,
+    RETARGETING_INTERVAL, SUBSIDY_HALVING_INTERVAL, TARGET_TIMESPAN_SECONDS,
 };
 
 pub fn is_retarget_height(height: u32) -> bool {
@@ -55,21 +55,42 @@ pub fn retarget_timespan(retarget_timestamp: u32, last_timestamp: u32) -> u32 {
     range_constrain(timespan, MIN_TIMESPAN as i64, MAX_TIMESPAN as i64) as u32
 }
 
+/// Returns number of canon blocks known to the store, up to `limit`
+fn known_chain_length(store: &dyn BlockHeaderProvider, limit: u32) -> u32 {
+    (0..limit)
+        .take_while(|number| store.block_header(BlockRef::Number(*number)).is_some())
+        .count() as u32
+}
+
+/// Returns sum of VDF iterations of the header and all its known ancestors
+pub fn cumulative_iterations(header: &BlockHeader, store: &dyn BlockHeaderProvider) -> u64 {
+    let ancestors = BlockAncestors::new(header.previous_header_hash.clone().into(), store);
+    ancestors.fold(header.iterations as u64, |sum, ancestor| {
+        sum + ancestor.raw.iterations as u64
+    })
+}
+
 /// Returns work required for given header
 pub fn work_required(
     parent_hash: H256,
     height: u32,
     store: &dyn BlockHeaderProvider,
     network: &Network,
-) -> Compact {
+) -> Result<Compact, DBError> {
     let max_bits = network.max_bits().into();
     if height == 0 {
-        return max_bits;
+        return Ok(max_bits);
     }
 
-    let parent_header = store
-        .block_header(parent_hash.clone().into())
-        .expect("self.height != 0; qed");
+    let parent_header = match store.block_header(parent_hash.clone().into()) {
+        Some(parent_header) => parent_header,
+        None => {
+            return Err(DBError::ChainTooShort {
+                required: height,
+                actual: known_chain_length(store, height),
+            })
+        }
+    };
 
     // TODO: retarget algorithm here
     // if is_retarget_height(height) {
@@ -80,7 +101,7 @@ pub fn work_required(
     //     return work_required_testnet(parent_hash, time, height, store, Network::Testnet);
     // }
 
-    parent_header.raw.bits
+    Ok(parent_header.raw.bits)
 }
 
 // pub fn work_required_testnet(
@@ -161,8 +182,8 @@ pub fn work_required(
 // }
 
 pub fn block_reward_satoshi(block_height: u32) -> u64 {
-    let mut res = 50 * 100 * 1000 * 1000;
-    for _ in 0..block_height / 210000 {
+    let mut res = INITIAL_BLOCK_REWARD_SATOSHI;
+    for _ in 0..block_height / SUBSIDY_HALVING_INTERVAL {
         res /= 2
     }
     res
@@ -170,10 +191,20 @@ pub fn block_reward_satoshi(block_height: u32) -> u64 {
 
 #[cfg(test)]
 mod tests {
-    use super::{block_reward_satoshi, is_valid_proof_of_work, is_valid_proof_of_work_hash};
+    extern crate test_data;
+
+    use self::test_data::ChainBuilder;
+    use super::{
+        block_reward_satoshi, cumulative_iterations, is_valid_proof_of_work,
+        is_valid_proof_of_work_hash, work_required,
+    };
+    use chain::IndexedBlock;
+    use constants::RETARGETING_INTERVAL;
+    use db::BlockChainDatabase;
     use network::Network;
     use primitives::compact::Compact;
     use primitives::hash::H256;
+    use storage::Error as DBError;
 
     fn is_valid_pow(max: Compact, bits: u32, hash: &'static str) -> bool {
         is_valid_proof_of_work_hash(bits.into(), &H256::from_reversed_str(hash))
@@ -224,4 +255,94 @@ mod tests {
         assert_eq!(block_reward_satoshi(630000), 625000000);
         assert_eq!(block_reward_satoshi(630001), 625000000);
     }
+
+    #[test]
+    fn work_required_genesis() {
+        let store = BlockChainDatabase::init_test_chain(vec![]);
+        assert_eq!(
+            work_required(0.into(), 0, &store, &Network::Unitest),
+            Ok(Network::Unitest.max_bits().into())
+        );
+    }
+
+    #[test]
+    fn work_required_after_genesis() {
+        let genesis: IndexedBlock = test_data::genesis().into();
+        let genesis_hash = genesis.hash().clone();
+        let genesis_bits = genesis.header.raw.bits;
+        let store = BlockChainDatabase::init_test_chain(vec![genesis]);
+        assert_eq!(
+            work_required(genesis_hash, 1, &store, &Network::Unitest),
+            Ok(genesis_bits)
+        );
+    }
+
+    #[test]
+    fn work_required_chain_too_short() {
+        let blocks: Vec<IndexedBlock> = ChainBuilder::new()
+            .next(2)
+            .build()
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let parent_hash = blocks[2].hash().clone();
+        // parent of the block at height 3 is not in the store
+        let store = BlockChainDatabase::init_test_chain(blocks[..2].to_vec());
+        assert_eq!(
+            work_required(parent_hash, 3, &store, &Network::Unitest),
+            Err(DBError::ChainTooShort {
+                required: 3,
+                actual: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn work_required_at_retarget_interval() {
+        let bits = Compact::new(0x1d00ffff);
+        let blocks: Vec<IndexedBlock> = ChainBuilder::new()
+            .with_bits(bits)
+            .build_at_height(RETARGETING_INTERVAL - 1)
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let parent_hash = blocks.last().unwrap().hash().clone();
+        let store = BlockChainDatabase::init_test_chain(blocks);
+
+        // no retargeting yet => parent bits are required
+        assert_eq!(
+            work_required(parent_hash, RETARGETING_INTERVAL, &store, &Network::Unitest),
+            Ok(bits)
+        );
+    }
+
+    #[test]
+    fn cumulative_iterations_sums_ancestors() {
+        let genesis = test_data::genesis();
+        let b1 = test_data::block_h1();
+        let b2 = test_data::block_h2();
+        let db = BlockChainDatabase::init_test_chain(vec![
+            genesis.clone().into(),
+            b1.clone().into(),
+            b2.clone().into(),
+        ]);
+
+        assert_eq!(
+            cumulative_iterations(genesis.header(), &db),
+            genesis.header().iterations as u64
+        );
+        assert_eq!(
+            cumulative_iterations(b2.header(), &db),
+            genesis.header().iterations as u64
+                + b1.header().iterations as u64
+                + b2.header().iterations as u64
+        );
+
+        // unknown ancestors are not counted
+        let db = BlockChainDatabase::init_test_chain(vec![genesis.into()]);
+        assert_eq!(
+            cumulative_iterations(b2.header(), &db),
+            b2.header().iterations as u64
+        );
+    }
 }